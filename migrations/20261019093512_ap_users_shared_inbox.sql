@@ -0,0 +1,12 @@
+alter table ap_users
+    add column shared_inbox_url varchar(255);
+
+-- All local users share the instance-wide inbox
+update ap_users
+    set shared_inbox_url = regexp_replace(inbox_url, '/ap/inbox/[^/]+$', '/ap/inbox')
+    where exists (
+        select 1
+        from users
+        where users.ap_user_id = ap_users.id
+    )
+;
@@ -0,0 +1,9 @@
+-- ActivityPub actor type, e.g. `Person` or `Application`
+alter table ap_users
+    add column kind varchar(20)
+    not null
+    default 'Person';
+
+alter table ap_users
+    alter column kind
+    drop default;
@@ -0,0 +1,40 @@
+create table domain_blocks (
+    id uuid
+        primary key
+        default gen_random_uuid()
+        not null,
+    created_at timestamp with time zone
+        default current_timestamp
+        not null,
+    -- Also matches all subdomains
+    domain varchar(255)
+        unique
+        not null,
+    -- Either `reject` or `silence`
+    severity varchar(20)
+        not null,
+    reason varchar(1000)
+        default null
+);
+
+create table actor_blocks (
+    id uuid
+        primary key
+        default gen_random_uuid()
+        not null,
+    created_at timestamp with time zone
+        default current_timestamp
+        not null,
+    -- The local user that is blocking
+    blocker_id uuid
+        references ap_users(id)
+        on delete cascade
+        not null,
+    -- The remote user being blocked
+    blocked_id uuid
+        references ap_users(id)
+        on delete cascade
+        not null,
+
+    unique (blocker_id, blocked_id)
+);
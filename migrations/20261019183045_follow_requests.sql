@@ -0,0 +1,30 @@
+alter table ap_users
+    add column manually_approves_followers boolean
+        default false
+        not null
+;
+
+create table follow_requests (
+    id uuid
+        primary key
+        default gen_random_uuid()
+        not null,
+    created_at timestamp with time zone
+        default current_timestamp
+        not null,
+    -- The id of the `Follow` activity, needed to accept or reject it later
+    ap_id varchar(255)
+        not null,
+    -- The remote user that wants to follow
+    follower_id uuid
+        references ap_users(id)
+        on delete cascade
+        not null,
+    -- The local user that needs to approve the follow
+    following_id uuid
+        references ap_users(id)
+        on delete cascade
+        not null,
+
+    unique (follower_id, following_id)
+);
@@ -0,0 +1,28 @@
+alter table ap_users
+    add column avatar_url varchar(255)
+        default null,
+    add column header_url varchar(255)
+        default null
+;
+
+-- Images uploaded by local users
+create table images (
+    id uuid
+        primary key
+        default gen_random_uuid()
+        not null,
+    created_at timestamp with time zone
+        default current_timestamp
+        not null,
+    ap_user_id uuid
+        references ap_users(id)
+        on delete cascade
+        not null,
+    -- Either `avatar` or `header`
+    kind varchar(20)
+        not null,
+    content_type varchar(100)
+        not null,
+    data bytea
+        not null
+);
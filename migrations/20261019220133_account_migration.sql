@@ -0,0 +1,9 @@
+alter table ap_users
+    -- Other accounts of the same person, needed to move to this account
+    add column also_known_as varchar(255)[]
+        default '{}'
+        not null,
+    -- Set once the account moved to a different one
+    add column moved_to varchar(255)
+        default null
+;
@@ -0,0 +1,56 @@
+-- Outgoing activities, one row per receiving inbox. Rows are deleted once the
+-- activity was delivered successfully.
+create table deliveries (
+    id uuid
+        primary key
+        default gen_random_uuid()
+        not null,
+    created_at timestamp with time zone
+        default current_timestamp
+        not null,
+    -- The local user sending the activity
+    actor_id uuid
+        references ap_users(id)
+        on delete cascade
+        not null,
+    activity_ap_id varchar(255)
+        not null,
+    activity jsonb
+        not null,
+    inbox_url varchar(255)
+        not null,
+    attempts integer
+        default 0
+        not null,
+    next_attempt_at timestamp with time zone
+        default current_timestamp
+        not null,
+    last_error text
+        default null,
+    -- Set once we gave up delivering, keeping the activity around so it can be
+    -- retried manually
+    failed_at timestamp with time zone
+        default null
+);
+
+create index deliveries_next_attempt_at_idx
+    on deliveries (next_attempt_at)
+    where failed_at is null;
+
+-- Inboxes that did not accept our last delivery
+create table inbox_failures (
+    inbox_url varchar(255)
+        primary key
+        not null,
+    -- Number of failed deliveries since the last successful one
+    failure_count integer
+        not null,
+    first_failure_at timestamp with time zone
+        default current_timestamp
+        not null,
+    last_failure_at timestamp with time zone
+        default current_timestamp
+        not null,
+    last_error text
+        not null
+);
@@ -0,0 +1,36 @@
+-- Received and sent activities, kept for a while to debug federation issues
+create table activity_log (
+    id uuid
+        primary key
+        default gen_random_uuid()
+        not null,
+    created_at timestamp with time zone
+        default current_timestamp
+        not null,
+    -- Either `incoming` or `outgoing`
+    direction varchar(20)
+        not null,
+    -- The following fields are null if the activity could not be parsed
+    activity_ap_id varchar(255)
+        default null,
+    activity_type varchar(255)
+        default null,
+    actor_ap_id varchar(255)
+        default null,
+    -- The sending instance for incoming activities, the receiving instance for
+    -- outgoing ones
+    remote_domain varchar(255)
+        default null,
+    -- Only known for incoming activities that got far enough to be verified
+    signature_valid boolean
+        default null,
+    -- The status we responded with for incoming activities
+    http_status integer
+        default null,
+    error text
+        default null,
+    body text
+        not null
+);
+
+create index activity_log_created_at_idx on activity_log (created_at);
@@ -0,0 +1,12 @@
+alter table bookmarks
+    -- The bookmark this one was saved from, if any
+    add column via_ap_id varchar(255)
+        default null,
+    -- The author of that bookmark, if we know them
+    add column via_ap_user_id uuid
+        references ap_users(id)
+        on delete set null
+        default null
+;
+
+create index bookmarks_via_ap_id_idx on bookmarks (via_ap_id);
@@ -0,0 +1,22 @@
+-- Users whose bookmarks someone wants to search, independent of following them
+create table trusts (
+    id uuid
+        primary key
+        default gen_random_uuid()
+        not null,
+    created_at timestamp with time zone
+        default current_timestamp
+        not null,
+    truster_id uuid
+        references ap_users(id)
+        on delete cascade
+        not null,
+    trusted_id uuid
+        references ap_users(id)
+        on delete cascade
+        not null,
+
+    unique (truster_id, trusted_id)
+);
+
+create index trusts_trusted_id_idx on trusts (trusted_id);
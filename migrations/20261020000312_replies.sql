@@ -0,0 +1,35 @@
+-- Comments on bookmarks, threaded by replying to other replies
+create table replies (
+    id uuid
+        primary key
+        default gen_random_uuid()
+        not null,
+    created_at timestamp with time zone
+        default current_timestamp
+        not null,
+    ap_id varchar(255)
+        unique
+        not null,
+    ap_user_id uuid
+        references ap_users(id)
+        on delete cascade
+        not null,
+    bookmark_id uuid
+        references bookmarks(id)
+        on delete cascade
+        not null,
+    -- The reply this one answers, null for replies to the bookmark itself
+    parent_id uuid
+        references replies(id)
+        on delete cascade
+        default null,
+    -- Plain text, remote HTML is converted when receiving it
+    content text
+        not null,
+    -- Hidden by the bookmark's owner
+    hidden boolean
+        default false
+        not null
+);
+
+create index replies_bookmark_id_idx on replies (bookmark_id);
@@ -0,0 +1,35 @@
+-- Likes and boosts of bookmarks, received from other instances
+create table interactions (
+    id uuid
+        primary key
+        default gen_random_uuid()
+        not null,
+    created_at timestamp with time zone
+        default current_timestamp
+        not null,
+    -- The id of the `Like` or `Announce` activity, needed to undo it later
+    ap_id varchar(255)
+        not null,
+    -- The user that interacted with the bookmark
+    ap_user_id uuid
+        references ap_users(id)
+        on delete cascade
+        not null,
+    bookmark_id uuid
+        references bookmarks(id)
+        on delete cascade
+        not null,
+    -- Either `like` or `boost`
+    kind varchar(20)
+        not null,
+
+    unique (ap_user_id, bookmark_id, kind)
+);
+
+create index interactions_bookmark_id_idx on interactions (bookmark_id);
+
+alter table users
+    add column notify_on_interactions boolean
+        default true
+        not null
+;
@@ -0,0 +1,8 @@
+-- Free-form hashtags, stored in lowercase without the leading `#`
+alter table bookmarks
+    add column tags varchar(100)[]
+        default '{}'
+        not null
+;
+
+create index bookmarks_tags_idx on bookmarks using gin (tags);
@@ -0,0 +1,55 @@
+create table relays (
+    id uuid
+        primary key
+        default gen_random_uuid()
+        not null,
+    created_at timestamp with time zone
+        default current_timestamp
+        not null,
+    -- Either `mastodon` or `litepub`
+    kind varchar(20)
+        not null,
+    -- The inbox of Mastodon-style relays, or the actor of LitePub relays
+    url varchar(255)
+        unique
+        not null,
+    -- Where we deliver public activities to
+    inbox_url varchar(255)
+        not null,
+    -- The actor of the relay. Mastodon-style relays only reveal it when
+    -- accepting our follow.
+    ap_user_id uuid
+        references ap_users(id)
+        on delete set null
+        default null,
+    -- The `Follow` activity the instance actor subscribed with
+    follow_ap_id varchar(255)
+        unique
+        not null,
+    -- Either `pending`, `accepted` or `rejected`
+    state varchar(20)
+        default 'pending'
+        not null
+);
+
+-- Bookmarks received through relays, which make up the discover feed
+create table relayed_bookmarks (
+    id uuid
+        primary key
+        default gen_random_uuid()
+        not null,
+    created_at timestamp with time zone
+        default current_timestamp
+        not null,
+    relay_id uuid
+        references relays(id)
+        on delete cascade
+        not null,
+    bookmark_id uuid
+        references bookmarks(id)
+        on delete cascade
+        unique
+        not null
+);
+
+create index relayed_bookmarks_created_at_idx on relayed_bookmarks (created_at);
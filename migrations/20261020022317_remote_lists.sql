@@ -0,0 +1,40 @@
+-- Public lists of remote users, copied from their instance
+create table remote_lists (
+    id uuid
+        primary key
+        default gen_random_uuid()
+        not null,
+    created_at timestamp with time zone
+        default current_timestamp
+        not null,
+    ap_id varchar(255)
+        unique
+        not null,
+    ap_user_id uuid
+        references ap_users(id)
+        on delete cascade
+        not null,
+    -- The page of the list on its instance
+    url varchar(255)
+        not null,
+    title text
+        not null,
+    content text,
+    -- How many bookmarks and lists the list links to
+    item_count bigint
+        not null
+);
+
+create index remote_lists_ap_user_id_idx on remote_lists (ap_user_id);
+
+-- When we last copied the outbox and public lists of a remote user
+create table remote_profile_fetches (
+    ap_user_id uuid
+        primary key
+        references ap_users(id)
+        on delete cascade
+        not null,
+    fetched_at timestamp with time zone
+        default current_timestamp
+        not null
+);
@@ -0,0 +1,50 @@
+-- Reports of abusive content, either by local users about remote content or
+-- received from other instances about local content
+create table reports (
+    id uuid
+        primary key
+        default gen_random_uuid()
+        not null,
+    created_at timestamp with time zone
+        default current_timestamp
+        not null,
+    -- The id of the `Flag` activity
+    ap_id varchar(255)
+        unique
+        not null,
+    -- A local user, or the remote actor that sent the `Flag`
+    reporter_id uuid
+        references ap_users(id)
+        on delete cascade
+        not null,
+    reported_id uuid
+        references ap_users(id)
+        on delete cascade
+        not null,
+    -- The reported bookmark, if the report isn't about the whole account
+    bookmark_id uuid
+        references bookmarks(id)
+        on delete set null
+        default null,
+    comment varchar(2000)
+        default ''
+        not null,
+    -- Set once an admin acted on the report
+    resolved_at timestamp with time zone
+        default null
+);
+
+create index reports_resolved_at_idx on reports (resolved_at);
+
+-- Accounts suspended by an admin. Suspended local users can't log in, and
+-- activities of suspended remote users are refused.
+create table suspensions (
+    ap_user_id uuid
+        primary key
+        references ap_users(id)
+        on delete cascade
+        not null,
+    created_at timestamp with time zone
+        default current_timestamp
+        not null
+);
@@ -0,0 +1,65 @@
+-- Settings admins can change at runtime. There is always exactly one row.
+create table instance_settings (
+    id boolean
+        primary key
+        default true
+        check (id),
+    -- One of 'closed', 'open', 'invite' or 'approval'
+    registration_mode varchar(20)
+        default 'closed'
+        not null,
+    -- Usernames nobody can sign up with, on top of the general username rules
+    reserved_usernames text[]
+        default '{}'
+        not null,
+    min_username_length integer
+        default 3
+        not null
+);
+
+insert into instance_settings default values;
+
+-- Single-use codes that let people sign up while registration is invite-only
+create table invites (
+    id uuid
+        primary key
+        default gen_random_uuid()
+        not null,
+    created_at timestamp with time zone
+        default current_timestamp
+        not null,
+    code varchar(64)
+        unique
+        not null,
+    created_by uuid
+        references users(id)
+        on delete cascade
+        not null,
+    used_by uuid
+        references users(id)
+        on delete set null
+        default null,
+    used_at timestamp with time zone
+        default null
+);
+
+-- Sign-ups waiting for an admin while registration requires approval. The user
+-- is only created once the request is approved.
+create table registration_requests (
+    id uuid
+        primary key
+        default gen_random_uuid()
+        not null,
+    created_at timestamp with time zone
+        default current_timestamp
+        not null,
+    username varchar(50)
+        unique
+        not null,
+    password_hash text
+        not null,
+    -- Why the person wants to join, shown to the admins
+    reason varchar(1000)
+        default ''
+        not null
+);
@@ -0,0 +1,8 @@
+alter table users
+    add column role varchar(20)
+        default 'user'
+        not null
+;
+
+-- Nobody is promoted here. The user given by `ADMIN_USERNAME` becomes an
+-- admin on startup, others can be promoted with `linkblocks user set-role`.
@@ -0,0 +1,22 @@
+-- Base32 encoded secret for time-based one-time passwords. Two-factor
+-- authentication is enabled once this is set.
+alter table users
+    add column totp_secret varchar(64)
+        default null;
+
+-- Single-use codes that replace a one-time password, e.g. after losing the
+-- authenticator app
+create table recovery_codes (
+    id uuid
+        primary key
+        default gen_random_uuid()
+        not null,
+    user_id uuid
+        references users(id)
+        on delete cascade
+        not null,
+    code_hash text
+        not null
+);
+
+create index on recovery_codes (user_id);
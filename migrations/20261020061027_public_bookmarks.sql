@@ -0,0 +1,15 @@
+-- Bookmarks of local users are only public in public lists, we only receive
+-- public bookmarks of remote users
+create view public_bookmarks as
+    select bookmarks.id from bookmarks
+    where not exists (
+            select 1 from users
+            where users.ap_user_id = bookmarks.ap_user_id
+        )
+        or exists (
+            select 1 from links
+            join lists on lists.id = links.src_list_id
+            where links.dest_bookmark_id = bookmarks.id
+                and not lists.private
+        )
+;
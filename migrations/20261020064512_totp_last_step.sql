@@ -0,0 +1,5 @@
+-- The time step of the last one-time password a user logged in with. Codes
+-- of this step or earlier are refused, so an observed code can't be replayed.
+alter table users
+    add column totp_last_step bigint
+        default null;
@@ -0,0 +1,16 @@
+-- Usernames users had before renaming themselves, in lowercase. Nobody else
+-- can sign up with them, so old links and mentions can't reach someone else.
+create table previous_usernames (
+    username varchar(50)
+        primary key
+        not null,
+    user_id uuid
+        references users(id)
+        on delete cascade
+        not null,
+    created_at timestamp with time zone
+        default current_timestamp
+        not null
+);
+
+create index on previous_usernames (user_id);
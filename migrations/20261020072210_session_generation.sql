@@ -0,0 +1,6 @@
+-- Sessions remember the generation they were created in. Changing the password
+-- increases it, which logs out all other sessions of the user.
+alter table users
+    add column session_generation integer
+        default 0
+        not null;
@@ -8,18 +8,20 @@ use axum::{
 use garde::Validate;
 use percent_encoding::utf8_percent_encode;
 use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
 use tower_sessions::Session;
 use url::Url;
 use uuid::Uuid;
 
 use crate::{
-    db::{self, AppTx, User},
+    db::{self, AppTx, User, users::Role},
     forms::users::{CreateOidcUser, CreateUser, Credentials},
     response_error::{ResponseError, ResponseResult},
     server::AppState,
+    totp,
 };
 
-pub fn hash_password(password: &String) -> ResponseResult<String> {
+pub fn hash_password(password: &str) -> ResponseResult<String> {
     let salt =
         argon2::password_hash::SaltString::generate(&mut argon2::password_hash::rand_core::OsRng);
 
@@ -32,12 +34,24 @@ pub fn hash_password(password: &String) -> ResponseResult<String> {
     )
 }
 
+/// A random password for users who can't choose one themselves, e.g. after an
+/// admin reset their password.
+pub fn generate_password() -> String {
+    Uuid::new_v4().simple().to_string()
+}
+
 pub fn verify_password(user: &db::User, password: &str) -> ResponseResult<()> {
     let existing_hash = user
         .password_hash
         .as_ref()
         .context("User has no password set")?;
-    let password_hash = &argon2::PasswordHash::new(existing_hash)
+
+    verify_hash(existing_hash, password)
+}
+
+/// Check a secret against a hash created with [`hash_password`].
+pub fn verify_hash(hash: &str, password: &str) -> ResponseResult<()> {
+    let password_hash = &argon2::PasswordHash::new(hash)
         .map_err(|e| anyhow!("Failed to create password hash: {e}"))?;
 
     argon2::Argon2::default()
@@ -47,16 +61,103 @@ pub fn verify_password(user: &db::User, password: &str) -> ResponseResult<()> {
     Ok(())
 }
 
-pub async fn login(tx: &mut AppTx, session: Session, creds: &Credentials) -> ResponseResult<()> {
+/// How far a login attempt got.
+#[derive(Debug, PartialEq, Eq)]
+pub enum LoginStep {
+    LoggedIn,
+    /// The password was correct, but the user has two-factor authentication
+    /// enabled. Continue with [`login_second_factor`].
+    SecondFactorRequired,
+}
+
+pub async fn login(
+    tx: &mut AppTx,
+    session: Session,
+    creds: &Credentials,
+) -> ResponseResult<LoginStep> {
     let user = db::users::by_username(tx, &creds.username).await?;
 
     verify_password(&user, &creds.password)?;
+    if db::reports::is_suspended(tx, user.ap_user_id).await? {
+        return Err(ResponseError::NotAuthenticated);
+    }
+
+    if user.totp_secret.is_some() {
+        totp::PendingLogin {
+            user_id: user.id,
+            failed_attempts: 0,
+        }
+        .save_in_session(&session)
+        .await?;
+        return Ok(LoginStep::SecondFactorRequired);
+    }
 
     AuthUser::save_in_session(&session, &user).await?;
 
+    Ok(LoginStep::LoggedIn)
+}
+
+/// Finish a login started with [`login`] using a one-time password or a
+/// recovery code.
+pub async fn login_second_factor(
+    tx: &mut AppTx,
+    session: &Session,
+    base_url: &Url,
+    code: &str,
+) -> ResponseResult<()> {
+    let mut pending = totp::PendingLogin::from_session(session)
+        .await?
+        .ok_or(ResponseError::NotAuthenticated)?;
+    let user = db::users::by_id(tx, pending.user_id).await?;
+    if db::reports::is_suspended(tx, user.ap_user_id).await? {
+        return Err(ResponseError::NotAuthenticated);
+    }
+
+    if let Err(e) = verify_second_factor(tx, &user, base_url, code).await {
+        // Make guessing codes impractical
+        pending.failed_attempts += 1;
+        if pending.failed_attempts >= totp::MAX_FAILED_ATTEMPTS {
+            totp::PendingLogin::remove_from_session(session).await?;
+        } else {
+            pending.save_in_session(session).await?;
+        }
+        return Err(e);
+    }
+
+    totp::PendingLogin::remove_from_session(session).await?;
+    AuthUser::save_in_session(session, &user).await?;
+
     Ok(())
 }
 
+/// Check a one-time password that wasn't used before, or use up a recovery
+/// code.
+pub async fn verify_second_factor(
+    tx: &mut AppTx,
+    user: &db::User,
+    base_url: &Url,
+    code: &str,
+) -> ResponseResult<()> {
+    let secret = user
+        .totp_secret
+        .as_ref()
+        .context("User has no two-factor authentication set up")?;
+
+    let totp_accepted =
+        match totp::check_code(secret, &user.username, base_url, code, user.totp_last_step)? {
+            Some(step) => db::users::advance_totp_step(tx, user.id, step).await?,
+            None => false,
+        };
+    // Checking recovery codes means verifying up to one hash per code
+    if totp_accepted
+        || (totp::is_recovery_code(code) && db::recovery_codes::redeem(tx, user.id, code).await?)
+    {
+        Ok(())
+    } else {
+        Err(ResponseError::NotAuthenticated)
+    }
+}
+
 pub async fn create_and_login_temp_user(
     tx: &mut AppTx,
     session: Session,
@@ -110,6 +211,8 @@ pub struct AuthUser {
 struct SessionValue {
     user_id: Uuid,
     ap_user_id: Uuid,
+    #[serde(default)]
+    session_generation: i32,
 }
 
 impl AuthUser {
@@ -119,6 +222,7 @@ impl AuthUser {
         let value = SessionValue {
             user_id: user.id,
             ap_user_id: user.ap_user_id,
+            session_generation: user.session_generation,
         };
         session
             .insert(Self::SESSION_KEY, value)
@@ -128,18 +232,38 @@ impl AuthUser {
         Ok(())
     }
 
-    pub async fn from_session(session: Session) -> ResponseResult<Self> {
+    /// Users suspended or deleted by an admin are logged out, as are sessions
+    /// from before the last password change.
+    pub async fn from_session(session: Session, pool: &PgPool) -> ResponseResult<Self> {
         let value: SessionValue = session
             .get(Self::SESSION_KEY)
             .await
             .context("Failed to load authenticated user id")?
             .ok_or(ResponseError::NotAuthenticated)?;
 
-        Ok(Self {
+        let auth_user = Self {
             user_id: value.user_id,
             ap_user_id: value.ap_user_id,
             session,
-        })
+        };
+
+        if !db::users::is_active(pool, auth_user.user_id, value.session_generation).await? {
+            auth_user.logout().await?;
+            return Err(ResponseError::NotAuthenticated);
+        }
+
+        Ok(auth_user)
+    }
+
+    /// Return [`ResponseError::NotFound`] if the user is not an admin, to avoid
+    /// revealing admin-only pages.
+    pub async fn require_admin(&self, tx: &mut AppTx) -> ResponseResult<()> {
+        let user = db::users::by_id(tx, self.user_id).await?;
+        if user.role != Role::Admin {
+            return Err(ResponseError::NotFound);
+        }
+
+        Ok(())
     }
 
     pub async fn logout(self) -> ResponseResult<()> {
@@ -176,7 +300,7 @@ impl FromRequestParts<AppState> for AuthUser {
             error_redirect.clone()
         })?;
 
-        let auth_user = AuthUser::from_session(session).await;
+        let auth_user = AuthUser::from_session(session, &state.pool).await;
         if let Err(ResponseError::NotAuthenticated) = auth_user {
             return Err(error_redirect);
         }
@@ -199,7 +323,7 @@ impl OptionalFromRequestParts<AppState> for AuthUser {
             .await
             .map_err(|(_status, description)| anyhow!(description))?;
 
-        let auth_user = AuthUser::from_session(session).await;
+        let auth_user = AuthUser::from_session(session, &state.pool).await;
         if let Err(ResponseError::NotAuthenticated) = auth_user {
             return Ok(None);
         }
@@ -1,4 +1,4 @@
-use std::{net::SocketAddr, path::PathBuf};
+use std::{net::SocketAddr, path::PathBuf, str::FromStr};
 
 use anyhow::{Result, anyhow};
 use clap::{Args, Parser, Subcommand};
@@ -9,9 +9,10 @@ use url::Url;
 #[cfg(debug_assertions)]
 use crate::insert_demo_data::insert_demo_data;
 use crate::{
-    db, federation,
+    db::{self, users::Role},
+    federation,
     forms::users::CreateUser,
-    oidc,
+    manage_users, oidc,
     server::{self, AppState},
 };
 
@@ -75,6 +76,14 @@ enum Command {
         /// all data periodically.
         #[clap(long, env, default_value = "false")]
         demo_mode: bool,
+        /// How many days to keep received and sent activities in the
+        /// federation debug log.
+        #[clap(long, env, default_value = "7")]
+        activity_log_retention_days: u16,
+        /// Only serve ActivityPub objects to other instances that sign their
+        /// requests, also known as "secure mode" in Mastodon.
+        #[clap(long, env, default_value = "false")]
+        authorized_fetch: bool,
         #[clap(flatten)]
         oidc_args: Option<OidcArgs>,
     },
@@ -82,6 +91,11 @@ enum Command {
         #[clap(subcommand)]
         command: DbCommand,
     },
+    /// Manage local users, e.g. to recover a locked-out account
+    User {
+        #[clap(subcommand)]
+        command: UserCommand,
+    },
     #[cfg(debug_assertions)]
     /// Put some demo data into the database
     InsertDemoData {
@@ -123,6 +137,40 @@ enum DbCommand {
     Migrate,
 }
 
+#[derive(Subcommand, Debug)]
+pub enum UserCommand {
+    /// Create a user. Prints a random password if none is given.
+    Create {
+        username: String,
+        #[clap(long, env = "USER_PASSWORD", hide_env_values = true)]
+        password: Option<redact::Secret<String>>,
+        /// Let the new user manage the instance.
+        #[clap(long)]
+        admin: bool,
+    },
+    /// List all local users
+    List,
+    /// Delete a user with all of their lists and bookmarks
+    Delete { username: String },
+    /// Replace the password of a user with a random one and print it
+    ResetPassword { username: String },
+    /// Turn off two-factor authentication for a user that lost their
+    /// authenticator app and recovery codes
+    DisableTotp { username: String },
+    /// Make a user an admin, or a regular user again
+    SetRole {
+        username: String,
+        /// Either `user` or `admin`.
+        #[clap(value_parser = Role::from_str)]
+        role: Role,
+    },
+    /// Show what the database knows about a local or remote user.
+    Show {
+        /// A username like `alice`, or a handle like `alice@example.com`.
+        handle: String,
+    },
+}
+
 #[derive(Args, Debug)]
 #[group(required = true, multiple = true)]
 pub struct ListenArgs {
@@ -157,6 +205,8 @@ pub async fn run() -> Result<()> {
             tls_cert,
             tls_key,
             demo_mode,
+            activity_log_retention_days,
+            authorized_fetch,
             oidc_args,
         } => {
             let pool = db::pool(&cli.config.database_url).await?;
@@ -179,7 +229,13 @@ pub async fn run() -> Result<()> {
                 base_url: base_url.clone(),
                 demo_mode,
                 oidc_state,
-                federation_config: federation::config::new_config(pool, base_url.clone()).await?,
+                activity_log_retention: time::Duration::days(activity_log_retention_days.into()),
+                federation_config: federation::config::new_config(
+                    pool,
+                    base_url.clone(),
+                    authorized_fetch,
+                )
+                .await?,
             })
             .await?;
             server::start(listen_address, base_url.clone(), app, tls_cert, tls_key).await?;
@@ -190,6 +246,11 @@ pub async fn run() -> Result<()> {
             let pool = db::pool(&cli.config.database_url).await?;
             db::migrate(&pool, &base_url, None).await?;
         }
+        Command::User { command } => {
+            let pool = db::pool(&cli.config.database_url).await?;
+            db::migrate(&pool, &base_url, None).await?;
+            manage_users::run(&pool, &base_url, command).await?;
+        }
         #[cfg(debug_assertions)]
         Command::InsertDemoData {
             dev_user_credentials,
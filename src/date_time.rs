@@ -6,6 +6,17 @@ pub fn time_to_chrono(time_date: time::OffsetDateTime) -> chrono::DateTime<chron
     )
 }
 
+/// A short, human-readable representation like `2025-02-15 04:01 UTC`.
+pub fn format_utc(time_date: time::OffsetDateTime) -> String {
+    let time_date = time_date.to_offset(time::UtcOffset::UTC);
+    format!(
+        "{} {:02}:{:02} UTC",
+        time_date.date(),
+        time_date.hour(),
+        time_date.minute()
+    )
+}
+
 #[cfg(test)]
 #[allow(clippy::unwrap_used)]
 mod test {
@@ -0,0 +1,179 @@
+use std::str::FromStr;
+
+use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, query, query_as};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use super::AppTx;
+use crate::{forms::activity_log::ActivityLogQuery, response_error::ResponseResult};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    /// Received in our inbox
+    Incoming,
+    /// Delivered to a remote inbox
+    Outgoing,
+}
+
+impl Direction {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Direction::Incoming => "incoming",
+            Direction::Outgoing => "outgoing",
+        }
+    }
+}
+
+impl FromStr for Direction {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "incoming" => Ok(Direction::Incoming),
+            "outgoing" => Ok(Direction::Outgoing),
+            other => Err(anyhow!("Unknown activity direction: {other}")),
+        }
+    }
+}
+
+/// A received or sent activity, kept around to debug federation issues.
+#[derive(Debug)]
+pub struct Entry {
+    #[expect(dead_code)]
+    pub id: Uuid,
+    pub created_at: OffsetDateTime,
+    pub direction: Direction,
+    pub activity_ap_id: Option<String>,
+    pub activity_type: Option<String>,
+    pub actor_ap_id: Option<String>,
+    pub remote_domain: Option<String>,
+    pub signature_valid: Option<bool>,
+    pub http_status: Option<i32>,
+    pub error: Option<String>,
+    pub body: String,
+}
+
+#[derive(FromRow, Debug)]
+struct EntryRow {
+    id: Uuid,
+    created_at: OffsetDateTime,
+    direction: String,
+    activity_ap_id: Option<String>,
+    activity_type: Option<String>,
+    actor_ap_id: Option<String>,
+    remote_domain: Option<String>,
+    signature_valid: Option<bool>,
+    http_status: Option<i32>,
+    error: Option<String>,
+    body: String,
+}
+
+impl TryFrom<EntryRow> for Entry {
+    type Error = anyhow::Error;
+
+    fn try_from(value: EntryRow) -> anyhow::Result<Self> {
+        Ok(Entry {
+            id: value.id,
+            created_at: value.created_at,
+            direction: value.direction.parse()?,
+            activity_ap_id: value.activity_ap_id,
+            activity_type: value.activity_type,
+            actor_ap_id: value.actor_ap_id,
+            remote_domain: value.remote_domain,
+            signature_valid: value.signature_valid,
+            http_status: value.http_status,
+            error: value.error,
+            body: value.body,
+        })
+    }
+}
+
+pub struct Insert {
+    pub direction: Direction,
+    pub activity_ap_id: Option<String>,
+    pub activity_type: Option<String>,
+    pub actor_ap_id: Option<String>,
+    pub remote_domain: Option<String>,
+    pub signature_valid: Option<bool>,
+    pub http_status: Option<i32>,
+    pub error: Option<String>,
+    pub body: String,
+}
+
+pub async fn insert(tx: &mut AppTx, insert: Insert) -> ResponseResult<()> {
+    query!(
+        r"
+        insert into activity_log
+        (
+            direction,
+            activity_ap_id,
+            activity_type,
+            actor_ap_id,
+            remote_domain,
+            signature_valid,
+            http_status,
+            error,
+            body
+        )
+        values ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+        ",
+        insert.direction.as_str(),
+        insert.activity_ap_id,
+        insert.activity_type,
+        insert.actor_ap_id,
+        insert.remote_domain,
+        insert.signature_valid,
+        insert.http_status,
+        insert.error,
+        insert.body,
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+/// The most recent entries matching all given filters.
+pub async fn list(
+    tx: &mut AppTx,
+    filter: &ActivityLogQuery,
+    limit: i64,
+) -> ResponseResult<Vec<Entry>> {
+    let entries = query_as!(
+        EntryRow,
+        r#"
+        select * from activity_log
+        where ($1::varchar is null or remote_domain = $1)
+            and ($2::varchar is null or activity_type = $2)
+        order by created_at desc
+        limit $3
+        "#,
+        filter.domain,
+        filter.activity_type,
+        limit,
+    )
+    .fetch_all(&mut **tx)
+    .await?
+    .into_iter()
+    .map(Entry::try_from)
+    .collect::<anyhow::Result<Vec<_>>>()?;
+
+    Ok(entries)
+}
+
+pub async fn delete_older_than(tx: &mut AppTx, before: OffsetDateTime) -> ResponseResult<u64> {
+    let result = query!(
+        r"
+        delete from activity_log
+        where created_at < $1
+        ",
+        before,
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(result.rows_affected())
+}
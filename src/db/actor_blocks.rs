@@ -0,0 +1,121 @@
+use sqlx::query;
+use url::Url;
+use uuid::Uuid;
+
+use crate::{db::AppTx, response_error::ResponseResult};
+
+pub struct Insert {
+    /// The local user that is blocking
+    pub blocker_id: Uuid,
+    /// The remote user being blocked
+    pub blocked_id: Uuid,
+}
+
+pub async fn insert(tx: &mut AppTx, insert: Insert) -> ResponseResult<()> {
+    query!(
+        r"
+        insert into actor_blocks
+        (
+            blocker_id,
+            blocked_id
+        )
+        values ($1, $2)
+        on conflict (blocker_id, blocked_id)
+            do nothing
+        ",
+        insert.blocker_id,
+        insert.blocked_id,
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn remove(tx: &mut AppTx, insert: Insert) -> ResponseResult<()> {
+    query!(
+        r"
+        delete from actor_blocks
+        where blocker_id = $1 and blocked_id = $2
+        ",
+        insert.blocker_id,
+        insert.blocked_id
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn exists(tx: &mut AppTx, blocker_id: Uuid, blocked_id: Uuid) -> ResponseResult<bool> {
+    let row = query!(
+        r#"
+        select exists (
+            select 1 from actor_blocks
+            where blocker_id = $1 and blocked_id = $2
+        ) as "exists!"
+        "#,
+        blocker_id,
+        blocked_id
+    )
+    .fetch_one(&mut **tx)
+    .await?;
+
+    Ok(row.exists)
+}
+
+/// List the ids of all users blocked by the user with the given `ap_id`.
+pub async fn list_blocked_ids(tx: &mut AppTx, blocker_ap_id: &Url) -> ResponseResult<Vec<Uuid>> {
+    let rows = query!(
+        r"
+        select actor_blocks.blocked_id from actor_blocks
+            join ap_users on ap_users.id = actor_blocks.blocker_id
+        where ap_users.ap_id = $1
+        ",
+        blocker_ap_id.as_str()
+    )
+    .fetch_all(&mut **tx)
+    .await?;
+
+    Ok(rows.into_iter().map(|row| row.blocked_id).collect())
+}
+
+/// Whether a local user blocked the actor with the given `ap_id`, and is
+/// concerned by one of the referenced urls: either by being named directly,
+/// or as the owner of a bookmark or reply.
+pub async fn blocked_by_referenced(
+    tx: &mut AppTx,
+    blocked_ap_id: &Url,
+    referenced: &[String],
+) -> ResponseResult<bool> {
+    let row = query!(
+        r#"
+        select exists (
+            select 1 from actor_blocks
+                join ap_users as blocked on blocked.id = actor_blocks.blocked_id
+                join ap_users as blocker on blocker.id = actor_blocks.blocker_id
+            where blocked.ap_id = $1
+                and (
+                    blocker.ap_id = any($2)
+                    or exists (
+                        select 1 from bookmarks
+                        where bookmarks.ap_id = any($2)
+                            and bookmarks.ap_user_id = blocker.id
+                    )
+                    or exists (
+                        select 1 from replies
+                            join bookmarks on bookmarks.id = replies.bookmark_id
+                        where replies.ap_id = any($2)
+                            and blocker.id in (replies.ap_user_id, bookmarks.ap_user_id)
+                    )
+                )
+        ) as "blocked!"
+        "#,
+        blocked_ap_id.as_str(),
+        referenced
+    )
+    .fetch_one(&mut **tx)
+    .await?;
+
+    Ok(row.blocked)
+}
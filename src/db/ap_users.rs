@@ -1,14 +1,14 @@
 use activitypub_federation::fetch::object_id::ObjectId;
-use sqlx::{FromRow, query_as};
+use sqlx::{FromRow, query, query_as};
 use time::OffsetDateTime;
 use url::Url;
 use uuid::Uuid;
 
 use super::AppTx;
 use crate::{
-    federation::webfinger,
-    forms::ap_users::{CreateApUser, UpdateApUser},
-    response_error::ResponseResult,
+    federation::{person::ActorKind, webfinger},
+    forms::ap_users::{CreateApUser, UpdateApUser, instance_actor_ap_id},
+    response_error::{ResponseError, ResponseResult},
 };
 
 #[derive(FromRow, Debug)]
@@ -21,7 +21,10 @@ pub struct ApUser {
     /// For remote users, it's an arbitrary URL.
     pub ap_id: ObjectId<ApUser>,
     pub username: String,
+    pub kind: ActorKind,
     pub inbox_url: Url,
+    /// For local users, this will be `{base_url}/ap/inbox`.
+    pub shared_inbox_url: Option<Url>,
     pub public_key: String,
 
     /// For local users, this is always present.
@@ -30,6 +33,14 @@ pub struct ApUser {
     pub last_refreshed_at: OffsetDateTime,
     pub display_name: Option<String>,
     pub bio: Option<String>,
+    /// If set, new followers need to be approved by this user.
+    pub manually_approves_followers: bool,
+    pub avatar_url: Option<Url>,
+    pub header_url: Option<Url>,
+    /// Other accounts of the same person
+    pub also_known_as: Vec<Url>,
+    /// The account this user moved to
+    pub moved_to: Option<Url>,
 }
 
 #[derive(FromRow, Debug)]
@@ -38,12 +49,19 @@ struct ApUserRow {
 
     ap_id: String,
     username: String,
+    kind: String,
     inbox_url: String,
+    shared_inbox_url: Option<String>,
     public_key: String,
     private_key: Option<String>,
     last_refreshed_at: OffsetDateTime,
     display_name: Option<String>,
     bio: Option<String>,
+    manually_approves_followers: bool,
+    avatar_url: Option<String>,
+    header_url: Option<String>,
+    also_known_as: Vec<String>,
+    moved_to: Option<String>,
 }
 
 impl TryFrom<ApUserRow> for ApUser {
@@ -52,12 +70,27 @@ impl TryFrom<ApUserRow> for ApUser {
             id: value.id,
             ap_id: value.ap_id.parse()?,
             username: value.username,
+            kind: value.kind.parse()?,
             inbox_url: value.inbox_url.parse()?,
+            shared_inbox_url: value
+                .shared_inbox_url
+                .as_deref()
+                .map(str::parse)
+                .transpose()?,
             public_key: value.public_key,
             private_key: value.private_key.map(redact::Secret::new),
             last_refreshed_at: value.last_refreshed_at,
             display_name: value.display_name,
             bio: value.bio,
+            manually_approves_followers: value.manually_approves_followers,
+            avatar_url: value.avatar_url.as_deref().map(str::parse).transpose()?,
+            header_url: value.header_url.as_deref().map(str::parse).transpose()?,
+            also_known_as: value
+                .also_known_as
+                .iter()
+                .map(|url| url.parse())
+                .collect::<Result<_, _>>()?,
+            moved_to: value.moved_to.as_deref().map(str::parse).transpose()?,
         })
     }
 
@@ -81,9 +114,16 @@ pub async fn insert(tx: &mut AppTx, create_user: CreateApUser) -> ResponseResult
             private_key,
             last_refreshed_at,
             display_name,
-            bio
+            bio,
+            shared_inbox_url,
+            kind,
+            manually_approves_followers,
+            avatar_url,
+            header_url,
+            also_known_as,
+            moved_to
         )
-        values ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+        values ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16)
         returning *
         "#,
         create_user.id,
@@ -95,6 +135,13 @@ pub async fn insert(tx: &mut AppTx, create_user: CreateApUser) -> ResponseResult
         create_user.last_refreshed_at,
         create_user.display_name,
         create_user.bio,
+        create_user.shared_inbox_url.as_ref().map(Url::as_str),
+        create_user.kind.as_str(),
+        create_user.manually_approves_followers,
+        create_user.avatar_url.as_ref().map(Url::as_str),
+        create_user.header_url.as_ref().map(Url::as_str),
+        &urls_to_strings(&create_user.also_known_as),
+        create_user.moved_to.as_ref().map(Url::as_str),
     )
     .fetch_one(&mut **tx)
     .await?
@@ -103,8 +150,6 @@ pub async fn insert(tx: &mut AppTx, create_user: CreateApUser) -> ResponseResult
     Ok(user)
 }
 
-// Currently only used in insert-demo-data script
-#[allow(dead_code)]
 pub async fn update(tx: &mut AppTx, id: Uuid, update: UpdateApUser) -> ResponseResult<ApUser> {
     let user = query_as!(
         ApUserRow,
@@ -126,6 +171,107 @@ pub async fn update(tx: &mut AppTx, id: Uuid, update: UpdateApUser) -> ResponseR
     Ok(user)
 }
 
+pub async fn set_avatar_url(tx: &mut AppTx, id: Uuid, avatar_url: &Url) -> ResponseResult<()> {
+    query!(
+        r"
+        update ap_users set
+            avatar_url = $2
+        where id = $1
+        ",
+        id,
+        avatar_url.as_str()
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn set_header_url(tx: &mut AppTx, id: Uuid, header_url: &Url) -> ResponseResult<()> {
+    query!(
+        r"
+        update ap_users set
+            header_url = $2
+        where id = $1
+        ",
+        id,
+        header_url.as_str()
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn set_also_known_as(
+    tx: &mut AppTx,
+    id: Uuid,
+    also_known_as: &[Url],
+) -> ResponseResult<ApUser> {
+    let user = query_as!(
+        ApUserRow,
+        r#"
+        update ap_users set
+            also_known_as = $2
+        where id = $1
+        returning *
+        "#,
+        id,
+        &urls_to_strings(also_known_as)
+    )
+    .fetch_one(&mut **tx)
+    .await?
+    .try_into()?;
+
+    Ok(user)
+}
+
+pub async fn set_moved_to(tx: &mut AppTx, id: Uuid, moved_to: &Url) -> ResponseResult<ApUser> {
+    let user = query_as!(
+        ApUserRow,
+        r#"
+        update ap_users set
+            moved_to = $2
+        where id = $1
+        returning *
+        "#,
+        id,
+        moved_to.as_str()
+    )
+    .fetch_one(&mut **tx)
+    .await?
+    .try_into()?;
+
+    Ok(user)
+}
+
+fn urls_to_strings(urls: &[Url]) -> Vec<String> {
+    urls.iter().map(ToString::to_string).collect()
+}
+
+pub async fn set_manually_approves_followers(
+    tx: &mut AppTx,
+    id: Uuid,
+    manually_approves_followers: bool,
+) -> ResponseResult<ApUser> {
+    let user = query_as!(
+        ApUserRow,
+        r#"
+        update ap_users set
+            manually_approves_followers = $2
+        where id = $1
+        returning *
+        "#,
+        id,
+        manually_approves_followers
+    )
+    .fetch_one(&mut **tx)
+    .await?
+    .try_into()?;
+
+    Ok(user)
+}
+
 pub async fn upsert(tx: &mut AppTx, create_user: CreateApUser) -> ResponseResult<ApUser> {
     let user = query_as!(
         ApUserRow,
@@ -141,9 +287,16 @@ pub async fn upsert(tx: &mut AppTx, create_user: CreateApUser) -> ResponseResult
             display_name,
             bio,
             -- insert id, but don't update it below
-            id
+            id,
+            shared_inbox_url,
+            kind,
+            manually_approves_followers,
+            avatar_url,
+            header_url,
+            also_known_as,
+            moved_to
         )
-        values ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+        values ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16)
         on conflict(ap_id) do update set
             ap_id = $1,
             username = $2,
@@ -152,7 +305,14 @@ pub async fn upsert(tx: &mut AppTx, create_user: CreateApUser) -> ResponseResult
             private_key = $5,
             last_refreshed_at = $6,
             display_name = $7,
-            bio = $8
+            bio = $8,
+            shared_inbox_url = $10,
+            kind = $11,
+            manually_approves_followers = $12,
+            avatar_url = $13,
+            header_url = $14,
+            also_known_as = $15,
+            moved_to = $16
         returning *
         "#,
         create_user.ap_id.to_string(),
@@ -164,6 +324,13 @@ pub async fn upsert(tx: &mut AppTx, create_user: CreateApUser) -> ResponseResult
         create_user.display_name,
         create_user.bio,
         create_user.id,
+        create_user.shared_inbox_url.as_ref().map(Url::as_str),
+        create_user.kind.as_str(),
+        create_user.manually_approves_followers,
+        create_user.avatar_url.as_ref().map(Url::as_str),
+        create_user.header_url.as_ref().map(Url::as_str),
+        &urls_to_strings(&create_user.also_known_as),
+        create_user.moved_to.as_ref().map(Url::as_str),
     )
     .fetch_one(&mut **tx)
     .await?
@@ -204,6 +371,44 @@ pub async fn read_by_id(tx: &mut AppTx, id: Uuid) -> ResponseResult<ApUser> {
     Ok(user)
 }
 
+pub async fn read_instance_actor(tx: &mut AppTx, base_url: &Url) -> ResponseResult<ApUser> {
+    read_by_ap_id(tx, &instance_actor_ap_id(base_url)?).await
+}
+
+pub async fn create_instance_actor_if_not_exists(
+    tx: &mut AppTx,
+    base_url: &Url,
+) -> ResponseResult<ApUser> {
+    match read_instance_actor(tx, base_url).await {
+        Err(ResponseError::NotFound) => {
+            tracing::info!("Creating instance actor");
+            insert(tx, CreateApUser::new_instance_actor(base_url)?).await
+        }
+        other => other,
+    }
+}
+
+/// Remote servers keep the same actor since its `ap_id` doesn't contain the
+/// username, but they only learn about the new handle with an `Update`.
+pub async fn set_username(tx: &mut AppTx, id: Uuid, username: &str) -> ResponseResult<ApUser> {
+    let user = query_as!(
+        ApUserRow,
+        r#"
+        update ap_users set
+            username = $2
+        where id = $1
+        returning *
+        "#,
+        id,
+        username
+    )
+    .fetch_one(&mut **tx)
+    .await?
+    .try_into()?;
+
+    Ok(user)
+}
+
 /// Since usernames are not unique, always pass in a domain as well.
 /// for local users, just use the configured `base_url`.
 pub async fn read_by_username(
@@ -247,3 +452,159 @@ pub async fn list_followers(tx: &mut AppTx, followed_id: Uuid) -> ResponseResult
 
     Ok(users)
 }
+
+/// List the local users following the given user.
+pub async fn list_local_followers(
+    tx: &mut AppTx,
+    followed_id: Uuid,
+) -> ResponseResult<Vec<ApUser>> {
+    let users = query_as!(
+        ApUserRow,
+        r#"
+        select ap_users.* from follows
+            join ap_users on ap_users.id = follows.follower_id
+        where follows.following_id = $1
+            and exists (
+                select 1 from users
+                where users.ap_user_id = ap_users.id
+            )
+        "#,
+        followed_id
+    )
+    .fetch_all(&mut **tx)
+    .await?
+    .into_iter()
+    .map(ApUser::try_from)
+    .collect::<Result<_, _>>()?;
+
+    Ok(users)
+}
+
+/// List the users waiting for approval to follow the given user.
+pub async fn list_pending_followers(
+    tx: &mut AppTx,
+    followed_id: Uuid,
+) -> ResponseResult<Vec<ApUser>> {
+    let users = query_as!(
+        ApUserRow,
+        r#"
+        select ap_users.* from follow_requests
+            join ap_users on ap_users.id = follow_requests.follower_id
+        where follow_requests.following_id = $1
+        order by follow_requests.created_at desc
+        "#,
+        followed_id
+    )
+    .fetch_all(&mut **tx)
+    .await?
+    .into_iter()
+    .map(ApUser::try_from)
+    .collect::<Result<_, _>>()?;
+
+    Ok(users)
+}
+
+/// Note that we're fetching a known remote user again, unless they were fetched
+/// after `refreshed_before`. Returns `false` for unknown users, too.
+pub async fn claim_refetch(
+    tx: &mut AppTx,
+    ap_id: &Url,
+    refreshed_before: OffsetDateTime,
+) -> ResponseResult<bool> {
+    let row = query!(
+        r"
+        update ap_users
+        set last_refreshed_at = current_timestamp
+        -- Only local users have a private key
+        where ap_id = $1
+            and private_key is null
+            and last_refreshed_at < $2
+        returning id
+        ",
+        ap_id.as_str(),
+        refreshed_before
+    )
+    .fetch_optional(&mut **tx)
+    .await?;
+
+    Ok(row.is_some())
+}
+
+/// List remote users that haven't been fetched since `refreshed_before`,
+/// oldest first.
+pub async fn list_stale_remote(
+    tx: &mut AppTx,
+    refreshed_before: OffsetDateTime,
+    limit: i64,
+) -> ResponseResult<Vec<ApUser>> {
+    let users = query_as!(
+        ApUserRow,
+        r#"
+        select * from ap_users
+        -- Only local users have a private key
+        where private_key is null
+            and last_refreshed_at < $1
+        order by last_refreshed_at asc
+        limit $2
+        "#,
+        refreshed_before,
+        limit
+    )
+    .fetch_all(&mut **tx)
+    .await?
+    .into_iter()
+    .map(ApUser::try_from)
+    .collect::<Result<_, _>>()?;
+
+    Ok(users)
+}
+
+/// Remove a remote user that was deleted on their instance, along with
+/// everything we stored about them.
+pub async fn delete_remote(tx: &mut AppTx, id: Uuid) -> ResponseResult<()> {
+    query!(
+        r"
+        delete from follows
+        where follower_id = $1 or following_id = $1
+        ",
+        id
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    query!(
+        r"
+        delete from links
+        where dest_bookmark_id in (
+            select id from bookmarks
+            where ap_user_id = $1
+        )
+        ",
+        id
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    query!(
+        r"
+        delete from bookmarks
+        where ap_user_id = $1
+        ",
+        id
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    query!(
+        r"
+        delete from ap_users
+        where id = $1
+            and private_key is null
+        ",
+        id
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
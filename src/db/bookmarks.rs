@@ -22,6 +22,12 @@ pub struct Bookmark {
     pub url: String,
     pub title: String,
     pub ap_id: ObjectId<Bookmark>,
+    /// The bookmark this one was saved from
+    pub via_ap_id: Option<ObjectId<Bookmark>>,
+    pub via_ap_user_id: Option<Uuid>,
+    /// Hashtags, in lowercase and without the leading `#`
+    #[serde(default)]
+    pub tags: Vec<String>,
 }
 
 #[derive(FromRow, Debug)]
@@ -33,6 +39,9 @@ struct BookmarkRow {
     url: String,
     title: String,
     ap_id: String,
+    via_ap_id: Option<String>,
+    via_ap_user_id: Option<Uuid>,
+    tags: Vec<String>,
 }
 
 impl TryFrom<BookmarkRow> for Bookmark {
@@ -46,6 +55,9 @@ impl TryFrom<BookmarkRow> for Bookmark {
             url: value.url,
             title: value.title,
             ap_id: value.ap_id.parse()?,
+            via_ap_id: value.via_ap_id.as_deref().map(str::parse).transpose()?,
+            via_ap_user_id: value.via_ap_user_id,
+            tags: value.tags,
         })
     }
 }
@@ -177,9 +189,9 @@ pub async fn upsert_remote(
         (ap_id, id, ap_user_id, url, title)
         values ($1, $2, $3, $4, $5)
         on conflict(ap_id) do update set
-            ap_user_id = $2,
-            url = $3,
-            title = $4
+            ap_user_id = $3,
+            url = $4,
+            title = $5
         returning *
         "#,
         ap_id.inner().as_str(),
@@ -195,6 +207,221 @@ pub async fn upsert_remote(
     Ok(user)
 }
 
+/// Remember which bookmark the given one was saved from.
+pub async fn set_via(
+    tx: &mut AppTx,
+    id: Uuid,
+    via_ap_id: &ObjectId<Bookmark>,
+    via_ap_user_id: Option<Uuid>,
+) -> ResponseResult<Bookmark> {
+    let bookmark = query_as!(
+        BookmarkRow,
+        r#"
+        update bookmarks
+        set via_ap_id = $2, via_ap_user_id = $3
+        where id = $1
+        returning *
+        "#,
+        id,
+        via_ap_id.inner().as_str(),
+        via_ap_user_id,
+    )
+    .fetch_one(&mut **tx)
+    .await?;
+
+    bookmark.try_into()
+}
+
+/// Replace the hashtags of a bookmark.
+pub async fn set_tags(tx: &mut AppTx, id: Uuid, tags: &[String]) -> ResponseResult<Bookmark> {
+    let bookmark = query_as!(
+        BookmarkRow,
+        r#"
+        update bookmarks
+        set tags = $2
+        where id = $1
+        returning *
+        "#,
+        id,
+        tags,
+    )
+    .fetch_one(&mut **tx)
+    .await?;
+
+    bookmark.try_into()
+}
+
+/// Public bookmarks with the given hashtag, newest first.
+pub async fn list_public_by_tag(
+    tx: &mut AppTx,
+    tag: &str,
+    limit: i64,
+) -> ResponseResult<Vec<Bookmark>> {
+    let bookmarks = query_as!(
+        BookmarkRow,
+        r#"
+        select bookmarks.* from bookmarks
+        join public_bookmarks on public_bookmarks.id = bookmarks.id
+        where $1 = any(tags)
+        order by created_at desc
+        limit $2
+        "#,
+        tag,
+        limit,
+    )
+    .fetch_all(&mut **tx)
+    .await?
+    .into_iter()
+    .map(Bookmark::try_from)
+    .collect::<ResponseResult<_>>()?;
+
+    Ok(bookmarks)
+}
+
+/// Public bookmarks of the given user, newest first.
+pub async fn list_public_by_ap_user(
+    tx: &mut AppTx,
+    ap_user_id: Uuid,
+    limit: i64,
+) -> ResponseResult<Vec<Bookmark>> {
+    let bookmarks = query_as!(
+        BookmarkRow,
+        r#"
+        select bookmarks.* from bookmarks
+        join public_bookmarks on public_bookmarks.id = bookmarks.id
+        where bookmarks.ap_user_id = $1
+        order by created_at desc
+        limit $2
+        "#,
+        ap_user_id,
+        limit,
+    )
+    .fetch_all(&mut **tx)
+    .await?
+    .into_iter()
+    .map(Bookmark::try_from)
+    .collect::<ResponseResult<_>>()?;
+
+    Ok(bookmarks)
+}
+
+/// Bookmarks received through relays, most recently relayed first.
+pub async fn list_relayed(tx: &mut AppTx, limit: i64) -> ResponseResult<Vec<Bookmark>> {
+    let bookmarks = query_as!(
+        BookmarkRow,
+        r#"
+        select bookmarks.* from bookmarks
+        join relayed_bookmarks on relayed_bookmarks.bookmark_id = bookmarks.id
+        order by relayed_bookmarks.created_at desc
+        limit $1
+        "#,
+        limit,
+    )
+    .fetch_all(&mut **tx)
+    .await?
+    .into_iter()
+    .map(Bookmark::try_from)
+    .collect::<ResponseResult<_>>()?;
+
+    Ok(bookmarks)
+}
+
+/// Count the people followed by the given user who saved the given bookmark,
+/// either by being its author or by saving it from there.
+pub async fn count_saved_by_followed(
+    tx: &mut AppTx,
+    ap_id: &ObjectId<Bookmark>,
+    follower_id: Uuid,
+) -> ResponseResult<i64> {
+    let saved = query!(
+        r#"
+        select count(distinct bookmarks.ap_user_id) as "count!"
+        from bookmarks
+        inner join follows on follows.following_id = bookmarks.ap_user_id
+        where follows.follower_id = $2
+            and (bookmarks.ap_id = $1 or bookmarks.via_ap_id = $1)
+        "#,
+        ap_id.inner().as_str(),
+        follower_id,
+    )
+    .fetch_one(&mut **tx)
+    .await?;
+
+    Ok(saved.count)
+}
+
+/// A URL saved by one or more trusted users.
+#[derive(Debug)]
+pub struct TrustedSearchResult {
+    /// The most recent bookmark of this URL
+    pub bookmark_id: Uuid,
+    pub url: String,
+    pub title: String,
+    /// How many trusted users saved this URL
+    pub saved_by_count: i64,
+}
+
+/// Search the public bookmarks of users trusted by the given user, optionally
+/// including users trusted by those. URLs saved by more trusted users rank
+/// higher. Remote users don't share whom they trust, so the second step only
+/// follows trust relationships of users on this instance.
+pub async fn search_trusted(
+    tx: &mut AppTx,
+    term: &str,
+    truster_id: Uuid,
+    include_trusted_of_trusted: bool,
+    limit: i64,
+) -> ResponseResult<Vec<TrustedSearchResult>> {
+    let results = query_as!(
+        TrustedSearchResult,
+        r#"
+        with trusted as (
+            select trusted_id as ap_user_id
+            from trusts
+            where truster_id = $2
+            union
+            select second.trusted_id
+            from trusts first
+            join trusts second on second.truster_id = first.trusted_id
+            where $3 and first.truster_id = $2 and second.trusted_id <> $2
+        ),
+        matches as (
+            select bookmarks.*
+            from bookmarks
+            join trusted on trusted.ap_user_id = bookmarks.ap_user_id
+            join public_bookmarks on public_bookmarks.id = bookmarks.id
+            where bookmarks.title ilike $1 or bookmarks.url ilike $1
+        )
+        select
+            (array_agg(id order by created_at desc))[1] as "bookmark_id!",
+            url,
+            (array_agg(title order by created_at desc))[1] as "title!",
+            count(distinct ap_user_id) as "saved_by_count!"
+        from matches
+        group by url
+        order by count(distinct ap_user_id) desc, max(created_at) desc
+        limit $4
+        "#,
+        like_pattern(term),
+        truster_id,
+        include_trusted_of_trusted,
+        limit,
+    )
+    .fetch_all(&mut **tx)
+    .await?;
+
+    Ok(results)
+}
+
+/// Match `term` anywhere, treating `%` and `_` in it literally.
+fn like_pattern(term: &str) -> String {
+    let escaped = term
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    format!("%{escaped}%")
+}
+
 /// Return true if at least one public list points to the given bookmark.
 pub async fn is_public(tx: &mut AppTx, bookmark_id: Uuid) -> ResponseResult<bool> {
     let public_destination_count = query!(
@@ -0,0 +1,287 @@
+use sqlx::{FromRow, query, query_as};
+use time::OffsetDateTime;
+use url::Url;
+use uuid::Uuid;
+
+use crate::{db::AppTx, response_error::ResponseResult};
+
+/// An activity waiting to be delivered to a single inbox.
+#[derive(Debug)]
+pub struct Delivery {
+    pub id: Uuid,
+    pub created_at: OffsetDateTime,
+    /// The local user sending the activity
+    pub actor_id: Uuid,
+    pub activity_ap_id: Url,
+    pub activity: serde_json::Value,
+    pub inbox_url: Url,
+    pub attempts: i32,
+    pub next_attempt_at: OffsetDateTime,
+    pub last_error: Option<String>,
+    /// Set once we gave up on delivering the activity
+    pub failed_at: Option<OffsetDateTime>,
+}
+
+#[derive(FromRow, Debug)]
+struct DeliveryRow {
+    id: Uuid,
+    created_at: OffsetDateTime,
+    actor_id: Uuid,
+    activity_ap_id: String,
+    activity: serde_json::Value,
+    inbox_url: String,
+    attempts: i32,
+    next_attempt_at: OffsetDateTime,
+    last_error: Option<String>,
+    failed_at: Option<OffsetDateTime>,
+}
+
+impl TryFrom<DeliveryRow> for Delivery {
+    type Error = anyhow::Error;
+
+    fn try_from(value: DeliveryRow) -> anyhow::Result<Self> {
+        Ok(Delivery {
+            id: value.id,
+            created_at: value.created_at,
+            actor_id: value.actor_id,
+            activity_ap_id: value.activity_ap_id.parse()?,
+            activity: value.activity,
+            inbox_url: value.inbox_url.parse()?,
+            attempts: value.attempts,
+            next_attempt_at: value.next_attempt_at,
+            last_error: value.last_error,
+            failed_at: value.failed_at,
+        })
+    }
+}
+
+/// An inbox that did not accept our last delivery.
+#[derive(Debug)]
+pub struct InboxFailure {
+    pub inbox_url: String,
+    pub failure_count: i32,
+    pub first_failure_at: OffsetDateTime,
+    pub last_failure_at: OffsetDateTime,
+    pub last_error: String,
+}
+
+pub struct Insert {
+    pub actor_id: Uuid,
+    pub activity_ap_id: Url,
+    pub activity: serde_json::Value,
+    pub inbox_url: Url,
+}
+
+/// Queue a delivery, claiming it for the sender until `claimed_until` so
+/// that it can be delivered right away.
+pub async fn insert(
+    tx: &mut AppTx,
+    insert: Insert,
+    claimed_until: OffsetDateTime,
+) -> ResponseResult<Delivery> {
+    let delivery = query_as!(
+        DeliveryRow,
+        r#"
+        insert into deliveries
+        (
+            actor_id,
+            activity_ap_id,
+            activity,
+            inbox_url,
+            next_attempt_at
+        )
+        values ($1, $2, $3, $4, $5)
+        returning *
+        "#,
+        insert.actor_id,
+        insert.activity_ap_id.as_str(),
+        insert.activity,
+        insert.inbox_url.as_str(),
+        claimed_until,
+    )
+    .fetch_one(&mut **tx)
+    .await?
+    .try_into()?;
+
+    Ok(delivery)
+}
+
+/// Claim deliveries that are due until `claimed_until`, so no one else tries
+/// to deliver them at the same time. If we crash while delivering, the claim
+/// runs out and the delivery is attempted again.
+pub async fn claim_due(
+    tx: &mut AppTx,
+    limit: i64,
+    claimed_until: OffsetDateTime,
+) -> ResponseResult<Vec<Delivery>> {
+    let deliveries = query_as!(
+        DeliveryRow,
+        r#"
+        update deliveries
+        set next_attempt_at = $2
+        where id in (
+            select id from deliveries
+            where failed_at is null
+                and next_attempt_at <= now()
+            order by next_attempt_at
+            limit $1
+            for update skip locked
+        )
+        returning *
+        "#,
+        limit,
+        claimed_until,
+    )
+    .fetch_all(&mut **tx)
+    .await?
+    .into_iter()
+    .map(Delivery::try_from)
+    .collect::<anyhow::Result<Vec<_>>>()?;
+
+    Ok(deliveries)
+}
+
+/// Remove a delivery that succeeded, and forget earlier failures of its inbox.
+pub async fn mark_delivered(tx: &mut AppTx, delivery: &Delivery) -> ResponseResult<()> {
+    query!(
+        r"
+        delete from deliveries
+        where id = $1
+        ",
+        delivery.id,
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    query!(
+        r"
+        delete from inbox_failures
+        where inbox_url = $1
+        ",
+        delivery.inbox_url.as_str(),
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+/// Record a failed attempt and the failure of its inbox. Try again at
+/// `next_attempt_at`, or give up if it's `None`.
+pub async fn mark_attempt_failed(
+    tx: &mut AppTx,
+    delivery: &Delivery,
+    error: &str,
+    next_attempt_at: Option<OffsetDateTime>,
+) -> ResponseResult<()> {
+    query!(
+        r"
+        update deliveries
+        set attempts = attempts + 1,
+            last_error = $2,
+            next_attempt_at = coalesce($3, next_attempt_at),
+            failed_at = case when $3 is null then now() else null end
+        where id = $1
+        ",
+        delivery.id,
+        error,
+        next_attempt_at,
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    query!(
+        r"
+        insert into inbox_failures
+        (
+            inbox_url,
+            failure_count,
+            last_error
+        )
+        values ($1, 1, $2)
+        on conflict (inbox_url)
+            do update set failure_count = inbox_failures.failure_count + 1,
+                last_failure_at = now(),
+                last_error = $2
+        ",
+        delivery.inbox_url.as_str(),
+        error,
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+/// Queue a delivery again, regardless of how often it failed before.
+pub async fn retry(tx: &mut AppTx, id: Uuid) -> ResponseResult<()> {
+    query!(
+        r"
+        update deliveries
+        set attempts = 0,
+            next_attempt_at = now(),
+            failed_at = null
+        where id = $1
+        returning id
+        ",
+        id,
+    )
+    .fetch_one(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn list_pending(tx: &mut AppTx, limit: i64) -> ResponseResult<Vec<Delivery>> {
+    let deliveries = query_as!(
+        DeliveryRow,
+        r#"
+        select * from deliveries
+        where failed_at is null
+        order by next_attempt_at
+        limit $1
+        "#,
+        limit,
+    )
+    .fetch_all(&mut **tx)
+    .await?
+    .into_iter()
+    .map(Delivery::try_from)
+    .collect::<anyhow::Result<Vec<_>>>()?;
+
+    Ok(deliveries)
+}
+
+pub async fn list_failed(tx: &mut AppTx, limit: i64) -> ResponseResult<Vec<Delivery>> {
+    let deliveries = query_as!(
+        DeliveryRow,
+        r#"
+        select * from deliveries
+        where failed_at is not null
+        order by failed_at desc
+        limit $1
+        "#,
+        limit,
+    )
+    .fetch_all(&mut **tx)
+    .await?
+    .into_iter()
+    .map(Delivery::try_from)
+    .collect::<anyhow::Result<Vec<_>>>()?;
+
+    Ok(deliveries)
+}
+
+pub async fn list_inbox_failures(tx: &mut AppTx) -> ResponseResult<Vec<InboxFailure>> {
+    let failures = query_as!(
+        InboxFailure,
+        r#"
+        select * from inbox_failures
+        order by last_failure_at desc
+        "#,
+    )
+    .fetch_all(&mut **tx)
+    .await?;
+
+    Ok(failures)
+}
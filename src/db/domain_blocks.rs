@@ -0,0 +1,191 @@
+use std::str::FromStr;
+
+use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, query, query_as};
+use time::OffsetDateTime;
+use url::Url;
+use uuid::Uuid;
+
+use super::AppTx;
+use crate::{forms::domain_blocks::CreateDomainBlock, response_error::ResponseResult};
+
+/// Ordered from most to least severe.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// Refuse all communication with the domain
+    #[default]
+    Reject,
+    /// Keep existing relationships, but ignore new follows from the domain
+    Silence,
+}
+
+impl Severity {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Severity::Reject => "reject",
+            Severity::Silence => "silence",
+        }
+    }
+}
+
+impl FromStr for Severity {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "reject" => Ok(Severity::Reject),
+            "silence" => Ok(Severity::Silence),
+            other => Err(anyhow!("Unknown domain block severity: {other}")),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct DomainBlock {
+    pub id: Uuid,
+    #[expect(dead_code)]
+    pub created_at: OffsetDateTime,
+    pub domain: String,
+    pub severity: Severity,
+    pub reason: Option<String>,
+}
+
+#[derive(FromRow, Debug)]
+struct DomainBlockRow {
+    id: Uuid,
+    created_at: OffsetDateTime,
+    domain: String,
+    severity: String,
+    reason: Option<String>,
+}
+
+impl TryFrom<DomainBlockRow> for DomainBlock {
+    type Error = anyhow::Error;
+
+    fn try_from(value: DomainBlockRow) -> anyhow::Result<Self> {
+        Ok(DomainBlock {
+            id: value.id,
+            created_at: value.created_at,
+            domain: value.domain,
+            severity: value.severity.parse()?,
+            reason: value.reason,
+        })
+    }
+}
+
+impl DomainBlock {
+    /// Check if this block applies to the given URL, including its subdomains.
+    pub fn matches(&self, url: &Url) -> bool {
+        blocked_domains(url).contains(&self.domain)
+    }
+}
+
+/// All block domains that would apply to the given URL: its host and every
+/// parent domain, each with and without the URL's port. Blocks without a port
+/// apply to all ports.
+fn blocked_domains(url: &Url) -> Vec<String> {
+    let Some(host) = url.host_str() else {
+        return Vec::new();
+    };
+    let mut domains = Vec::new();
+    let mut suffix = host;
+    loop {
+        domains.push(suffix.to_string());
+        if let Some(port) = url.port() {
+            domains.push(format!("{suffix}:{port}"));
+        }
+        match suffix.split_once('.') {
+            Some((_, parent)) => suffix = parent,
+            None => break,
+        }
+    }
+    domains
+}
+
+/// The domain of the given URL, with the port appended if it's not the
+/// default one for the URL's scheme.
+pub fn url_domain(url: &Url) -> Option<String> {
+    let host = url.host_str()?;
+    Some(match url.port() {
+        Some(port) => format!("{host}:{port}"),
+        None => host.to_string(),
+    })
+}
+
+/// Block a domain, or update the existing block for it.
+pub async fn upsert(tx: &mut AppTx, create: CreateDomainBlock) -> ResponseResult<DomainBlock> {
+    let block = query_as!(
+        DomainBlockRow,
+        r#"
+        insert into domain_blocks
+        (domain, severity, reason)
+        values ($1, $2, $3)
+        on conflict (domain) do update set
+            severity = $2,
+            reason = $3
+        returning *
+        "#,
+        create.domain,
+        create.severity.as_str(),
+        create.reason,
+    )
+    .fetch_one(&mut **tx)
+    .await?
+    .try_into()?;
+
+    Ok(block)
+}
+
+pub async fn list(tx: &mut AppTx) -> ResponseResult<Vec<DomainBlock>> {
+    let blocks = query_as!(
+        DomainBlockRow,
+        r#"
+        select * from domain_blocks
+        order by domain
+        "#
+    )
+    .fetch_all(&mut **tx)
+    .await?
+    .into_iter()
+    .map(DomainBlock::try_from)
+    .collect::<Result<_, _>>()?;
+
+    Ok(blocks)
+}
+
+/// Find the most severe block applying to the given URL.
+pub async fn for_url(tx: &mut AppTx, url: &Url) -> ResponseResult<Option<DomainBlock>> {
+    let block = query_as!(
+        DomainBlockRow,
+        r#"
+        select * from domain_blocks
+        where domain = any($1)
+        "#,
+        &blocked_domains(url)
+    )
+    .fetch_all(&mut **tx)
+    .await?
+    .into_iter()
+    .map(DomainBlock::try_from)
+    .collect::<Result<Vec<_>, _>>()?
+    .into_iter()
+    .min_by_key(|block| block.severity);
+
+    Ok(block)
+}
+
+pub async fn delete_by_id(tx: &mut AppTx, id: Uuid) -> ResponseResult<()> {
+    query!(
+        r"
+        delete from domain_blocks
+        where id = $1
+        ",
+        id
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,124 @@
+use sqlx::{FromRow, query, query_as};
+use time::OffsetDateTime;
+use url::Url;
+use uuid::Uuid;
+
+use crate::{db::AppTx, response_error::ResponseResult};
+
+/// A follow that is waiting for approval by the followed user.
+#[derive(Debug)]
+#[expect(dead_code)]
+pub struct FollowRequest {
+    pub id: Uuid,
+    pub created_at: OffsetDateTime,
+    /// The id of the original `Follow` activity
+    pub ap_id: Url,
+    /// The user that wants to follow
+    pub follower_id: Uuid,
+    /// The user that needs to approve the follow
+    pub following_id: Uuid,
+}
+
+#[derive(FromRow, Debug)]
+struct FollowRequestRow {
+    id: Uuid,
+    created_at: OffsetDateTime,
+    ap_id: String,
+    follower_id: Uuid,
+    following_id: Uuid,
+}
+
+impl TryFrom<FollowRequestRow> for FollowRequest {
+    type Error = anyhow::Error;
+
+    fn try_from(value: FollowRequestRow) -> anyhow::Result<Self> {
+        Ok(FollowRequest {
+            id: value.id,
+            created_at: value.created_at,
+            ap_id: value.ap_id.parse()?,
+            follower_id: value.follower_id,
+            following_id: value.following_id,
+        })
+    }
+}
+
+pub struct Insert {
+    pub ap_id: Url,
+    pub follower_id: Uuid,
+    pub following_id: Uuid,
+}
+
+/// Store a follow request. If the follower already requested to follow,
+/// remember the most recent `Follow` activity.
+pub async fn upsert(tx: &mut AppTx, insert: Insert) -> ResponseResult<()> {
+    query!(
+        r"
+        insert into follow_requests
+        (
+            ap_id,
+            follower_id,
+            following_id
+        )
+        values ($1, $2, $3)
+        on conflict (follower_id, following_id)
+            do update set ap_id = $1
+        ",
+        insert.ap_id.as_str(),
+        insert.follower_id,
+        insert.following_id,
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn read(
+    tx: &mut AppTx,
+    follower_id: Uuid,
+    following_id: Uuid,
+) -> ResponseResult<FollowRequest> {
+    let request = query_as!(
+        FollowRequestRow,
+        r#"
+        select * from follow_requests
+        where follower_id = $1 and following_id = $2
+        "#,
+        follower_id,
+        following_id
+    )
+    .fetch_one(&mut **tx)
+    .await?
+    .try_into()?;
+
+    Ok(request)
+}
+
+pub async fn remove(tx: &mut AppTx, follower_id: Uuid, following_id: Uuid) -> ResponseResult<()> {
+    query!(
+        r"
+        delete from follow_requests
+        where follower_id = $1 and following_id = $2
+        ",
+        follower_id,
+        following_id
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn count_for_user(tx: &mut AppTx, following_id: Uuid) -> ResponseResult<i64> {
+    let row = query!(
+        r#"
+        select count(*) as "count!" from follow_requests
+        where following_id = $1
+        "#,
+        following_id
+    )
+    .fetch_one(&mut **tx)
+    .await?;
+
+    Ok(row.count)
+}
@@ -1,4 +1,5 @@
 use sqlx::{prelude::FromRow, query};
+use url::Url;
 use uuid::Uuid;
 
 use crate::{db::AppTx, response_error::ResponseResult};
@@ -56,3 +57,90 @@ pub async fn remove(tx: &mut AppTx, insert: Insert) -> ResponseResult<()> {
 
     Ok(())
 }
+
+pub async fn exists(tx: &mut AppTx, follow: &Insert) -> ResponseResult<bool> {
+    let row = query!(
+        r#"
+        select exists (
+            select 1 from follows
+            where follower_id = $1 and following_id = $2
+        ) as "exists!"
+        "#,
+        follow.follower_id,
+        follow.following_id
+    )
+    .fetch_one(&mut **tx)
+    .await?;
+
+    Ok(row.exists)
+}
+
+/// Make local users that follow `from_id` follow `to_id` instead.
+pub async fn move_local_followers(
+    tx: &mut AppTx,
+    from_id: Uuid,
+    to_id: Uuid,
+) -> ResponseResult<()> {
+    query!(
+        r"
+        update follows
+        set following_id = $2
+        where following_id = $1
+            and exists (
+                select 1 from users
+                where users.ap_user_id = follows.follower_id
+            )
+            -- Skip users that already follow the new account
+            and not exists (
+                select 1 from follows existing
+                where existing.follower_id = follows.follower_id
+                    and existing.following_id = $2
+            )
+        ",
+        from_id,
+        to_id
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    query!(
+        r"
+        delete from follows
+        where following_id = $1
+            and exists (
+                select 1 from users
+                where users.ap_user_id = follows.follower_id
+            )
+        ",
+        from_id
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+/// Stop following the accounts that moved to `moved_to`, once the follow of
+/// the new account was accepted.
+pub async fn remove_moved_from(
+    tx: &mut AppTx,
+    follower_id: Uuid,
+    moved_to: &Url,
+) -> ResponseResult<()> {
+    query!(
+        r"
+        delete from follows
+        where follower_id = $1
+            and following_id in (
+                select id from ap_users
+                where moved_to = $2
+            )
+        ",
+        follower_id,
+        moved_to.as_str()
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
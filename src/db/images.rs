@@ -0,0 +1,102 @@
+use sqlx::{query, query_as};
+use url::Url;
+use uuid::Uuid;
+
+use crate::{db::AppTx, response_error::ResponseResult};
+
+/// Uploaded images are small, so they're stored right in the database.
+pub const MAX_IMAGE_SIZE: usize = 2 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageKind {
+    Avatar,
+    Header,
+}
+
+impl ImageKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ImageKind::Avatar => "avatar",
+            ImageKind::Header => "header",
+        }
+    }
+}
+
+pub struct Image {
+    pub content_type: String,
+    pub data: Vec<u8>,
+}
+
+pub fn url(base_url: &Url, id: Uuid) -> ResponseResult<Url> {
+    Ok(base_url.join("/images/")?.join(&id.to_string())?)
+}
+
+/// Detect the content type of an image from its first bytes, so we don't have
+/// to trust the content type sent along with the upload.
+/// Returns `None` for unsupported formats.
+pub fn detect_content_type(data: &[u8]) -> Option<&'static str> {
+    if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some("image/png")
+    } else if data.starts_with(b"\xff\xd8\xff") {
+        Some("image/jpeg")
+    } else if data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a") {
+        Some("image/gif")
+    } else if data.starts_with(b"RIFF") && data.get(8..12) == Some(b"WEBP".as_slice()) {
+        Some("image/webp")
+    } else {
+        None
+    }
+}
+
+/// Store a new image, replacing the previous image of the same kind for this
+/// user.
+pub async fn replace(
+    tx: &mut AppTx,
+    ap_user_id: Uuid,
+    kind: ImageKind,
+    content_type: &str,
+    data: &[u8],
+) -> ResponseResult<Uuid> {
+    query!(
+        r"
+        delete from images
+        where ap_user_id = $1 and kind = $2
+        ",
+        ap_user_id,
+        kind.as_str()
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    let row = query!(
+        r"
+        insert into images
+        (ap_user_id, kind, content_type, data)
+        values ($1, $2, $3, $4)
+        returning id
+        ",
+        ap_user_id,
+        kind.as_str(),
+        content_type,
+        data
+    )
+    .fetch_one(&mut **tx)
+    .await?;
+
+    Ok(row.id)
+}
+
+pub async fn by_id(tx: &mut AppTx, id: Uuid) -> ResponseResult<Image> {
+    let image = query_as!(
+        Image,
+        r"
+        select content_type, data from images
+        where id = $1
+        ",
+        id
+    )
+    .fetch_one(&mut **tx)
+    .await?;
+
+    Ok(image)
+}
@@ -0,0 +1,136 @@
+use std::collections::BTreeMap;
+
+use sqlx::{query, query_as};
+use time::OffsetDateTime;
+use url::Url;
+
+use super::AppTx;
+use crate::{
+    db::{
+        deliveries,
+        domain_blocks::{self, Severity},
+    },
+    response_error::ResponseResult,
+};
+
+/// Numbers describing the contents of this instance, e.g. for NodeInfo.
+pub struct Stats {
+    pub users: i64,
+    pub local_bookmarks: i64,
+    pub local_lists: i64,
+}
+
+pub async fn stats(tx: &mut AppTx) -> ResponseResult<Stats> {
+    let stats = query_as!(
+        Stats,
+        r#"
+        select
+            (select count(*) from users) as "users!",
+            (
+                select count(*) from bookmarks
+                where exists (
+                    select 1 from users
+                    where users.ap_user_id = bookmarks.ap_user_id
+                )
+            ) as "local_bookmarks!",
+            (
+                select count(*) from lists
+                where exists (
+                    select 1 from users
+                    where users.ap_user_id = lists.ap_user_id
+                )
+            ) as "local_lists!"
+        "#
+    )
+    .fetch_one(&mut **tx)
+    .await?;
+
+    Ok(stats)
+}
+
+/// Another server we know users of.
+#[derive(Debug)]
+pub struct RemoteInstance {
+    pub domain: String,
+    pub user_count: i64,
+    pub bookmark_count: i64,
+    /// When we last fetched the profile of one of its users
+    pub last_refreshed_at: OffsetDateTime,
+    /// Inboxes on this instance that did not accept our last delivery
+    pub failing_inboxes: i64,
+    /// The most severe domain block applying to the instance
+    pub block: Option<Severity>,
+}
+
+/// All instances with known users, those with the most users first.
+pub async fn list_remote(tx: &mut AppTx) -> ResponseResult<Vec<RemoteInstance>> {
+    let rows = query!(
+        r#"
+        select
+            ap_users.ap_id,
+            ap_users.last_refreshed_at,
+            (
+                select count(*) from bookmarks
+                where bookmarks.ap_user_id = ap_users.id
+            ) as "bookmark_count!"
+        from ap_users
+        where private_key is null
+        "#
+    )
+    .fetch_all(&mut **tx)
+    .await?;
+
+    let mut instances: BTreeMap<String, RemoteInstance> = BTreeMap::new();
+    for row in rows {
+        let Some(domain) = row
+            .ap_id
+            .parse::<Url>()
+            .ok()
+            .as_ref()
+            .and_then(domain_blocks::url_domain)
+        else {
+            continue;
+        };
+        let instance = instances
+            .entry(domain.clone())
+            .or_insert_with(|| RemoteInstance {
+                domain,
+                user_count: 0,
+                bookmark_count: 0,
+                last_refreshed_at: row.last_refreshed_at,
+                failing_inboxes: 0,
+                block: None,
+            });
+        instance.user_count += 1;
+        instance.bookmark_count += row.bookmark_count;
+        instance.last_refreshed_at = instance.last_refreshed_at.max(row.last_refreshed_at);
+    }
+
+    for failure in deliveries::list_inbox_failures(tx).await? {
+        let domain = failure
+            .inbox_url
+            .parse::<Url>()
+            .ok()
+            .as_ref()
+            .and_then(domain_blocks::url_domain);
+        if let Some(instance) = domain.and_then(|domain| instances.get_mut(&domain)) {
+            instance.failing_inboxes += 1;
+        }
+    }
+
+    let blocks = domain_blocks::list(tx).await?;
+    let mut instances: Vec<RemoteInstance> = instances.into_values().collect();
+    for instance in &mut instances {
+        let Ok(url) = Url::parse(&format!("https://{}", instance.domain)) else {
+            continue;
+        };
+        instance.block = blocks
+            .iter()
+            .filter(|block| block.matches(&url))
+            .map(|block| block.severity)
+            .min();
+    }
+    instances.sort_by_key(|instance| std::cmp::Reverse(instance.user_count));
+
+    Ok(instances)
+}
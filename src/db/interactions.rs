@@ -0,0 +1,170 @@
+use std::str::FromStr;
+
+use anyhow::anyhow;
+use sqlx::{FromRow, query, query_as};
+use time::OffsetDateTime;
+use url::Url;
+use uuid::Uuid;
+
+use super::AppTx;
+use crate::response_error::{ResponseError, ResponseResult};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    /// Received as a `Like`, also called a "favourite" by Mastodon
+    Like,
+    /// Received as an `Announce`
+    Boost,
+}
+
+impl Kind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Kind::Like => "like",
+            Kind::Boost => "boost",
+        }
+    }
+}
+
+impl FromStr for Kind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "like" => Ok(Kind::Like),
+            "boost" => Ok(Kind::Boost),
+            other => Err(anyhow!("Unknown interaction kind: {other}")),
+        }
+    }
+}
+
+/// A like or boost of a bookmark by a remote user.
+#[derive(Debug, Clone)]
+pub struct Interaction {
+    pub created_at: OffsetDateTime,
+    pub ap_user_id: Uuid,
+    pub bookmark_id: Uuid,
+    pub kind: Kind,
+}
+
+#[derive(FromRow, Debug)]
+struct InteractionRow {
+    created_at: OffsetDateTime,
+    ap_user_id: Uuid,
+    bookmark_id: Uuid,
+    kind: String,
+}
+
+impl TryFrom<InteractionRow> for Interaction {
+    type Error = ResponseError;
+
+    fn try_from(value: InteractionRow) -> Result<Self, Self::Error> {
+        Ok(Interaction {
+            created_at: value.created_at,
+            ap_user_id: value.ap_user_id,
+            bookmark_id: value.bookmark_id,
+            kind: value.kind.parse()?,
+        })
+    }
+}
+
+pub struct Insert {
+    pub ap_id: Url,
+    pub ap_user_id: Uuid,
+    pub bookmark_id: Uuid,
+    pub kind: Kind,
+}
+
+/// Store an interaction. Liking or boosting the same bookmark twice only
+/// remembers the most recent activity.
+pub async fn upsert(tx: &mut AppTx, insert: Insert) -> ResponseResult<()> {
+    query!(
+        r"
+        insert into interactions
+        (ap_id, ap_user_id, bookmark_id, kind)
+        values ($1, $2, $3, $4)
+        on conflict (ap_user_id, bookmark_id, kind)
+            do update set ap_id = $1
+        ",
+        insert.ap_id.as_str(),
+        insert.ap_user_id,
+        insert.bookmark_id,
+        insert.kind.as_str(),
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+/// Remove the interaction created by the activity `ap_id`, as long as it
+/// belongs to `ap_user_id`.
+pub async fn remove(tx: &mut AppTx, ap_id: &Url, ap_user_id: Uuid) -> ResponseResult<()> {
+    query!(
+        r"
+        delete from interactions
+        where ap_id = $1 and ap_user_id = $2
+        ",
+        ap_id.as_str(),
+        ap_user_id
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+/// All interactions with a bookmark, oldest first.
+pub async fn list_by_bookmark(
+    tx: &mut AppTx,
+    bookmark_id: Uuid,
+) -> ResponseResult<Vec<Interaction>> {
+    let interactions = query_as!(
+        InteractionRow,
+        r#"
+        select created_at, ap_user_id, bookmark_id, kind from interactions
+        where bookmark_id = $1
+        order by created_at asc
+        "#,
+        bookmark_id
+    )
+    .fetch_all(&mut **tx)
+    .await?
+    .into_iter()
+    .map(Interaction::try_from)
+    .collect::<ResponseResult<_>>()?;
+
+    Ok(interactions)
+}
+
+/// The most recent interactions with bookmarks of `owner_id`, newest first.
+pub async fn list_recent_for_owner(
+    tx: &mut AppTx,
+    owner_id: Uuid,
+    limit: i64,
+) -> ResponseResult<Vec<Interaction>> {
+    let interactions = query_as!(
+        InteractionRow,
+        r#"
+        select
+            interactions.created_at,
+            interactions.ap_user_id,
+            interactions.bookmark_id,
+            interactions.kind
+        from interactions
+        join bookmarks on bookmarks.id = interactions.bookmark_id
+        where bookmarks.ap_user_id = $1
+        order by interactions.created_at desc
+        limit $2
+        "#,
+        owner_id,
+        limit
+    )
+    .fetch_all(&mut **tx)
+    .await?
+    .into_iter()
+    .map(Interaction::try_from)
+    .collect::<ResponseResult<_>>()?;
+
+    Ok(interactions)
+}
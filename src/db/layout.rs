@@ -1,30 +1,38 @@
 use uuid::Uuid;
 
 use super::AppTx;
-use crate::{db, response_error::ResponseResult};
+use crate::{
+    db::{self, users::Role},
+    response_error::ResponseResult,
+};
 
 pub struct AuthedInfo {
     pub username: String,
     pub lists: Vec<db::List>,
     pub ap_user_id: Uuid,
+    pub is_admin: bool,
+    pub pending_follow_requests: i64,
 }
 
 pub async fn by_ap_user_id(tx: &mut AppTx, ap_user_id: Uuid) -> ResponseResult<AuthedInfo> {
     let lists = db::lists::list_pinned_by_user(tx, ap_user_id).await?;
-    let username = sqlx::query!(
+    let user = sqlx::query!(
         "
-    select ap_users.username from ap_users
-    where id = $1
+    select ap_users.username, users.role from ap_users
+    join users on users.ap_user_id = ap_users.id
+    where ap_users.id = $1
     ",
         ap_user_id
     )
     .fetch_one(&mut **tx)
-    .await?
-    .username;
+    .await?;
+    let pending_follow_requests = db::follow_requests::count_for_user(tx, ap_user_id).await?;
 
     Ok(AuthedInfo {
-        username,
+        username: user.username,
         lists,
         ap_user_id,
+        is_admin: user.role.parse::<Role>()? == Role::Admin,
+        pending_follow_requests,
     })
 }
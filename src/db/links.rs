@@ -226,6 +226,21 @@ pub async fn list_by_list(
     Ok(results)
 }
 
+pub async fn by_id(tx: &mut AppTx, id: Uuid) -> ResponseResult<Link> {
+    let link = query_as!(
+        Link,
+        r#"
+        select * from links
+        where id = $1
+        "#,
+        id
+    )
+    .fetch_one(&mut **tx)
+    .await?;
+
+    Ok(link)
+}
+
 pub async fn delete_by_id(tx: &mut AppTx, id: Uuid) -> ResponseResult<Link> {
     let link = query_as!(
         Link,
@@ -241,3 +256,18 @@ pub async fn delete_by_id(tx: &mut AppTx, id: Uuid) -> ResponseResult<Link> {
 
     Ok(link)
 }
+
+/// Remove a bookmark from all lists, e.g. before deleting it.
+pub async fn delete_by_bookmark(tx: &mut AppTx, bookmark_id: Uuid) -> ResponseResult<()> {
+    query!(
+        r#"
+        delete from links
+        where dest_bookmark_id = $1
+        "#,
+        bookmark_id
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
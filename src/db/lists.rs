@@ -239,6 +239,32 @@ pub async fn list_recent(tx: &mut AppTx, ap_user_id: Uuid) -> ResponseResult<Vec
     Ok(lists)
 }
 
+/// Lists linking to the given bookmark that are public, or belong to the given
+/// user.
+pub async fn list_by_bookmark(
+    tx: &mut AppTx,
+    bookmark_id: Uuid,
+    ap_user_id: Option<Uuid>,
+) -> ResponseResult<Vec<List>> {
+    let lists = query_as!(
+        List,
+        r#"
+            select lists.*
+            from lists
+            inner join links on links.src_list_id = lists.id
+            where links.dest_bookmark_id = $1
+                and (not lists.private or lists.ap_user_id = $2)
+            order by lists.title
+        "#,
+        bookmark_id,
+        ap_user_id,
+    )
+    .fetch_all(&mut **tx)
+    .await?;
+
+    Ok(lists)
+}
+
 pub struct UnpinnedList {
     pub id: Uuid,
 
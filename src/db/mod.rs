@@ -1,10 +1,25 @@
 use anyhow::{Context, Result};
 use sqlx::PgPool;
 
+pub mod activity_log;
+pub mod actor_blocks;
 pub mod all;
 pub mod ap_users;
+pub mod deliveries;
+pub mod domain_blocks;
+pub mod follow_requests;
 pub mod follows;
+pub mod images;
+pub mod instance;
+pub mod interactions;
+pub mod recovery_codes;
+pub mod registrations;
+pub mod relays;
+pub mod remote_lists;
+pub mod replies;
+pub mod reports;
 pub mod run_migrations;
+pub mod trusts;
 pub use ap_users::ApUser;
 pub mod items;
 pub mod layout;
@@ -18,6 +33,7 @@ pub use users::User;
 pub mod bookmarks;
 pub mod migration_hooks;
 pub use bookmarks::Bookmark;
+pub use replies::Reply;
 
 pub async fn migrate(pool: &PgPool, base_url: &Url, up_to_version: Option<i64>) -> Result<()> {
     tracing::info!("Migrating the database...");
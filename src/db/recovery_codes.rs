@@ -0,0 +1,92 @@
+use sqlx::query;
+use uuid::Uuid;
+
+use super::AppTx;
+use crate::{
+    authentication::{hash_password, verify_hash},
+    response_error::ResponseResult,
+};
+
+/// Replace all recovery codes of a user. Only hashes of the codes are stored.
+pub async fn replace(tx: &mut AppTx, user_id: Uuid, codes: &[String]) -> ResponseResult<()> {
+    delete_all(tx, user_id).await?;
+
+    let hashes = codes
+        .iter()
+        .map(|code| hash_password(code))
+        .collect::<ResponseResult<Vec<_>>>()?;
+    query!(
+        r#"
+        insert into recovery_codes (user_id, code_hash)
+        select $1::uuid, code_hash from unnest($2::text[]) as code_hash
+        "#,
+        user_id,
+        &hashes
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+/// Use up a recovery code. Returns false if the code doesn't match any of the
+/// unused codes of the user.
+pub async fn redeem(tx: &mut AppTx, user_id: Uuid, code: &str) -> ResponseResult<bool> {
+    let code = code.trim();
+    let rows = query!(
+        r#"
+        select id, code_hash from recovery_codes
+        where user_id = $1
+        "#,
+        user_id
+    )
+    .fetch_all(&mut **tx)
+    .await?;
+
+    let Some(row) = rows
+        .into_iter()
+        .find(|row| verify_hash(&row.code_hash, code).is_ok())
+    else {
+        return Ok(false);
+    };
+
+    query!(
+        r#"
+        delete from recovery_codes
+        where id = $1
+        "#,
+        row.id
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(true)
+}
+
+pub async fn count(tx: &mut AppTx, user_id: Uuid) -> ResponseResult<i64> {
+    let row = query!(
+        r#"
+        select count(*) as "count!" from recovery_codes
+        where user_id = $1
+        "#,
+        user_id
+    )
+    .fetch_one(&mut **tx)
+    .await?;
+
+    Ok(row.count)
+}
+
+pub async fn delete_all(tx: &mut AppTx, user_id: Uuid) -> ResponseResult<()> {
+    query!(
+        r#"
+        delete from recovery_codes
+        where user_id = $1
+        "#,
+        user_id
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,319 @@
+use std::str::FromStr;
+
+use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, query, query_as};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use super::AppTx;
+use crate::{
+    forms::registrations::EditRegistrationSettings,
+    response_error::{ResponseError, ResponseResult},
+};
+
+/// Who can create an account on the sign-up page.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum RegistrationMode {
+    /// Only admins can create users, e.g. on the command line
+    #[default]
+    Closed,
+    /// Anyone can sign up
+    Open,
+    /// People need an invite code from an admin to sign up
+    Invite,
+    /// Sign-ups need to be approved by an admin
+    Approval,
+}
+
+impl RegistrationMode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RegistrationMode::Closed => "closed",
+            RegistrationMode::Open => "open",
+            RegistrationMode::Invite => "invite",
+            RegistrationMode::Approval => "approval",
+        }
+    }
+}
+
+impl FromStr for RegistrationMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "closed" => Ok(RegistrationMode::Closed),
+            "open" => Ok(RegistrationMode::Open),
+            "invite" => Ok(RegistrationMode::Invite),
+            "approval" => Ok(RegistrationMode::Approval),
+            other => Err(anyhow!("Unknown registration mode: {other}")),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Settings {
+    pub registration_mode: RegistrationMode,
+    /// Usernames nobody can sign up with, in lowercase
+    pub reserved_usernames: Vec<String>,
+    pub min_username_length: i32,
+}
+
+#[derive(FromRow, Debug)]
+struct SettingsRow {
+    registration_mode: String,
+    reserved_usernames: Vec<String>,
+    min_username_length: i32,
+}
+
+impl TryFrom<SettingsRow> for Settings {
+    type Error = ResponseError;
+
+    fn try_from(value: SettingsRow) -> Result<Self, Self::Error> {
+        Ok(Settings {
+            registration_mode: value.registration_mode.parse()?,
+            reserved_usernames: value.reserved_usernames,
+            min_username_length: value.min_username_length,
+        })
+    }
+}
+
+impl Settings {
+    /// Check a username against the rules set by the admins. The rules of
+    /// [`crate::forms::users::CreateUser`] apply on top of these.
+    pub fn username_error(&self, username: &str) -> Option<String> {
+        let length = i32::try_from(username.chars().count()).unwrap_or(i32::MAX);
+        if length < self.min_username_length {
+            return Some(format!(
+                "Usernames need at least {} characters",
+                self.min_username_length
+            ));
+        }
+
+        let lowercase = username.to_lowercase();
+        if self.reserved_usernames.contains(&lowercase) {
+            return Some("This username is reserved".to_string());
+        }
+
+        None
+    }
+}
+
+pub async fn read_settings(tx: &mut AppTx) -> ResponseResult<Settings> {
+    let settings = query_as!(
+        SettingsRow,
+        r#"
+        select registration_mode, reserved_usernames, min_username_length
+        from instance_settings
+        "#
+    )
+    .fetch_one(&mut **tx)
+    .await?;
+
+    settings.try_into()
+}
+
+pub async fn update_settings(
+    tx: &mut AppTx,
+    edit: EditRegistrationSettings,
+) -> ResponseResult<Settings> {
+    let settings = query_as!(
+        SettingsRow,
+        r#"
+        update instance_settings
+        set registration_mode = $1,
+            reserved_usernames = $2,
+            min_username_length = $3
+        returning registration_mode, reserved_usernames, min_username_length
+        "#,
+        edit.registration_mode.as_str(),
+        &edit.reserved_username_list(),
+        edit.min_username_length,
+    )
+    .fetch_one(&mut **tx)
+    .await?;
+
+    settings.try_into()
+}
+
+/// Check if a local user or a pending registration already uses this
+/// username, or a user had it before renaming themselves. Usernames differing
+/// only in case count as the same.
+pub async fn is_username_taken(tx: &mut AppTx, username: &str) -> ResponseResult<bool> {
+    let row = query!(
+        r#"
+        select exists (
+            select 1 from users
+            where lower(username) = lower($1)
+            union all
+            select 1 from registration_requests
+            where lower(username) = lower($1)
+            union all
+            select 1 from previous_usernames
+            where username = lower($1)
+        ) as "exists!"
+        "#,
+        username
+    )
+    .fetch_one(&mut **tx)
+    .await?;
+
+    Ok(row.exists)
+}
+
+#[derive(FromRow, Debug)]
+pub struct Invite {
+    pub id: Uuid,
+    pub created_at: OffsetDateTime,
+    pub code: String,
+    pub used_at: Option<OffsetDateTime>,
+    /// The user that signed up with this invite, unless they were deleted
+    pub used_by_username: Option<String>,
+}
+
+/// Create an invite with a random code.
+pub async fn insert_invite(tx: &mut AppTx, created_by: Uuid) -> ResponseResult<()> {
+    query!(
+        r#"
+        insert into invites (code, created_by)
+        values ($1, $2)
+        "#,
+        Uuid::new_v4().simple().to_string(),
+        created_by
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+/// All invites, newest first.
+pub async fn list_invites(tx: &mut AppTx) -> ResponseResult<Vec<Invite>> {
+    let invites = query_as!(
+        Invite,
+        r#"
+        select invites.id, invites.created_at, invites.code, invites.used_at,
+            users.username as "used_by_username?"
+        from invites
+        left join users on users.id = invites.used_by
+        order by invites.created_at desc
+        "#
+    )
+    .fetch_all(&mut **tx)
+    .await?;
+
+    Ok(invites)
+}
+
+pub async fn is_invite_unused(tx: &mut AppTx, code: &str) -> ResponseResult<bool> {
+    let row = query!(
+        r#"
+        select exists (
+            select 1 from invites
+            where code = $1 and used_at is null
+        ) as "exists!"
+        "#,
+        code
+    )
+    .fetch_one(&mut **tx)
+    .await?;
+
+    Ok(row.exists)
+}
+
+/// Mark an invite as used. Returns false if someone else used it in the
+/// meantime.
+pub async fn redeem_invite(tx: &mut AppTx, code: &str, user_id: Uuid) -> ResponseResult<bool> {
+    let row = query!(
+        r#"
+        update invites
+        set used_by = $2, used_at = current_timestamp
+        where code = $1 and used_at is null
+        returning id
+        "#,
+        code,
+        user_id
+    )
+    .fetch_optional(&mut **tx)
+    .await?;
+
+    Ok(row.is_some())
+}
+
+pub async fn delete_invite(tx: &mut AppTx, id: Uuid) -> ResponseResult<()> {
+    query!(
+        r#"
+        delete from invites
+        where id = $1
+        "#,
+        id
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+#[derive(FromRow, Debug)]
+pub struct Request {
+    pub id: Uuid,
+    pub created_at: OffsetDateTime,
+    pub username: String,
+    pub password_hash: String,
+    pub reason: String,
+}
+
+pub struct InsertRequest {
+    pub username: String,
+    pub password_hash: String,
+    pub reason: String,
+}
+
+pub async fn insert_request(tx: &mut AppTx, insert: InsertRequest) -> ResponseResult<()> {
+    query!(
+        r#"
+        insert into registration_requests (username, password_hash, reason)
+        values ($1, $2, $3)
+        "#,
+        insert.username,
+        insert.password_hash,
+        insert.reason
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+/// Pending registrations, oldest first.
+pub async fn list_requests(tx: &mut AppTx) -> ResponseResult<Vec<Request>> {
+    let requests = query_as!(
+        Request,
+        r#"
+        select * from registration_requests
+        order by created_at
+        "#
+    )
+    .fetch_all(&mut **tx)
+    .await?;
+
+    Ok(requests)
+}
+
+/// Remove a pending registration, either because it was approved or rejected.
+pub async fn delete_request(tx: &mut AppTx, id: Uuid) -> ResponseResult<Request> {
+    let request = query_as!(
+        Request,
+        r#"
+        delete from registration_requests
+        where id = $1
+        returning *
+        "#,
+        id
+    )
+    .fetch_one(&mut **tx)
+    .await?;
+
+    Ok(request)
+}
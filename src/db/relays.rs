@@ -0,0 +1,290 @@
+use std::str::FromStr;
+
+use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, query, query_as};
+use time::OffsetDateTime;
+use url::Url;
+use uuid::Uuid;
+
+use super::AppTx;
+use crate::response_error::{ResponseError, ResponseResult};
+
+/// The two flavors of relays in the fediverse. They differ in what they expect
+/// us to follow, and in how they pass on activities.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Kind {
+    /// We follow the public collection by sending a `Follow` to the relay's
+    /// inbox. The relay forwards activities as they are.
+    #[default]
+    Mastodon,
+    /// We follow the relay's actor, which `Announce`s the objects it relays.
+    Litepub,
+}
+
+impl Kind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Kind::Mastodon => "mastodon",
+            Kind::Litepub => "litepub",
+        }
+    }
+}
+
+impl FromStr for Kind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "mastodon" => Ok(Kind::Mastodon),
+            "litepub" => Ok(Kind::Litepub),
+            other => Err(anyhow!("Unknown relay kind: {other}")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum State {
+    /// Waiting for the relay to answer our follow
+    Pending,
+    Accepted,
+    Rejected,
+}
+
+impl State {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            State::Pending => "pending",
+            State::Accepted => "accepted",
+            State::Rejected => "rejected",
+        }
+    }
+}
+
+impl FromStr for State {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "pending" => Ok(State::Pending),
+            "accepted" => Ok(State::Accepted),
+            "rejected" => Ok(State::Rejected),
+            other => Err(anyhow!("Unknown relay state: {other}")),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Relay {
+    pub id: Uuid,
+    pub created_at: OffsetDateTime,
+    pub kind: Kind,
+    /// The inbox of Mastodon-style relays, or the actor of LitePub relays
+    pub url: Url,
+    pub inbox_url: Url,
+    /// The actor of the relay, if we know it yet
+    pub ap_user_id: Option<Uuid>,
+    /// The `Follow` activity the instance actor subscribed with
+    pub follow_ap_id: Url,
+    pub state: State,
+}
+
+#[derive(FromRow, Debug)]
+struct RelayRow {
+    id: Uuid,
+    created_at: OffsetDateTime,
+    kind: String,
+    url: String,
+    inbox_url: String,
+    ap_user_id: Option<Uuid>,
+    follow_ap_id: String,
+    state: String,
+}
+
+impl TryFrom<RelayRow> for Relay {
+    type Error = ResponseError;
+
+    fn try_from(value: RelayRow) -> Result<Self, Self::Error> {
+        Ok(Relay {
+            id: value.id,
+            created_at: value.created_at,
+            kind: value.kind.parse()?,
+            url: value.url.parse()?,
+            inbox_url: value.inbox_url.parse()?,
+            ap_user_id: value.ap_user_id,
+            follow_ap_id: value.follow_ap_id.parse()?,
+            state: value.state.parse()?,
+        })
+    }
+}
+
+pub struct Insert {
+    pub kind: Kind,
+    pub url: Url,
+    pub inbox_url: Url,
+    pub ap_user_id: Option<Uuid>,
+    pub follow_ap_id: Url,
+}
+
+pub async fn insert(tx: &mut AppTx, insert: Insert) -> ResponseResult<Relay> {
+    let relay = query_as!(
+        RelayRow,
+        r#"
+        insert into relays
+        (kind, url, inbox_url, ap_user_id, follow_ap_id)
+        values ($1, $2, $3, $4, $5)
+        returning *
+        "#,
+        insert.kind.as_str(),
+        insert.url.as_str(),
+        insert.inbox_url.as_str(),
+        insert.ap_user_id,
+        insert.follow_ap_id.as_str(),
+    )
+    .fetch_one(&mut **tx)
+    .await?;
+
+    relay.try_into()
+}
+
+pub async fn list(tx: &mut AppTx) -> ResponseResult<Vec<Relay>> {
+    let relays = query_as!(
+        RelayRow,
+        r#"
+        select * from relays
+        order by created_at
+        "#
+    )
+    .fetch_all(&mut **tx)
+    .await?
+    .into_iter()
+    .map(Relay::try_from)
+    .collect::<ResponseResult<_>>()?;
+
+    Ok(relays)
+}
+
+pub async fn by_id(tx: &mut AppTx, id: Uuid) -> ResponseResult<Relay> {
+    let relay = query_as!(
+        RelayRow,
+        r#"
+        select * from relays
+        where id = $1
+        "#,
+        id
+    )
+    .fetch_one(&mut **tx)
+    .await?;
+
+    relay.try_into()
+}
+
+/// Find the relay we subscribed to with the given `Follow` activity.
+pub async fn by_follow_ap_id(tx: &mut AppTx, follow_ap_id: &Url) -> ResponseResult<Relay> {
+    let relay = query_as!(
+        RelayRow,
+        r#"
+        select * from relays
+        where follow_ap_id = $1
+        "#,
+        follow_ap_id.as_str()
+    )
+    .fetch_one(&mut **tx)
+    .await?;
+
+    relay.try_into()
+}
+
+/// Find the accepted relay subscription of the given actor.
+pub async fn accepted_by_ap_user(tx: &mut AppTx, ap_user_id: Uuid) -> ResponseResult<Relay> {
+    let relay = query_as!(
+        RelayRow,
+        r#"
+        select * from relays
+        where ap_user_id = $1 and state = 'accepted'
+        limit 1
+        "#,
+        ap_user_id
+    )
+    .fetch_one(&mut **tx)
+    .await?;
+
+    relay.try_into()
+}
+
+/// The inboxes of all relays that accepted our subscription.
+pub async fn list_accepted_inboxes(tx: &mut AppTx) -> ResponseResult<Vec<Url>> {
+    let inboxes = query!(
+        r#"
+        select distinct inbox_url from relays
+        where state = 'accepted'
+        "#
+    )
+    .fetch_all(&mut **tx)
+    .await?
+    .into_iter()
+    .map(|row| row.inbox_url.parse())
+    .collect::<Result<_, _>>()?;
+
+    Ok(inboxes)
+}
+
+/// Record the relay's answer to our subscription, along with its actor.
+pub async fn set_state(
+    tx: &mut AppTx,
+    id: Uuid,
+    state: State,
+    ap_user_id: Uuid,
+) -> ResponseResult<()> {
+    query!(
+        r"
+        update relays
+        set state = $2, ap_user_id = $3
+        where id = $1
+        ",
+        id,
+        state.as_str(),
+        ap_user_id
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn delete_by_id(tx: &mut AppTx, id: Uuid) -> ResponseResult<()> {
+    query!(
+        r"
+        delete from relays
+        where id = $1
+        ",
+        id
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+/// Add a bookmark to the discover feed.
+pub async fn insert_relayed_bookmark(
+    tx: &mut AppTx,
+    relay_id: Uuid,
+    bookmark_id: Uuid,
+) -> ResponseResult<()> {
+    query!(
+        r"
+        insert into relayed_bookmarks
+        (relay_id, bookmark_id)
+        values ($1, $2)
+        on conflict (bookmark_id) do nothing
+        ",
+        relay_id,
+        bookmark_id
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
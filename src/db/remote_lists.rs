@@ -0,0 +1,131 @@
+use sqlx::{FromRow, query, query_as};
+use time::OffsetDateTime;
+use url::Url;
+use uuid::Uuid;
+
+use super::AppTx;
+use crate::response_error::{ResponseError, ResponseResult};
+
+/// A public list of a remote user. We only keep what's needed to show it on
+/// their profile, the list itself lives on their instance.
+#[derive(Debug, Clone)]
+pub struct RemoteList {
+    /// The page of the list on its instance
+    pub url: Url,
+    pub title: String,
+    pub content: Option<String>,
+    /// How many bookmarks and lists the list links to
+    pub item_count: i64,
+}
+
+#[derive(FromRow, Debug)]
+struct RemoteListRow {
+    url: String,
+    title: String,
+    content: Option<String>,
+    item_count: i64,
+}
+
+impl TryFrom<RemoteListRow> for RemoteList {
+    type Error = ResponseError;
+
+    fn try_from(value: RemoteListRow) -> Result<Self, Self::Error> {
+        Ok(RemoteList {
+            url: value.url.parse()?,
+            title: value.title,
+            content: value.content,
+            item_count: value.item_count,
+        })
+    }
+}
+
+pub struct Insert {
+    pub ap_id: Url,
+    pub url: Url,
+    pub title: String,
+    pub content: Option<String>,
+    pub item_count: i64,
+}
+
+/// Replace the copies of a remote user's public lists with the given ones.
+pub async fn replace_by_ap_user(
+    tx: &mut AppTx,
+    ap_user_id: Uuid,
+    lists: Vec<Insert>,
+) -> ResponseResult<()> {
+    query!(
+        r"
+        delete from remote_lists
+        where ap_user_id = $1
+        ",
+        ap_user_id
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    for list in lists {
+        query!(
+            r"
+            insert into remote_lists
+            (ap_id, ap_user_id, url, title, content, item_count)
+            values ($1, $2, $3, $4, $5, $6)
+            on conflict (ap_id) do nothing
+            ",
+            list.ap_id.as_str(),
+            ap_user_id,
+            list.url.as_str(),
+            list.title,
+            list.content,
+            list.item_count,
+        )
+        .execute(&mut **tx)
+        .await?;
+    }
+
+    Ok(())
+}
+
+pub async fn list_by_ap_user(tx: &mut AppTx, ap_user_id: Uuid) -> ResponseResult<Vec<RemoteList>> {
+    let lists = query_as!(
+        RemoteListRow,
+        r#"
+        select url, title, content, item_count from remote_lists
+        where ap_user_id = $1
+        order by title
+        "#,
+        ap_user_id
+    )
+    .fetch_all(&mut **tx)
+    .await?
+    .into_iter()
+    .map(RemoteList::try_from)
+    .collect::<ResponseResult<_>>()?;
+
+    Ok(lists)
+}
+
+/// Note that we're copying the outbox and public lists of a remote user now,
+/// unless we started doing so after `fetched_before`. Returns `false` if
+/// someone else is taking care of it.
+pub async fn claim_fetch(
+    tx: &mut AppTx,
+    ap_user_id: Uuid,
+    fetched_before: OffsetDateTime,
+) -> ResponseResult<bool> {
+    let row = query!(
+        r"
+        insert into remote_profile_fetches (ap_user_id)
+        values ($1)
+        on conflict (ap_user_id)
+            do update set fetched_at = current_timestamp
+            where remote_profile_fetches.fetched_at < $2
+        returning ap_user_id
+        ",
+        ap_user_id,
+        fetched_before
+    )
+    .fetch_optional(&mut **tx)
+    .await?;
+
+    Ok(row.is_some())
+}
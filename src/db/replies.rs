@@ -0,0 +1,202 @@
+use activitypub_federation::fetch::object_id::ObjectId;
+use sqlx::{FromRow, query_as};
+use time::OffsetDateTime;
+use url::Url;
+use uuid::Uuid;
+
+use super::AppTx;
+use crate::response_error::{ResponseError, ResponseResult};
+
+/// A comment on a bookmark.
+#[derive(Debug, Clone)]
+pub struct Reply {
+    pub id: Uuid,
+    pub created_at: OffsetDateTime,
+    pub ap_id: ObjectId<Reply>,
+    /// The author
+    pub ap_user_id: Uuid,
+    pub bookmark_id: Uuid,
+    /// The reply this one answers, `None` for replies to the bookmark itself
+    pub parent_id: Option<Uuid>,
+    pub content: String,
+    pub hidden: bool,
+}
+
+#[derive(FromRow, Debug)]
+struct ReplyRow {
+    id: Uuid,
+    created_at: OffsetDateTime,
+    ap_id: String,
+    ap_user_id: Uuid,
+    bookmark_id: Uuid,
+    parent_id: Option<Uuid>,
+    content: String,
+    hidden: bool,
+}
+
+impl TryFrom<ReplyRow> for Reply {
+    type Error = ResponseError;
+
+    fn try_from(value: ReplyRow) -> Result<Self, Self::Error> {
+        Ok(Reply {
+            id: value.id,
+            created_at: value.created_at,
+            ap_id: value.ap_id.parse()?,
+            ap_user_id: value.ap_user_id,
+            bookmark_id: value.bookmark_id,
+            parent_id: value.parent_id,
+            content: value.content,
+            hidden: value.hidden,
+        })
+    }
+}
+
+pub struct Insert {
+    pub ap_user_id: Uuid,
+    pub bookmark_id: Uuid,
+    pub parent_id: Option<Uuid>,
+    pub content: String,
+}
+
+pub async fn insert_local(tx: &mut AppTx, insert: Insert, base_url: &Url) -> ResponseResult<Reply> {
+    let id = Uuid::new_v4();
+    let ap_id = base_url.join("/ap/reply/")?.join(&id.to_string())?;
+    let reply = query_as!(
+        ReplyRow,
+        r#"
+        insert into replies
+        (id, ap_id, ap_user_id, bookmark_id, parent_id, content)
+        values ($1, $2, $3, $4, $5, $6)
+        returning *
+        "#,
+        id,
+        ap_id.as_str(),
+        insert.ap_user_id,
+        insert.bookmark_id,
+        insert.parent_id,
+        insert.content,
+    )
+    .fetch_one(&mut **tx)
+    .await?;
+
+    reply.try_into()
+}
+
+pub async fn upsert_remote(
+    tx: &mut AppTx,
+    ap_id: &ObjectId<Reply>,
+    insert: Insert,
+) -> ResponseResult<Reply> {
+    let reply = query_as!(
+        ReplyRow,
+        r#"
+        insert into replies
+        (ap_id, ap_user_id, bookmark_id, parent_id, content)
+        values ($1, $2, $3, $4, $5)
+        on conflict (ap_id) do update set
+            content = $5
+        returning *
+        "#,
+        ap_id.inner().as_str(),
+        insert.ap_user_id,
+        insert.bookmark_id,
+        insert.parent_id,
+        insert.content,
+    )
+    .fetch_one(&mut **tx)
+    .await?;
+
+    reply.try_into()
+}
+
+pub async fn by_id(tx: &mut AppTx, id: Uuid) -> ResponseResult<Reply> {
+    let reply = query_as!(
+        ReplyRow,
+        r#"
+        select * from replies
+        where id = $1
+        "#,
+        id
+    )
+    .fetch_one(&mut **tx)
+    .await?;
+
+    reply.try_into()
+}
+
+pub async fn by_ap_id(tx: &mut AppTx, ap_id: &Url) -> ResponseResult<Reply> {
+    let reply = query_as!(
+        ReplyRow,
+        r#"
+        select * from replies
+        where ap_id = $1
+        "#,
+        ap_id.as_str()
+    )
+    .fetch_one(&mut **tx)
+    .await?;
+
+    reply.try_into()
+}
+
+/// All replies to a bookmark, oldest first. Hidden replies are only included
+/// if `include_hidden` is set.
+pub async fn list_by_bookmark(
+    tx: &mut AppTx,
+    bookmark_id: Uuid,
+    include_hidden: bool,
+) -> ResponseResult<Vec<Reply>> {
+    let replies = query_as!(
+        ReplyRow,
+        r#"
+        select * from replies
+        where bookmark_id = $1
+            and ($2 or not hidden)
+        order by created_at asc
+        "#,
+        bookmark_id,
+        include_hidden
+    )
+    .fetch_all(&mut **tx)
+    .await?
+    .into_iter()
+    .map(Reply::try_from)
+    .collect::<ResponseResult<_>>()?;
+
+    Ok(replies)
+}
+
+pub async fn set_hidden(tx: &mut AppTx, id: Uuid, hidden: bool) -> ResponseResult<Reply> {
+    let reply = query_as!(
+        ReplyRow,
+        r#"
+        update replies
+        set hidden = $2
+        where id = $1
+        returning *
+        "#,
+        id,
+        hidden
+    )
+    .fetch_one(&mut **tx)
+    .await?;
+
+    reply.try_into()
+}
+
+/// Delete a reply along with all replies to it.
+pub async fn delete_by_id(tx: &mut AppTx, id: Uuid) -> ResponseResult<Reply> {
+    let reply = query_as!(
+        ReplyRow,
+        r#"
+        delete from replies
+        where id = $1
+        returning *
+        "#,
+        id
+    )
+    .fetch_one(&mut **tx)
+    .await?;
+
+    reply.try_into()
+}
@@ -0,0 +1,164 @@
+use sqlx::{FromRow, query, query_as};
+use time::OffsetDateTime;
+use url::Url;
+use uuid::Uuid;
+
+use super::AppTx;
+use crate::response_error::ResponseResult;
+
+#[derive(FromRow, Debug)]
+pub struct Report {
+    pub id: Uuid,
+    pub created_at: OffsetDateTime,
+    /// A local user, or the remote actor that sent the report
+    pub reporter_id: Uuid,
+    pub reported_id: Uuid,
+    /// The reported bookmark, if the report isn't about the whole account
+    pub bookmark_id: Option<Uuid>,
+    pub comment: String,
+}
+
+pub struct Insert {
+    pub ap_id: Url,
+    pub reporter_id: Uuid,
+    pub reported_id: Uuid,
+    pub bookmark_id: Option<Uuid>,
+    pub comment: String,
+}
+
+/// Store a report. Receiving the same `Flag` twice only stores it once.
+pub async fn insert(tx: &mut AppTx, insert: Insert) -> ResponseResult<()> {
+    query!(
+        r"
+        insert into reports
+        (ap_id, reporter_id, reported_id, bookmark_id, comment)
+        values ($1, $2, $3, $4, $5)
+        on conflict (ap_id) do nothing
+        ",
+        insert.ap_id.as_str(),
+        insert.reporter_id,
+        insert.reported_id,
+        insert.bookmark_id,
+        insert.comment,
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn by_id(tx: &mut AppTx, id: Uuid) -> ResponseResult<Report> {
+    let report = query_as!(
+        Report,
+        r#"
+        select id, created_at, reporter_id, reported_id, bookmark_id, comment
+        from reports
+        where id = $1
+        "#,
+        id
+    )
+    .fetch_one(&mut **tx)
+    .await?;
+
+    Ok(report)
+}
+
+/// Reports no admin acted on yet, oldest first.
+pub async fn list_open(tx: &mut AppTx) -> ResponseResult<Vec<Report>> {
+    let reports = query_as!(
+        Report,
+        r#"
+        select id, created_at, reporter_id, reported_id, bookmark_id, comment
+        from reports
+        where resolved_at is null
+        order by created_at
+        "#
+    )
+    .fetch_all(&mut **tx)
+    .await?;
+
+    Ok(reports)
+}
+
+/// Remove a report from the moderation queue, along with all other reports
+/// about the same account, since the action taken applies to those, too.
+pub async fn resolve(tx: &mut AppTx, report: &Report, whole_account: bool) -> ResponseResult<()> {
+    query!(
+        r"
+        update reports
+        set resolved_at = current_timestamp
+        where resolved_at is null
+            and (id = $1 or ($2 and reported_id = $3))
+        ",
+        report.id,
+        whole_account,
+        report.reported_id,
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn suspend(tx: &mut AppTx, ap_user_id: Uuid) -> ResponseResult<()> {
+    query!(
+        r"
+        insert into suspensions (ap_user_id)
+        values ($1)
+        on conflict (ap_user_id) do nothing
+        ",
+        ap_user_id
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn unsuspend(tx: &mut AppTx, ap_user_id: Uuid) -> ResponseResult<()> {
+    query!(
+        r"
+        delete from suspensions
+        where ap_user_id = $1
+        ",
+        ap_user_id
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn is_suspended(tx: &mut AppTx, ap_user_id: Uuid) -> ResponseResult<bool> {
+    let row = query!(
+        r#"
+        select exists (
+            select 1 from suspensions
+            where ap_user_id = $1
+        ) as "exists!"
+        "#,
+        ap_user_id
+    )
+    .fetch_one(&mut **tx)
+    .await?;
+
+    Ok(row.exists)
+}
+
+/// Check if the given ActivityPub id belongs to a suspended actor.
+pub async fn is_suspended_ap_id(tx: &mut AppTx, ap_id: &Url) -> ResponseResult<bool> {
+    let row = query!(
+        r#"
+        select exists (
+            select 1 from suspensions
+            join ap_users on ap_users.id = suspensions.ap_user_id
+            where ap_users.ap_id = $1
+        ) as "exists!"
+        "#,
+        ap_id.as_str()
+    )
+    .fetch_one(&mut **tx)
+    .await?;
+
+    Ok(row.exists)
+}
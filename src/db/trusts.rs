@@ -0,0 +1,82 @@
+use sqlx::query;
+use uuid::Uuid;
+
+use crate::{db::AppTx, response_error::ResponseResult};
+
+pub struct Insert {
+    /// The local user that is trusting
+    pub truster_id: Uuid,
+    /// The local or remote user being trusted
+    pub trusted_id: Uuid,
+}
+
+pub async fn insert(tx: &mut AppTx, insert: Insert) -> ResponseResult<()> {
+    query!(
+        r"
+        insert into trusts
+        (
+            truster_id,
+            trusted_id
+        )
+        values ($1, $2)
+        on conflict (truster_id, trusted_id)
+            do nothing
+        ",
+        insert.truster_id,
+        insert.trusted_id,
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn remove(tx: &mut AppTx, insert: Insert) -> ResponseResult<()> {
+    query!(
+        r"
+        delete from trusts
+        where truster_id = $1 and trusted_id = $2
+        ",
+        insert.truster_id,
+        insert.trusted_id
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn exists(tx: &mut AppTx, truster_id: Uuid, trusted_id: Uuid) -> ResponseResult<bool> {
+    let row = query!(
+        r#"
+        select exists (
+            select 1 from trusts
+            where truster_id = $1 and trusted_id = $2
+        ) as "exists!"
+        "#,
+        truster_id,
+        trusted_id
+    )
+    .fetch_one(&mut **tx)
+    .await?;
+
+    Ok(row.exists)
+}
+
+/// Whether any user on this instance trusts the given user.
+pub async fn is_trusted_locally(tx: &mut AppTx, trusted_id: Uuid) -> ResponseResult<bool> {
+    let row = query!(
+        r#"
+        select exists (
+            select 1 from trusts
+            join users on users.ap_user_id = trusts.truster_id
+            where trusts.trusted_id = $1
+        ) as "exists!"
+        "#,
+        trusted_id
+    )
+    .fetch_one(&mut **tx)
+    .await?;
+
+    Ok(row.exists)
+}
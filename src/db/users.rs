@@ -1,4 +1,7 @@
-use sqlx::{FromRow, query_as};
+use std::str::FromStr;
+
+use anyhow::anyhow;
+use sqlx::{FromRow, PgPool, query, query_as};
 use url::Url;
 use uuid::Uuid;
 
@@ -16,28 +19,98 @@ use crate::{
 pub struct User {
     pub id: Uuid,
 
-    // TODO this is only used in tests so far, which breaks
-    // `#[expect(dead_code)]` for some reason
-    #[allow(dead_code)]
     pub username: String,
 
     // Password-based login data
     pub password_hash: Option<String>,
 
     // SSO-related data
-    #[expect(dead_code)]
     pub email: Option<String>,
-    #[expect(dead_code)]
     pub oidc_id: Option<String>,
 
     // ActivityPub data
     #[allow(dead_code)]
     pub ap_user_id: Uuid,
+
+    pub role: Role,
+
+    /// Show likes and boosts of the user's bookmarks in their notifications
+    pub notify_on_interactions: bool,
+
+    /// Set while two-factor authentication is enabled
+    pub totp_secret: Option<String>,
+    /// The time step of the last one-time password used to log in
+    pub totp_last_step: Option<i64>,
+    /// Sessions from an earlier generation are logged out
+    pub session_generation: i32,
+}
+
+#[derive(FromRow, Debug)]
+struct UserRow {
+    id: Uuid,
+    username: String,
+    password_hash: Option<String>,
+    email: Option<String>,
+    oidc_id: Option<String>,
+    ap_user_id: Uuid,
+    role: String,
+    notify_on_interactions: bool,
+    totp_secret: Option<String>,
+    totp_last_step: Option<i64>,
+    session_generation: i32,
+}
+
+impl TryFrom<UserRow> for User {
+    type Error = anyhow::Error;
+
+    fn try_from(value: UserRow) -> anyhow::Result<Self> {
+        Ok(User {
+            id: value.id,
+            username: value.username,
+            password_hash: value.password_hash,
+            email: value.email,
+            oidc_id: value.oidc_id,
+            ap_user_id: value.ap_user_id,
+            role: value.role.parse()?,
+            notify_on_interactions: value.notify_on_interactions,
+            totp_secret: value.totp_secret,
+            totp_last_step: value.totp_last_step,
+            session_generation: value.session_generation,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    User,
+    /// Can moderate federation, e.g. by blocking other instances
+    Admin,
+}
+
+impl Role {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Role::User => "user",
+            Role::Admin => "admin",
+        }
+    }
+}
+
+impl FromStr for Role {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "user" => Ok(Role::User),
+            "admin" => Ok(Role::Admin),
+            other => Err(anyhow!("Unknown role: {other}")),
+        }
+    }
 }
 
 pub async fn by_oidc_id(tx: &mut AppTx, oidc_id: &str) -> ResponseResult<User> {
     let user = query_as!(
-        User,
+        UserRow,
         r#"
         select * from users
         where oidc_id = $1
@@ -45,7 +118,8 @@ pub async fn by_oidc_id(tx: &mut AppTx, oidc_id: &str) -> ResponseResult<User> {
         oidc_id
     )
     .fetch_one(&mut **tx)
-    .await?;
+    .await?
+    .try_into()?;
 
     Ok(user)
 }
@@ -60,7 +134,7 @@ pub async fn insert_oidc(
     let ap_user = super::ap_users::insert(tx, create_ap_user).await?;
 
     let user = query_as!(
-        User,
+        UserRow,
         r#"
         insert into users
         (email, oidc_id, username, ap_user_id)
@@ -72,7 +146,8 @@ pub async fn insert_oidc(
         ap_user.id
     )
     .fetch_one(&mut **tx)
-    .await?;
+    .await?
+    .try_into()?;
 
     Ok(user)
 }
@@ -83,30 +158,42 @@ pub async fn insert(
     base_url: &Url,
 ) -> ResponseResult<User> {
     let hashed_password = hash_password(&create_user.password)?;
-    let create_ap_user = CreateApUser::new_local(base_url, create_user.username.clone())?;
+    insert_with_password_hash(tx, create_user.username, &hashed_password, base_url).await
+}
+
+/// Create a user whose password was hashed before, e.g. when approving a
+/// registration request.
+pub async fn insert_with_password_hash(
+    tx: &mut AppTx,
+    username: String,
+    password_hash: &str,
+    base_url: &Url,
+) -> ResponseResult<User> {
+    let create_ap_user = CreateApUser::new_local(base_url, username.clone())?;
     let ap_user = super::ap_users::insert(tx, create_ap_user).await?;
 
     let user = query_as!(
-        User,
+        UserRow,
         r#"
         insert into users
         (username, password_hash, ap_user_id)
         values ($1, $2, $3)
         returning *
         "#,
-        create_user.username,
-        hashed_password,
+        username,
+        password_hash,
         ap_user.id
     )
     .fetch_one(&mut **tx)
-    .await?;
+    .await?
+    .try_into()?;
 
     Ok(user)
 }
 
 pub async fn by_username(tx: &mut AppTx, username: &str) -> ResponseResult<User> {
     let user = query_as!(
-        User,
+        UserRow,
         r#"
         select * from users
         where username = $1
@@ -114,14 +201,15 @@ pub async fn by_username(tx: &mut AppTx, username: &str) -> ResponseResult<User>
         username
     )
     .fetch_one(&mut **tx)
-    .await?;
+    .await?
+    .try_into()?;
 
     Ok(user)
 }
 
 pub async fn by_ap_user_id(tx: &mut AppTx, ap_user_id: Uuid) -> ResponseResult<Option<User>> {
     let user = query_as!(
-        User,
+        UserRow,
         r#"
         select * from users
         where ap_user_id = $1
@@ -129,7 +217,9 @@ pub async fn by_ap_user_id(tx: &mut AppTx, ap_user_id: Uuid) -> ResponseResult<O
         ap_user_id
     )
     .fetch_optional(&mut **tx)
-    .await?;
+    .await?
+    .map(User::try_from)
+    .transpose()?;
 
     Ok(user)
 }
@@ -152,5 +242,409 @@ pub async fn create_if_not_exists(
         }
         Err(other) => return Err(other),
     };
-    Ok(actual_user)
+    set_role(tx, actual_user.id, Role::Admin).await
+}
+
+pub async fn by_id(tx: &mut AppTx, id: Uuid) -> ResponseResult<User> {
+    let user = query_as!(
+        UserRow,
+        r#"
+        select * from users
+        where id = $1
+        "#,
+        id
+    )
+    .fetch_one(&mut **tx)
+    .await?
+    .try_into()?;
+
+    Ok(user)
+}
+
+/// Check that the user still exists, isn't suspended and didn't change their
+/// password since the session was created. This runs on every authenticated
+/// request, so it uses a single query outside of a transaction.
+pub async fn is_active(pool: &PgPool, id: Uuid, session_generation: i32) -> ResponseResult<bool> {
+    let row = query!(
+        r#"
+        select exists (
+            select 1 from users
+            where id = $1
+            and session_generation = $2
+            and not exists (
+                select 1 from suspensions
+                where suspensions.ap_user_id = users.ap_user_id
+            )
+        ) as "exists!"
+        "#,
+        id,
+        session_generation
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(row.exists)
+}
+
+pub async fn set_role(tx: &mut AppTx, id: Uuid, role: Role) -> ResponseResult<User> {
+    let user = query_as!(
+        UserRow,
+        r#"
+        update users
+        set role = $2
+        where id = $1
+        returning *
+        "#,
+        id,
+        role.as_str()
+    )
+    .fetch_one(&mut **tx)
+    .await?
+    .try_into()?;
+
+    Ok(user)
+}
+
+pub async fn set_notify_on_interactions(
+    tx: &mut AppTx,
+    id: Uuid,
+    notify_on_interactions: bool,
+) -> ResponseResult<User> {
+    let user = query_as!(
+        UserRow,
+        r#"
+        update users
+        set notify_on_interactions = $2
+        where id = $1
+        returning *
+        "#,
+        id,
+        notify_on_interactions
+    )
+    .fetch_one(&mut **tx)
+    .await?
+    .try_into()?;
+
+    Ok(user)
+}
+
+/// Set a new password, logging out all sessions of the user.
+pub async fn set_password(tx: &mut AppTx, id: Uuid, password: &str) -> ResponseResult<User> {
+    let user = query_as!(
+        UserRow,
+        r#"
+        update users
+        set password_hash = $2,
+            session_generation = session_generation + 1
+        where id = $1
+        returning *
+        "#,
+        id,
+        hash_password(password)?
+    )
+    .fetch_one(&mut **tx)
+    .await?
+    .try_into()?;
+
+    Ok(user)
+}
+
+/// Check if the user used this username before. Usernames differing only in
+/// case count as the same.
+pub async fn had_username(tx: &mut AppTx, id: Uuid, username: &str) -> ResponseResult<bool> {
+    let row = query!(
+        r#"
+        select exists (
+            select 1 from previous_usernames
+            where user_id = $1
+            and username = lower($2)
+        ) as "exists!"
+        "#,
+        id,
+        username
+    )
+    .fetch_one(&mut **tx)
+    .await?;
+
+    Ok(row.exists)
+}
+
+pub async fn set_email(tx: &mut AppTx, id: Uuid, email: Option<&str>) -> ResponseResult<User> {
+    let user = query_as!(
+        UserRow,
+        r#"
+        update users
+        set email = $2
+        where id = $1
+        returning *
+        "#,
+        id,
+        email
+    )
+    .fetch_one(&mut **tx)
+    .await?
+    .try_into()?;
+
+    Ok(user)
+}
+
+/// Link or unlink the OIDC identity used for single sign-on.
+pub async fn set_oidc_id(tx: &mut AppTx, id: Uuid, oidc_id: Option<&str>) -> ResponseResult<User> {
+    let user = query_as!(
+        UserRow,
+        r#"
+        update users
+        set oidc_id = $2
+        where id = $1
+        returning *
+        "#,
+        id,
+        oidc_id
+    )
+    .fetch_one(&mut **tx)
+    .await?
+    .try_into()?;
+
+    Ok(user)
+}
+
+/// Only changes the login name. Use [`super::ap_users::set_username`] to
+/// change the public handle as well. The previous username stays reserved for
+/// this user.
+pub async fn set_username(tx: &mut AppTx, id: Uuid, username: &str) -> ResponseResult<User> {
+    query!(
+        r#"
+        insert into previous_usernames (username, user_id)
+        select lower(username), id from users
+        where id = $1
+        on conflict (username) do nothing
+        "#,
+        id
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    let user = query_as!(
+        UserRow,
+        r#"
+        update users
+        set username = $2
+        where id = $1
+        returning *
+        "#,
+        id,
+        username
+    )
+    .fetch_one(&mut **tx)
+    .await?
+    .try_into()?;
+
+    Ok(user)
+}
+
+/// Enable two-factor authentication by setting a secret, or disable it by
+/// passing `None`. Codes of the previous secret are forgotten either way.
+pub async fn set_totp_secret(
+    tx: &mut AppTx,
+    id: Uuid,
+    totp_secret: Option<&str>,
+) -> ResponseResult<User> {
+    let user = query_as!(
+        UserRow,
+        r#"
+        update users
+        set totp_secret = $2, totp_last_step = null
+        where id = $1
+        returning *
+        "#,
+        id,
+        totp_secret
+    )
+    .fetch_one(&mut **tx)
+    .await?
+    .try_into()?;
+
+    Ok(user)
+}
+
+/// Remember the time step of a one-time password that was just used. Returns
+/// false if a code of the same or a later step was used before, e.g. by a
+/// concurrent request.
+pub async fn advance_totp_step(tx: &mut AppTx, id: Uuid, step: i64) -> ResponseResult<bool> {
+    let row = query!(
+        r#"
+        update users
+        set totp_last_step = $2
+        where id = $1
+            and (totp_last_step is null or totp_last_step < $2)
+        returning id
+        "#,
+        id,
+        step
+    )
+    .fetch_optional(&mut **tx)
+    .await?;
+
+    Ok(row.is_some())
+}
+
+/// A local user, as listed for admins.
+#[derive(Debug)]
+pub struct UserWithStats {
+    pub id: Uuid,
+    pub username: String,
+    pub role: Role,
+    pub bookmark_count: i64,
+    pub list_count: i64,
+    /// Approximate size of the user's bookmarks, lists and images in bytes
+    pub storage_bytes: i64,
+    pub suspended: bool,
+}
+
+#[derive(FromRow, Debug)]
+struct UserWithStatsRow {
+    id: Uuid,
+    username: String,
+    role: String,
+    bookmark_count: i64,
+    list_count: i64,
+    storage_bytes: i64,
+    suspended: bool,
+}
+
+impl TryFrom<UserWithStatsRow> for UserWithStats {
+    type Error = anyhow::Error;
+
+    fn try_from(value: UserWithStatsRow) -> anyhow::Result<Self> {
+        Ok(UserWithStats {
+            id: value.id,
+            username: value.username,
+            role: value.role.parse()?,
+            bookmark_count: value.bookmark_count,
+            list_count: value.list_count,
+            storage_bytes: value.storage_bytes,
+            suspended: value.suspended,
+        })
+    }
+}
+
+pub async fn list_with_stats(tx: &mut AppTx) -> ResponseResult<Vec<UserWithStats>> {
+    let users = query_as!(
+        UserWithStatsRow,
+        r#"
+        select
+            users.id,
+            users.username,
+            users.role,
+            (
+                select count(*) from bookmarks
+                where bookmarks.ap_user_id = users.ap_user_id
+            ) as "bookmark_count!",
+            (
+                select count(*) from lists
+                where lists.ap_user_id = users.ap_user_id
+            ) as "list_count!",
+            (
+                coalesce((
+                    select sum(pg_column_size(bookmarks.*)) from bookmarks
+                    where bookmarks.ap_user_id = users.ap_user_id
+                ), 0)
+                + coalesce((
+                    select sum(pg_column_size(lists.*)) from lists
+                    where lists.ap_user_id = users.ap_user_id
+                ), 0)
+                + coalesce((
+                    select sum(octet_length(images.data)) from images
+                    where images.ap_user_id = users.ap_user_id
+                ), 0)
+            )::bigint as "storage_bytes!",
+            exists (
+                select 1 from suspensions
+                where suspensions.ap_user_id = users.ap_user_id
+            ) as "suspended!"
+        from users
+        order by users.username
+        "#
+    )
+    .fetch_all(&mut **tx)
+    .await?
+    .into_iter()
+    .map(UserWithStats::try_from)
+    .collect::<Result<_, _>>()?;
+
+    Ok(users)
+}
+
+/// Delete a local user with all of their lists and bookmarks. Links of other
+/// users pointing to them are removed as well. Call
+/// [`crate::federation::Delete::prepare_for_person`] first, so that other
+/// instances learn about it.
+pub async fn delete(tx: &mut AppTx, id: Uuid) -> ResponseResult<()> {
+    let user = by_id(tx, id).await?;
+
+    query!(
+        r"
+        delete from links
+        where user_id = $1
+            or src_list_id in (select id from lists where ap_user_id = $2)
+            or dest_list_id in (select id from lists where ap_user_id = $2)
+            or dest_bookmark_id in (select id from bookmarks where ap_user_id = $2)
+        ",
+        user.id,
+        user.ap_user_id
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    query!(
+        r"
+        delete from bookmarks
+        where ap_user_id = $1
+        ",
+        user.ap_user_id
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    query!(
+        r"
+        delete from lists
+        where ap_user_id = $1
+        ",
+        user.ap_user_id
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    query!(
+        r"
+        delete from follows
+        where follower_id = $1 or following_id = $1
+        ",
+        user.ap_user_id
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    query!(
+        r"
+        delete from users
+        where id = $1
+        ",
+        user.id
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    query!(
+        r"
+        delete from ap_users
+        where id = $1
+        ",
+        user.ap_user_id
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
 }
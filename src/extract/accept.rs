@@ -0,0 +1,78 @@
+use std::convert::Infallible;
+
+use axum::{
+    extract::FromRequestParts,
+    http::{HeaderValue, header, request::Parts},
+    response::{IntoResponse, Response},
+};
+
+/// The representation of a resource a client asked for, based on its `Accept`
+/// header. This allows us to serve the HTML page and the ActivityPub object
+/// for the same URL.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Accept {
+    Html,
+    ActivityJson,
+}
+
+const ACTIVITY_JSON_TYPES: [&str; 2] = ["application/activity+json", "application/ld+json"];
+
+impl Accept {
+    /// Whichever of HTML and ActivityPub JSON has the higher `q` weight wins,
+    /// or the one listed first if their weights are equal.
+    /// If neither is listed, e.g. for `*/*`, we default to HTML.
+    fn from_header(value: &str) -> Self {
+        let mut best: Option<(f32, Accept)> = None;
+        for media_range in value.split(',') {
+            let mut params = media_range.split(';');
+            let media_type = params
+                .next()
+                .unwrap_or_default()
+                .trim()
+                .to_ascii_lowercase();
+            let accept = if media_type == "text/html" {
+                Accept::Html
+            } else if ACTIVITY_JSON_TYPES.contains(&media_type.as_str()) {
+                Accept::ActivityJson
+            } else {
+                continue;
+            };
+            let weight = params
+                .find_map(|param| param.trim().strip_prefix("q="))
+                .map_or(Ok(1.0), |q| q.trim().parse::<f32>())
+                .unwrap_or(0.0);
+            if weight > 0.0 && best.is_none_or(|(best_weight, _)| weight > best_weight) {
+                best = Some((weight, accept));
+            }
+        }
+        best.map_or(Accept::Html, |(_, accept)| accept)
+    }
+}
+
+impl<S> FromRequestParts<S> for Accept
+where
+    S: Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let accept = parts
+            .headers
+            .get_all(header::ACCEPT)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .collect::<Vec<_>>()
+            .join(",");
+
+        Ok(Self::from_header(&accept))
+    }
+}
+
+/// Tell caches that the response depends on the `Accept` header.
+pub fn negotiated(response: impl IntoResponse) -> Response {
+    let mut response = response.into_response();
+    response
+        .headers_mut()
+        .append(header::VARY, HeaderValue::from_static("accept"));
+    response
+}
@@ -3,7 +3,9 @@ use axum::{extract::FromRequestParts, http::request::Parts};
 
 use crate::{db::AppTx, response_error::ResponseError, server::AppState};
 
+pub mod accept;
 pub mod qs_form;
+pub mod signed_fetch;
 pub struct Tx(pub AppTx);
 
 impl FromRequestParts<AppState> for Tx {
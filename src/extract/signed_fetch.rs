@@ -0,0 +1,65 @@
+use std::convert::Infallible;
+
+use activitypub_federation::http_signatures::signing_actor;
+use axum::{
+    extract::FromRequestParts,
+    http::{HeaderMap, Method, Uri, request::Parts},
+};
+
+use crate::{
+    db::{self, domain_blocks::Severity},
+    federation,
+    response_error::{ResponseError, ResponseResult},
+};
+
+/// The parts of a request needed to check its HTTP signature. Other instances
+/// sign their requests for our ActivityPub objects with the key of one of
+/// their actors.
+pub struct SignedFetch {
+    method: Method,
+    uri: Uri,
+    headers: HeaderMap,
+}
+
+impl<S> FromRequestParts<S> for SignedFetch
+where
+    S: Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(Self {
+            method: parts.method.clone(),
+            uri: parts.uri.clone(),
+            headers: parts.headers.clone(),
+        })
+    }
+}
+
+impl SignedFetch {
+    /// In authorized fetch mode, only allow requests signed by an actor on a
+    /// domain that wasn't rejected by an admin. Without authorized fetch, all
+    /// requests are allowed.
+    pub async fn verify(&self, data: &federation::Data) -> ResponseResult<()> {
+        if !data.authorized_fetch {
+            return Ok(());
+        }
+
+        let actor = signing_actor::<db::ApUser, _>(&self.headers, &self.method, &self.uri, data)
+            .await
+            .map_err(|e| {
+                tracing::debug!("Refusing unsigned fetch: {e:?}");
+                ResponseError::Unauthorized
+            })?;
+
+        // We might know the actor from before their domain was blocked, in
+        // which case it wasn't fetched again
+        let mut tx = data.db_pool.begin().await?;
+        let block = db::domain_blocks::for_url(&mut tx, actor.ap_id.inner()).await?;
+        if block.is_some_and(|block| block.severity == Severity::Reject) {
+            return Err(ResponseError::Unauthorized);
+        }
+
+        Ok(())
+    }
+}
@@ -1,12 +1,17 @@
 use activitypub_federation::{
-    config::Data, fetch::object_id::ObjectId, kinds::activity::AcceptType, traits::ActivityHandler,
+    config::Data,
+    fetch::object_id::ObjectId,
+    kinds::{activity::AcceptType, public},
+    protocol::verification::{verify_is_remote_object, verify_urls_match},
+    traits::ActivityHandler,
 };
+use anyhow::anyhow;
 use serde::{Deserialize, Serialize};
 use url::Url;
 
 use crate::{
-    db,
-    federation::{activity, follow::Follow},
+    db::{self, relays},
+    federation::{activity, follow::Follow, relay},
     response_error::{ResponseError, ResponseResult},
 };
 
@@ -53,11 +58,40 @@ impl ActivityHandler for Accept {
         self.actor.inner()
     }
 
-    async fn verify(&self, _data: &Data<Self::DataType>) -> Result<(), Self::Error> {
+    async fn verify(&self, data: &Data<Self::DataType>) -> Result<(), Self::Error> {
+        // Only the followed user can accept a follow. Relays accept follows of
+        // the public collection, which we check when receiving.
+        if *self.object.object.inner() != public() {
+            verify_urls_match(self.actor.inner(), self.object.object.inner())?;
+        }
+        verify_is_remote_object(&self.actor, data)?;
         Ok(())
     }
 
-    async fn receive(self, _data: &Data<Self::DataType>) -> Result<(), Self::Error> {
-        Err(ResponseError::NotFound)
+    async fn receive(self, data: &Data<Self::DataType>) -> Result<(), Self::Error> {
+        if relay::receive_answer(&self.object, &self.actor, relays::State::Accepted, data).await? {
+            return Ok(());
+        }
+        if *self.object.object.inner() == public() {
+            return Err(anyhow!("Unknown relay subscription").into());
+        }
+
+        let follower = self.object.actor.dereference_local(data).await?;
+        let following = self.actor.dereference(data).await?;
+
+        let mut tx = data.db_pool.begin().await?;
+        db::follows::upsert(
+            &mut tx,
+            db::follows::Insert {
+                follower_id: follower.id,
+                following_id: following.id,
+            },
+        )
+        .await?;
+        // Switch over from the accounts that moved here
+        db::follows::remove_moved_from(&mut tx, follower.id, following.ap_id.inner()).await?;
+        tx.commit().await?;
+
+        Ok(())
     }
 }
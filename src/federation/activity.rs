@@ -1,15 +1,18 @@
 use std::fmt::Debug;
 
-use activitypub_federation::{
-    activity_queue::queue_activity,
-    protocol::context::WithContext,
-    traits::{ActivityHandler, Actor},
-};
+use activitypub_federation::traits::{ActivityHandler, Actor};
 use serde::Serialize;
 use url::Url;
 use uuid::Uuid;
 
-use crate::{db, federation::context::Data};
+use crate::{
+    db::{self, domain_blocks::Severity},
+    federation::{
+        context::{Data, with_context},
+        delivery,
+    },
+    response_error::ResponseError,
+};
 
 pub async fn send<Activity, ActorType: Actor>(
     actor: &ActorType,
@@ -19,15 +22,106 @@ pub async fn send<Activity, ActorType: Actor>(
 ) -> Result<(), <Activity as ActivityHandler>::Error>
 where
     Activity: ActivityHandler + Serialize + Debug + Send + Sync,
-    <Activity as ActivityHandler>::Error: From<activitypub_federation::error::Error>,
+    <Activity as ActivityHandler>::Error:
+        From<activitypub_federation::error::Error> + From<ResponseError>,
 {
-    let activity = WithContext::new_default(activity);
-    let inboxes = recipients
+    send_to_inboxes(actor, activity, recipients, Vec::new(), context).await
+}
+
+/// Like [`send`], but also deliver the activity to all relays the instance is
+/// subscribed to. Only use this for public activities.
+pub async fn send_public<Activity, ActorType: Actor>(
+    actor: &ActorType,
+    activity: Activity,
+    recipients: &[&db::ApUser],
+    context: &Data,
+) -> Result<(), <Activity as ActivityHandler>::Error>
+where
+    Activity: ActivityHandler + Serialize + Debug + Send + Sync,
+    <Activity as ActivityHandler>::Error:
+        From<activitypub_federation::error::Error> + From<ResponseError>,
+{
+    let mut tx = context.db_pool.begin().await.map_err(ResponseError::from)?;
+    let relay_inboxes = db::relays::list_accepted_inboxes(&mut tx).await?;
+    drop(tx);
+
+    send_to_inboxes(actor, activity, recipients, relay_inboxes, context).await
+}
+
+/// Like [`send`], but prepare the deliveries without the queue, since queued
+/// deliveries are removed along with their actor. Only use this for the last
+/// activity of a local user that is about to be deleted, and deliver it once
+/// the deletion is committed.
+pub async fn prepare_unqueued<Activity, ActorType: Actor>(
+    actor: &ActorType,
+    activity: Activity,
+    recipients: &[&db::ApUser],
+    context: &Data,
+) -> Result<delivery::Unqueued, ResponseError>
+where
+    Activity: ActivityHandler + Serialize + Debug + Send + Sync,
+{
+    let (sender, inboxes) = inboxes(actor, recipients, Vec::new(), context).await?;
+    delivery::Unqueued::prepare(&with_context(activity), &sender, inboxes, context).await
+}
+
+async fn send_to_inboxes<Activity, ActorType: Actor>(
+    actor: &ActorType,
+    activity: Activity,
+    recipients: &[&db::ApUser],
+    extra_inboxes: Vec<Url>,
+    context: &Data,
+) -> Result<(), <Activity as ActivityHandler>::Error>
+where
+    Activity: ActivityHandler + Serialize + Debug + Send + Sync,
+    <Activity as ActivityHandler>::Error:
+        From<activitypub_federation::error::Error> + From<ResponseError>,
+{
+    let (sender, inboxes) = inboxes(actor, recipients, extra_inboxes, context).await?;
+    delivery::enqueue(&with_context(activity), sender.id, inboxes, context).await?;
+    Ok(())
+}
+
+/// The sender along with the inboxes of all recipients that should get the
+/// activity, leaving out local users, blocked users and rejected domains.
+async fn inboxes<ActorType: Actor>(
+    actor: &ActorType,
+    recipients: &[&db::ApUser],
+    extra_inboxes: Vec<Url>,
+    context: &Data,
+) -> Result<(db::ApUser, Vec<Url>), ResponseError> {
+    let mut tx = context.db_pool.begin().await?;
+    let rejected_domains = db::domain_blocks::list(&mut tx)
+        .await?
+        .into_iter()
+        .filter(|block| block.severity == Severity::Reject)
+        .collect::<Vec<_>>();
+    let blocked_ids = db::actor_blocks::list_blocked_ids(&mut tx, &actor.id()).await?;
+    let sender = db::ap_users::read_by_ap_id(&mut tx, &actor.id()).await?;
+    drop(tx);
+
+    let mut inboxes: Vec<Url> = recipients
         .iter()
+        // Local users don't need to be notified through their inbox
+        .filter(|ap_user| !ap_user.ap_id.is_local(context))
+        .filter(|ap_user| !blocked_ids.contains(&ap_user.id))
+        .filter(|ap_user| {
+            !rejected_domains
+                .iter()
+                .any(|block| block.matches(ap_user.ap_id.inner()))
+        })
         .map(|ap_user| ap_user.shared_inbox_or_inbox())
+        .chain(
+            extra_inboxes
+                .into_iter()
+                .filter(|inbox| !rejected_domains.iter().any(|block| block.matches(inbox))),
+        )
         .collect();
-    queue_activity(&activity, actor, inboxes, context).await?;
-    Ok(())
+    // Recipients on the same instance usually share an inbox, only deliver once
+    inboxes.sort();
+    inboxes.dedup();
+
+    Ok((sender, inboxes))
 }
 
 pub fn generate_id(context: &Data) -> Result<Url, url::ParseError> {
@@ -0,0 +1,177 @@
+//! Keep a log of received and sent activities for a while, so admins can debug
+//! problems with other fediverse software.
+
+use std::time::Duration;
+
+use activitypub_federation::error::Error;
+use axum::http::StatusCode;
+use sqlx::PgPool;
+use time::OffsetDateTime;
+use url::Url;
+
+use crate::{
+    db::{
+        self,
+        activity_log::{Direction, Insert},
+        deliveries::Delivery,
+        domain_blocks::url_domain,
+    },
+    response_error::{ResponseError, ResponseResult},
+};
+
+/// How often to delete entries older than the retention period
+const PRUNE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+/// Anyone can post to our inbox, so we only keep the start of activities that
+/// we didn't accept
+const MAX_UNVERIFIED_BODY_LENGTH: usize = 4 * 1024;
+/// The length of the `varchar` columns of the log
+const MAX_FIELD_LENGTH: usize = 255;
+
+pub async fn periodically_prune(pool: PgPool, retention: time::Duration) {
+    let mut interval = tokio::time::interval(PRUNE_INTERVAL);
+
+    loop {
+        interval.tick().await;
+        if let Err(e) = prune(&pool, retention).await {
+            tracing::error!("Failed to prune activity log: {e:?}");
+        }
+    }
+}
+
+async fn prune(pool: &PgPool, retention: time::Duration) -> ResponseResult<()> {
+    let mut tx = pool.begin().await?;
+    let deleted =
+        db::activity_log::delete_older_than(&mut tx, OffsetDateTime::now_utc() - retention).await?;
+    tx.commit().await?;
+    tracing::debug!("Pruned {deleted} activity log entries");
+
+    Ok(())
+}
+
+/// Record an activity received in one of our inboxes, along with our response.
+pub async fn record_incoming(pool: &PgPool, body: &[u8], result: &ResponseResult<()>) {
+    let body = String::from_utf8_lossy(body);
+    let fields = ActivityFields::parse(&body);
+    let signature_valid = match result {
+        Ok(()) => Some(true),
+        Err(ResponseError::FederationError(
+            Error::ActivitySignatureInvalid | Error::ActivityBodyDigestInvalid,
+        )) => Some(false),
+        Err(_) => None,
+    };
+    let (http_status, error) = match result {
+        Ok(()) => (StatusCode::OK, None),
+        Err(e) => (e.status_code(), Some(format!("{e:?}"))),
+    };
+    let body = if result.is_ok() {
+        body.into_owned()
+    } else {
+        truncate(&body, MAX_UNVERIFIED_BODY_LENGTH)
+    };
+
+    record(
+        pool,
+        Insert {
+            direction: Direction::Incoming,
+            remote_domain: fields
+                .actor
+                .as_deref()
+                .and_then(|actor| Url::parse(actor).ok())
+                .and_then(|actor| url_domain(&actor))
+                .map(|domain| truncate(&domain, MAX_FIELD_LENGTH)),
+            activity_ap_id: fields.id,
+            activity_type: fields.kind,
+            actor_ap_id: fields.actor,
+            signature_valid,
+            http_status: Some(i32::from(http_status.as_u16())),
+            error,
+            body,
+        },
+    )
+    .await;
+}
+
+/// Record an attempt to deliver an activity to a remote inbox.
+pub async fn record_outgoing(pool: &PgPool, delivery: &Delivery, result: &ResponseResult<()>) {
+    let body = delivery.activity.to_string();
+    let fields = ActivityFields::parse(&body);
+
+    record(
+        pool,
+        Insert {
+            direction: Direction::Outgoing,
+            activity_ap_id: fields.id,
+            activity_type: fields.kind,
+            actor_ap_id: fields.actor,
+            remote_domain: url_domain(&delivery.inbox_url),
+            signature_valid: None,
+            http_status: delivery_status(result).map(|status| i32::from(status.as_u16())),
+            error: result.as_ref().err().map(|e| format!("{e:?}")),
+            body,
+        },
+    )
+    .await;
+}
+
+/// The federation library doesn't hand us the response of the remote inbox.
+/// Deliveries it gives up on because of the response fail with
+/// [`Error::Other`], naming the status, e.g. "... after failure with status
+/// 503 Service Unavailable: ...". Other errors happen before any response.
+fn delivery_status(result: &ResponseResult<()>) -> Option<StatusCode> {
+    let message = match result {
+        Ok(()) => return Some(StatusCode::OK),
+        Err(ResponseError::FederationError(Error::Other(message))) => message,
+        Err(_) => return None,
+    };
+    let (_, status) = message.split_once(" with status ")?;
+    let code = status.get(..3)?.parse().ok()?;
+    StatusCode::from_u16(code).ok()
+}
+
+/// Failing to log an activity should never stop us from processing it.
+async fn record(pool: &PgPool, insert: Insert) {
+    let result: ResponseResult<()> = async {
+        let mut tx = pool.begin().await?;
+        db::activity_log::insert(&mut tx, insert).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+    .await;
+
+    if let Err(e) = result {
+        tracing::warn!("Failed to record activity in log: {e:?}");
+    }
+}
+
+/// The fields of an activity worth filtering by, read leniently since we
+/// also want to log activities we failed to parse.
+struct ActivityFields {
+    id: Option<String>,
+    kind: Option<String>,
+    actor: Option<String>,
+}
+
+impl ActivityFields {
+    fn parse(body: &str) -> Self {
+        let json = serde_json::from_str::<serde_json::Value>(body).unwrap_or_default();
+        let string_field = |name: &str| json.get(name).and_then(|value| value.as_str());
+        // Some implementations embed the actor instead of linking to it
+        let actor = string_field("actor").or_else(|| {
+            json.get("actor")
+                .and_then(|actor| actor.get("id"))
+                .and_then(|id| id.as_str())
+        });
+
+        let field = |value: &str| truncate(value, MAX_FIELD_LENGTH);
+        ActivityFields {
+            id: string_field("id").map(field),
+            kind: string_field("type").map(field),
+            actor: actor.map(field),
+        }
+    }
+}
+
+/// The first `max_chars` characters of `value`.
+fn truncate(value: &str, max_chars: usize) -> String {
+    value.chars().take(max_chars).collect()
+}
@@ -1,34 +1,68 @@
 use activitypub_federation::{
     config::Data,
     fetch::object_id::ObjectId,
-    kinds::{link::LinkType, object::NoteType, public},
-    protocol::verification::{verify_domains_match, verify_is_remote_object},
+    kinds::{link::LinkType, public},
+    protocol::{
+        helpers::deserialize_one_or_many,
+        verification::{verify_domains_match, verify_is_remote_object},
+    },
     traits::Object,
 };
 use anyhow::anyhow;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, de::DeserializeOwned};
 use url::Url;
 
 use crate::{
     db::{self, bookmarks::InsertBookmark},
+    federation::html,
+    forms::bookmarks::{MAX_TAGS, parse_tag},
     response_error::{ResponseError, into_option},
 };
 
+/// Titles taken from the text of a post are cut off after this many characters
+const MAX_DERIVED_TITLE_LENGTH: usize = 100;
+
+/// Object types we accept as bookmarks. We send `Note`s, like Mastodon and
+/// Betula do, while Lemmy sends link posts as `Page`s.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BookmarkKind {
+    Note,
+    Page,
+}
+
 #[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct BookmarkJson {
     pub id: ObjectId<db::Bookmark>,
     #[serde(rename = "type")]
-    pub kind: NoteType,
+    pub kind: BookmarkKind,
     pub attributed_to: ObjectId<db::ApUser>,
+    #[serde(default, deserialize_with = "deserialize_one_or_many")]
     pub to: Vec<Url>,
     /// Formatted content with the url inlined, for platforms that don't support
     /// link attachments
     pub content: Option<String>,
     /// The title
     pub name: Option<String>,
-    #[serde(default)]
+    /// The bookmark's page on its instance, as opposed to the bookmarked URL.
+    /// Lemmy uses this for the bookmarked URL of a `Page`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<Url>,
+    /// Other software calls this `attachment` and also attaches images, which
+    /// we skip.
+    #[serde(default, alias = "attachment", deserialize_with = "deserialize_valid")]
     pub(crate) attachments: Vec<Link>,
+    /// Hashtags, other software also puts mentions and emojis in here, which
+    /// we skip.
+    #[serde(
+        default,
+        deserialize_with = "deserialize_valid",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub tag: Vec<Hashtag>,
+    /// The bookmark this one was saved from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub via: Option<ObjectId<db::Bookmark>>,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -40,22 +74,134 @@ pub(crate) struct Link {
     kind: LinkType,
 }
 
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HashtagType {
+    Hashtag,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Hashtag {
+    /// A page listing posts with this hashtag
+    pub href: Url,
+    /// The hashtag including the leading `#`
+    pub name: String,
+    #[serde(rename = "type")]
+    pub kind: HashtagType,
+}
+
+impl Hashtag {
+    fn new(tag: &str, base_url: &Url) -> Result<Self, url::ParseError> {
+        Ok(Hashtag {
+            href: base_url.join("/tags/")?.join(tag)?,
+            name: format!("#{tag}"),
+            kind: HashtagType::Hashtag,
+        })
+    }
+}
+
+/// Read one or many entries, skipping those that don't match the expected
+/// type.
+fn deserialize_valid<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: DeserializeOwned,
+{
+    let entries = match serde_json::Value::deserialize(deserializer)? {
+        serde_json::Value::Array(entries) => entries,
+        entry => vec![entry],
+    };
+
+    Ok(entries
+        .into_iter()
+        .filter_map(|entry| serde_json::from_value(entry).ok())
+        .collect())
+}
+
+impl BookmarkJson {
+    /// The bookmarked URL, in order of preference:
+    /// - a link attachment, sent by linkblocks and newer Lemmy versions
+    /// - the `url` of a Lemmy `Page`
+    /// - the only link in the content, for Mastodon statuses
+    /// - the first link in the content of titled notes, for Betula
+    fn bookmarked_url(&self) -> Option<String> {
+        if let Some(attachment) = self.attachments.first() {
+            return Some(attachment.href.clone());
+        }
+
+        if let Some(url) = self
+            .url
+            .as_ref()
+            .filter(|_| self.kind == BookmarkKind::Page)
+        {
+            return Some(url.to_string());
+        }
+
+        let links = html::links(self.content.as_deref().unwrap_or_default());
+        match links.as_slice() {
+            [link] => Some(link.to_string()),
+            [link, ..] if self.title().is_some() => Some(link.to_string()),
+            _ => None,
+        }
+    }
+
+    /// Hashtags in our normalized form, ignoring invalid ones.
+    pub fn tags(&self) -> Vec<String> {
+        let mut tags: Vec<String> = Vec::new();
+        for tag in self
+            .tag
+            .iter()
+            .filter_map(|hashtag| parse_tag(&hashtag.name))
+        {
+            if !tags.contains(&tag) {
+                tags.push(tag);
+            }
+        }
+        tags.truncate(MAX_TAGS);
+        tags
+    }
+
+    fn title(&self) -> Option<String> {
+        self.name
+            .as_deref()
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(str::to_string)
+    }
+
+    /// Statuses on microblogging platforms have no title, so we use their text
+    fn title_from_content(&self) -> Option<String> {
+        let text = html::text_without_links(self.content.as_deref()?);
+        if text.is_empty() {
+            return None;
+        }
+        if text.chars().count() <= MAX_DERIVED_TITLE_LENGTH {
+            return Some(text);
+        }
+
+        let mut title = text
+            .chars()
+            .take(MAX_DERIVED_TITLE_LENGTH - 1)
+            .collect::<String>()
+            .trim_end()
+            .to_string();
+        title.push('…');
+        Some(title)
+    }
+}
+
 impl TryFrom<BookmarkJson> for InsertBookmark {
     type Error = anyhow::Error;
 
     fn try_from(value: BookmarkJson) -> Result<Self, Self::Error> {
-        let first_attachment = value.attachments.first();
-        let url = if let Some(attachment) = first_attachment.cloned() {
-            Some(attachment.href)
-        } else {
-            None
-        }
-        .ok_or_else(|| anyhow!("Missing URL"))?;
+        let url = value
+            .bookmarked_url()
+            .ok_or_else(|| anyhow!("Missing URL"))?;
+        let title = value
+            .title()
+            .or_else(|| value.title_from_content())
+            .unwrap_or_else(|| url.clone());
 
-        let create_bookmark = InsertBookmark {
-            url,
-            title: value.name.ok_or_else(|| anyhow!("Missing title"))?,
-        };
+        let create_bookmark = InsertBookmark { url, title };
 
         // TODO how to validate InsertBookmark?
         // ideally, we'd find a single point for the validation rules that
@@ -93,18 +239,58 @@ impl Object for db::Bookmark {
             media_type: None,
             kind: LinkType::Link,
         }];
+        let via_author = match self.via_ap_user_id {
+            Some(id) => Some(db::ap_users::read_by_id(&mut tx, id).await?),
+            None => None,
+        };
+        // Credit the original author for platforms that only show the content
+        let attribution = match (&self.via_ap_id, via_author) {
+            (Some(via), Some(via_author)) => format!(
+                r#"<p>via <a href="{}">{}</a></p>"#,
+                html::escape(via.inner().as_str()),
+                html::escape(&via_author.display_name.unwrap_or(via_author.username))
+            ),
+            _ => String::new(),
+        };
+        let tag = self
+            .tags
+            .iter()
+            .map(|tag| Hashtag::new(tag, &data.base_url))
+            .collect::<Result<Vec<_>, _>>()?;
+        // Render hashtags like Mastodon does, so it links them to its own
+        // hashtag timelines
+        let hashtags = if tag.is_empty() {
+            String::new()
+        } else {
+            let links = tag
+                .iter()
+                .map(|hashtag| {
+                    format!(
+                        r#"<a href="{}" class="mention hashtag" rel="tag">{}</a>"#,
+                        html::escape(hashtag.href.as_str()),
+                        html::escape(&hashtag.name)
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join(" ");
+            format!("<p>{links}</p>")
+        };
+        let url = html::escape(&self.url);
         let content = format!(
-            r#"<p>{}</p><a href="{}">{}</p>"#,
-            self.title, self.url, self.url
+            r#"{}<a href="{url}">{url}</a>{attribution}{hashtags}"#,
+            html::from_text(&self.title)
         );
         Ok(BookmarkJson {
             id: self.ap_id,
-            kind: NoteType::Note,
+            kind: BookmarkKind::Note,
             attributed_to: author.ap_id,
             to: vec![public()],
             content: Some(content),
             name: Some(self.title),
+            url: Some(data.base_url.join(&self.path())?),
             attachments,
+            tag,
+            via: self.via_ap_id,
         })
     }
 
@@ -125,9 +311,21 @@ impl Object for db::Bookmark {
         let ap_user = json.attributed_to.dereference(data).await?;
         let mut tx = data.db_pool.begin().await?;
         let ap_id = json.id.clone();
+        let via = json.via.clone();
+        let tags = json.tags();
         let insert_bookmark = json.try_into()?;
-        let new_bookmark =
+        let mut new_bookmark =
             db::bookmarks::upsert_remote(&mut tx, ap_user.id, &ap_id, insert_bookmark).await?;
+        if tags != new_bookmark.tags {
+            new_bookmark = db::bookmarks::set_tags(&mut tx, new_bookmark.id, &tags).await?;
+        }
+        if let Some(via) = via {
+            // We only know the original author if we've seen the original before
+            let via_ap_user_id = into_option(db::bookmarks::by_ap_id(&mut tx, via.clone()).await)?
+                .map(|via_bookmark| via_bookmark.ap_user_id);
+            new_bookmark =
+                db::bookmarks::set_via(&mut tx, new_bookmark.id, &via, via_ap_user_id).await?;
+        }
         tx.commit().await?;
         Ok(new_bookmark)
     }
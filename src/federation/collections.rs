@@ -0,0 +1,258 @@
+//! The public content of a user, as collections other instances can browse:
+//! the outbox with their public bookmarks, and their public lists. Other
+//! software only knows about the outbox, the lists are specific to linkblocks.
+
+use activitypub_federation::{
+    fetch::fetch_object_http,
+    kinds::{activity::CreateType, collection::OrderedCollectionType, public},
+    protocol::verification::verify_domains_match,
+    traits::Object,
+};
+use anyhow::{Context, anyhow};
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use url::Url;
+use uuid::Uuid;
+
+use crate::{
+    db,
+    federation::{self, CreateBookmark, list::ListJson, person::Person},
+    response_error::ResponseResult,
+};
+
+/// How many bookmarks to serve in and take from an outbox
+const OUTBOX_LIMIT: i64 = 20;
+/// Copies of a remote user's bookmarks and lists older than this are fetched
+/// again when viewing their profile
+const MAX_AGE: time::Duration = time::Duration::hours(1);
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderedCollection<T> {
+    pub id: Url,
+    #[serde(rename = "type")]
+    pub kind: OrderedCollectionType,
+    pub total_items: usize,
+    pub ordered_items: Vec<T>,
+}
+
+impl<T> OrderedCollection<T> {
+    fn new(id: Url, ordered_items: Vec<T>) -> Self {
+        OrderedCollection {
+            id,
+            kind: OrderedCollectionType::OrderedCollection,
+            total_items: ordered_items.len(),
+            ordered_items,
+        }
+    }
+}
+
+/// The outbox of a local user, containing the `Create` activities of their
+/// most recent public bookmarks.
+pub async fn outbox(
+    ap_user: &db::ApUser,
+    data: &federation::Data,
+) -> ResponseResult<OrderedCollection<CreateBookmark>> {
+    let mut tx = data.db_pool.begin().await?;
+    let bookmarks =
+        db::bookmarks::list_public_by_ap_user(&mut tx, ap_user.id, OUTBOX_LIMIT).await?;
+    drop(tx);
+
+    let mut creates = Vec::with_capacity(bookmarks.len());
+    for bookmark in bookmarks {
+        // The activity isn't stored anywhere, so derive a stable id from the
+        // bookmark
+        let mut id = bookmark.ap_id.inner().clone();
+        id.set_fragment(Some("create"));
+        creates.push(CreateBookmark {
+            actor: ap_user.ap_id.clone(),
+            to: vec![public()],
+            object: bookmark.into_json(data).await?,
+            kind: CreateType::Create,
+            id,
+        });
+    }
+
+    Ok(OrderedCollection::new(
+        ap_user.outbox_url(&data.base_url)?,
+        creates,
+    ))
+}
+
+/// The public lists of a local user.
+pub async fn lists(
+    ap_user: &db::ApUser,
+    data: &federation::Data,
+) -> ResponseResult<OrderedCollection<ListJson>> {
+    let mut tx = data.db_pool.begin().await?;
+    let public_lists = db::lists::list_public_by_user(&mut tx, ap_user.id).await?;
+    let mut lists = Vec::with_capacity(public_lists.len());
+    for public_list in public_lists {
+        lists.push(ListJson::read(&mut tx, public_list.list, &data.base_url).await?);
+    }
+
+    Ok(OrderedCollection::new(
+        ap_user.lists_url(&data.base_url)?,
+        lists,
+    ))
+}
+
+/// Copy the public bookmarks and lists of a remote user in the background,
+/// unless we tried to recently. Until the copy is done, we show what we have.
+/// The attempt is recorded up front, so a slow or failing remote instance
+/// doesn't get another fetch on every visit.
+pub async fn refresh_remote_profile(
+    ap_user: &db::ApUser,
+    data: &federation::Data,
+) -> ResponseResult<()> {
+    let mut tx = data.db_pool.begin().await?;
+    let claimed =
+        db::remote_lists::claim_fetch(&mut tx, ap_user.id, OffsetDateTime::now_utc() - MAX_AGE)
+            .await?;
+    tx.commit().await?;
+    if !claimed {
+        return Ok(());
+    }
+
+    // Like deliveries, fetch synchronously during development to make
+    // debugging easier
+    let ap_id = ap_user.ap_id.inner().clone();
+    if cfg!(debug_assertions) {
+        log_failed_fetch(&ap_id, fetch_remote_profile(ap_user.id, data).await);
+    } else {
+        let ap_user_id = ap_user.id;
+        let data = data.reset_request_count();
+        tokio::task::spawn(async move {
+            log_failed_fetch(&ap_id, fetch_remote_profile(ap_user_id, &data).await);
+        });
+    }
+
+    Ok(())
+}
+
+/// Failing to fetch a profile isn't an error, we'll try again on a visit after
+/// [`MAX_AGE`].
+fn log_failed_fetch(ap_id: &Url, result: ResponseResult<()>) {
+    if let Err(e) = result {
+        tracing::warn!("Failed to fetch the profile of {ap_id}: {e:?}");
+    }
+}
+
+async fn fetch_remote_profile(ap_user_id: Uuid, data: &federation::Data) -> ResponseResult<()> {
+    let mut tx = data.db_pool.begin().await?;
+    let ap_user = db::ap_users::read_by_id(&mut tx, ap_user_id).await?;
+    drop(tx);
+
+    // We don't store the collections of remote users, so look them up again
+    let person = fetch_object_http::<_, Person>(ap_user.ap_id.inner(), data)
+        .await?
+        .object;
+
+    let items = fetch_collection_items(&person.outbox, data).await?;
+    let limit = usize::try_from(OUTBOX_LIMIT).context("Invalid outbox limit")?;
+    for item in items.into_iter().take(limit) {
+        if let Err(e) = receive_outbox_item(item, &ap_user, &person.outbox, data).await {
+            tracing::debug!("Ignoring outbox item: {e:?}");
+        }
+    }
+
+    let lists = match &person.lists {
+        Some(lists_url) => fetch_collection_items(lists_url, data)
+            .await?
+            .into_iter()
+            .filter_map(|item| match remote_list(item, &ap_user) {
+                Ok(list) => Some(list),
+                Err(e) => {
+                    tracing::debug!("Ignoring list: {e:?}");
+                    None
+                }
+            })
+            .collect(),
+        None => Vec::new(),
+    };
+    let mut tx = data.db_pool.begin().await?;
+    db::remote_lists::replace_by_ap_user(&mut tx, ap_user.id, lists).await?;
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// Collections as other software serves them. Mastodon only links to the first
+/// page of its outbox, which contains the actual items.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RemoteCollection {
+    #[serde(default)]
+    ordered_items: Vec<serde_json::Value>,
+    #[serde(default)]
+    first: Option<RemoteCollectionPage>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RemoteCollectionPage {
+    Id(Url),
+    Page(Box<RemoteCollection>),
+}
+
+async fn fetch_collection_items(
+    url: &Url,
+    data: &federation::Data,
+) -> ResponseResult<Vec<serde_json::Value>> {
+    let collection = fetch_object_http::<_, RemoteCollection>(url, data)
+        .await?
+        .object;
+    if !collection.ordered_items.is_empty() {
+        return Ok(collection.ordered_items);
+    }
+
+    match collection.first {
+        Some(RemoteCollectionPage::Id(page_url)) => {
+            let page = fetch_object_http::<_, RemoteCollection>(&page_url, data)
+                .await?
+                .object;
+            Ok(page.ordered_items)
+        }
+        Some(RemoteCollectionPage::Page(page)) => Ok(page.ordered_items),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// Save the bookmark created by an outbox item. Other activities and posts
+/// that aren't link posts fail to parse.
+async fn receive_outbox_item(
+    item: serde_json::Value,
+    ap_user: &db::ApUser,
+    outbox_url: &Url,
+    data: &federation::Data,
+) -> ResponseResult<()> {
+    let create: CreateBookmark =
+        serde_json::from_value(item).context("Failed to parse outbox item")?;
+    let bookmark = create.object;
+    db::Bookmark::verify(&bookmark, outbox_url, data).await?;
+    if bookmark.attributed_to.inner() != ap_user.ap_id.inner() {
+        return Err(anyhow!("Outbox contains a bookmark of someone else").into());
+    }
+    db::Bookmark::from_json(bookmark, data).await?;
+
+    Ok(())
+}
+
+fn remote_list(
+    item: serde_json::Value,
+    ap_user: &db::ApUser,
+) -> ResponseResult<db::remote_lists::Insert> {
+    let list: ListJson = serde_json::from_value(item).context("Failed to parse list")?;
+    verify_domains_match(&list.id, ap_user.ap_id.inner())?;
+    if list.attributed_to.inner() != ap_user.ap_id.inner() {
+        return Err(anyhow!("List belongs to someone else").into());
+    }
+
+    Ok(db::remote_lists::Insert {
+        ap_id: list.id,
+        url: list.url,
+        title: list.name,
+        content: list.summary,
+        item_count: i64::try_from(list.total_items).context("Invalid list size")?,
+    })
+}
@@ -1,15 +1,30 @@
-use activitypub_federation::config::FederationConfig;
+use activitypub_federation::{
+    config::{FederationConfig, UrlVerifier},
+    error::Error,
+};
 use anyhow::{Context, Result};
 use sqlx::PgPool;
 use url::Url;
 
+use crate::db::{self, domain_blocks::Severity};
+
+/// When `authorized_fetch` is set, our ActivityPub objects are only served to
+/// requests signed by a remote actor, like Mastodon's secure mode does. Either
+/// way, we sign our own fetches with the instance actor.
 pub async fn new_config(
     db_pool: PgPool,
     base_url: Url,
+    authorized_fetch: bool,
 ) -> Result<FederationConfig<super::Context>> {
+    let mut tx = db_pool.begin().await?;
+    let instance_actor =
+        db::ap_users::create_instance_actor_if_not_exists(&mut tx, &base_url).await?;
+    tx.commit().await?;
+
     let context = super::Context {
-        db_pool,
+        db_pool: db_pool.clone(),
         base_url: base_url.clone(),
+        authorized_fetch,
     };
     let domain = base_url
         .domain()
@@ -19,8 +34,38 @@ pub async fn new_config(
         .domain(format!("{domain}{port}"))
         .app_data(context)
         .http_fetch_limit(1000)
+        .url_verifier(Box::new(DomainBlockVerifier { db_pool }))
+        .signed_fetch_actor(&instance_actor)
         .debug(cfg!(debug_assertions))
         .build()
         .await
         .context("Failed to build activitypub config")
 }
+
+/// Refuses to send to, receive from or fetch anything on domains that were
+/// rejected by an admin.
+#[derive(Clone)]
+struct DomainBlockVerifier {
+    db_pool: PgPool,
+}
+
+#[async_trait::async_trait]
+impl UrlVerifier for DomainBlockVerifier {
+    async fn verify(&self, url: &Url) -> Result<(), Error> {
+        let mut tx = self
+            .db_pool
+            .begin()
+            .await
+            .map_err(|e| Error::Other(format!("Failed to check domain blocks: {e:?}")))?;
+        let block = db::domain_blocks::for_url(&mut tx, url)
+            .await
+            .map_err(|e| Error::Other(format!("Failed to check domain blocks: {e:?}")))?;
+
+        match block {
+            Some(block) if block.severity == Severity::Reject => {
+                Err(Error::Other(format!("Domain {} is blocked", block.domain)))
+            }
+            _ => Ok(()),
+        }
+    }
+}
@@ -1,9 +1,30 @@
+use activitypub_federation::protocol::context::WithContext;
+use serde_json::json;
 use url::Url;
 
 #[derive(Clone)]
 pub struct Context {
     pub db_pool: sqlx::PgPool,
     pub base_url: Url,
+    /// Only serve ActivityPub objects to requests signed by a remote actor
+    pub authorized_fetch: bool,
 }
 
 pub type Data = activitypub_federation::config::Data<Context>;
+
+/// Wrap an object or activity in the JSON-LD context we use everywhere. On
+/// top of the default context, it declares the properties only linkblocks
+/// uses.
+pub fn with_context<T>(data: T) -> WithContext<T> {
+    WithContext::new(
+        data,
+        json!([
+            "https://www.w3.org/ns/activitystreams",
+            "https://w3id.org/security/v1",
+            {
+                "linkblocks": "https://linkblocks.rafa.ee/ns#",
+                "via": { "@id": "linkblocks:via", "@type": "@id" }
+            }
+        ]),
+    )
+}
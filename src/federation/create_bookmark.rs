@@ -1,6 +1,6 @@
 use activitypub_federation::{
     fetch::object_id::ObjectId,
-    kinds::activity::CreateType,
+    kinds::{activity::CreateType, public},
     protocol::{
         helpers::deserialize_one_or_many,
         verification::{verify_domains_match, verify_is_remote_object},
@@ -38,9 +38,13 @@ impl CreateBookmark {
 
         let mut tx = context.db_pool.begin().await?;
         let followers = db::ap_users::list_followers(&mut tx, actor.id).await?;
-        let to = followers
-            .iter()
-            .map(|ap_user| ap_user.ap_id.clone().into_inner())
+        // Relays only pass on activities addressed to the public
+        let to = std::iter::once(public())
+            .chain(
+                followers
+                    .iter()
+                    .map(|ap_user| ap_user.ap_id.clone().into_inner()),
+            )
             .collect();
         let create = CreateBookmark {
             actor: actor.ap_id.clone(),
@@ -50,7 +54,7 @@ impl CreateBookmark {
             id,
         };
 
-        super::activity::send(
+        super::activity::send_public(
             actor,
             create,
             &followers.iter().collect::<Vec<_>>(),
@@ -83,7 +87,27 @@ impl ActivityHandler for CreateBookmark {
         Ok(())
     }
 
-    async fn receive(self, _data: &super::Data) -> Result<(), Self::Error> {
-        Err(ResponseError::NotFound)
+    async fn receive(self, data: &super::Data) -> Result<(), Self::Error> {
+        let actor = self.actor.dereference(data).await?;
+
+        let mut tx = data.db_pool.begin().await?;
+        let local_followers = db::ap_users::list_local_followers(&mut tx, actor.id).await?;
+        let trusted = db::trusts::is_trusted_locally(&mut tx, actor.id).await?;
+        tx.commit().await?;
+
+        // Shared inboxes receive all public posts of a remote user, but we're
+        // only interested in those that someone on this instance follows or
+        // trusts
+        if local_followers.is_empty() && !trusted {
+            tracing::debug!(
+                "Ignoring bookmark by {}, no local followers or trusters",
+                actor.ap_id.inner()
+            );
+            return Ok(());
+        }
+
+        db::Bookmark::from_json(self.object, data).await?;
+
+        Ok(())
     }
 }
@@ -0,0 +1,112 @@
+use activitypub_federation::{
+    fetch::object_id::ObjectId,
+    kinds::activity::CreateType,
+    protocol::{
+        helpers::deserialize_one_or_many,
+        verification::{verify_domains_match, verify_is_remote_object},
+    },
+    traits::{ActivityHandler, Object},
+};
+use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::{
+    db,
+    federation::reply::{ReplyJson, find_target},
+    response_error::{ResponseError, ResponseResult},
+};
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateReply {
+    pub actor: ObjectId<db::ApUser>,
+    #[serde(deserialize_with = "deserialize_one_or_many")]
+    pub to: Vec<Url>,
+    #[serde(default, deserialize_with = "deserialize_one_or_many")]
+    pub cc: Vec<Url>,
+    pub object: ReplyJson,
+    #[serde(rename = "type")]
+    pub kind: CreateType,
+    pub id: Url,
+}
+
+impl CreateReply {
+    /// Send a reply to the authors it answers and to the followers of its
+    /// author.
+    pub async fn send(
+        actor: &db::ApUser,
+        reply: db::Reply,
+        context: &super::Data,
+    ) -> ResponseResult<()> {
+        let mut tx = context.db_pool.begin().await?;
+        let mut recipients = db::ap_users::list_followers(&mut tx, actor.id).await?;
+        recipients.extend(reply.replied_to_authors(&mut tx).await?);
+        drop(tx);
+
+        let object = reply.into_json(context).await?;
+        let create = CreateReply {
+            actor: actor.ap_id.clone(),
+            to: object.to.clone(),
+            cc: object.cc.clone(),
+            object,
+            kind: CreateType::Create,
+            id: super::activity::generate_id(context)?,
+        };
+
+        super::activity::send(
+            actor,
+            create,
+            &recipients.iter().collect::<Vec<_>>(),
+            context,
+        )
+        .await?;
+
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl ActivityHandler for CreateReply {
+    type DataType = super::context::Context;
+    type Error = ResponseError;
+
+    fn id(&self) -> &Url {
+        &self.id
+    }
+
+    fn actor(&self) -> &Url {
+        self.actor.inner()
+    }
+
+    async fn verify(&self, data: &super::Data) -> Result<(), Self::Error> {
+        verify_is_remote_object(&self.actor, data)?;
+        verify_domains_match(self.actor.inner(), self.object.id.inner())?;
+        if self.object.attributed_to.inner() != self.actor.inner() {
+            return Err(anyhow!("Reply is attributed to someone else").into());
+        }
+        db::Reply::verify(&self.object, self.actor.inner(), data).await?;
+
+        Ok(())
+    }
+
+    async fn receive(self, data: &super::Data) -> Result<(), Self::Error> {
+        let mut tx = data.db_pool.begin().await?;
+        let target = find_target(&mut tx, &self.object.in_reply_to).await?;
+        tx.commit().await?;
+
+        // We receive all kinds of replies through shared inboxes, but only
+        // store those in threads we know about
+        if target.is_none() {
+            tracing::debug!(
+                "Ignoring reply to unknown object {}",
+                self.object.in_reply_to
+            );
+            return Ok(());
+        }
+
+        db::Reply::from_json(self.object, data).await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,194 @@
+//! Deletions of accounts, bookmarks and replies. Accounts and bookmarks are
+//! deleted by their owners or by admins of their instance, replies by their
+//! authors.
+
+use activitypub_federation::{
+    fetch::object_id::ObjectId,
+    kinds::{activity::DeleteType, public},
+    protocol::{
+        helpers::deserialize_one_or_many,
+        verification::{verify_domains_match, verify_is_remote_object},
+    },
+    traits::ActivityHandler,
+};
+use serde::{Deserialize, Deserializer, Serialize};
+use url::Url;
+
+use crate::{
+    db::{self, AppTx},
+    federation::{self, delivery::Unqueued},
+    response_error::{ResponseError, ResponseResult, into_option},
+};
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Delete {
+    pub actor: ObjectId<db::ApUser>,
+    #[serde(default, deserialize_with = "deserialize_one_or_many")]
+    pub to: Vec<Url>,
+    /// The id of the deleted object
+    #[serde(deserialize_with = "deserialize_object_id")]
+    pub object: Url,
+    #[serde(rename = "type")]
+    pub kind: DeleteType,
+    pub id: Url,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ObjectReference {
+    Id(Url),
+    /// Mastodon sends a `Tombstone` with the id of the deleted object, relays
+    /// embed the whole object
+    Object {
+        id: Url,
+    },
+}
+
+/// Take the id of an activity's object, whether it's embedded or not.
+pub fn deserialize_object_id<'de, D>(deserializer: D) -> Result<Url, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(match ObjectReference::deserialize(deserializer)? {
+        ObjectReference::Id(id) | ObjectReference::Object { id } => id,
+    })
+}
+
+impl Delete {
+    /// Everyone who might have received the reply: followers of its author and
+    /// the authors it answers. Call this before deleting the reply from the
+    /// database.
+    pub async fn reply_recipients(
+        tx: &mut AppTx,
+        reply: &db::Reply,
+    ) -> ResponseResult<Vec<db::ApUser>> {
+        let mut recipients = db::ap_users::list_followers(tx, reply.ap_user_id).await?;
+        recipients.extend(reply.replied_to_authors(tx).await?);
+
+        Ok(recipients)
+    }
+
+    pub async fn send(
+        actor: &db::ApUser,
+        object: Url,
+        recipients: &[db::ApUser],
+        data: &federation::Data,
+    ) -> ResponseResult<()> {
+        let delete = Delete {
+            actor: actor.ap_id.clone(),
+            to: vec![public()],
+            object,
+            kind: DeleteType::Delete,
+            id: super::activity::generate_id(data)?,
+        };
+        super::activity::send(actor, delete, &recipients.iter().collect::<Vec<_>>(), data).await
+    }
+
+    /// Delete a public bookmark everywhere its creation was sent: to the
+    /// followers of the actor and to relays.
+    pub async fn send_to_followers(
+        actor: &db::ApUser,
+        object: Url,
+        data: &federation::Data,
+    ) -> ResponseResult<()> {
+        let mut tx = data.db_pool.begin().await?;
+        let followers = db::ap_users::list_followers(&mut tx, actor.id).await?;
+        drop(tx);
+
+        let delete = Delete {
+            actor: actor.ap_id.clone(),
+            to: vec![public()],
+            object,
+            kind: DeleteType::Delete,
+            id: super::activity::generate_id(data)?,
+        };
+        super::activity::send_public(actor, delete, &followers.iter().collect::<Vec<_>>(), data)
+            .await
+    }
+
+    /// Prepare telling the followers of a local user that their account is
+    /// gone. Call this before deleting the user from the database, since we
+    /// need their key to sign the activity, and deliver it after committing
+    /// the deletion.
+    pub async fn prepare_for_person(
+        actor: &db::ApUser,
+        data: &federation::Data,
+    ) -> ResponseResult<Unqueued> {
+        let mut tx = data.db_pool.begin().await?;
+        let followers = db::ap_users::list_followers(&mut tx, actor.id).await?;
+        drop(tx);
+
+        let delete = Delete {
+            actor: actor.ap_id.clone(),
+            to: vec![public()],
+            object: actor.ap_id.inner().clone(),
+            kind: DeleteType::Delete,
+            id: super::activity::generate_id(data)?,
+        };
+        super::activity::prepare_unqueued(
+            actor,
+            delete,
+            &followers.iter().collect::<Vec<_>>(),
+            data,
+        )
+        .await
+    }
+}
+
+#[async_trait::async_trait]
+impl ActivityHandler for Delete {
+    type DataType = super::Context;
+    type Error = ResponseError;
+
+    fn id(&self) -> &Url {
+        &self.id
+    }
+
+    fn actor(&self) -> &Url {
+        self.actor.inner()
+    }
+
+    async fn verify(&self, data: &federation::Data) -> Result<(), Self::Error> {
+        verify_is_remote_object(&self.actor, data)?;
+        verify_domains_match(self.actor.inner(), &self.id)?;
+        Ok(())
+    }
+
+    /// Deleted objects might be gone from their instance already, so we only
+    /// look at what we stored ourselves.
+    async fn receive(self, data: &federation::Data) -> Result<(), Self::Error> {
+        let mut tx = data.db_pool.begin().await?;
+        let Some(actor) =
+            into_option(db::ap_users::read_by_ap_id(&mut tx, self.actor.inner()).await)?
+        else {
+            tracing::debug!("Ignoring delete by unknown actor");
+            return Ok(());
+        };
+
+        if &self.object == self.actor.inner() {
+            db::ap_users::delete_remote(&mut tx, actor.id).await?;
+        } else if let Some(reply) = into_option(db::replies::by_ap_id(&mut tx, &self.object).await)?
+        {
+            if reply.ap_user_id == actor.id {
+                db::replies::delete_by_id(&mut tx, reply.id).await?;
+            } else {
+                tracing::debug!("Ignoring delete of a reply by someone else");
+            }
+        } else if let Some(bookmark) =
+            into_option(db::bookmarks::by_ap_id(&mut tx, self.object.clone().into()).await)?
+        {
+            if bookmark.ap_user_id == actor.id {
+                db::links::delete_by_bookmark(&mut tx, bookmark.id).await?;
+                db::bookmarks::delete_by_id(&mut tx, bookmark.id).await?;
+            } else {
+                tracing::debug!("Ignoring delete of a bookmark by someone else");
+            }
+        } else {
+            tracing::debug!("Ignoring delete of unknown object");
+        }
+        tx.commit().await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,260 @@
+//! A persistent queue for outgoing activities. Deliveries survive restarts and
+//! are retried with exponential backoff until they succeed, or until we give up
+//! and keep them around for admins to retry manually.
+
+use std::time::Duration;
+
+use activitypub_federation::{
+    activity_sending::SendActivityTask, config::FederationConfig, protocol::context::WithContext,
+    traits::ActivityHandler,
+};
+use anyhow::{Context as _, anyhow};
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use url::Url;
+use uuid::Uuid;
+
+use crate::{
+    db::{self, deliveries::Delivery},
+    federation::{Context, Data, activity_log},
+    response_error::{ResponseError, ResponseResult},
+};
+
+/// How often to look for deliveries that need to be retried
+const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+const BATCH_SIZE: i64 = 100;
+/// How long a delivery is reserved for the task attempting it. If the server
+/// stops in the meantime, the delivery is retried after this.
+const CLAIM_DURATION: time::Duration = time::Duration::minutes(5);
+/// Wait this long after the first failed attempt, doubling the wait after
+/// every failure
+const INITIAL_BACKOFF: time::Duration = time::Duration::minutes(1);
+/// With the backoff above, we give up after about a day and a half
+pub const MAX_ATTEMPTS: i32 = 12;
+
+pub async fn periodically_deliver(config: FederationConfig<Context>) {
+    let mut interval = tokio::time::interval(CHECK_INTERVAL);
+
+    loop {
+        interval.tick().await;
+        if let Err(e) = deliver_due(&config.to_request_data()).await {
+            tracing::error!("Failed to deliver queued activities: {e:?}");
+        }
+    }
+}
+
+/// Store an activity for delivery to each of the inboxes, and try delivering
+/// it right away.
+pub async fn enqueue<Activity>(
+    activity: &WithContext<Activity>,
+    actor_id: Uuid,
+    inboxes: Vec<Url>,
+    data: &Data,
+) -> ResponseResult<()>
+where
+    Activity: ActivityHandler + Serialize + Send + Sync,
+{
+    let json = serde_json::to_value(activity).context("Failed to serialize activity")?;
+    let claimed_until = OffsetDateTime::now_utc() + CLAIM_DURATION;
+
+    let mut tx = data.db_pool.begin().await?;
+    let mut deliveries = Vec::new();
+    for inbox_url in inboxes {
+        let delivery = db::deliveries::insert(
+            &mut tx,
+            db::deliveries::Insert {
+                actor_id,
+                activity_ap_id: activity.id().clone(),
+                activity: json.clone(),
+                inbox_url,
+            },
+            claimed_until,
+        )
+        .await?;
+        deliveries.push(delivery);
+    }
+    tx.commit().await?;
+
+    // Like the in-memory queue of `activitypub_federation`, deliver
+    // synchronously during development to make debugging easier
+    if cfg!(debug_assertions) {
+        deliver_all(deliveries, data).await;
+    } else {
+        let data = data.reset_request_count();
+        tokio::task::spawn(async move { deliver_all(deliveries, &data).await });
+    }
+
+    Ok(())
+}
+
+/// An activity to deliver once, without storing it. Failed deliveries are only
+/// logged, they are never retried.
+pub struct Unqueued {
+    activity_id: Url,
+    tasks: Vec<SendActivityTask>,
+}
+
+impl Unqueued {
+    /// Prepare the deliveries while the actor still exists, the tasks keep
+    /// everything needed to sign them.
+    pub async fn prepare<Activity>(
+        activity: &WithContext<Activity>,
+        actor: &db::ApUser,
+        inboxes: Vec<Url>,
+        data: &Data,
+    ) -> ResponseResult<Self>
+    where
+        Activity: ActivityHandler + Serialize + Send + Sync,
+    {
+        let tasks = SendActivityTask::prepare(activity, actor, inboxes, data).await?;
+        Ok(Unqueued {
+            activity_id: activity.id().clone(),
+            tasks,
+        })
+    }
+
+    pub async fn deliver(self, data: &Data) {
+        for task in self.tasks {
+            if let Err(e) = task.sign_and_send(data).await {
+                tracing::warn!("Failed to deliver {}: {e:?}", self.activity_id);
+            }
+        }
+    }
+
+    /// Deliver without making the caller wait for slow inboxes, except during
+    /// development, like [`enqueue`].
+    pub async fn deliver_in_background(self, data: &Data) {
+        if cfg!(debug_assertions) {
+            self.deliver(data).await;
+        } else {
+            let data = data.reset_request_count();
+            tokio::task::spawn(async move { self.deliver(&data).await });
+        }
+    }
+}
+
+pub async fn deliver_due(data: &Data) -> ResponseResult<()> {
+    let mut tx = data.db_pool.begin().await?;
+    let deliveries = db::deliveries::claim_due(
+        &mut tx,
+        BATCH_SIZE,
+        OffsetDateTime::now_utc() + CLAIM_DURATION,
+    )
+    .await?;
+    tx.commit().await?;
+
+    deliver_all(deliveries, data).await;
+
+    Ok(())
+}
+
+async fn deliver_all(deliveries: Vec<Delivery>, data: &Data) {
+    for delivery in deliveries {
+        let id = delivery.id;
+        if let Err(e) = deliver(delivery, data).await {
+            tracing::error!("Failed to update delivery {id}: {e:?}");
+        }
+    }
+}
+
+async fn deliver(delivery: Delivery, data: &Data) -> ResponseResult<()> {
+    let result = sign_and_send(&delivery, data).await;
+    activity_log::record_outgoing(&data.db_pool, &delivery, &result).await;
+
+    let mut tx = data.db_pool.begin().await?;
+    match result {
+        Ok(()) => db::deliveries::mark_delivered(&mut tx, &delivery).await?,
+        Err(e) => {
+            let error = format!("{e:?}");
+            tracing::warn!(
+                "Failed to deliver {} to {}: {error}",
+                delivery.activity_ap_id,
+                delivery.inbox_url
+            );
+            let attempts = delivery.attempts + 1;
+            let next_attempt_at =
+                (attempts < MAX_ATTEMPTS).then(|| OffsetDateTime::now_utc() + backoff(attempts));
+            db::deliveries::mark_attempt_failed(&mut tx, &delivery, &error, next_attempt_at)
+                .await?;
+        }
+    }
+    tx.commit().await?;
+
+    Ok(())
+}
+
+async fn sign_and_send(delivery: &Delivery, data: &Data) -> ResponseResult<()> {
+    let mut tx = data.db_pool.begin().await?;
+    let actor = db::ap_users::read_by_id(&mut tx, delivery.actor_id).await?;
+    drop(tx);
+
+    let activity = StoredActivity::new(delivery.activity.clone())?;
+    let tasks =
+        SendActivityTask::prepare(&activity, &actor, vec![delivery.inbox_url.clone()], data)
+            .await?;
+    for task in tasks {
+        task.sign_and_send(data).await?;
+    }
+
+    Ok(())
+}
+
+/// How long to wait after the given number of failed attempts.
+fn backoff(attempts: i32) -> time::Duration {
+    let exponent = u32::try_from(attempts - 1).unwrap_or_default();
+    INITIAL_BACKOFF * 2_i32.saturating_pow(exponent)
+}
+
+/// An activity as it was stored in the queue. Sent exactly as it was
+/// serialized when it was first queued.
+#[derive(Debug)]
+struct StoredActivity {
+    id: Url,
+    actor: Url,
+    json: serde_json::Value,
+}
+
+impl StoredActivity {
+    fn new(json: serde_json::Value) -> anyhow::Result<Self> {
+        #[derive(Deserialize)]
+        struct Ids {
+            id: Url,
+            actor: Url,
+        }
+
+        let Ids { id, actor } =
+            serde_json::from_value(json.clone()).context("Stored activity is missing ids")?;
+        Ok(StoredActivity { id, actor, json })
+    }
+}
+
+impl Serialize for StoredActivity {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.json.serialize(serializer)
+    }
+}
+
+#[async_trait::async_trait]
+impl ActivityHandler for StoredActivity {
+    type DataType = Context;
+    type Error = ResponseError;
+
+    fn id(&self) -> &Url {
+        &self.id
+    }
+
+    fn actor(&self) -> &Url {
+        &self.actor
+    }
+
+    async fn verify(&self, _data: &Data) -> Result<(), Self::Error> {
+        Err(anyhow!("Stored activities are only sent, never received").into())
+    }
+
+    async fn receive(self, _data: &Data) -> Result<(), Self::Error> {
+        Err(anyhow!("Stored activities are only sent, never received").into())
+    }
+}
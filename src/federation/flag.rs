@@ -0,0 +1,156 @@
+//! Reports of abusive content. Reports by local users are sent to the instance
+//! of the reported user in the name of the instance actor, so the reporter
+//! stays anonymous, like Mastodon does it. Reports received from other
+//! instances end up in the moderation queue if they concern local content.
+
+use activitypub_federation::{
+    fetch::object_id::ObjectId,
+    kinds::activity::FlagType,
+    protocol::{
+        helpers::deserialize_one_or_many,
+        verification::{verify_domains_match, verify_is_remote_object},
+    },
+    traits::ActivityHandler,
+};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::{
+    db, federation,
+    response_error::{ResponseError, ResponseResult, into_option},
+};
+
+/// Longer comments of received reports are cut off
+const MAX_COMMENT_LENGTH: usize = 2_000;
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Flag {
+    pub actor: ObjectId<db::ApUser>,
+    /// The reported account, followed by the reported posts
+    #[serde(deserialize_with = "deserialize_one_or_many")]
+    pub object: Vec<Url>,
+    /// Why the content was reported
+    #[serde(default)]
+    pub content: Option<String>,
+    #[serde(rename = "type")]
+    pub kind: FlagType,
+    pub id: Url,
+}
+
+impl Flag {
+    /// Store a report for our own admins. Reports about remote users are also
+    /// sent to their instance.
+    pub async fn send(
+        reporter: &db::ApUser,
+        reported: &db::ApUser,
+        bookmark: Option<&db::Bookmark>,
+        comment: String,
+        data: &federation::Data,
+    ) -> ResponseResult<()> {
+        let id = super::activity::generate_id(data)?;
+
+        let mut tx = data.db_pool.begin().await?;
+        db::reports::insert(
+            &mut tx,
+            db::reports::Insert {
+                ap_id: id.clone(),
+                reporter_id: reporter.id,
+                reported_id: reported.id,
+                bookmark_id: bookmark.map(|bookmark| bookmark.id),
+                comment: comment.clone(),
+            },
+        )
+        .await?;
+        let instance_actor = db::ap_users::read_instance_actor(&mut tx, &data.base_url).await?;
+        tx.commit().await?;
+
+        if reported.ap_id.is_local(data) {
+            return Ok(());
+        }
+
+        let object = std::iter::once(reported.ap_id.inner().clone())
+            .chain(bookmark.map(|bookmark| bookmark.ap_id.inner().clone()))
+            .collect();
+        let flag = Flag {
+            actor: instance_actor.ap_id.clone(),
+            object,
+            content: Some(comment).filter(|comment| !comment.is_empty()),
+            kind: FlagType::Flag,
+            id,
+        };
+        super::activity::send(&instance_actor, flag, &[reported], data).await?;
+
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl ActivityHandler for Flag {
+    type DataType = super::Context;
+    type Error = ResponseError;
+
+    fn id(&self) -> &Url {
+        &self.id
+    }
+
+    fn actor(&self) -> &Url {
+        self.actor.inner()
+    }
+
+    async fn verify(&self, data: &federation::Data) -> Result<(), Self::Error> {
+        verify_is_remote_object(&self.actor, data)?;
+        verify_domains_match(self.actor.inner(), &self.id)?;
+        Ok(())
+    }
+
+    /// Other instances also forward reports about their own users to the
+    /// instances of the reporters, so we ignore reports about remote content.
+    async fn receive(self, data: &federation::Data) -> Result<(), Self::Error> {
+        let mut tx = data.db_pool.begin().await?;
+        let mut reported = None;
+        let mut bookmark = None;
+        for object in &self.object {
+            if let Some(ap_user) = into_option(db::ap_users::read_by_ap_id(&mut tx, object).await)?
+                .filter(|ap_user| ap_user.ap_id.is_local(data))
+            {
+                reported = Some(ap_user.id);
+            } else if let Some(found) =
+                into_option(db::bookmarks::by_ap_id(&mut tx, object.clone().into()).await)?
+                    .filter(|bookmark| bookmark.ap_id.is_local(data))
+            {
+                bookmark = Some(found);
+            }
+        }
+        drop(tx);
+
+        let Some(reported_id) =
+            reported.or_else(|| bookmark.as_ref().map(|bookmark| bookmark.ap_user_id))
+        else {
+            tracing::debug!("Ignoring report without local content");
+            return Ok(());
+        };
+
+        let reporter = self.actor.dereference(data).await?;
+        let mut tx = data.db_pool.begin().await?;
+        db::reports::insert(
+            &mut tx,
+            db::reports::Insert {
+                ap_id: self.id,
+                reporter_id: reporter.id,
+                reported_id,
+                bookmark_id: bookmark.map(|bookmark| bookmark.id),
+                comment: self
+                    .content
+                    .unwrap_or_default()
+                    .chars()
+                    .take(MAX_COMMENT_LENGTH)
+                    .collect(),
+            },
+        )
+        .await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+}
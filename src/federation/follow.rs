@@ -5,11 +5,12 @@ use activitypub_federation::{
     protocol::verification::{verify_domains_match, verify_is_remote_object},
     traits::ActivityHandler,
 };
+use anyhow::anyhow;
 use serde::{Deserialize, Serialize};
 use url::Url;
 
 use crate::{
-    db,
+    db::{self, domain_blocks::Severity},
     federation::{self, activity},
     response_error::{ResponseError, ResponseResult},
 };
@@ -67,8 +68,9 @@ impl ActivityHandler for Follow {
     async fn verify(&self, data: &Data<Self::DataType>) -> Result<(), Self::Error> {
         verify_is_remote_object(&self.actor, data)?;
         verify_domains_match(self.actor.inner(), &self.id)?;
-        // TODO verify that someone on this server is following the actor
-        // https://github.com/raffomania/linkblocks/issues/180
+        if !self.object.is_local(data) {
+            return Err(anyhow!("Can only follow users on this instance").into());
+        }
         Ok(())
     }
 
@@ -77,14 +79,32 @@ impl ActivityHandler for Follow {
         let followed = self.object.dereference_local(data).await?;
 
         let mut tx = data.db_pool.begin().await?;
-        db::follows::upsert(
-            &mut tx,
-            db::follows::Insert {
-                follower_id: actor.id,
-                following_id: followed.id,
-            },
-        )
-        .await?;
+        let domain_block = db::domain_blocks::for_url(&mut tx, self.actor.inner()).await?;
+        if domain_block.is_some_and(|block| block.severity == Severity::Silence) {
+            tracing::debug!("Ignoring follow from silenced domain");
+            return Ok(());
+        }
+
+        let follow = db::follows::Insert {
+            follower_id: actor.id,
+            following_id: followed.id,
+        };
+        // Followers that were approved before don't need to be approved again
+        if followed.manually_approves_followers && !db::follows::exists(&mut tx, &follow).await? {
+            db::follow_requests::upsert(
+                &mut tx,
+                db::follow_requests::Insert {
+                    ap_id: self.id,
+                    follower_id: actor.id,
+                    following_id: followed.id,
+                },
+            )
+            .await?;
+            tx.commit().await?;
+            return Ok(());
+        }
+
+        db::follows::upsert(&mut tx, follow).await?;
         tx.commit().await?;
 
         federation::Accept::send(&followed, self, data).await?;
@@ -0,0 +1,88 @@
+//! Read data from the HTML `content` of objects sent by other fediverse
+//! software.
+
+use url::Url;
+use visdom::Vis;
+
+/// Links in the content, excluding mentions and hashtags which Mastodon and
+/// others render as links, too.
+pub fn links(content: &str) -> Vec<Url> {
+    let Ok(dom) = Vis::load(content.to_string()) else {
+        return Vec::new();
+    };
+
+    let mut links: Vec<Url> = Vec::new();
+    for anchor in dom.find("a") {
+        let attribute = |name: &str| {
+            anchor
+                .get_attribute(name)
+                .map(|value| value.to_string())
+                .unwrap_or_default()
+        };
+        let is_mention_or_hashtag = attribute("class")
+            .split_whitespace()
+            .any(|class| class == "mention" || class == "hashtag")
+            || attribute("rel").split_whitespace().any(|rel| rel == "tag");
+        if is_mention_or_hashtag {
+            continue;
+        }
+
+        let Ok(url) = Url::parse(&attribute("href")) else {
+            continue;
+        };
+        if matches!(url.scheme(), "http" | "https") && !links.contains(&url) {
+            links.push(url);
+        }
+    }
+
+    links
+}
+
+/// The plain text of the content with all links removed, on a single line.
+pub fn text_without_links(content: &str) -> String {
+    // Keep words in separate paragraphs and lines apart once tags are gone
+    let content = content.replace("</p>", "</p> ").replace("<br", " <br");
+    let Ok(dom) = Vis::load(content) else {
+        return String::new();
+    };
+    dom.find("a").remove();
+
+    dom.text().split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// The plain text of the content, keeping paragraphs and line breaks.
+pub fn text(content: &str) -> String {
+    let content = content.replace("</p>", "</p>\n\n").replace("<br", "\n<br");
+    let Ok(dom) = Vis::load(content) else {
+        return String::new();
+    };
+
+    let mut lines: Vec<String> = Vec::new();
+    for line in dom.text().lines() {
+        let line = line.split_whitespace().collect::<Vec<_>>().join(" ");
+        // Collapse runs of empty lines into a single paragraph break
+        if line.is_empty() && lines.last().is_none_or(String::is_empty) {
+            continue;
+        }
+        lines.push(line);
+    }
+
+    lines.join("\n").trim().to_string()
+}
+
+/// Format plain text as HTML, the inverse of [`text`].
+pub fn from_text(text: &str) -> String {
+    text.split("\n\n")
+        .map(str::trim)
+        .filter(|paragraph| !paragraph.is_empty())
+        .map(|paragraph| format!("<p>{}</p>", escape(paragraph).replace('\n', "<br>")))
+        .collect()
+}
+
+/// Escape text so it can be put into HTML content and attribute values.
+pub fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,205 @@
+//! Likes and boosts of our bookmarks by users of other platforms, and their
+//! `Undo`s. We only receive these, local users don't send them. LitePub relays
+//! also use `Announce` to pass on objects, see [`federation::relay`].
+
+use activitypub_federation::{
+    fetch::object_id::ObjectId,
+    kinds::activity::{AnnounceType, LikeType, UndoType},
+    protocol::verification::{verify_domains_match, verify_is_remote_object, verify_urls_match},
+    traits::ActivityHandler,
+};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::{
+    db::{self, interactions::Kind},
+    federation,
+    response_error::{ResponseError, ResponseResult, into_option},
+};
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Like {
+    pub actor: ObjectId<db::ApUser>,
+    pub object: Url,
+    #[serde(rename = "type")]
+    pub kind: LikeType,
+    pub id: Url,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Announce {
+    pub actor: ObjectId<db::ApUser>,
+    pub object: Url,
+    #[serde(rename = "type")]
+    pub kind: AnnounceType,
+    pub id: Url,
+}
+
+/// Either of the activities an [`UndoInteraction`] can revert.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(untagged)]
+pub enum Interaction {
+    Like(Like),
+    Announce(Announce),
+}
+
+impl Interaction {
+    fn actor(&self) -> &ObjectId<db::ApUser> {
+        match self {
+            Interaction::Like(like) => &like.actor,
+            Interaction::Announce(announce) => &announce.actor,
+        }
+    }
+
+    fn id(&self) -> &Url {
+        match self {
+            Interaction::Like(like) => &like.id,
+            Interaction::Announce(announce) => &announce.id,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct UndoInteraction {
+    pub actor: ObjectId<db::ApUser>,
+    pub object: Interaction,
+    #[serde(rename = "type")]
+    pub kind: UndoType,
+    pub id: Url,
+}
+
+fn verify_interaction(
+    actor: &ObjectId<db::ApUser>,
+    id: &Url,
+    data: &federation::Data,
+) -> ResponseResult<()> {
+    verify_is_remote_object(actor, data)?;
+    verify_domains_match(actor.inner(), id)?;
+    Ok(())
+}
+
+/// Remember an interaction with one of our bookmarks. Users also boost all
+/// kinds of other posts, which reach us through the shared inbox, so we ignore
+/// interactions with anything else.
+async fn receive_interaction(
+    actor: &ObjectId<db::ApUser>,
+    object: Url,
+    id: Url,
+    kind: Kind,
+    data: &federation::Data,
+) -> ResponseResult<()> {
+    let mut tx = data.db_pool.begin().await?;
+    let bookmark = into_option(db::bookmarks::by_ap_id(&mut tx, object.into()).await)?;
+    let Some(bookmark) = bookmark.filter(|bookmark| bookmark.ap_id.is_local(data)) else {
+        tracing::debug!("Ignoring {} of unknown object", kind.as_str());
+        return Ok(());
+    };
+    // Private bookmarks are never sent to anyone, so nobody can like them
+    if !db::bookmarks::is_public(&mut tx, bookmark.id).await? {
+        tracing::debug!("Ignoring {} of private bookmark", kind.as_str());
+        return Ok(());
+    }
+    drop(tx);
+
+    let actor = actor.dereference(data).await?;
+
+    let mut tx = data.db_pool.begin().await?;
+    db::interactions::upsert(
+        &mut tx,
+        db::interactions::Insert {
+            ap_id: id,
+            ap_user_id: actor.id,
+            bookmark_id: bookmark.id,
+            kind,
+        },
+    )
+    .await?;
+    tx.commit().await?;
+
+    Ok(())
+}
+
+#[async_trait::async_trait]
+impl ActivityHandler for Like {
+    type DataType = super::Context;
+    type Error = ResponseError;
+
+    fn id(&self) -> &Url {
+        &self.id
+    }
+
+    fn actor(&self) -> &Url {
+        self.actor.inner()
+    }
+
+    async fn verify(&self, data: &federation::Data) -> Result<(), Self::Error> {
+        verify_interaction(&self.actor, &self.id, data)
+    }
+
+    async fn receive(self, data: &federation::Data) -> Result<(), Self::Error> {
+        receive_interaction(&self.actor, self.object, self.id, Kind::Like, data).await
+    }
+}
+
+#[async_trait::async_trait]
+impl ActivityHandler for Announce {
+    type DataType = super::Context;
+    type Error = ResponseError;
+
+    fn id(&self) -> &Url {
+        &self.id
+    }
+
+    fn actor(&self) -> &Url {
+        self.actor.inner()
+    }
+
+    async fn verify(&self, data: &federation::Data) -> Result<(), Self::Error> {
+        verify_interaction(&self.actor, &self.id, data)
+    }
+
+    async fn receive(self, data: &federation::Data) -> Result<(), Self::Error> {
+        if federation::relay::receive_announce(&self.actor, self.object.clone(), data).await? {
+            return Ok(());
+        }
+        receive_interaction(&self.actor, self.object, self.id, Kind::Boost, data).await
+    }
+}
+
+#[async_trait::async_trait]
+impl ActivityHandler for UndoInteraction {
+    type DataType = super::Context;
+    type Error = ResponseError;
+
+    fn id(&self) -> &Url {
+        &self.id
+    }
+
+    fn actor(&self) -> &Url {
+        self.actor.inner()
+    }
+
+    async fn verify(&self, data: &federation::Data) -> Result<(), Self::Error> {
+        verify_urls_match(self.actor.inner(), self.object.actor().inner())?;
+        verify_interaction(&self.actor, &self.id, data)?;
+        verify_domains_match(self.actor.inner(), self.object.id())?;
+        Ok(())
+    }
+
+    async fn receive(self, data: &federation::Data) -> Result<(), Self::Error> {
+        let mut tx = data.db_pool.begin().await?;
+        // We don't know the actor if we never stored any of their interactions
+        let Some(actor) =
+            into_option(db::ap_users::read_by_ap_id(&mut tx, self.actor.inner()).await)?
+        else {
+            return Ok(());
+        };
+        db::interactions::remove(&mut tx, self.object.id(), actor.id).await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+}
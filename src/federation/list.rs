@@ -0,0 +1,57 @@
+use activitypub_federation::{
+    fetch::object_id::ObjectId, kinds::collection::OrderedCollectionType,
+};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::{
+    db::{self, AppTx, LinkDestinationWithChildren},
+    response_error::{ResponseError, ResponseResult},
+};
+
+/// Public lists as we send them to other instances. The items are the ids of
+/// the bookmarks and public lists linked from the list.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ListJson {
+    pub id: Url,
+    #[serde(rename = "type")]
+    pub kind: OrderedCollectionType,
+    pub attributed_to: ObjectId<db::ApUser>,
+    pub name: String,
+    pub summary: Option<String>,
+    /// "Identifies one or more links to representations of the object"
+    pub url: Url,
+    pub total_items: usize,
+    pub ordered_items: Vec<Url>,
+}
+
+impl ListJson {
+    pub async fn read(tx: &mut AppTx, list: db::List, base_url: &Url) -> ResponseResult<Self> {
+        if list.private {
+            return Err(ResponseError::NotFound);
+        }
+
+        let author = db::ap_users::read_by_id(tx, list.ap_user_id).await?;
+        let ordered_items = db::links::list_by_list(tx, list.id, None)
+            .await?
+            .into_iter()
+            .map(|link| match link.dest {
+                LinkDestinationWithChildren::Bookmark(bookmark) => Ok(bookmark.ap_id.into_inner()),
+                LinkDestinationWithChildren::List(child) => Ok(base_url.join(&child.list.path())?),
+            })
+            .collect::<ResponseResult<Vec<_>>>()?;
+        let url = base_url.join(&list.path())?;
+
+        Ok(ListJson {
+            id: url.clone(),
+            kind: OrderedCollectionType::OrderedCollection,
+            attributed_to: author.ap_id,
+            name: list.title,
+            summary: list.content,
+            url,
+            total_items: ordered_items.len(),
+            ordered_items,
+        })
+    }
+}
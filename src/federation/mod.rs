@@ -1,18 +1,42 @@
 pub mod accept;
 pub mod activity;
+pub mod activity_log;
 pub mod bookmark;
+pub mod collections;
 pub mod config;
 pub mod context;
 pub mod create_bookmark;
+pub mod create_reply;
+pub mod delete;
+pub mod delivery;
+pub mod flag;
 pub mod follow;
+pub mod html;
+pub mod interaction;
+pub mod list;
+pub mod move_actor;
+pub mod nodeinfo;
 pub mod person;
+pub mod refresh;
+pub mod reject;
+pub mod relay;
+pub mod reply;
 pub mod signing;
+pub mod trust;
 pub mod undo_follow;
+pub mod update_person;
 pub mod webfinger;
 
 pub use accept::Accept;
 pub use bookmark::BookmarkJson;
 pub use context::{Context, Data};
 pub use create_bookmark::CreateBookmark;
+pub use create_reply::CreateReply;
+pub use delete::Delete;
+pub use flag::Flag;
 pub use follow::Follow;
+pub use interaction::{Announce, Like, UndoInteraction};
+pub use move_actor::Move;
+pub use reject::Reject;
 pub use undo_follow::UndoFollow;
+pub use update_person::UpdatePerson;
@@ -0,0 +1,147 @@
+use activitypub_federation::{
+    fetch::object_id::ObjectId,
+    kinds::activity::MoveType,
+    protocol::{
+        helpers::deserialize_one_or_many,
+        verification::{verify_is_remote_object, verify_urls_match},
+    },
+    traits::ActivityHandler,
+};
+use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::{
+    db, federation,
+    response_error::{ResponseError, ResponseResult},
+};
+
+/// Sent when a user moves to a different account, so their followers can
+/// follow the new account instead.
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Move {
+    pub actor: ObjectId<db::ApUser>,
+    #[serde(deserialize_with = "deserialize_one_or_many")]
+    pub to: Vec<Url>,
+    /// The account that moved, always the same as the actor
+    pub object: ObjectId<db::ApUser>,
+    /// The account that the actor moved to
+    pub target: ObjectId<db::ApUser>,
+    #[serde(rename = "type")]
+    pub kind: MoveType,
+    pub id: Url,
+}
+
+impl Move {
+    pub async fn send_to_followers(
+        actor: &db::ApUser,
+        target: &db::ApUser,
+        context: &federation::Data,
+    ) -> ResponseResult<()> {
+        let id = super::activity::generate_id(context)?;
+
+        let mut tx = context.db_pool.begin().await?;
+        let followers = db::ap_users::list_followers(&mut tx, actor.id).await?;
+        drop(tx);
+
+        let to = followers
+            .iter()
+            .map(|ap_user| ap_user.ap_id.clone().into_inner())
+            .collect();
+        let move_activity = Move {
+            actor: actor.ap_id.clone(),
+            to,
+            object: actor.ap_id.clone(),
+            target: target.ap_id.clone(),
+            kind: MoveType::Move,
+            id,
+        };
+
+        super::activity::send(
+            actor,
+            move_activity,
+            &followers.iter().collect::<Vec<_>>(),
+            context,
+        )
+        .await?;
+
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl ActivityHandler for Move {
+    type DataType = super::Context;
+    type Error = ResponseError;
+
+    fn id(&self) -> &Url {
+        &self.id
+    }
+
+    fn actor(&self) -> &Url {
+        self.actor.inner()
+    }
+
+    async fn verify(&self, data: &federation::Data) -> Result<(), Self::Error> {
+        // Users can only move their own account
+        verify_urls_match(self.actor.inner(), self.object.inner())?;
+        verify_is_remote_object(&self.actor, data)?;
+        Ok(())
+    }
+
+    async fn receive(self, data: &federation::Data) -> Result<(), Self::Error> {
+        // Fetch both accounts again to get their current aliases
+        let actor = self.actor.dereference_forced(data).await?;
+        let target = if self.target.is_local(data) {
+            self.target.dereference_local(data).await?
+        } else {
+            self.target.dereference_forced(data).await?
+        };
+
+        // Only follow the move if the new account confirms that it belongs
+        // to the same person
+        if !target.also_known_as.contains(actor.ap_id.inner()) {
+            return Err(anyhow!(
+                "{} is not an alias of {}",
+                actor.ap_id.inner(),
+                target.ap_id.inner()
+            )
+            .into());
+        }
+
+        // Remember the move, so follows of the old account can be dropped
+        // once the new account accepts them
+        let mut tx = data.db_pool.begin().await?;
+        let actor = db::ap_users::set_moved_to(&mut tx, actor.id, target.ap_id.inner()).await?;
+        tx.commit().await?;
+
+        move_local_followers(&actor, &target, data).await
+    }
+}
+
+/// Make all local followers of `actor` follow `target` instead. `actor` must
+/// already have moved to `target`. Local followers of a remote account keep
+/// following `actor` until `target` accepts their `Follow`.
+pub async fn move_local_followers(
+    actor: &db::ApUser,
+    target: &db::ApUser,
+    data: &federation::Data,
+) -> ResponseResult<()> {
+    let mut tx = data.db_pool.begin().await?;
+    if target.ap_id.is_local(data) {
+        db::follows::move_local_followers(&mut tx, actor.id, target.id).await?;
+        tx.commit().await?;
+        return Ok(());
+    }
+    let local_followers = db::ap_users::list_local_followers(&mut tx, actor.id).await?;
+    drop(tx);
+
+    for follower in local_followers {
+        federation::Follow::new(&follower, target, data)?
+            .send(&follower, target, data)
+            .await?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,139 @@
+//! [NodeInfo](https://nodeinfo.diaspora.software) documents, which let other
+//! servers and crawlers discover basic information about this instance.
+
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::db;
+
+const REPOSITORY: &str = "https://github.com/raffomania/linkblocks";
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub enum Version {
+    #[serde(rename = "2.0")]
+    V2_0,
+    #[serde(rename = "2.1")]
+    V2_1,
+}
+
+impl Version {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Version::V2_0 => "2.0",
+            Version::V2_1 => "2.1",
+        }
+    }
+
+    pub fn schema_url(self) -> String {
+        format!(
+            "http://nodeinfo.diaspora.software/ns/schema/{}",
+            self.as_str()
+        )
+    }
+}
+
+/// Served at `/.well-known/nodeinfo`, points to the documents for each
+/// supported NodeInfo version.
+#[derive(Serialize, Debug)]
+pub struct Discovery {
+    links: Vec<DiscoveryLink>,
+}
+
+#[derive(Serialize, Debug)]
+struct DiscoveryLink {
+    rel: String,
+    href: Url,
+}
+
+impl Discovery {
+    pub fn new(base_url: &Url) -> Result<Self, url::ParseError> {
+        let links = [Version::V2_1, Version::V2_0]
+            .into_iter()
+            .map(|version| {
+                Ok(DiscoveryLink {
+                    rel: version.schema_url(),
+                    href: base_url.join("/nodeinfo/")?.join(version.as_str())?,
+                })
+            })
+            .collect::<Result<_, url::ParseError>>()?;
+
+        Ok(Discovery { links })
+    }
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeInfo {
+    version: Version,
+    software: Software,
+    protocols: Vec<&'static str>,
+    services: Services,
+    open_registrations: bool,
+    usage: Usage,
+    metadata: Metadata,
+}
+
+#[derive(Serialize, Debug)]
+struct Software {
+    name: &'static str,
+    version: &'static str,
+    /// Only available since NodeInfo 2.1
+    #[serde(skip_serializing_if = "Option::is_none")]
+    repository: Option<&'static str>,
+}
+
+#[derive(Serialize, Debug)]
+struct Services {
+    inbound: Vec<&'static str>,
+    outbound: Vec<&'static str>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct Usage {
+    users: UsageUsers,
+    local_posts: i64,
+}
+
+#[derive(Serialize, Debug)]
+struct UsageUsers {
+    total: i64,
+}
+
+/// Free-form, linkblocks-specific information
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct Metadata {
+    local_lists: i64,
+}
+
+impl NodeInfo {
+    pub fn new(version: Version, stats: db::instance::Stats, open_registrations: bool) -> Self {
+        let repository = match version {
+            Version::V2_0 => None,
+            Version::V2_1 => Some(REPOSITORY),
+        };
+
+        NodeInfo {
+            version,
+            software: Software {
+                name: "linkblocks",
+                version: env!("CARGO_PKG_VERSION"),
+                repository,
+            },
+            protocols: vec!["activitypub"],
+            services: Services {
+                inbound: Vec::new(),
+                outbound: Vec::new(),
+            },
+            open_registrations,
+            usage: Usage {
+                users: UsageUsers { total: stats.users },
+                local_posts: stats.local_bookmarks,
+            },
+            metadata: Metadata {
+                local_lists: stats.local_lists,
+            },
+        }
+    }
+}
@@ -1,16 +1,19 @@
 //! Adapter to make [`db::ApUser`] compatible with the
 //! [`activitypub_federation`] crate
 
+use std::str::FromStr;
+
 use activitypub_federation::{
     fetch::object_id::ObjectId,
-    kinds::actor::PersonType,
+    kinds::object::ImageType,
     protocol::{
+        helpers::deserialize_skip_error,
         public_key::PublicKey,
         verification::{verify_domains_match, verify_is_remote_object},
     },
     traits::{Actor, Object},
 };
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, anyhow};
 use garde::Validate;
 use serde::{Deserialize, Serialize};
 use url::Url;
@@ -28,15 +31,144 @@ use crate::{
 pub struct Person {
     pub id: ObjectId<db::ApUser>,
     #[serde(rename = "type")]
-    pub kind: PersonType,
+    pub kind: ActorKind,
     pub preferred_username: String,
     pub name: Option<String>,
     pub summary: Option<String>,
     pub inbox: Url,
     pub outbox: Url,
+    /// The public lists of linkblocks users. Other software doesn't have
+    /// lists.
+    #[serde(
+        deserialize_with = "deserialize_skip_error",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub lists: Option<Url>,
     pub public_key: PublicKey,
     /// "Identifies one or more links to representations of the object"
     pub url: Url,
+    #[serde(
+        deserialize_with = "deserialize_skip_error",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub endpoints: Option<Endpoints>,
+    /// Whether followers need to be approved, also known as a "locked" account
+    #[serde(default)]
+    pub manually_approves_followers: bool,
+    /// The avatar
+    #[serde(
+        deserialize_with = "deserialize_skip_error",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub icon: Option<Image>,
+    /// The header image
+    #[serde(
+        deserialize_with = "deserialize_skip_error",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub image: Option<Image>,
+    /// Other accounts of the same person. Needed to move from one of those
+    /// accounts to this one.
+    #[serde(
+        deserialize_with = "deserialize_skip_error",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub also_known_as: Vec<Url>,
+    #[serde(
+        deserialize_with = "deserialize_skip_error",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub moved_to: Option<Url>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Image {
+    #[serde(rename = "type")]
+    pub kind: ImageType,
+    pub url: Url,
+}
+
+impl Image {
+    fn new(url: Url) -> Self {
+        Image {
+            kind: ImageType::Image,
+            url,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Endpoints {
+    /// An inbox shared by all users of an instance, used to deliver public
+    /// activities to many recipients with a single request
+    pub shared_inbox: Url,
+}
+
+/// The actor types we know how to interact with.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ActorKind {
+    Person,
+    /// Used for the instance actor, which acts on behalf of the whole instance
+    Application,
+    Service,
+    Group,
+    Organization,
+}
+
+impl ActorKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ActorKind::Person => "Person",
+            ActorKind::Application => "Application",
+            ActorKind::Service => "Service",
+            ActorKind::Group => "Group",
+            ActorKind::Organization => "Organization",
+        }
+    }
+}
+
+impl FromStr for ActorKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "Person" => Ok(ActorKind::Person),
+            "Application" => Ok(ActorKind::Application),
+            "Service" => Ok(ActorKind::Service),
+            "Group" => Ok(ActorKind::Group),
+            "Organization" => Ok(ActorKind::Organization),
+            other => Err(anyhow!("Unknown actor type: {other}")),
+        }
+    }
+}
+
+impl db::ApUser {
+    /// The HTML page of a local user.
+    pub fn profile_url(&self, base_url: &Url) -> Result<Url, url::ParseError> {
+        match self.kind {
+            // The instance actor has no profile page, so point to the instance itself
+            ActorKind::Application => Ok(base_url.clone()),
+            _ => base_url.join("/user/")?.join(&self.username),
+        }
+    }
+
+    /// The outbox of a local user.
+    pub fn outbox_url(&self, base_url: &Url) -> Result<Url, url::ParseError> {
+        base_url.join("/ap/outbox/")?.join(&self.id.to_string())
+    }
+
+    /// The collection of a local user's public lists.
+    pub fn lists_url(&self, base_url: &Url) -> Result<Url, url::ParseError> {
+        base_url.join("/ap/lists/")?.join(&self.id.to_string())
+    }
 }
 
 #[async_trait::async_trait]
@@ -57,21 +189,28 @@ impl Object for db::ApUser {
 
     async fn into_json(self, context: &super::Data) -> Result<Self::Kind, Self::Error> {
         let public_key = self.public_key();
-        let url = context.base_url.join("/user/")?.join(&self.username)?;
-        let outbox = context
-            .base_url
-            .join("/ap/outbox/")?
-            .join(&self.id.to_string())?;
+        let url = self.profile_url(&context.base_url)?;
+        let outbox = self.outbox_url(&context.base_url)?;
+        let lists = self.lists_url(&context.base_url)?;
         Ok(Person {
             id: self.ap_id,
             name: self.display_name,
             preferred_username: self.username,
-            kind: PersonType::Person,
+            kind: self.kind,
             inbox: self.inbox_url,
             outbox,
+            lists: Some(lists),
             public_key,
             summary: self.bio,
             url,
+            endpoints: self
+                .shared_inbox_url
+                .map(|shared_inbox| Endpoints { shared_inbox }),
+            manually_approves_followers: self.manually_approves_followers,
+            icon: self.avatar_url.map(Image::new),
+            image: self.header_url.map(Image::new),
+            also_known_as: self.also_known_as,
+            moved_to: self.moved_to,
         })
     }
 
@@ -95,6 +234,14 @@ impl Object for db::ApUser {
         tx.commit().await?;
         Ok(new_user)
     }
+
+    /// Called when the instance of a remote user responds with `410 Gone`
+    async fn delete(self, data: &super::Data) -> Result<(), Self::Error> {
+        let mut tx = data.db_pool.begin().await?;
+        db::ap_users::delete_remote(&mut tx, self.id).await?;
+        tx.commit().await?;
+        Ok(())
+    }
 }
 
 impl Actor for db::ApUser {
@@ -113,4 +260,8 @@ impl Actor for db::ApUser {
     fn inbox(&self) -> Url {
         self.inbox_url.clone()
     }
+
+    fn shared_inbox(&self) -> Option<Url> {
+        self.shared_inbox_url.clone()
+    }
 }
@@ -0,0 +1,61 @@
+//! Keep our copies of remote actors up to date, even if their instance
+//! doesn't send us `Update` activities.
+
+use std::time::Duration;
+
+use activitypub_federation::{config::FederationConfig, error::Error};
+use time::OffsetDateTime;
+
+use crate::{
+    db,
+    federation::{Context, Data},
+    response_error::{ResponseError, ResponseResult},
+};
+
+/// How often to look for remote actors that need to be fetched again
+const CHECK_INTERVAL: Duration = Duration::from_secs(60 * 60);
+/// Remote actors fetched longer ago than this are considered stale
+const MAX_AGE: time::Duration = time::Duration::days(1);
+/// Spread the load on remote instances by refreshing only a few actors per
+/// check
+const BATCH_SIZE: i64 = 100;
+
+pub async fn periodically_refresh_remote_actors(config: FederationConfig<Context>) {
+    let mut interval = tokio::time::interval(CHECK_INTERVAL);
+    // The first tick completes immediately, give the server some time to start
+    // up instead
+    interval.tick().await;
+
+    loop {
+        interval.tick().await;
+        if let Err(e) = refresh_remote_actors(&config.to_request_data()).await {
+            tracing::error!("Failed to refresh remote actors: {e:?}");
+        }
+    }
+}
+
+pub async fn refresh_remote_actors(data: &Data) -> ResponseResult<()> {
+    let mut tx = data.db_pool.begin().await?;
+    let stale_actors =
+        db::ap_users::list_stale_remote(&mut tx, OffsetDateTime::now_utc() - MAX_AGE, BATCH_SIZE)
+            .await?;
+    drop(tx);
+
+    // These actors are stale for the federation library, too, so it fetches
+    // them again
+    for actor in stale_actors {
+        match actor.ap_id.dereference(data).await {
+            Ok(_) => {}
+            // The instance told us that the actor is gone for good, and the
+            // library already removed it through `Object::delete`
+            Err(ResponseError::FederationError(Error::ObjectDeleted(..))) => {
+                tracing::info!("Removed deleted actor {}", actor.ap_id.inner());
+            }
+            Err(e) => {
+                tracing::warn!("Failed to refresh actor {}: {e:?}", actor.ap_id.inner());
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,97 @@
+use activitypub_federation::{
+    config::Data,
+    fetch::object_id::ObjectId,
+    kinds::{activity::RejectType, public},
+    protocol::verification::{verify_is_remote_object, verify_urls_match},
+    traits::ActivityHandler,
+};
+use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::{
+    db::{self, relays},
+    federation::{activity, follow::Follow, relay},
+    response_error::{ResponseError, ResponseResult},
+};
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Reject {
+    actor: ObjectId<db::ApUser>,
+    object: Follow,
+    #[serde(rename = "type")]
+    kind: RejectType,
+    id: Url,
+}
+
+impl Reject {
+    pub async fn send(
+        actor: &db::ApUser,
+        object: Follow,
+        context: &Data<super::context::Context>,
+    ) -> ResponseResult<()> {
+        let id = super::activity::generate_id(context)?;
+        let follower = object.actor.dereference(context).await?;
+        let reject = Reject {
+            actor: actor.ap_id.clone(),
+            object,
+            kind: RejectType::Reject,
+            id,
+        };
+        activity::send(actor, reject, &[&follower], context).await?;
+
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl ActivityHandler for Reject {
+    type DataType = super::context::Context;
+    type Error = ResponseError;
+
+    fn id(&self) -> &Url {
+        &self.id
+    }
+
+    fn actor(&self) -> &Url {
+        self.actor.inner()
+    }
+
+    async fn verify(&self, data: &Data<Self::DataType>) -> Result<(), Self::Error> {
+        // Only the followed user can reject a follow. Relays reject follows of
+        // the public collection, which we check when receiving.
+        if *self.object.object.inner() != public() {
+            verify_urls_match(self.actor.inner(), self.object.object.inner())?;
+        }
+        verify_is_remote_object(&self.actor, data)?;
+        Ok(())
+    }
+
+    async fn receive(self, data: &Data<Self::DataType>) -> Result<(), Self::Error> {
+        if relay::receive_answer(&self.object, &self.actor, relays::State::Rejected, data).await? {
+            return Ok(());
+        }
+        if *self.object.object.inner() == public() {
+            return Err(anyhow!("Unknown relay subscription").into());
+        }
+
+        let follower = self.object.actor.dereference_local(data).await?;
+        let following = self.actor.dereference(data).await?;
+
+        // Remote users can also reject follows they accepted earlier, which
+        // removes the follower
+        let mut tx = data.db_pool.begin().await?;
+        db::follows::remove(
+            &mut tx,
+            db::follows::Insert {
+                follower_id: follower.id,
+                following_id: following.id,
+            },
+        )
+        .await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+}
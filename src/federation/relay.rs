@@ -0,0 +1,266 @@
+//! Relays pass public activities on to every instance subscribed to them, so
+//! small instances get to see more of the network. The instance actor
+//! subscribes on behalf of the whole instance, and link posts we receive
+//! through relays end up in the discover feed.
+
+use activitypub_federation::{
+    error::Error,
+    fetch::object_id::ObjectId,
+    http_signatures::signing_actor,
+    kinds::{
+        activity::{FollowType, UndoType},
+        public,
+    },
+    protocol::verification::verify_domains_match,
+    traits::{ActivityHandler, Actor},
+};
+use anyhow::Context as _;
+use axum::http::request::Parts;
+use serde::{Deserialize, Serialize};
+use url::Url;
+use uuid::Uuid;
+
+use crate::{
+    db::{
+        self,
+        relays::{Kind, Relay, State},
+    },
+    federation::{
+        self, Follow, UndoFollow, activity, context::with_context, delete::deserialize_object_id,
+        delivery,
+    },
+    response_error::{ResponseError, ResponseResult, into_option},
+};
+
+/// Subscribe to a relay. Mastodon-style relays are given by their inbox,
+/// LitePub relays by their actor.
+pub async fn subscribe(kind: Kind, url: Url, data: &federation::Data) -> ResponseResult<Relay> {
+    let (inbox_url, ap_user_id) = match kind {
+        Kind::Mastodon => (url.clone(), None),
+        Kind::Litepub => {
+            let relay_actor = ObjectId::<db::ApUser>::from(url.clone())
+                .dereference(data)
+                .await?;
+            (relay_actor.shared_inbox_or_inbox(), Some(relay_actor.id))
+        }
+    };
+
+    let mut tx = data.db_pool.begin().await?;
+    let instance_actor = db::ap_users::read_instance_actor(&mut tx, &data.base_url).await?;
+    let relay = db::relays::insert(
+        &mut tx,
+        db::relays::Insert {
+            kind,
+            url,
+            inbox_url,
+            ap_user_id,
+            follow_ap_id: activity::generate_id(data)?,
+        },
+    )
+    .await?;
+    tx.commit().await?;
+
+    deliver(
+        &instance_actor,
+        follow(&relay, &instance_actor),
+        &relay,
+        data,
+    )
+    .await?;
+
+    Ok(relay)
+}
+
+/// Unsubscribe from a relay and forget about it.
+pub async fn unsubscribe(id: Uuid, data: &federation::Data) -> ResponseResult<()> {
+    let mut tx = data.db_pool.begin().await?;
+    let relay = db::relays::by_id(&mut tx, id).await?;
+    let instance_actor = db::ap_users::read_instance_actor(&mut tx, &data.base_url).await?;
+    db::relays::delete_by_id(&mut tx, id).await?;
+    tx.commit().await?;
+
+    let follow = follow(&relay, &instance_actor);
+    let undo = UndoFollow {
+        actor: instance_actor.ap_id.clone(),
+        to: Some([follow.object.clone()]),
+        object: follow,
+        kind: UndoType::Undo,
+        id: activity::generate_id(data)?,
+    };
+    deliver(&instance_actor, undo, &relay, data).await?;
+
+    Ok(())
+}
+
+/// The `Follow` we subscribed to the relay with.
+fn follow(relay: &Relay, instance_actor: &db::ApUser) -> Follow {
+    let object = match relay.kind {
+        Kind::Mastodon => public(),
+        Kind::Litepub => relay.url.clone(),
+    };
+    Follow {
+        actor: instance_actor.ap_id.clone(),
+        object: object.into(),
+        kind: FollowType::Follow,
+        id: relay.follow_ap_id.clone(),
+    }
+}
+
+/// Relays aren't users we know, so deliver straight to their inbox.
+async fn deliver<Activity>(
+    instance_actor: &db::ApUser,
+    activity: Activity,
+    relay: &Relay,
+    data: &federation::Data,
+) -> ResponseResult<()>
+where
+    Activity: ActivityHandler + Serialize + Send + Sync,
+{
+    delivery::enqueue(
+        &with_context(activity),
+        instance_actor.id,
+        vec![relay.inbox_url.clone()],
+        data,
+    )
+    .await
+}
+
+/// Handle a relay accepting or rejecting our subscription. Returns `false` if
+/// the follow wasn't sent to a relay.
+pub async fn receive_answer(
+    follow: &Follow,
+    actor: &ObjectId<db::ApUser>,
+    state: State,
+    data: &federation::Data,
+) -> ResponseResult<bool> {
+    let mut tx = data.db_pool.begin().await?;
+    let Some(relay) = into_option(db::relays::by_follow_ap_id(&mut tx, &follow.id).await)? else {
+        return Ok(false);
+    };
+    drop(tx);
+    verify_domains_match(actor.inner(), &relay.url)?;
+
+    let actor = actor.dereference(data).await?;
+    // LitePub relays are subscribed to by their actor, so only it may answer
+    if relay.ap_user_id.is_some_and(|id| id != actor.id) {
+        return Err(Error::ActivitySignatureInvalid.into());
+    }
+    let mut tx = data.db_pool.begin().await?;
+    db::relays::set_state(&mut tx, relay.id, state, actor.id).await?;
+    tx.commit().await?;
+
+    Ok(true)
+}
+
+/// LitePub relays `Announce` the objects they pass on. Returns `false` if the
+/// actor isn't a relay we subscribed to.
+pub async fn receive_announce(
+    actor: &ObjectId<db::ApUser>,
+    object: Url,
+    data: &federation::Data,
+) -> ResponseResult<bool> {
+    let mut tx = data.db_pool.begin().await?;
+    let Some(ap_user) = into_option(db::ap_users::read_by_ap_id(&mut tx, actor.inner()).await)?
+    else {
+        return Ok(false);
+    };
+    let Some(relay) = into_option(db::relays::accepted_by_ap_user(&mut tx, ap_user.id).await)?
+    else {
+        return Ok(false);
+    };
+    drop(tx);
+
+    receive_relayed(&relay, object, data).await?;
+    Ok(true)
+}
+
+#[derive(Deserialize)]
+struct ForwardedActivity {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(deserialize_with = "deserialize_object_id")]
+    object: Url,
+}
+
+/// Mastodon-style relays forward activities of other users, signed with the
+/// relay's key instead of the author's. We can't verify those, so if the
+/// signature belongs to a relay we subscribed to, we fetch the relayed object
+/// from its origin instead of trusting the forwarded copy.
+pub async fn receive_forwarded(
+    parts: &Parts,
+    body: &[u8],
+    data: &federation::Data,
+) -> ResponseResult<()> {
+    let invalid_signature = || ResponseError::from(Error::ActivitySignatureInvalid);
+
+    // Most activities with invalid signatures don't come from relays at all,
+    // so don't bother fetching the signing actor for those
+    let Some(key_host) = signature_key_host(parts) else {
+        return Err(invalid_signature());
+    };
+    let mut tx = data.db_pool.begin().await?;
+    let relays = db::relays::list(&mut tx).await?;
+    drop(tx);
+    let is_relay_host = relays.iter().any(|relay| {
+        relay.state == State::Accepted
+            && [&relay.url, &relay.inbox_url]
+                .iter()
+                .any(|url| url.host_str() == Some(key_host.as_str()))
+    });
+    if !is_relay_host {
+        return Err(invalid_signature());
+    }
+
+    let signer = signing_actor::<db::ApUser, _>(&parts.headers, &parts.method, &parts.uri, data)
+        .await
+        .map_err(|_| invalid_signature())?;
+    let mut tx = data.db_pool.begin().await?;
+    let relay = into_option(db::relays::accepted_by_ap_user(&mut tx, signer.id).await)?
+        .ok_or_else(invalid_signature)?;
+    drop(tx);
+
+    let activity: ForwardedActivity =
+        serde_json::from_slice(body).context("Failed to parse forwarded activity")?;
+    if activity.kind != "Create" {
+        tracing::debug!("Ignoring relayed {} activity", activity.kind);
+        return Ok(());
+    }
+    receive_relayed(&relay, activity.object, data).await
+}
+
+/// The host of the key an HTTP signature was made with, without fetching it.
+fn signature_key_host(parts: &Parts) -> Option<String> {
+    let signature = parts.headers.get("signature")?.to_str().ok()?;
+    let key_id = signature
+        .split(',')
+        .find_map(|param| param.trim().strip_prefix("keyId="))?;
+    let key_id = Url::parse(key_id.trim_matches('"')).ok()?;
+    key_id.host_str().map(ToString::to_string)
+}
+
+/// Add a relayed object to the discover feed, as long as it's a link post.
+async fn receive_relayed(
+    relay: &Relay,
+    object: Url,
+    data: &federation::Data,
+) -> ResponseResult<()> {
+    let bookmark = match ObjectId::<db::Bookmark>::from(object)
+        .dereference(data)
+        .await
+    {
+        Ok(bookmark) => bookmark,
+        Err(e) => {
+            tracing::debug!("Ignoring relayed object: {e:?}");
+            return Ok(());
+        }
+    };
+    if bookmark.ap_id.is_local(data) {
+        return Ok(());
+    }
+
+    let mut tx = data.db_pool.begin().await?;
+    db::relays::insert_relayed_bookmark(&mut tx, relay.id, bookmark.id).await?;
+    tx.commit().await?;
+
+    Ok(())
+}
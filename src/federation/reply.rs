@@ -0,0 +1,172 @@
+use activitypub_federation::{
+    config::Data,
+    fetch::object_id::ObjectId,
+    kinds::{link::MentionType, object::NoteType, public},
+    protocol::{
+        helpers::deserialize_one_or_many,
+        verification::{verify_domains_match, verify_is_remote_object},
+    },
+    traits::Object,
+};
+use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
+use url::Url;
+use uuid::Uuid;
+
+use crate::{
+    db::{self, AppTx},
+    federation::html,
+    response_error::{ResponseError, ResponseResult, into_option},
+};
+
+/// A comment on a bookmark, or on another comment.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplyJson {
+    pub id: ObjectId<db::Reply>,
+    #[serde(rename = "type")]
+    pub kind: NoteType,
+    pub attributed_to: ObjectId<db::ApUser>,
+    #[serde(default, deserialize_with = "deserialize_one_or_many")]
+    pub to: Vec<Url>,
+    #[serde(default, deserialize_with = "deserialize_one_or_many")]
+    pub cc: Vec<Url>,
+    pub content: String,
+    /// The bookmark or reply this one answers
+    pub in_reply_to: Url,
+    /// Mentions of the authors we reply to, so platforms like Mastodon notify
+    /// them
+    #[serde(default, skip_deserializing)]
+    pub tag: Vec<Mention>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Mention {
+    href: Url,
+    #[serde(rename = "type")]
+    kind: MentionType,
+}
+
+/// Where a reply belongs in our database.
+pub struct ReplyTarget {
+    pub bookmark_id: Uuid,
+    pub parent_id: Option<Uuid>,
+}
+
+/// Find the bookmark or reply with the given `ap_id`, if we know about it.
+pub async fn find_target(tx: &mut AppTx, in_reply_to: &Url) -> ResponseResult<Option<ReplyTarget>> {
+    if let Some(parent) = into_option(db::replies::by_ap_id(tx, in_reply_to).await)? {
+        return Ok(Some(ReplyTarget {
+            bookmark_id: parent.bookmark_id,
+            parent_id: Some(parent.id),
+        }));
+    }
+
+    let bookmark = into_option(db::bookmarks::by_ap_id(tx, in_reply_to.clone().into()).await)?;
+    Ok(bookmark.map(|bookmark| ReplyTarget {
+        bookmark_id: bookmark.id,
+        parent_id: None,
+    }))
+}
+
+impl db::Reply {
+    /// The users this reply answers: the author of the bookmark, and the
+    /// author of the parent reply, if any.
+    pub async fn replied_to_authors(&self, tx: &mut AppTx) -> ResponseResult<Vec<db::ApUser>> {
+        let bookmark = db::bookmarks::by_id(tx, self.bookmark_id).await?;
+        let mut authors = vec![db::ap_users::read_by_id(tx, bookmark.ap_user_id).await?];
+        if let Some(parent_id) = self.parent_id {
+            let parent = db::replies::by_id(tx, parent_id).await?;
+            if parent.ap_user_id != bookmark.ap_user_id {
+                authors.push(db::ap_users::read_by_id(tx, parent.ap_user_id).await?);
+            }
+        }
+
+        Ok(authors)
+    }
+}
+
+#[async_trait::async_trait]
+impl Object for db::Reply {
+    type DataType = super::Context;
+    type Kind = ReplyJson;
+    type Error = ResponseError;
+
+    async fn read_from_id(
+        url: Url,
+        data: &Data<Self::DataType>,
+    ) -> Result<Option<Self>, Self::Error> {
+        let mut tx = data.db_pool.begin().await?;
+        into_option(db::replies::by_ap_id(&mut tx, &url).await)
+    }
+
+    async fn into_json(self, data: &Data<Self::DataType>) -> Result<Self::Kind, Self::Error> {
+        let mut tx = data.db_pool.begin().await?;
+        let author = db::ap_users::read_by_id(&mut tx, self.ap_user_id).await?;
+        let in_reply_to = match self.parent_id {
+            Some(parent_id) => db::replies::by_id(&mut tx, parent_id)
+                .await?
+                .ap_id
+                .into_inner(),
+            None => db::bookmarks::by_id(&mut tx, self.bookmark_id)
+                .await?
+                .ap_id
+                .into_inner(),
+        };
+        let replied_to = self
+            .replied_to_authors(&mut tx)
+            .await?
+            .into_iter()
+            .map(|ap_user| ap_user.ap_id.into_inner())
+            .collect::<Vec<_>>();
+
+        Ok(ReplyJson {
+            id: self.ap_id,
+            kind: NoteType::Note,
+            attributed_to: author.ap_id,
+            to: vec![public()],
+            cc: replied_to.clone(),
+            content: html::from_text(&self.content),
+            in_reply_to,
+            tag: replied_to
+                .into_iter()
+                .map(|href| Mention {
+                    href,
+                    kind: MentionType::Mention,
+                })
+                .collect(),
+        })
+    }
+
+    async fn verify(
+        json: &Self::Kind,
+        expected_domain: &Url,
+        data: &Data<Self::DataType>,
+    ) -> Result<(), Self::Error> {
+        verify_domains_match(json.id.inner(), expected_domain)?;
+        verify_is_remote_object(&json.id, data)?;
+        Ok(())
+    }
+
+    async fn from_json(json: Self::Kind, data: &Data<Self::DataType>) -> Result<Self, Self::Error> {
+        let author = json.attributed_to.dereference(data).await?;
+        let mut tx = data.db_pool.begin().await?;
+        let target = find_target(&mut tx, &json.in_reply_to)
+            .await?
+            .ok_or_else(|| anyhow!("Reply to unknown object {}", json.in_reply_to))?;
+        let reply = db::replies::upsert_remote(
+            &mut tx,
+            &json.id,
+            db::replies::Insert {
+                ap_user_id: author.id,
+                bookmark_id: target.bookmark_id,
+                parent_id: target.parent_id,
+                content: html::text(&json.content),
+            },
+        )
+        .await?;
+        tx.commit().await?;
+
+        Ok(reply)
+    }
+}
@@ -0,0 +1,65 @@
+//! Users only receive bookmarks from remote users they follow. To search the
+//! bookmarks of trusted users without following them, the instance actor
+//! follows everyone trusted by someone on this instance instead.
+
+use crate::{
+    db,
+    federation::{self, Follow, UndoFollow},
+    response_error::ResponseResult,
+};
+
+/// Start receiving the public bookmarks of a newly trusted remote user.
+pub async fn follow_trusted(trusted: &db::ApUser, data: &federation::Data) -> ResponseResult<()> {
+    let mut tx = data.db_pool.begin().await?;
+    let instance_actor = db::ap_users::read_instance_actor(&mut tx, &data.base_url).await?;
+    let already_following = db::follows::exists(
+        &mut tx,
+        &db::follows::Insert {
+            follower_id: instance_actor.id,
+            following_id: trusted.id,
+        },
+    )
+    .await?;
+    drop(tx);
+
+    if trusted.ap_id.is_local(data) || already_following {
+        return Ok(());
+    }
+
+    Follow::new(&instance_actor, trusted, data)?
+        .send(&instance_actor, trusted, data)
+        .await
+}
+
+/// Stop receiving bookmarks of a remote user once nobody on this instance
+/// trusts them anymore.
+pub async fn unfollow_untrusted(
+    untrusted: &db::ApUser,
+    data: &federation::Data,
+) -> ResponseResult<()> {
+    if untrusted.ap_id.is_local(data) {
+        return Ok(());
+    }
+
+    let mut tx = data.db_pool.begin().await?;
+    if db::trusts::is_trusted_locally(&mut tx, untrusted.id).await? {
+        return Ok(());
+    }
+    let instance_actor = db::ap_users::read_instance_actor(&mut tx, &data.base_url).await?;
+    let follow = db::follows::Insert {
+        follower_id: instance_actor.id,
+        following_id: untrusted.id,
+    };
+    if !db::follows::exists(&mut tx, &follow).await? {
+        return Ok(());
+    }
+    db::follows::remove(&mut tx, follow).await?;
+    tx.commit().await?;
+
+    UndoFollow::send(
+        &instance_actor,
+        Follow::new(&instance_actor, untrusted, data)?,
+        data,
+    )
+    .await
+}
@@ -92,6 +92,7 @@ impl ActivityHandler for UndoFollow {
             },
         )
         .await?;
+        db::follow_requests::remove(&mut tx, follower.id, following.id).await?;
         tx.commit().await?;
 
         Ok(())
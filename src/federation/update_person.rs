@@ -0,0 +1,97 @@
+use activitypub_federation::{
+    fetch::object_id::ObjectId,
+    kinds::activity::UpdateType,
+    protocol::{
+        helpers::deserialize_one_or_many,
+        verification::{verify_is_remote_object, verify_urls_match},
+    },
+    traits::{ActivityHandler, Object},
+};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::{
+    db,
+    federation::{self, person::Person},
+    response_error::{ResponseError, ResponseResult},
+};
+
+/// Sent when a user changes their profile, so other instances can refresh
+/// their copy of it.
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdatePerson {
+    pub actor: ObjectId<db::ApUser>,
+    #[serde(deserialize_with = "deserialize_one_or_many")]
+    pub to: Vec<Url>,
+    pub object: Person,
+    #[serde(rename = "type")]
+    pub kind: UpdateType,
+    pub id: Url,
+}
+
+impl UpdatePerson {
+    pub async fn send_to_followers(
+        actor: &db::ApUser,
+        context: &federation::Data,
+    ) -> ResponseResult<()> {
+        let id = super::activity::generate_id(context)?;
+
+        let mut tx = context.db_pool.begin().await?;
+        let followers = db::ap_users::list_followers(&mut tx, actor.id).await?;
+        let object = db::ap_users::read_by_id(&mut tx, actor.id)
+            .await?
+            .into_json(context)
+            .await?;
+        drop(tx);
+
+        let to = followers
+            .iter()
+            .map(|ap_user| ap_user.ap_id.clone().into_inner())
+            .collect();
+        let update = UpdatePerson {
+            actor: actor.ap_id.clone(),
+            to,
+            object,
+            kind: UpdateType::Update,
+            id,
+        };
+
+        super::activity::send(
+            actor,
+            update,
+            &followers.iter().collect::<Vec<_>>(),
+            context,
+        )
+        .await?;
+
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl ActivityHandler for UpdatePerson {
+    type DataType = super::Context;
+    type Error = ResponseError;
+
+    fn id(&self) -> &Url {
+        &self.id
+    }
+
+    fn actor(&self) -> &Url {
+        self.actor.inner()
+    }
+
+    async fn verify(&self, data: &federation::Data) -> Result<(), Self::Error> {
+        // Users can only update their own profile
+        verify_urls_match(self.actor.inner(), self.object.id.inner())?;
+        verify_is_remote_object(&self.actor, data)?;
+        db::ApUser::verify(&self.object, self.actor.inner(), data).await?;
+        Ok(())
+    }
+
+    async fn receive(self, data: &federation::Data) -> Result<(), Self::Error> {
+        db::ApUser::from_json(self.object, data).await?;
+        Ok(())
+    }
+}
@@ -1,8 +1,11 @@
 use std::fmt::Write;
 
+use activitypub_federation::fetch::{object_id::ObjectId, webfinger::webfinger_resolve_actor};
 use anyhow::Context;
 use url::Url;
 
+use crate::{db, federation, response_error::ResponseResult};
+
 #[derive(Debug)]
 pub struct Resource {
     pub name: String,
@@ -34,3 +37,19 @@ impl Resource {
         Ok(Resource { name, domain })
     }
 }
+
+/// Find a user by their handle, e.g. `user@example.com`, or by the URL of
+/// their actor. Fetches the user from their instance if we don't know them
+/// yet.
+pub async fn resolve_actor(reference: &str, data: &federation::Data) -> ResponseResult<db::ApUser> {
+    let reference = reference.trim();
+    match Url::parse(reference) {
+        Ok(url) if matches!(url.scheme(), "http" | "https") => {
+            ObjectId::<db::ApUser>::from(url).dereference(data).await
+        }
+        _ => {
+            let handle = reference.strip_prefix('@').unwrap_or(reference);
+            webfinger_resolve_actor::<federation::Context, db::ApUser>(handle, data).await
+        }
+    }
+}
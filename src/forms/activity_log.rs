@@ -0,0 +1,20 @@
+use serde::{Deserialize, Serialize};
+
+/// Filters for the federation debug log. Empty fields match everything.
+#[derive(Default, Deserialize, Serialize, Debug)]
+pub struct ActivityLogQuery {
+    #[serde(default, deserialize_with = "empty_string_as_none")]
+    pub domain: Option<String>,
+    #[serde(default, deserialize_with = "empty_string_as_none")]
+    pub activity_type: Option<String>,
+}
+
+fn empty_string_as_none<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let value = Option::<String>::deserialize(deserializer)?;
+    Ok(value
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty()))
+}
@@ -1,10 +1,27 @@
 use anyhow::Result;
 use garde::Validate;
+use serde::{Deserialize, Serialize};
 use time::OffsetDateTime;
 use url::Url;
 use uuid::Uuid;
 
-use crate::federation::{self, person::Person};
+use crate::federation::{
+    self,
+    person::{ActorKind, Person},
+};
+
+/// Enforced for both local and remote users, matching the size of the database
+/// column.
+pub const MAX_BIO_LENGTH: usize = 5_000;
+
+pub const MAX_ALIASES: usize = 20;
+
+/// Contains a dot so it can never collide with the username of a real user.
+pub const INSTANCE_ACTOR_USERNAME: &str = "instance.actor";
+
+pub fn instance_actor_ap_id(base_url: &Url) -> Result<Url> {
+    Ok(base_url.join("/ap/actor")?)
+}
 
 #[derive(Validate)]
 pub struct CreateApUser {
@@ -14,8 +31,12 @@ pub struct CreateApUser {
     pub ap_id: Url,
     #[garde(length(max = 50))]
     pub username: String,
+    #[garde(skip)]
+    pub kind: ActorKind,
     #[garde(length(max = 255))]
     pub inbox_url: Url,
+    #[garde(length(max = 255))]
+    pub shared_inbox_url: Option<Url>,
     #[garde(length(max = 10_000))]
     pub public_key: String,
     #[garde(length(max = 10_000))]
@@ -24,8 +45,18 @@ pub struct CreateApUser {
     pub last_refreshed_at: OffsetDateTime,
     #[garde(length(max = 100))]
     pub display_name: Option<String>,
-    #[garde(length(max = 5_000))]
+    #[garde(length(max = MAX_BIO_LENGTH))]
     pub bio: Option<String>,
+    #[garde(skip)]
+    pub manually_approves_followers: bool,
+    #[garde(length(max = 255))]
+    pub avatar_url: Option<Url>,
+    #[garde(length(max = 255))]
+    pub header_url: Option<Url>,
+    #[garde(length(max = MAX_ALIASES), inner(length(max = 255)))]
+    pub also_known_as: Vec<Url>,
+    #[garde(length(max = 255))]
+    pub moved_to: Option<Url>,
 }
 
 impl CreateApUser {
@@ -35,18 +66,55 @@ impl CreateApUser {
         let ap_id = base_url.join("/ap/user/")?.join(&id.to_string())?;
 
         let inbox_url = base_url.join("/ap/inbox/")?.join(&id.to_string())?;
+        let shared_inbox_url = base_url.join("/ap/inbox")?;
 
         let ap_keypair = federation::signing::generate_keypair()?;
         let create = CreateApUser {
             id,
             ap_id,
             username,
+            kind: ActorKind::Person,
             inbox_url,
+            shared_inbox_url: Some(shared_inbox_url),
             public_key: ap_keypair.public_key,
             private_key: Some(ap_keypair.private_key),
             last_refreshed_at: OffsetDateTime::now_utc(),
             display_name: None,
             bio: None,
+            manually_approves_followers: false,
+            avatar_url: None,
+            header_url: None,
+            also_known_as: Vec::new(),
+            moved_to: None,
+        };
+
+        create.validate()?;
+
+        Ok(create)
+    }
+
+    /// Create the actor representing this instance as a whole, e.g. for
+    /// signing requests that are not made on behalf of a specific user.
+    pub fn new_instance_actor(base_url: &Url) -> Result<Self> {
+        let inbox_url = base_url.join("/ap/inbox")?;
+        let ap_keypair = federation::signing::generate_keypair()?;
+        let create = CreateApUser {
+            id: Uuid::new_v4(),
+            ap_id: instance_actor_ap_id(base_url)?,
+            username: INSTANCE_ACTOR_USERNAME.to_string(),
+            kind: ActorKind::Application,
+            inbox_url: inbox_url.clone(),
+            shared_inbox_url: Some(inbox_url),
+            public_key: ap_keypair.public_key,
+            private_key: Some(ap_keypair.private_key),
+            last_refreshed_at: OffsetDateTime::now_utc(),
+            display_name: None,
+            bio: None,
+            manually_approves_followers: false,
+            avatar_url: None,
+            header_url: None,
+            also_known_as: Vec::new(),
+            moved_to: None,
         };
 
         create.validate()?;
@@ -61,12 +129,19 @@ impl CreateApUser {
             id: Uuid::new_v4(),
             ap_id: json.id.into_inner(),
             username: json.preferred_username,
+            kind: json.kind,
             inbox_url: json.inbox,
+            shared_inbox_url: json.endpoints.map(|endpoints| endpoints.shared_inbox),
             public_key: json.public_key.public_key_pem,
             private_key: None,
             last_refreshed_at: OffsetDateTime::now_utc(),
             display_name: json.name,
             bio: json.summary,
+            manually_approves_followers: json.manually_approves_followers,
+            avatar_url: json.icon.map(|icon| icon.url),
+            header_url: json.image.map(|image| image.url),
+            also_known_as: json.also_known_as,
+            moved_to: json.moved_to,
         };
 
         create_user.validate()?;
@@ -75,12 +150,22 @@ impl CreateApUser {
     }
 }
 
-// Currently only used in insert-demo-data script
-#[allow(dead_code)]
-#[derive(Validate)]
+#[derive(Validate, Default, Debug)]
 pub struct UpdateApUser {
     #[garde(length(max = 100))]
     pub display_name: Option<String>,
-    #[garde(length(max = 1_000))]
+    #[garde(length(max = MAX_BIO_LENGTH))]
     pub bio: Option<String>,
 }
+
+#[derive(Deserialize)]
+pub struct EditManuallyApprovesFollowers {
+    pub manually_approves_followers: bool,
+}
+
+/// A handle like `user@example.com` or the URL of an actor
+#[derive(Validate, Deserialize, Serialize, Default, Debug)]
+pub struct ActorReference {
+    #[garde(length(min = 1, max = 255))]
+    pub actor: String,
+}
@@ -4,6 +4,11 @@ use uuid::Uuid;
 
 use crate::{db::bookmarks::InsertBookmark, form_errors::FormErrors};
 
+pub const MAX_TAGS: usize = 20;
+
+/// Matches the size of the database column.
+const MAX_TAG_LENGTH: usize = 100;
+
 #[derive(Validate, Default, Deserialize, Clone, Debug)]
 pub struct CreateBookmark {
     #[garde(skip)]
@@ -16,8 +21,15 @@ pub struct CreateBookmark {
     pub url: String,
     #[garde(custom(not_empty))]
     pub title: String,
+    /// Hashtags separated by spaces or commas, with or without a leading `#`
+    #[garde(custom(valid_tags))]
+    #[serde(default)]
+    pub tags: String,
     #[garde(length(max = 100))]
     pub list_search_term: Option<String>,
+    /// The bookmark this one is saved from
+    #[garde(skip)]
+    pub via: Option<Uuid>,
     #[garde(skip)]
     #[serde(default)]
     pub submitted: bool,
@@ -48,3 +60,46 @@ fn not_empty(value: &str, _: &()) -> garde::Result {
         Ok(())
     }
 }
+
+/// Normalize a hashtag to lowercase without the leading `#`. Returns `None` for
+/// names Mastodon wouldn't recognize as a hashtag either.
+pub fn parse_tag(name: &str) -> Option<String> {
+    let tag = name.trim().trim_start_matches('#').to_lowercase();
+    let is_valid = !tag.is_empty()
+        && tag.chars().count() <= MAX_TAG_LENGTH
+        && tag.chars().all(|c| c.is_alphanumeric() || c == '_');
+    is_valid.then_some(tag)
+}
+
+/// The distinct, valid hashtags of user input like `#rust, web`.
+pub fn parse_tags(input: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for tag in input
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter_map(parse_tag)
+    {
+        if !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    tags
+}
+
+#[expect(clippy::trivially_copy_pass_by_ref)]
+fn valid_tags(value: &str, _: &()) -> garde::Result {
+    let names = value
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|name| !name.is_empty())
+        .collect::<Vec<_>>();
+    if let Some(invalid) = names.iter().find(|name| parse_tag(name).is_none()) {
+        return Err(garde::Error::new(format!(
+            "{invalid} is not a valid hashtag, use only letters, numbers and underscores"
+        )));
+    }
+    if names.len() > MAX_TAGS {
+        return Err(garde::Error::new(format!(
+            "cannot have more than {MAX_TAGS} hashtags"
+        )));
+    }
+    Ok(())
+}
@@ -0,0 +1,15 @@
+use garde::Validate;
+use serde::{Deserialize, Serialize};
+
+use crate::db::domain_blocks::Severity;
+
+#[derive(Validate, Default, Deserialize, Serialize, Debug)]
+pub struct CreateDomainBlock {
+    /// A domain name, optionally followed by a port
+    #[garde(pattern(r"^[a-z0-9.-]+(:[0-9]+)?$"), length(min = 1, max = 255))]
+    pub domain: String,
+    #[garde(skip)]
+    pub severity: Severity,
+    #[garde(length(max = 1_000))]
+    pub reason: Option<String>,
+}
@@ -1,5 +1,12 @@
+pub mod activity_log;
 pub mod ap_users;
 pub mod bookmarks;
+pub mod domain_blocks;
 pub mod links;
 pub mod lists;
+pub mod registrations;
+pub mod relays;
+pub mod replies;
+pub mod reports;
+pub mod search;
 pub mod users;
@@ -0,0 +1,40 @@
+use garde::Validate;
+use serde::{Deserialize, Serialize};
+
+use crate::db::registrations::{RegistrationMode, Settings};
+
+#[derive(Validate, Default, Deserialize, Serialize, Debug)]
+pub struct EditRegistrationSettings {
+    #[garde(skip)]
+    pub registration_mode: RegistrationMode,
+    /// Separated by commas or whitespace
+    #[garde(length(max = 10_000))]
+    pub reserved_usernames: String,
+    #[garde(range(min = 3, max = 50))]
+    pub min_username_length: i32,
+}
+
+impl EditRegistrationSettings {
+    /// The reserved usernames in lowercase, without duplicates.
+    pub fn reserved_username_list(&self) -> Vec<String> {
+        let mut usernames: Vec<String> = self
+            .reserved_usernames
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|username| !username.is_empty())
+            .map(str::to_lowercase)
+            .collect();
+        usernames.sort();
+        usernames.dedup();
+        usernames
+    }
+}
+
+impl From<Settings> for EditRegistrationSettings {
+    fn from(settings: Settings) -> Self {
+        EditRegistrationSettings {
+            registration_mode: settings.registration_mode,
+            reserved_usernames: settings.reserved_usernames.join(", "),
+            min_username_length: settings.min_username_length,
+        }
+    }
+}
@@ -0,0 +1,13 @@
+use garde::Validate;
+use serde::{Deserialize, Serialize};
+
+use crate::db::relays::Kind;
+
+#[derive(Validate, Default, Deserialize, Serialize, Debug)]
+pub struct CreateRelay {
+    /// The inbox of Mastodon-style relays, or the actor of LitePub relays
+    #[garde(url, length(max = 255))]
+    pub url: String,
+    #[garde(skip)]
+    pub kind: Kind,
+}
@@ -0,0 +1,12 @@
+use garde::Validate;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Validate, Default, Deserialize, Serialize, Debug)]
+pub struct CreateReply {
+    #[garde(length(min = 1, max = 5000))]
+    pub content: String,
+    /// The reply to answer, or `None` to reply to the bookmark itself
+    #[garde(skip)]
+    pub parent_id: Option<Uuid>,
+}
@@ -0,0 +1,34 @@
+use garde::Validate;
+use serde::{Deserialize, Serialize};
+
+#[derive(Validate, Default, Deserialize, Serialize, Debug)]
+pub struct CreateReport {
+    /// Why the content is reported, shown to the admins
+    #[garde(length(max = 2_000))]
+    pub comment: String,
+}
+
+/// What an admin can do about a report.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ModerationAction {
+    /// Close the report without doing anything
+    Dismiss,
+    /// Delete the reported bookmark
+    DeleteContent,
+    /// Suspend the reported account
+    Suspend,
+    /// Reject all communication with the instance of the reported account
+    BlockDomain,
+}
+
+impl ModerationAction {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ModerationAction::Dismiss => "dismiss",
+            ModerationAction::DeleteContent => "delete_content",
+            ModerationAction::Suspend => "suspend",
+            ModerationAction::BlockDomain => "block_domain",
+        }
+    }
+}
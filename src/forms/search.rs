@@ -0,0 +1,11 @@
+use serde::{Deserialize, Serialize};
+
+/// Search the bookmarks of trusted users.
+#[derive(Default, Deserialize, Serialize, Debug)]
+pub struct SearchQuery {
+    #[serde(default)]
+    pub q: String,
+    /// Also search the bookmarks of users trusted by trusted users
+    #[serde(default)]
+    pub include_trusted_of_trusted: bool,
+}
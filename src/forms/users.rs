@@ -3,7 +3,7 @@ use openidconnect::{AuthorizationCode, CsrfToken};
 use serde::{Deserialize, Serialize};
 use url::Url;
 
-#[derive(Validate)]
+#[derive(Validate, Default, Deserialize, Serialize, Debug, Clone)]
 pub struct CreateUser {
     #[garde(pattern("^[a-zA-Z0-9_]+$"), length(min = 3, max = 50))]
     pub username: String,
@@ -11,6 +11,18 @@ pub struct CreateUser {
     pub password: String,
 }
 
+#[derive(Validate, Default, Deserialize, Serialize, Debug)]
+pub struct SignUp {
+    #[garde(dive)]
+    pub user: CreateUser,
+    /// Only needed while registration is invite-only
+    #[garde(length(max = 64))]
+    pub invite_code: Option<String>,
+    /// Only asked for while registration requires approval
+    #[garde(length(max = 1_000))]
+    pub reason: Option<String>,
+}
+
 #[derive(Validate, Default, Deserialize, Debug)]
 pub struct OidcSelectUsername {
     #[garde(pattern("^[a-zA-Z0-9_]+$"), ascii, length(min = 3, max = 50))]
@@ -48,3 +60,75 @@ pub struct CreateOidcUser {
     #[garde(pattern("^[a-zA-Z0-9_]+$"), length(min = 3, max = 50))]
     pub username: String,
 }
+
+#[derive(Deserialize)]
+pub struct EditNotifyOnInteractions {
+    pub notify_on_interactions: bool,
+}
+
+#[derive(Validate, Default, Deserialize, Debug)]
+pub struct ChangePassword {
+    /// Not needed for users that only logged in via OIDC so far
+    #[garde(length(max = 100))]
+    pub current_password: Option<String>,
+    #[garde(length(min = 10, max = 100))]
+    pub new_password: String,
+}
+
+#[derive(Validate, Default, Deserialize, Debug)]
+pub struct EditEmail {
+    /// Leave empty to remove the email address
+    #[garde(pattern(r"^$|^[^@\s]+@[^@\s]+$"), length(max = 500))]
+    pub email: String,
+}
+
+#[derive(Validate, Default, Deserialize, Debug)]
+pub struct ChangeUsername {
+    #[garde(pattern("^[a-zA-Z0-9_]+$"), length(min = 3, max = 50))]
+    pub username: String,
+}
+
+/// The second login step for users with two-factor authentication
+#[derive(Validate, Default, Deserialize, Debug)]
+pub struct TotpLogin {
+    #[garde(length(max = 1000))]
+    pub previous_uri: Option<Url>,
+    /// A one-time password or a recovery code
+    #[garde(length(min = 1, max = 50))]
+    pub code: String,
+}
+
+#[derive(Validate, Default, Deserialize, Debug)]
+pub struct TotpCode {
+    /// A one-time password or, when disabling, a recovery code
+    #[garde(length(min = 1, max = 50))]
+    pub code: String,
+}
+
+/// What an admin can do to a local user.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum UserAction {
+    /// Log the user out and keep them from logging in again
+    Suspend,
+    Unsuspend,
+    /// Replace the password with a random one, shown to the admin once
+    ResetPassword,
+    MakeAdmin,
+    RemoveAdmin,
+    /// Delete the user with all of their lists and bookmarks
+    Delete,
+}
+
+impl UserAction {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            UserAction::Suspend => "suspend",
+            UserAction::Unsuspend => "unsuspend",
+            UserAction::ResetPassword => "reset_password",
+            UserAction::MakeAdmin => "make_admin",
+            UserAction::RemoveAdmin => "remove_admin",
+            UserAction::Delete => "delete",
+        }
+    }
+}
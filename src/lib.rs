@@ -14,6 +14,7 @@ mod oidc;
 mod response_error;
 mod routes;
 pub mod server;
+mod totp;
 mod views;
 
 mod date_time;
@@ -21,5 +22,6 @@ mod federation;
 mod htmf_response;
 #[cfg(debug_assertions)]
 mod insert_demo_data;
+mod manage_users;
 #[cfg(test)]
 mod tests;
@@ -0,0 +1,158 @@
+use anyhow::{Context, Result, anyhow};
+use garde::Validate;
+use sqlx::PgPool;
+use url::Url;
+
+use crate::{
+    authentication::generate_password,
+    cli::UserCommand,
+    db::{self, AppTx, users::Role},
+    federation::{self, webfinger::Resource},
+    forms::users::CreateUser,
+};
+
+/// Run a `linkblocks user` subcommand directly on the database.
+pub async fn run(pool: &PgPool, base_url: &Url, command: UserCommand) -> Result<()> {
+    let mut tx = pool.begin().await?;
+
+    match command {
+        UserCommand::Create {
+            username,
+            password,
+            admin,
+        } => {
+            let generated_password = password.is_none();
+            let create = CreateUser {
+                username,
+                password: password.map_or_else(generate_password, |password| {
+                    password.expose_secret().clone()
+                }),
+            };
+            if let Err(e) = create.validate() {
+                return Err(anyhow!("Invalid credentials for new user provided:\n{e}"));
+            }
+            if db::registrations::is_username_taken(&mut tx, &create.username).await? {
+                return Err(anyhow!("The username {} is taken", create.username));
+            }
+
+            let password = create.password.clone();
+            let user = db::users::insert(&mut tx, create, base_url).await?;
+            if admin {
+                db::users::set_role(&mut tx, user.id, Role::Admin).await?;
+            }
+
+            println!("Created user {}", user.username);
+            if generated_password {
+                println!("Password: {password}");
+            }
+        }
+        UserCommand::List => {
+            for user in db::users::list_with_stats(&mut tx).await? {
+                println!(
+                    "{}\t{}\t{} bookmarks\t{} lists{}",
+                    user.username,
+                    user.role.as_str(),
+                    user.bookmark_count,
+                    user.list_count,
+                    if user.suspended { "\tsuspended" } else { "" }
+                );
+            }
+        }
+        UserCommand::Delete { username } => {
+            let user = by_username(&mut tx, &username).await?;
+            let ap_user = db::ap_users::read_by_id(&mut tx, user.ap_user_id).await?;
+            let data = federation::config::new_config(pool.clone(), base_url.clone(), false)
+                .await?
+                .to_request_data();
+            let deletion = federation::Delete::prepare_for_person(&ap_user, &data).await?;
+            db::users::delete(&mut tx, user.id).await?;
+            tx.commit().await?;
+            deletion.deliver(&data).await;
+
+            println!("Deleted user {username} with all of their lists and bookmarks");
+            return Ok(());
+        }
+        UserCommand::ResetPassword { username } => {
+            let user = by_username(&mut tx, &username).await?;
+            let password = generate_password();
+            db::users::set_password(&mut tx, user.id, &password).await?;
+
+            println!("New password for {username}: {password}");
+        }
+        UserCommand::DisableTotp { username } => {
+            let user = by_username(&mut tx, &username).await?;
+            db::users::set_totp_secret(&mut tx, user.id, None).await?;
+            db::recovery_codes::delete_all(&mut tx, user.id).await?;
+
+            println!("Disabled two-factor authentication for {username}");
+        }
+        UserCommand::SetRole { username, role } => {
+            let user = by_username(&mut tx, &username).await?;
+            db::users::set_role(&mut tx, user.id, role).await?;
+
+            println!("{username} is now a {} user", role.as_str());
+        }
+        UserCommand::Show { handle } => {
+            show(&mut tx, base_url, &handle).await?;
+        }
+    }
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+async fn by_username(tx: &mut AppTx, username: &str) -> Result<db::User> {
+    db::users::by_username(tx, username)
+        .await
+        .with_context(|| format!("Found no local user named {username}"))
+}
+
+/// Print what we know about a local or remote user.
+async fn show(tx: &mut AppTx, base_url: &Url, handle: &str) -> Result<()> {
+    let handle = handle.trim().strip_prefix('@').unwrap_or(handle.trim());
+    let resource = Resource::parse_handle(handle, base_url)?;
+    let ap_user = db::ap_users::read_by_username(tx, resource)
+        .await
+        .with_context(|| format!("Found no user with the handle {handle}"))?;
+
+    println!("Username: {}", ap_user.username);
+    println!("ActivityPub id: {}", ap_user.ap_id.inner());
+    if let Some(display_name) = &ap_user.display_name {
+        println!("Display name: {display_name}");
+    }
+    println!(
+        "Suspended: {}",
+        yes_no(db::reports::is_suspended(tx, ap_user.id).await?)
+    );
+
+    let Some(user) = db::users::by_ap_user_id(tx, ap_user.id).await? else {
+        println!("Local: no");
+        return Ok(());
+    };
+    println!("Local: yes");
+    println!("Role: {}", user.role.as_str());
+    println!("Password login: {}", yes_no(user.password_hash.is_some()));
+    println!("Single sign-on: {}", yes_no(user.oidc_id.is_some()));
+    println!(
+        "Two-factor authentication: {}",
+        yes_no(user.totp_secret.is_some())
+    );
+    if let Some(email) = &user.email {
+        println!("Email: {email}");
+    }
+    if let Some(stats) = db::users::list_with_stats(tx)
+        .await?
+        .into_iter()
+        .find(|stats| stats.id == user.id)
+    {
+        println!("Bookmarks: {}", stats.bookmark_count);
+        println!("Lists: {}", stats.list_count);
+    }
+
+    Ok(())
+}
+
+fn yes_no(value: bool) -> &'static str {
+    if value { "yes" } else { "no" }
+}
@@ -8,6 +8,7 @@ use openidconnect::{
 };
 use serde::{Deserialize, Serialize};
 use tower_sessions::Session;
+use uuid::Uuid;
 
 use crate::{cli::OidcArgs, response_error::ResponseResult};
 
@@ -44,6 +45,9 @@ pub struct LoginAttempt {
     pub csrf_token: CsrfToken,
     pub pkce_verifier: PkceCodeVerifier,
     pub authorize_url: Url,
+    /// Set when a logged in user links their account instead of logging in
+    #[serde(default)]
+    pub link_to_user: Option<Uuid>,
 }
 
 impl LoginAttempt {
@@ -69,6 +73,15 @@ impl LoginAttempt {
             csrf_token,
             pkce_verifier,
             authorize_url,
+            link_to_user: None,
+        }
+    }
+
+    /// Start linking the OIDC identity to an existing local user.
+    pub fn for_linking(client: &ConfiguredClient, user_id: Uuid) -> Self {
+        LoginAttempt {
+            link_to_user: Some(user_id),
+            ..Self::new(client)
         }
     }
 
@@ -12,6 +12,8 @@ pub enum ResponseError {
     NotFound,
     #[error("Authentication Failed")]
     NotAuthenticated,
+    #[error("Unauthorized")]
+    Unauthorized,
     #[error("Internal Error")]
     Anyhow(#[from] anyhow::Error),
     #[error("Internal Error")]
@@ -20,16 +22,31 @@ pub enum ResponseError {
     FederationError(#[from] activitypub_federation::error::Error),
 }
 
+impl ResponseError {
+    /// The status of the response this error turns into.
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            ResponseError::NotFound => StatusCode::NOT_FOUND,
+            ResponseError::NotAuthenticated => StatusCode::SEE_OTHER,
+            ResponseError::Unauthorized => StatusCode::UNAUTHORIZED,
+            ResponseError::Anyhow(_)
+            | ResponseError::UrlParseError(_)
+            | ResponseError::FederationError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
 impl IntoResponse for ResponseError {
     fn into_response(self) -> Response {
         tracing::error!("{self:?}");
         match self {
-            ResponseError::NotFound => (StatusCode::NOT_FOUND, self.to_string()).into_response(),
             ResponseError::NotAuthenticated => Redirect::to("/login").into_response(),
-            ResponseError::Anyhow(_)
+            ResponseError::NotFound
+            | ResponseError::Unauthorized
+            | ResponseError::Anyhow(_)
             | ResponseError::UrlParseError(_)
             | ResponseError::FederationError(_) => {
-                (StatusCode::INTERNAL_SERVER_ERROR, self.to_string()).into_response()
+                (self.status_code(), self.to_string()).into_response()
             }
         }
     }
@@ -0,0 +1,571 @@
+use anyhow::Context;
+use axum::{
+    Form, Router,
+    extract::{Path, Query, State},
+    response::{IntoResponse, Redirect, Response},
+    routing::{get, post},
+};
+use garde::{Report, Validate};
+use url::Url;
+use uuid::Uuid;
+
+use crate::{
+    authentication::{self, AuthUser},
+    db::{self, domain_blocks::Severity, users::Role},
+    extract, federation,
+    form_errors::FormErrors,
+    forms::{
+        activity_log::ActivityLogQuery, domain_blocks::CreateDomainBlock,
+        registrations::EditRegistrationSettings, relays::CreateRelay, reports::ModerationAction,
+        users::UserAction,
+    },
+    htmf_response::HtmfResponse,
+    response_error::{ResponseError, ResponseResult, into_option},
+    server::AppState,
+    views::{self, layout},
+};
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/admin", get(get_overview))
+        .route("/admin/users", get(get_users))
+        .route("/admin/users/{id}/{action}", post(post_user_action))
+        .route("/admin/instances", get(get_instances))
+        .route("/admin/reports", get(get_reports))
+        .route("/admin/reports/{id}/{action}", post(post_report_action))
+        .route(
+            "/admin/registrations",
+            get(get_registrations).post(post_registrations),
+        )
+        .route("/admin/invites", post(post_invite))
+        .route("/admin/invites/{id}/delete", post(post_delete_invite))
+        .route(
+            "/admin/registration_requests/{id}/approve",
+            post(post_approve_registration),
+        )
+        .route(
+            "/admin/registration_requests/{id}/reject",
+            post(post_reject_registration),
+        )
+        .route(
+            "/admin/domain_blocks",
+            get(get_domain_blocks).post(post_domain_block),
+        )
+        .route(
+            "/admin/domain_blocks/{id}/delete",
+            post(post_delete_domain_block),
+        )
+        .route("/admin/relays", get(get_relays).post(post_relay))
+        .route("/admin/relays/{id}/delete", post(post_delete_relay))
+        .route("/admin/deliveries", get(get_deliveries))
+        .route("/admin/activity_log", get(get_activity_log))
+        .route("/admin/deliveries/{id}/retry", post(post_retry_delivery))
+}
+
+async fn get_overview(
+    extract::Tx(mut tx): extract::Tx,
+    auth_user: AuthUser,
+) -> ResponseResult<HtmfResponse> {
+    auth_user.require_admin(&mut tx).await?;
+
+    Ok(HtmfResponse(views::admin_overview::view(
+        &views::admin_overview::Data {
+            layout: layout::Template::from_db(&mut tx, Some(&auth_user)).await?,
+            stats: db::instance::stats(&mut tx).await?,
+            remote_instances: db::instance::list_remote(&mut tx).await?.len(),
+            open_reports: db::reports::list_open(&mut tx).await?.len(),
+            pending_registrations: db::registrations::list_requests(&mut tx).await?.len(),
+            failing_inboxes: db::deliveries::list_inbox_failures(&mut tx).await?.len(),
+        },
+    )))
+}
+
+async fn get_users(
+    extract::Tx(mut tx): extract::Tx,
+    auth_user: AuthUser,
+) -> ResponseResult<HtmfResponse> {
+    auth_user.require_admin(&mut tx).await?;
+
+    users_view(&mut tx, &auth_user, None).await
+}
+
+async fn users_view(
+    tx: &mut db::AppTx,
+    auth_user: &AuthUser,
+    new_password: Option<views::admin_users::NewPassword>,
+) -> ResponseResult<HtmfResponse> {
+    Ok(HtmfResponse(views::admin_users::view(
+        &views::admin_users::Data {
+            layout: layout::Template::from_db(tx, Some(auth_user)).await?,
+            users: db::users::list_with_stats(tx).await?,
+            current_user_id: auth_user.user_id,
+            new_password,
+        },
+    )))
+}
+
+async fn post_user_action(
+    extract::Tx(mut tx): extract::Tx,
+    auth_user: AuthUser,
+    data: federation::Data,
+    Path((id, action)): Path<(Uuid, UserAction)>,
+) -> ResponseResult<Response> {
+    auth_user.require_admin(&mut tx).await?;
+
+    // Keep admins from locking themselves out
+    if id == auth_user.user_id && action != UserAction::ResetPassword {
+        return Err(ResponseError::Unauthorized);
+    }
+
+    let user = db::users::by_id(&mut tx, id).await?;
+    match action {
+        UserAction::Suspend => db::reports::suspend(&mut tx, user.ap_user_id).await?,
+        UserAction::Unsuspend => db::reports::unsuspend(&mut tx, user.ap_user_id).await?,
+        UserAction::ResetPassword => {
+            let password = authentication::generate_password();
+            db::users::set_password(&mut tx, user.id, &password).await?;
+            let response = users_view(
+                &mut tx,
+                &auth_user,
+                Some(views::admin_users::NewPassword {
+                    username: user.username,
+                    password,
+                }),
+            )
+            .await?;
+            tx.commit().await?;
+
+            return Ok(response.into_response());
+        }
+        UserAction::MakeAdmin => {
+            db::users::set_role(&mut tx, user.id, Role::Admin).await?;
+        }
+        UserAction::RemoveAdmin => {
+            db::users::set_role(&mut tx, user.id, Role::User).await?;
+        }
+        UserAction::Delete => {
+            let ap_user = db::ap_users::read_by_id(&mut tx, user.ap_user_id).await?;
+            let deletion = federation::Delete::prepare_for_person(&ap_user, &data).await?;
+            db::users::delete(&mut tx, user.id).await?;
+            tx.commit().await?;
+            deletion.deliver_in_background(&data).await;
+
+            return Ok(Redirect::to("/admin/users").into_response());
+        }
+    }
+
+    tx.commit().await?;
+
+    Ok(Redirect::to("/admin/users").into_response())
+}
+
+async fn get_instances(
+    extract::Tx(mut tx): extract::Tx,
+    auth_user: AuthUser,
+) -> ResponseResult<HtmfResponse> {
+    auth_user.require_admin(&mut tx).await?;
+
+    Ok(HtmfResponse(views::instances::view(
+        &views::instances::Data {
+            layout: layout::Template::from_db(&mut tx, Some(&auth_user)).await?,
+            instances: db::instance::list_remote(&mut tx).await?,
+        },
+    )))
+}
+
+async fn get_reports(
+    extract::Tx(mut tx): extract::Tx,
+    auth_user: AuthUser,
+    data: federation::Data,
+) -> ResponseResult<HtmfResponse> {
+    auth_user.require_admin(&mut tx).await?;
+
+    let mut entries = Vec::new();
+    for report in db::reports::list_open(&mut tx).await? {
+        let reporter = db::ap_users::read_by_id(&mut tx, report.reporter_id).await?;
+        let reported = db::ap_users::read_by_id(&mut tx, report.reported_id).await?;
+        let bookmark = match report.bookmark_id {
+            Some(id) => into_option(db::bookmarks::by_id(&mut tx, id).await)?,
+            None => None,
+        };
+        entries.push(views::reports::Entry {
+            reporter_handle: handle(&reporter)?,
+            reported_handle: handle(&reported)?,
+            reported_is_local: reported.ap_id.is_local(&data),
+            bookmark,
+            report,
+        });
+    }
+
+    Ok(HtmfResponse(views::reports::view(&views::reports::Data {
+        layout: layout::Template::from_db(&mut tx, Some(&auth_user)).await?,
+        entries,
+    })))
+}
+
+/// Act on a report. Suspending an account or blocking its domain also closes
+/// all other reports about the account.
+async fn post_report_action(
+    extract::Tx(mut tx): extract::Tx,
+    auth_user: AuthUser,
+    data: federation::Data,
+    Path((id, action)): Path<(Uuid, ModerationAction)>,
+) -> ResponseResult<Redirect> {
+    auth_user.require_admin(&mut tx).await?;
+
+    let report = db::reports::by_id(&mut tx, id).await?;
+    let mut deleted_bookmark = None;
+    match action {
+        ModerationAction::Dismiss => {
+            db::reports::resolve(&mut tx, &report, false).await?;
+        }
+        ModerationAction::DeleteContent => {
+            let bookmark_id = report.bookmark_id.ok_or(ResponseError::NotFound)?;
+            let public = db::bookmarks::is_public(&mut tx, bookmark_id).await?;
+            db::links::delete_by_bookmark(&mut tx, bookmark_id).await?;
+            let bookmark = db::bookmarks::delete_by_id(&mut tx, bookmark_id).await?;
+            db::reports::resolve(&mut tx, &report, false).await?;
+            if public && bookmark.ap_id.is_local(&data) {
+                deleted_bookmark = Some(bookmark);
+            }
+        }
+        ModerationAction::Suspend => {
+            db::reports::suspend(&mut tx, report.reported_id).await?;
+            db::reports::resolve(&mut tx, &report, true).await?;
+        }
+        ModerationAction::BlockDomain => {
+            let reported = db::ap_users::read_by_id(&mut tx, report.reported_id).await?;
+            if reported.ap_id.is_local(&data) {
+                return Err(ResponseError::NotFound);
+            }
+            let domain = db::domain_blocks::url_domain(reported.ap_id.inner())
+                .context("Reported user has no domain")?;
+            db::domain_blocks::upsert(
+                &mut tx,
+                CreateDomainBlock {
+                    domain,
+                    severity: Severity::Reject,
+                    reason: Some(format!("Reported: @{}", handle(&reported)?)),
+                },
+            )
+            .await?;
+            db::reports::resolve(&mut tx, &report, true).await?;
+        }
+    }
+
+    // Other instances only learn about local bookmarks from us
+    if let Some(bookmark) = deleted_bookmark {
+        let owner = db::ap_users::read_by_id(&mut tx, bookmark.ap_user_id).await?;
+        tx.commit().await?;
+        federation::Delete::send_to_followers(&owner, bookmark.ap_id.into_inner(), &data).await?;
+    } else {
+        tx.commit().await?;
+    }
+
+    Ok(Redirect::to("/admin/reports"))
+}
+
+fn handle(ap_user: &db::ApUser) -> ResponseResult<String> {
+    let resource = federation::webfinger::Resource::from_name_and_url(
+        ap_user.username.clone(),
+        ap_user.ap_id.inner(),
+    )?;
+    Ok(format!("{}@{}", resource.name, resource.domain))
+}
+
+async fn get_registrations(
+    extract::Tx(mut tx): extract::Tx,
+    auth_user: AuthUser,
+) -> ResponseResult<HtmfResponse> {
+    auth_user.require_admin(&mut tx).await?;
+
+    let settings = db::registrations::read_settings(&mut tx).await?;
+    registrations_view(&mut tx, &auth_user, settings.into(), FormErrors::default()).await
+}
+
+async fn post_registrations(
+    extract::Tx(mut tx): extract::Tx,
+    auth_user: AuthUser,
+    Form(input): Form<EditRegistrationSettings>,
+) -> ResponseResult<Response> {
+    auth_user.require_admin(&mut tx).await?;
+
+    if let Err(errors) = input.validate() {
+        return Ok(
+            registrations_view(&mut tx, &auth_user, input, errors.into())
+                .await?
+                .into_response(),
+        );
+    }
+
+    db::registrations::update_settings(&mut tx, input).await?;
+
+    tx.commit().await?;
+
+    Ok(Redirect::to("/admin/registrations").into_response())
+}
+
+async fn registrations_view(
+    tx: &mut db::AppTx,
+    auth_user: &AuthUser,
+    input: EditRegistrationSettings,
+    errors: FormErrors,
+) -> ResponseResult<HtmfResponse> {
+    Ok(HtmfResponse(views::registrations::view(
+        &views::registrations::Data {
+            layout: layout::Template::from_db(tx, Some(auth_user)).await?,
+            input,
+            errors,
+            requests: db::registrations::list_requests(tx).await?,
+            invites: db::registrations::list_invites(tx).await?,
+        },
+    )))
+}
+
+async fn post_invite(
+    extract::Tx(mut tx): extract::Tx,
+    auth_user: AuthUser,
+) -> ResponseResult<Redirect> {
+    auth_user.require_admin(&mut tx).await?;
+
+    db::registrations::insert_invite(&mut tx, auth_user.user_id).await?;
+
+    tx.commit().await?;
+
+    Ok(Redirect::to("/admin/registrations"))
+}
+
+async fn post_delete_invite(
+    extract::Tx(mut tx): extract::Tx,
+    auth_user: AuthUser,
+    Path(id): Path<Uuid>,
+) -> ResponseResult<Redirect> {
+    auth_user.require_admin(&mut tx).await?;
+
+    db::registrations::delete_invite(&mut tx, id).await?;
+
+    tx.commit().await?;
+
+    Ok(Redirect::to("/admin/registrations"))
+}
+
+/// Create the user that asked to sign up. They can log in right away.
+async fn post_approve_registration(
+    extract::Tx(mut tx): extract::Tx,
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> ResponseResult<Redirect> {
+    auth_user.require_admin(&mut tx).await?;
+
+    let request = db::registrations::delete_request(&mut tx, id).await?;
+    db::users::insert_with_password_hash(
+        &mut tx,
+        request.username,
+        &request.password_hash,
+        &state.base_url,
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(Redirect::to("/admin/registrations"))
+}
+
+async fn post_reject_registration(
+    extract::Tx(mut tx): extract::Tx,
+    auth_user: AuthUser,
+    Path(id): Path<Uuid>,
+) -> ResponseResult<Redirect> {
+    auth_user.require_admin(&mut tx).await?;
+
+    db::registrations::delete_request(&mut tx, id).await?;
+
+    tx.commit().await?;
+
+    Ok(Redirect::to("/admin/registrations"))
+}
+
+async fn get_domain_blocks(
+    extract::Tx(mut tx): extract::Tx,
+    auth_user: AuthUser,
+) -> ResponseResult<HtmfResponse> {
+    auth_user.require_admin(&mut tx).await?;
+
+    Ok(HtmfResponse(views::domain_blocks::view(
+        &views::domain_blocks::Data {
+            layout: layout::Template::from_db(&mut tx, Some(&auth_user)).await?,
+            blocks: db::domain_blocks::list(&mut tx).await?,
+            input: CreateDomainBlock::default(),
+            errors: FormErrors::default(),
+        },
+    )))
+}
+
+async fn post_domain_block(
+    extract::Tx(mut tx): extract::Tx,
+    auth_user: AuthUser,
+    Form(mut input): Form<CreateDomainBlock>,
+) -> ResponseResult<Response> {
+    auth_user.require_admin(&mut tx).await?;
+
+    input.domain = input.domain.trim().to_lowercase();
+    input.reason = input.reason.filter(|reason| !reason.is_empty());
+
+    if let Err(errors) = input.validate() {
+        return Ok(
+            HtmfResponse(views::domain_blocks::view(&views::domain_blocks::Data {
+                layout: layout::Template::from_db(&mut tx, Some(&auth_user)).await?,
+                blocks: db::domain_blocks::list(&mut tx).await?,
+                input,
+                errors: errors.into(),
+            }))
+            .into_response(),
+        );
+    }
+
+    db::domain_blocks::upsert(&mut tx, input).await?;
+
+    tx.commit().await?;
+
+    Ok(Redirect::to("/admin/domain_blocks").into_response())
+}
+
+async fn post_delete_domain_block(
+    extract::Tx(mut tx): extract::Tx,
+    auth_user: AuthUser,
+    Path(id): Path<Uuid>,
+) -> ResponseResult<Redirect> {
+    auth_user.require_admin(&mut tx).await?;
+
+    db::domain_blocks::delete_by_id(&mut tx, id).await?;
+
+    tx.commit().await?;
+
+    Ok(Redirect::to("/admin/domain_blocks"))
+}
+
+async fn get_relays(
+    extract::Tx(mut tx): extract::Tx,
+    auth_user: AuthUser,
+) -> ResponseResult<HtmfResponse> {
+    auth_user.require_admin(&mut tx).await?;
+
+    Ok(HtmfResponse(views::relays::view(&views::relays::Data {
+        layout: layout::Template::from_db(&mut tx, Some(&auth_user)).await?,
+        relays: db::relays::list(&mut tx).await?,
+        input: CreateRelay::default(),
+        errors: FormErrors::default(),
+    })))
+}
+
+async fn post_relay(
+    extract::Tx(mut tx): extract::Tx,
+    auth_user: AuthUser,
+    data: federation::Data,
+    Form(mut input): Form<CreateRelay>,
+) -> ResponseResult<Response> {
+    auth_user.require_admin(&mut tx).await?;
+
+    input.url = input.url.trim().to_string();
+
+    let errors = match input.validate() {
+        Err(errors) => Some(errors),
+        Ok(()) => {
+            let url: Url = input.url.parse()?;
+            match federation::relay::subscribe(input.kind, url, &data).await {
+                Ok(_) => None,
+                Err(e) => {
+                    tracing::debug!("Failed to subscribe to relay: {e:?}");
+                    let mut errors = Report::new();
+                    errors.append(
+                        garde::Path::new("root"),
+                        garde::Error::new("Could not subscribe to this relay"),
+                    );
+                    Some(errors)
+                }
+            }
+        }
+    };
+
+    if let Some(errors) = errors {
+        return Ok(HtmfResponse(views::relays::view(&views::relays::Data {
+            layout: layout::Template::from_db(&mut tx, Some(&auth_user)).await?,
+            relays: db::relays::list(&mut tx).await?,
+            input,
+            errors: errors.into(),
+        }))
+        .into_response());
+    }
+
+    Ok(Redirect::to("/admin/relays").into_response())
+}
+
+async fn post_delete_relay(
+    extract::Tx(mut tx): extract::Tx,
+    auth_user: AuthUser,
+    data: federation::Data,
+    Path(id): Path<Uuid>,
+) -> ResponseResult<Redirect> {
+    auth_user.require_admin(&mut tx).await?;
+    drop(tx);
+
+    federation::relay::unsubscribe(id, &data).await?;
+
+    Ok(Redirect::to("/admin/relays"))
+}
+
+/// How many deliveries to show per category
+const DELIVERY_LIST_LIMIT: i64 = 100;
+
+async fn get_deliveries(
+    extract::Tx(mut tx): extract::Tx,
+    auth_user: AuthUser,
+) -> ResponseResult<HtmfResponse> {
+    auth_user.require_admin(&mut tx).await?;
+
+    Ok(HtmfResponse(views::deliveries::view(
+        &views::deliveries::Data {
+            layout: layout::Template::from_db(&mut tx, Some(&auth_user)).await?,
+            pending: db::deliveries::list_pending(&mut tx, DELIVERY_LIST_LIMIT).await?,
+            failed: db::deliveries::list_failed(&mut tx, DELIVERY_LIST_LIMIT).await?,
+            inbox_failures: db::deliveries::list_inbox_failures(&mut tx).await?,
+        },
+    )))
+}
+
+/// Queue a failed delivery again. The background worker picks it up shortly.
+async fn post_retry_delivery(
+    extract::Tx(mut tx): extract::Tx,
+    auth_user: AuthUser,
+    Path(id): Path<Uuid>,
+) -> ResponseResult<Redirect> {
+    auth_user.require_admin(&mut tx).await?;
+
+    db::deliveries::retry(&mut tx, id).await?;
+
+    tx.commit().await?;
+
+    Ok(Redirect::to("/admin/deliveries"))
+}
+
+/// How many activities to show at once
+const ACTIVITY_LOG_LIMIT: i64 = 200;
+
+async fn get_activity_log(
+    extract::Tx(mut tx): extract::Tx,
+    auth_user: AuthUser,
+    Query(query): Query<ActivityLogQuery>,
+) -> ResponseResult<HtmfResponse> {
+    auth_user.require_admin(&mut tx).await?;
+
+    let entries = db::activity_log::list(&mut tx, &query, ACTIVITY_LOG_LIMIT).await?;
+
+    Ok(HtmfResponse(views::activity_log::view(
+        &views::activity_log::Data {
+            layout: layout::Template::from_db(&mut tx, Some(&auth_user)).await?,
+            query,
+            entries,
+        },
+    )))
+}
@@ -4,20 +4,32 @@ use axum::{
     extract::{Path, Query, State},
     http::HeaderMap,
     response::{IntoResponse, Redirect, Response},
-    routing::{delete, get},
+    routing::{get, post},
 };
+use garde::Validate;
 use serde::Deserialize;
 use uuid::Uuid;
 
 use crate::{
     authentication::AuthUser,
-    db::{self, bookmarks::InsertBookmark},
-    extract::{self, qs_form::QsForm},
-    federation,
+    db::{self, bookmarks::InsertBookmark, interactions::Kind},
+    extract::{
+        self,
+        accept::{Accept, negotiated},
+        qs_form::QsForm,
+        signed_fetch::SignedFetch,
+    },
+    federation::{self, webfinger},
     form_errors::FormErrors,
-    forms::{bookmarks::CreateBookmark, links::CreateLink, lists::CreateList},
+    forms::{
+        bookmarks::{CreateBookmark, parse_tags},
+        links::CreateLink,
+        lists::CreateList,
+        replies::CreateReply,
+    },
     htmf_response::HtmfResponse,
-    response_error::ResponseResult,
+    response_error::{ResponseError, ResponseResult},
+    routes,
     server::AppState,
     views::{self, layout, unsorted_bookmarks},
 };
@@ -26,7 +38,20 @@ pub fn router() -> Router<AppState> {
     Router::new()
         .route("/bookmarks/create", get(get_create).post(post_create))
         .route("/bookmarks/unsorted", get(get_unsorted))
-        .route("/bookmarks/{id}", delete(delete_by_id))
+        .route("/bookmarks/{id}", get(get_show).delete(delete_by_id))
+        .route("/bookmarks/{id}/replies", post(post_reply))
+        .route(
+            "/bookmarks/{id}/replies/{reply_id}/hide",
+            post(post_hide_reply),
+        )
+        .route(
+            "/bookmarks/{id}/replies/{reply_id}/unhide",
+            post(post_unhide_reply),
+        )
+        .route(
+            "/bookmarks/{id}/replies/{reply_id}/delete",
+            post(post_delete_reply),
+        )
 }
 
 async fn post_create(
@@ -39,6 +64,15 @@ async fn post_create(
     let layout = layout::Template::from_db(&mut tx, Some(&auth_user)).await?;
 
     let selected_parents = db::lists::list_by_id(&mut tx, &input.parents).await?;
+    // Users can only add bookmarks to their own lists
+    let owns_parents = input.parents.iter().all(|id| {
+        selected_parents
+            .iter()
+            .any(|list| list.id == *id && list.ap_user_id == auth_user.ap_user_id)
+    });
+    if !owns_parents {
+        return Err(ResponseError::NotFound);
+    }
 
     // TODO exclude items that are already linked
     let search_results = match input.list_search_term.as_ref() {
@@ -62,7 +96,7 @@ async fn post_create(
         Ok(i) => i,
     };
 
-    let bookmark = db::bookmarks::insert_local(
+    let mut bookmark = db::bookmarks::insert_local(
         &mut tx,
         auth_user.ap_user_id,
         insert_bookmark,
@@ -70,6 +104,20 @@ async fn post_create(
     )
     .await?;
 
+    if let Some(via_id) = input.via {
+        let via = db::bookmarks::by_id(&mut tx, via_id).await?;
+        if !is_visible(&mut tx, &via, Some(&auth_user), &federation_data).await? {
+            return Err(ResponseError::NotFound);
+        }
+        bookmark =
+            db::bookmarks::set_via(&mut tx, bookmark.id, &via.ap_id, Some(via.ap_user_id)).await?;
+    }
+
+    let tags = parse_tags(&input.tags);
+    if !tags.is_empty() {
+        bookmark = db::bookmarks::set_tags(&mut tx, bookmark.id, &tags).await?;
+    }
+
     let mut first_created_parent = Option::None;
     for parent_title in input.create_parents {
         let parent = db::lists::insert(
@@ -132,11 +180,14 @@ struct CreateBookmarkQuery {
     parent_id: Option<Uuid>,
     url: Option<String>,
     title: Option<String>,
+    /// Save a copy of this bookmark, crediting its author
+    via: Option<Uuid>,
 }
 
 async fn get_create(
     extract::Tx(mut tx): extract::Tx,
     auth_user: AuthUser,
+    federation_data: federation::Data,
     Query(query): Query<CreateBookmarkQuery>,
 ) -> ResponseResult<HtmfResponse> {
     let layout = layout::Template::from_db(&mut tx, Some(&auth_user)).await?;
@@ -144,6 +195,18 @@ async fn get_create(
     let selected_parent = match query.parent_id {
         Some(id) => Some(db::lists::by_id(&mut tx, id).await?),
         _ => None,
+    }
+    .filter(|list| list.ap_user_id == auth_user.ap_user_id);
+
+    let via = match query.via {
+        Some(id) => {
+            let via = db::bookmarks::by_id(&mut tx, id).await?;
+            if !is_visible(&mut tx, &via, Some(&auth_user), &federation_data).await? {
+                return Err(ResponseError::NotFound);
+            }
+            Some(via)
+        }
+        None => None,
     };
 
     Ok(HtmfResponse(views::create_bookmark::view(
@@ -152,13 +215,24 @@ async fn get_create(
             errors: FormErrors::default(),
             input: CreateBookmark {
                 parents: Vec::new(),
-                url: query.url.unwrap_or_default(),
-                title: query.title.unwrap_or_default(),
+                url: query
+                    .url
+                    .or_else(|| via.as_ref().map(|via| via.url.clone()))
+                    .unwrap_or_default(),
+                title: query
+                    .title
+                    .or_else(|| via.as_ref().map(|via| via.title.clone()))
+                    .unwrap_or_default(),
+                tags: via
+                    .as_ref()
+                    .map(|via| via.tags.join(" "))
+                    .unwrap_or_default(),
+                via: via.map(|via| via.id),
                 ..Default::default()
             },
             selected_parents: selected_parent.into_iter().collect(),
             // TODO exclude items that are already linked
-            search_results: db::lists::list_recent(&mut tx, auth_user.user_id).await?,
+            search_results: db::lists::list_recent(&mut tx, auth_user.ap_user_id).await?,
         },
     )))
 }
@@ -175,10 +249,291 @@ async fn get_unsorted(
     )))
 }
 
+#[derive(Deserialize)]
+struct ShowBookmarkQuery {
+    /// Prepare the reply form for answering this reply
+    reply_to: Option<Uuid>,
+}
+
+/// Show a bookmark to browsers, or serve it as JSON to other instances.
+async fn get_show(
+    extract::Tx(mut tx): extract::Tx,
+    auth_user: Option<AuthUser>,
+    accept: Accept,
+    signed_fetch: SignedFetch,
+    federation_data: federation::Data,
+    Path(id): Path<Uuid>,
+    Query(query): Query<ShowBookmarkQuery>,
+) -> ResponseResult<Response> {
+    let bookmark = db::bookmarks::by_id(&mut tx, id).await?;
+    if accept == Accept::ActivityJson {
+        signed_fetch.verify(&federation_data).await?;
+        return routes::federation::bookmark_json_response(&mut tx, bookmark, &federation_data)
+            .await;
+    }
+
+    if !is_visible(&mut tx, &bookmark, auth_user.as_ref(), &federation_data).await? {
+        return Err(ResponseError::NotFound);
+    }
+
+    Ok(negotiated(HtmfResponse(
+        show(
+            &mut tx,
+            bookmark,
+            auth_user.as_ref(),
+            CreateReply {
+                parent_id: query.reply_to,
+                ..Default::default()
+            },
+            FormErrors::default(),
+        )
+        .await?,
+    )))
+}
+
+async fn show(
+    tx: &mut db::AppTx,
+    bookmark: db::Bookmark,
+    auth_user: Option<&AuthUser>,
+    reply_input: CreateReply,
+    reply_errors: FormErrors,
+) -> ResponseResult<htmf::element::Element> {
+    let author = db::ap_users::read_by_id(tx, bookmark.ap_user_id).await?;
+    let via_author = match bookmark.via_ap_user_id {
+        Some(id) => {
+            let via_author = db::ap_users::read_by_id(tx, id).await?;
+            Some((handle(&via_author)?, via_author))
+        }
+        None => None,
+    };
+    let saved_by_followed_count = match auth_user {
+        Some(user) => {
+            db::bookmarks::count_saved_by_followed(
+                tx,
+                bookmark.via_ap_id.as_ref().unwrap_or(&bookmark.ap_id),
+                user.ap_user_id,
+            )
+            .await?
+        }
+        None => 0,
+    };
+    let lists =
+        db::lists::list_by_bookmark(tx, bookmark.id, auth_user.map(|user| user.ap_user_id)).await?;
+
+    let mut likes = Vec::new();
+    let mut boosts = Vec::new();
+    for interaction in db::interactions::list_by_bookmark(tx, bookmark.id).await? {
+        let ap_user = db::ap_users::read_by_id(tx, interaction.ap_user_id).await?;
+        let interacted = (handle(&ap_user)?, ap_user);
+        match interaction.kind {
+            Kind::Like => likes.push(interacted),
+            Kind::Boost => boosts.push(interacted),
+        }
+    }
+
+    let is_owner = auth_user.is_some_and(|user| user.ap_user_id == bookmark.ap_user_id);
+    // Remote bookmarks can be reported to their instance
+    let reportable = auth_user.is_some()
+        && !is_owner
+        && db::users::by_ap_user_id(tx, bookmark.ap_user_id)
+            .await?
+            .is_none();
+    let mut replies = Vec::new();
+    for reply in db::replies::list_by_bookmark(tx, bookmark.id, is_owner).await? {
+        let author = db::ap_users::read_by_id(tx, reply.ap_user_id).await?;
+        replies.push(views::bookmark::ThreadReply {
+            reply,
+            author_handle: handle(&author)?,
+            author,
+        });
+    }
+
+    Ok(views::bookmark::view(&views::bookmark::Data {
+        layout: layout::Template::from_db(tx, auth_user).await?,
+        bookmark,
+        author_handle: handle(&author)?,
+        author,
+        via_author,
+        saved_by_followed_count,
+        likes,
+        boosts,
+        lists,
+        reportable,
+        replies,
+        reply_input,
+        reply_errors,
+    }))
+}
+
+async fn post_reply(
+    extract::Tx(mut tx): extract::Tx,
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+    federation_data: federation::Data,
+    Path(id): Path<Uuid>,
+    QsForm(mut input): QsForm<CreateReply>,
+) -> ResponseResult<Response> {
+    let bookmark = db::bookmarks::by_id(&mut tx, id).await?;
+    if !is_visible(&mut tx, &bookmark, Some(&auth_user), &federation_data).await? {
+        return Err(ResponseError::NotFound);
+    }
+
+    input.content = input.content.trim().to_string();
+    if let Err(errors) = input.validate() {
+        return Ok(HtmfResponse(
+            show(&mut tx, bookmark, Some(&auth_user), input, errors.into()).await?,
+        )
+        .into_response());
+    }
+
+    if let Some(parent_id) = input.parent_id {
+        let parent = db::replies::by_id(&mut tx, parent_id).await?;
+        if parent.bookmark_id != bookmark.id {
+            return Err(ResponseError::NotFound);
+        }
+    }
+
+    let reply = db::replies::insert_local(
+        &mut tx,
+        db::replies::Insert {
+            ap_user_id: auth_user.ap_user_id,
+            bookmark_id: bookmark.id,
+            parent_id: input.parent_id,
+            content: input.content,
+        },
+        &state.base_url,
+    )
+    .await?;
+
+    let is_federated = is_reply_federated(&mut tx, &bookmark, &federation_data).await?;
+    let actor = db::ap_users::read_by_id(&mut tx, auth_user.ap_user_id).await?;
+    tx.commit().await?;
+
+    if is_federated {
+        federation::CreateReply::send(&actor, reply, &federation_data).await?;
+    }
+
+    Ok(Redirect::to(&bookmark.path()).into_response())
+}
+
+/// Replies to private bookmarks stay on this instance, like the bookmark.
+async fn is_reply_federated(
+    tx: &mut db::AppTx,
+    bookmark: &db::Bookmark,
+    federation_data: &federation::Data,
+) -> ResponseResult<bool> {
+    Ok(!bookmark.ap_id.is_local(federation_data)
+        || db::bookmarks::is_public(tx, bookmark.id).await?)
+}
+
+async fn post_hide_reply(
+    extract::Tx(tx): extract::Tx,
+    auth_user: AuthUser,
+    Path((id, reply_id)): Path<(Uuid, Uuid)>,
+) -> ResponseResult<Redirect> {
+    set_reply_hidden(tx, &auth_user, id, reply_id, true).await
+}
+
+async fn post_unhide_reply(
+    extract::Tx(tx): extract::Tx,
+    auth_user: AuthUser,
+    Path((id, reply_id)): Path<(Uuid, Uuid)>,
+) -> ResponseResult<Redirect> {
+    set_reply_hidden(tx, &auth_user, id, reply_id, false).await
+}
+
+/// Only the owner of a bookmark moderates the replies to it. Hiding is
+/// reversible, so it stays on this instance.
+async fn set_reply_hidden(
+    mut tx: db::AppTx,
+    auth_user: &AuthUser,
+    id: Uuid,
+    reply_id: Uuid,
+    hidden: bool,
+) -> ResponseResult<Redirect> {
+    let bookmark = db::bookmarks::by_id(&mut tx, id).await?;
+    let reply = db::replies::by_id(&mut tx, reply_id).await?;
+    if bookmark.ap_user_id != auth_user.ap_user_id || reply.bookmark_id != bookmark.id {
+        return Err(ResponseError::NotFound);
+    }
+
+    db::replies::set_hidden(&mut tx, reply.id, hidden).await?;
+    tx.commit().await?;
+
+    Ok(Redirect::to(&bookmark.path()))
+}
+
+/// Replies can be deleted by their author and by the owner of the bookmark.
+async fn post_delete_reply(
+    extract::Tx(mut tx): extract::Tx,
+    auth_user: AuthUser,
+    federation_data: federation::Data,
+    Path((id, reply_id)): Path<(Uuid, Uuid)>,
+) -> ResponseResult<Redirect> {
+    let bookmark = db::bookmarks::by_id(&mut tx, id).await?;
+    let reply = db::replies::by_id(&mut tx, reply_id).await?;
+    let is_allowed =
+        bookmark.ap_user_id == auth_user.ap_user_id || reply.ap_user_id == auth_user.ap_user_id;
+    if !is_allowed || reply.bookmark_id != bookmark.id {
+        return Err(ResponseError::NotFound);
+    }
+
+    // Only authors delete their replies everywhere, bookmark owners remove
+    // them from this instance
+    let actor = db::ap_users::read_by_id(&mut tx, auth_user.ap_user_id).await?;
+    let is_author = reply.ap_user_id == auth_user.ap_user_id;
+    let recipients =
+        if is_author && is_reply_federated(&mut tx, &bookmark, &federation_data).await? {
+            federation::Delete::reply_recipients(&mut tx, &reply).await?
+        } else {
+            Vec::new()
+        };
+    let reply = db::replies::delete_by_id(&mut tx, reply.id).await?;
+    tx.commit().await?;
+
+    if !recipients.is_empty() {
+        federation::Delete::send(
+            &actor,
+            reply.ap_id.into_inner(),
+            &recipients,
+            &federation_data,
+        )
+        .await?;
+    }
+
+    Ok(Redirect::to(&bookmark.path()))
+}
+
+fn handle(ap_user: &db::ApUser) -> ResponseResult<String> {
+    let resource =
+        webfinger::Resource::from_name_and_url(ap_user.username.clone(), ap_user.ap_id.inner())?;
+    Ok(format!("{}@{}", resource.name, resource.domain))
+}
+
+/// Local bookmarks that aren't in any public list are only visible to their
+/// owner.
+pub async fn is_visible(
+    tx: &mut db::AppTx,
+    bookmark: &db::Bookmark,
+    auth_user: Option<&AuthUser>,
+    federation_data: &federation::Data,
+) -> ResponseResult<bool> {
+    let is_owner = auth_user.is_some_and(|user| user.ap_user_id == bookmark.ap_user_id);
+    Ok(is_owner
+        || !bookmark.ap_id.is_local(federation_data)
+        || db::bookmarks::is_public(tx, bookmark.id).await?)
+}
+
 async fn delete_by_id(
     extract::Tx(mut tx): extract::Tx,
+    auth_user: AuthUser,
     Path(id): Path<Uuid>,
 ) -> ResponseResult<HeaderMap> {
+    let bookmark = db::bookmarks::by_id(&mut tx, id).await?;
+    if bookmark.ap_user_id != auth_user.ap_user_id {
+        return Err(ResponseError::NotFound);
+    }
+
     db::bookmarks::delete_by_id(&mut tx, id).await?;
 
     tx.commit().await?;
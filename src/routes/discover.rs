@@ -0,0 +1,31 @@
+use axum::{Router, routing::get};
+
+use crate::{
+    authentication::AuthUser,
+    db, extract,
+    htmf_response::HtmfResponse,
+    response_error::ResponseResult,
+    server::AppState,
+    views::{self, layout},
+};
+
+const DISCOVER_LIMIT: i64 = 100;
+
+pub fn router() -> Router<AppState> {
+    Router::new().route("/discover", get(get_discover))
+}
+
+/// Bookmarks from the wider network that reached us through relays.
+async fn get_discover(
+    extract::Tx(mut tx): extract::Tx,
+    auth_user: AuthUser,
+) -> ResponseResult<HtmfResponse> {
+    let bookmarks = db::bookmarks::list_relayed(&mut tx, DISCOVER_LIMIT).await?;
+
+    Ok(HtmfResponse(views::discover::view(
+        &views::discover::Data {
+            layout: layout::Template::from_db(&mut tx, Some(&auth_user)).await?,
+            bookmarks,
+        },
+    )))
+}
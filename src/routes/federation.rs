@@ -4,47 +4,107 @@ use activitypub_federation::{
         json::FederationJson,
     },
     config::Data,
-    fetch::webfinger::{Webfinger, build_webfinger_response, extract_webfinger_name},
+    error::Error,
+    fetch::{
+        object_id::ObjectId,
+        webfinger::{Webfinger, build_webfinger_response, extract_webfinger_name},
+    },
     protocol::context::WithContext,
     traits::{ActivityHandler, Object},
 };
+use anyhow::{Context, anyhow};
 use axum::{
     Json, Router,
-    extract::{Path, Query, State},
+    body::{Body, Bytes},
+    extract::{FromRequest, Path, Query, Request, State},
+    http::{header, request::Parts},
+    response::{IntoResponse, Redirect, Response},
     routing::{get, post},
 };
 use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
 use url::Url;
 use uuid::Uuid;
 
 use crate::{
     db::{self},
-    extract,
-    federation::{self, person::Person},
-    response_error::ResponseResult,
+    extract::{
+        self,
+        accept::{Accept, negotiated},
+        signed_fetch::SignedFetch,
+    },
+    federation::{
+        self, activity_log,
+        collections::{self, OrderedCollection},
+        context::with_context,
+        list::ListJson,
+        nodeinfo,
+    },
+    response_error::{ResponseError, ResponseResult},
     server::AppState,
 };
 
 pub fn router() -> Router<AppState> {
     Router::new()
         .route("/ap/user/{id}", get(get_person))
+        .route("/ap/actor", get(get_instance_actor))
+        .route("/ap/inbox", post(post_inbox))
         .route("/ap/inbox/{user_id}", post(post_inbox))
         .route("/ap/outbox/{user_id}", get(get_outbox))
+        .route("/ap/lists/{user_id}", get(get_lists))
         .route("/ap/bookmark/{id}", get(get_bookmark))
+        .route("/ap/reply/{id}", get(get_reply))
         .route("/.well-known/webfinger", get(webfinger))
+        .route("/.well-known/nodeinfo", get(nodeinfo_discovery))
+        .route("/nodeinfo/{version}", get(get_nodeinfo))
 }
 
 /// Read a local person by requesting the URL that is it's `ap_id`.
+/// Browsers are redirected to the profile page.
 async fn get_person(
     extract::Tx(mut tx): extract::Tx,
-    State(state): State<AppState>,
+    accept: Accept,
+    signed_fetch: SignedFetch,
+    data: federation::Data,
     Path(id): Path<Uuid>,
-) -> ResponseResult<FederationJson<WithContext<Person>>> {
+) -> ResponseResult<Response> {
     let ap_user = db::ap_users::read_by_id(&mut tx, id).await?;
-    let json_person = ap_user
-        .into_json(&state.federation_config.to_request_data())
-        .await?;
-    Ok(FederationJson(WithContext::new_default(json_person)))
+    if !ap_user.ap_id.is_local(&data) {
+        return Err(ResponseError::NotFound);
+    }
+    if accept == Accept::ActivityJson {
+        signed_fetch.verify(&data).await?;
+    }
+    person_response(ap_user, accept, &data).await
+}
+
+/// The actor representing the instance itself. It's served without a
+/// signature even in authorized fetch mode, since other instances need its key
+/// to verify the fetches we sign with it.
+async fn get_instance_actor(
+    extract::Tx(mut tx): extract::Tx,
+    accept: Accept,
+    data: federation::Data,
+) -> ResponseResult<Response> {
+    let ap_user = db::ap_users::read_instance_actor(&mut tx, &data.base_url).await?;
+    person_response(ap_user, accept, &data).await
+}
+
+/// Serve a local person as JSON, or redirect browsers to their profile page.
+pub async fn person_response(
+    ap_user: db::ApUser,
+    accept: Accept,
+    data: &federation::Data,
+) -> ResponseResult<Response> {
+    match accept {
+        Accept::Html => Ok(negotiated(Redirect::to(
+            ap_user.profile_url(&data.base_url)?.as_str(),
+        ))),
+        Accept::ActivityJson => {
+            let json_person = ap_user.into_json(data).await?;
+            Ok(negotiated(FederationJson(with_context(json_person))))
+        }
+    }
 }
 
 #[derive(Deserialize, Serialize, Debug)]
@@ -53,38 +113,245 @@ async fn get_person(
 pub enum PersonAcceptedActivities {
     Follow(federation::Follow),
     UndoFollow(federation::UndoFollow),
+    Like(federation::Like),
+    Announce(federation::Announce),
+    UndoInteraction(federation::UndoInteraction),
+    Accept(federation::Accept),
+    Reject(federation::Reject),
+    // Replies are notes, too, so try them before bookmarks
+    CreateReply(federation::CreateReply),
+    CreateBookmark(federation::CreateBookmark),
+    UpdatePerson(federation::UpdatePerson),
+    Move(federation::Move),
+    Flag(federation::Flag),
+    Delete(federation::Delete),
 }
 
-async fn post_inbox(data: federation::Data, activity_data: ActivityData) -> ResponseResult<()> {
+/// Handles both the shared inbox and the inboxes of individual users.
+/// Activities always name the local users they concern, so we don't need to
+/// know which inbox they were delivered to.
+///
+/// Activities from domains rejected by an admin are refused by the
+/// `UrlVerifier` set up in [`federation::config::new_config`].
+///
+/// Activities from actors blocked by the local users they concern are ignored
+/// in [`receive`].
+///
+/// Every activity ends up in the activity log, along with our response.
+async fn post_inbox(data: federation::Data, request: Request) -> ResponseResult<()> {
+    let (parts, body) = request.into_parts();
+    let body = axum::body::to_bytes(body, MAX_ACTIVITY_SIZE)
+        .await
+        .context("Failed to read activity")?;
+
+    let result = match receive(&parts, body.clone(), &data).await {
+        // The remote instance might have rotated its keys since we last fetched
+        // the actor, so fetch it again and give the activity a second chance
+        Err(ResponseError::FederationError(Error::ActivitySignatureInvalid)) => {
+            receive_with_refreshed_actor(&parts, body.clone(), &data).await
+        }
+        other => other,
+    };
+    // Relays forward activities of other users, signed with their own key
+    let result = match result {
+        Err(ResponseError::FederationError(Error::ActivitySignatureInvalid)) => {
+            federation::relay::receive_forwarded(&parts, &body, &data).await
+        }
+        other => other,
+    };
+    activity_log::record_incoming(&data.db_pool, &body, &result).await;
+
+    result
+}
+
+/// Anyone can send us activities with invalid signatures, so we only fetch
+/// actors we already know again, and only once per [`MIN_REFETCH_INTERVAL`].
+async fn receive_with_refreshed_actor(
+    parts: &Parts,
+    body: Bytes,
+    data: &federation::Data,
+) -> ResponseResult<()> {
+    let activity: UnverifiedActivity =
+        serde_json::from_slice(&body).context("Failed to parse activity")?;
+    let mut tx = data.db_pool.begin().await?;
+    let claimed = db::ap_users::claim_refetch(
+        &mut tx,
+        activity.actor.inner(),
+        OffsetDateTime::now_utc() - MIN_REFETCH_INTERVAL,
+    )
+    .await?;
+    tx.commit().await?;
+    if !claimed {
+        return Err(Error::ActivitySignatureInvalid.into());
+    }
+
+    activity.actor.dereference_forced(data).await?;
+    receive(parts, body, data).await
+}
+
+const MAX_ACTIVITY_SIZE: usize = 2 * 1024 * 1024;
+const MIN_REFETCH_INTERVAL: time::Duration = time::Duration::minutes(1);
+
+#[derive(Deserialize)]
+struct UnverifiedActivity {
+    actor: ObjectId<db::ApUser>,
+}
+
+async fn receive(parts: &Parts, body: Bytes, data: &federation::Data) -> ResponseResult<()> {
+    // Activities of suspended actors are refused, just like those of rejected
+    // domains
+    let json: serde_json::Value =
+        serde_json::from_slice(&body).context("Failed to parse activity")?;
+    let activity = UnverifiedActivity::deserialize(&json).context("Failed to parse activity")?;
+    let mut tx = data.db_pool.begin().await?;
+    if db::reports::is_suspended_ap_id(&mut tx, activity.actor.inner()).await? {
+        return Err(anyhow!("Actor {} is suspended", activity.actor.inner()).into());
+    }
+    // Activities concerning a local user who blocked the actor are dropped
+    // silently, whatever their type
+    let mut referenced = Vec::new();
+    collect_urls(&json, &mut referenced);
+    if db::actor_blocks::blocked_by_referenced(&mut tx, activity.actor.inner(), &referenced).await?
+    {
+        tracing::debug!("Ignoring activity from blocked user");
+        return Ok(());
+    }
+    drop(tx);
+
+    let mut request = axum::http::Request::builder()
+        .method(parts.method.clone())
+        .uri(parts.uri.clone())
+        .version(parts.version)
+        .body(Body::from(body))
+        .context("Failed to rebuild request")?;
+    *request.headers_mut() = parts.headers.clone();
+    let activity_data = ActivityData::from_request(request, &())
+        .await
+        .map_err(|_| anyhow!("Failed to read activity"))?;
+
     receive_activity::<WithContext<PersonAcceptedActivities>, db::ApUser, federation::Context>(
         activity_data,
-        &data,
+        data,
     )
-    .await?;
+    .await
+}
 
-    Ok(())
+/// All urls anywhere in an activity, including embedded objects.
+fn collect_urls(value: &serde_json::Value, urls: &mut Vec<String>) {
+    match value {
+        serde_json::Value::String(string) if string.starts_with("http") => {
+            urls.push(string.clone());
+        }
+        serde_json::Value::Array(values) => {
+            for value in values {
+                collect_urls(value, urls);
+            }
+        }
+        serde_json::Value::Object(map) => {
+            for value in map.values() {
+                collect_urls(value, urls);
+            }
+        }
+        _ => {}
+    }
 }
 
-async fn get_outbox() -> ResponseResult<FederationJson<WithContext<serde_json::Value>>> {
-    let empty_outbox = serde_json::json!({
-        "type": "OrderedCollection",
-        "orderedItems": [],
-        "totalItems": 0
-    });
-    Ok(FederationJson(WithContext::new_default(empty_outbox)))
+/// Serve the most recent public bookmarks of a local user.
+async fn get_outbox(
+    extract::Tx(mut tx): extract::Tx,
+    signed_fetch: SignedFetch,
+    data: federation::Data,
+    Path(user_id): Path<Uuid>,
+) -> ResponseResult<FederationJson<WithContext<OrderedCollection<federation::CreateBookmark>>>> {
+    let ap_user = read_local_ap_user(&mut tx, user_id, &data).await?;
+    signed_fetch.verify(&data).await?;
+    let outbox = collections::outbox(&ap_user, &data).await?;
+    Ok(FederationJson(with_context(outbox)))
+}
+
+/// Serve the public lists of a local user to other linkblocks instances.
+async fn get_lists(
+    extract::Tx(mut tx): extract::Tx,
+    signed_fetch: SignedFetch,
+    data: federation::Data,
+    Path(user_id): Path<Uuid>,
+) -> ResponseResult<FederationJson<WithContext<OrderedCollection<ListJson>>>> {
+    let ap_user = read_local_ap_user(&mut tx, user_id, &data).await?;
+    signed_fetch.verify(&data).await?;
+    let lists = collections::lists(&ap_user, &data).await?;
+    Ok(FederationJson(with_context(lists)))
+}
+
+async fn read_local_ap_user(
+    tx: &mut db::AppTx,
+    id: Uuid,
+    data: &federation::Data,
+) -> ResponseResult<db::ApUser> {
+    let ap_user = db::ap_users::read_by_id(tx, id).await?;
+    if !ap_user.ap_id.is_local(data) {
+        return Err(ResponseError::NotFound);
+    }
+    Ok(ap_user)
 }
 
 /// Read a local bookmark by requesting the URL that is it's `ap_id`.
+/// Browsers are redirected to the bookmark page.
 async fn get_bookmark(
     extract::Tx(mut tx): extract::Tx,
-    State(state): State<AppState>,
+    accept: Accept,
+    signed_fetch: SignedFetch,
+    data: federation::Data,
     Path(id): Path<Uuid>,
-) -> ResponseResult<FederationJson<WithContext<federation::bookmark::BookmarkJson>>> {
+) -> ResponseResult<Response> {
     let bookmark = db::bookmarks::by_id(&mut tx, id).await?;
-    let json_bookmark = bookmark
-        .into_json(&state.federation_config.to_request_data())
-        .await?;
-    Ok(FederationJson(WithContext::new_default(json_bookmark)))
+    match accept {
+        Accept::Html => Ok(negotiated(Redirect::to(&bookmark.path()))),
+        Accept::ActivityJson => {
+            signed_fetch.verify(&data).await?;
+            bookmark_json_response(&mut tx, bookmark, &data).await
+        }
+    }
+}
+
+/// Serve a local, public bookmark as JSON.
+pub async fn bookmark_json_response(
+    tx: &mut db::AppTx,
+    bookmark: db::Bookmark,
+    data: &federation::Data,
+) -> ResponseResult<Response> {
+    if !bookmark.ap_id.is_local(data) || !db::bookmarks::is_public(tx, bookmark.id).await? {
+        return Err(ResponseError::NotFound);
+    }
+    let json_bookmark = bookmark.into_json(data).await?;
+    Ok(negotiated(FederationJson(with_context(json_bookmark))))
+}
+
+/// Read a local reply by requesting the URL that is its `ap_id`.
+/// Browsers are redirected to the page of the bookmark it belongs to.
+async fn get_reply(
+    extract::Tx(mut tx): extract::Tx,
+    accept: Accept,
+    signed_fetch: SignedFetch,
+    data: federation::Data,
+    Path(id): Path<Uuid>,
+) -> ResponseResult<Response> {
+    let reply = db::replies::by_id(&mut tx, id).await?;
+    let bookmark = db::bookmarks::by_id(&mut tx, reply.bookmark_id).await?;
+    let is_visible = !reply.hidden
+        && (!bookmark.ap_id.is_local(&data)
+            || db::bookmarks::is_public(&mut tx, bookmark.id).await?);
+    if !reply.ap_id.is_local(&data) || !is_visible {
+        return Err(ResponseError::NotFound);
+    }
+
+    match accept {
+        Accept::Html => Ok(negotiated(Redirect::to(&bookmark.path()))),
+        Accept::ActivityJson => {
+            signed_fetch.verify(&data).await?;
+            let json_reply = reply.into_json(&data).await?;
+            Ok(negotiated(FederationJson(with_context(json_reply))))
+        }
+    }
 }
 
 #[derive(Deserialize)]
@@ -111,3 +378,24 @@ async fn webfinger(
         ap_id.into_inner(),
     )))
 }
+
+async fn nodeinfo_discovery(
+    State(state): State<AppState>,
+) -> ResponseResult<Json<nodeinfo::Discovery>> {
+    Ok(Json(nodeinfo::Discovery::new(&state.base_url)?))
+}
+
+async fn get_nodeinfo(
+    extract::Tx(mut tx): extract::Tx,
+    Path(version): Path<nodeinfo::Version>,
+) -> ResponseResult<impl IntoResponse> {
+    let stats = db::instance::stats(&mut tx).await?;
+    let settings = db::registrations::read_settings(&mut tx).await?;
+    let open_registrations =
+        settings.registration_mode != db::registrations::RegistrationMode::Closed;
+    let content_type = format!(r#"application/json; profile="{}#""#, version.schema_url());
+    Ok((
+        [(header::CONTENT_TYPE, content_type)],
+        Json(nodeinfo::NodeInfo::new(version, stats, open_registrations)),
+    ))
+}
@@ -0,0 +1,41 @@
+use anyhow::Context;
+use axum::{
+    Router,
+    extract::Path,
+    http::{HeaderMap, HeaderValue, header},
+    routing::get,
+};
+use uuid::Uuid;
+
+use crate::{db, extract, response_error::ResponseResult, server::AppState};
+
+pub fn router() -> Router<AppState> {
+    Router::new().route("/images/{id}", get(get_image))
+}
+
+async fn get_image(
+    extract::Tx(mut tx): extract::Tx,
+    Path(id): Path<Uuid>,
+) -> ResponseResult<(HeaderMap, Vec<u8>)> {
+    let image = db::images::by_id(&mut tx, id).await?;
+
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        header::CONTENT_TYPE,
+        image
+            .content_type
+            .parse()
+            .context("Failed to convert content type to header")?,
+    );
+    headers.insert(
+        header::X_CONTENT_TYPE_OPTIONS,
+        HeaderValue::from_static("nosniff"),
+    );
+    // Replaced images get a new id, so they can be cached forever
+    headers.insert(
+        header::CACHE_CONTROL,
+        HeaderValue::from_static("public, max-age=31536000, immutable"),
+    );
+
+    Ok((headers, image.data))
+}
@@ -12,12 +12,14 @@ use uuid::Uuid;
 
 use crate::{
     authentication::AuthUser,
-    db::{self, LinkDestination},
+    db::{self, AppTx, LinkDestination},
     extract::{self, qs_form::QsForm},
+    federation,
     form_errors::FormErrors,
     forms::links::{CreateLink, PartialCreateLink},
     htmf_response::HtmfResponse,
-    response_error::ResponseResult,
+    response_error::{ResponseError, ResponseResult},
+    routes,
     server::AppState,
     views::{self, layout},
 };
@@ -31,16 +33,17 @@ pub fn router() -> Router<AppState> {
 async fn post_create(
     extract::Tx(mut tx): extract::Tx,
     auth_user: AuthUser,
+    federation_data: federation::Data,
     // TODO handle failed extractors in forms better
     QsForm(input): QsForm<PartialCreateLink>,
 ) -> ResponseResult<Response> {
     let layout = layout::Template::from_db(&mut tx, Some(&auth_user)).await?;
     let src_from_db = match input.src {
-        Some(id) => Some(db::items::by_id(&mut tx, id).await?),
+        Some(id) => Some(read_src(&mut tx, &auth_user, id).await?),
         None => None,
     };
     let dest_from_db = match input.dest {
-        Some(id) => Some(db::items::by_id(&mut tx, id).await?),
+        Some(id) => Some(read_dest(&mut tx, &auth_user, id, &federation_data).await?),
         None => None,
     };
 
@@ -114,17 +117,18 @@ struct CreateLinkQueryString {
 async fn get_create(
     extract::Tx(mut tx): extract::Tx,
     auth_user: AuthUser,
+    federation_data: federation::Data,
     Query(query): Query<CreateLinkQueryString>,
 ) -> ResponseResult<HtmfResponse> {
     let layout = layout::Template::from_db(&mut tx, Some(&auth_user)).await?;
 
     let src = match query.src_id {
-        Some(id) => Some(db::items::by_id(&mut tx, id).await?),
+        Some(id) => Some(read_src(&mut tx, &auth_user, id).await?),
         _ => None,
     };
 
     let dest = match query.dest_id {
-        Some(id) => Some(db::items::by_id(&mut tx, id).await?),
+        Some(id) => Some(read_dest(&mut tx, &auth_user, id, &federation_data).await?),
         _ => None,
     };
 
@@ -151,8 +155,14 @@ async fn get_create(
 
 async fn delete_by_id(
     extract::Tx(mut tx): extract::Tx,
+    auth_user: AuthUser,
     Path(id): Path<Uuid>,
 ) -> ResponseResult<HeaderMap> {
+    let link = db::links::by_id(&mut tx, id).await?;
+    if link.user_id != auth_user.user_id {
+        return Err(ResponseError::NotFound);
+    }
+
     db::links::delete_by_id(&mut tx, id).await?;
 
     tx.commit().await?;
@@ -165,3 +175,37 @@ async fn delete_by_id(
 
     Ok(headers)
 }
+
+/// Users can only link from their own lists.
+async fn read_src(
+    tx: &mut AppTx,
+    auth_user: &AuthUser,
+    id: Uuid,
+) -> ResponseResult<LinkDestination> {
+    let src = db::items::by_id(tx, id).await?;
+    match &src {
+        LinkDestination::List(list) if list.ap_user_id == auth_user.ap_user_id => Ok(src),
+        _ => Err(ResponseError::NotFound),
+    }
+}
+
+/// Users can link to anything they are allowed to see.
+async fn read_dest(
+    tx: &mut AppTx,
+    auth_user: &AuthUser,
+    id: Uuid,
+    federation_data: &federation::Data,
+) -> ResponseResult<LinkDestination> {
+    let dest = db::items::by_id(tx, id).await?;
+    let is_visible = match &dest {
+        LinkDestination::List(list) => !list.private || list.ap_user_id == auth_user.ap_user_id,
+        LinkDestination::Bookmark(bookmark) => {
+            routes::bookmarks::is_visible(tx, bookmark, Some(auth_user), federation_data).await?
+        }
+    };
+    if !is_visible {
+        return Err(ResponseError::NotFound);
+    }
+
+    Ok(dest)
+}
@@ -1,6 +1,7 @@
+use activitypub_federation::axum::json::FederationJson;
 use axum::{
     Form, Router,
-    extract::Path,
+    extract::{Path, State},
     response::{IntoResponse, Redirect, Response},
     routing::{get, post},
 };
@@ -10,7 +11,12 @@ use uuid::Uuid;
 use crate::{
     authentication::AuthUser,
     db::{self},
-    extract,
+    extract::{
+        self,
+        accept::{Accept, negotiated},
+        signed_fetch::SignedFetch,
+    },
+    federation::{self, context::with_context, list::ListJson},
     form_errors::FormErrors,
     forms,
     forms::lists::{CreateList, EditListPinned, EditListPrivate},
@@ -33,14 +39,28 @@ pub fn router() -> Router<AppState> {
         .route("/lists/unpinned", get(list_unpinned))
 }
 
+/// Show a list to browsers, or serve public lists as JSON to other instances.
 async fn get_show(
     auth_user: Option<AuthUser>,
     extract::Tx(mut tx): extract::Tx,
+    accept: Accept,
+    signed_fetch: SignedFetch,
+    federation_data: federation::Data,
+    State(state): State<AppState>,
     Path(list_id): Path<Uuid>,
-) -> ResponseResult<HtmfResponse> {
+) -> ResponseResult<Response> {
+    let list = db::lists::by_id(&mut tx, list_id).await?;
+    if accept == Accept::ActivityJson {
+        if list.private {
+            return Err(ResponseError::NotFound);
+        }
+        signed_fetch.verify(&federation_data).await?;
+        let json_list = ListJson::read(&mut tx, list, &state.base_url).await?;
+        return Ok(negotiated(FederationJson(with_context(json_list))));
+    }
+
     let links =
         db::links::list_by_list(&mut tx, list_id, auth_user.as_ref().map(|u| u.ap_user_id)).await?;
-    let list = db::lists::by_id(&mut tx, list_id).await?;
 
     match auth_user {
         Some(ref user) => {
@@ -55,12 +75,14 @@ async fn get_show(
         }
     }
 
-    Ok(HtmfResponse(views::list::view(&views::list::Data {
-        layout: layout::Template::from_db(&mut tx, auth_user.as_ref()).await?,
-        links,
-        list,
-        metadata: db::lists::metadata_by_id(&mut tx, list_id).await?,
-    })))
+    Ok(negotiated(HtmfResponse(views::list::view(
+        &views::list::Data {
+            layout: layout::Template::from_db(&mut tx, auth_user.as_ref()).await?,
+            links,
+            list,
+            metadata: db::lists::metadata_by_id(&mut tx, list_id).await?,
+        },
+    ))))
 }
 
 async fn post_create(
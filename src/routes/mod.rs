@@ -1,7 +1,15 @@
+pub mod admin;
 pub mod assets;
 pub mod bookmarks;
+pub mod discover;
 pub mod federation;
+pub mod images;
 pub mod index;
 pub mod links;
 pub mod lists;
+pub mod notifications;
+pub mod reports;
+pub mod search;
+pub mod settings;
+pub mod tags;
 pub mod users;
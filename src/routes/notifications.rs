@@ -0,0 +1,193 @@
+use activitypub_federation::kinds::activity::FollowType;
+use axum::{
+    Form, Router,
+    extract::Path,
+    response::Redirect,
+    routing::{get, post},
+};
+use uuid::Uuid;
+
+use crate::{
+    authentication::AuthUser,
+    db::{self, AppTx},
+    extract, federation,
+    forms::{ap_users::EditManuallyApprovesFollowers, users::EditNotifyOnInteractions},
+    htmf_response::HtmfResponse,
+    response_error::ResponseResult,
+    server::AppState,
+    views::{self, layout},
+};
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/notifications", get(get_notifications))
+        .route(
+            "/notifications/manually_approves_followers",
+            post(post_manually_approves_followers),
+        )
+        .route(
+            "/notifications/notify_on_interactions",
+            post(post_notify_on_interactions),
+        )
+        .route(
+            "/notifications/follow_requests/{follower_id}/accept",
+            post(post_accept_follow_request),
+        )
+        .route(
+            "/notifications/follow_requests/{follower_id}/reject",
+            post(post_reject_follow_request),
+        )
+}
+
+/// How many likes and boosts to show in the notifications.
+const MAX_INTERACTIONS: i64 = 50;
+
+async fn get_notifications(
+    extract::Tx(mut tx): extract::Tx,
+    auth_user: AuthUser,
+) -> ResponseResult<HtmfResponse> {
+    let user = db::users::by_id(&mut tx, auth_user.user_id).await?;
+    let mut interactions = Vec::new();
+    if user.notify_on_interactions {
+        for interaction in
+            db::interactions::list_recent_for_owner(&mut tx, auth_user.ap_user_id, MAX_INTERACTIONS)
+                .await?
+        {
+            interactions.push(views::notifications::ReceivedInteraction {
+                ap_user: db::ap_users::read_by_id(&mut tx, interaction.ap_user_id).await?,
+                bookmark: db::bookmarks::by_id(&mut tx, interaction.bookmark_id).await?,
+                interaction,
+            });
+        }
+    }
+
+    Ok(HtmfResponse(views::notifications::view(
+        &views::notifications::Data {
+            layout: layout::Template::from_db(&mut tx, Some(&auth_user)).await?,
+            ap_user: db::ap_users::read_by_id(&mut tx, auth_user.ap_user_id).await?,
+            pending_followers: db::ap_users::list_pending_followers(&mut tx, auth_user.ap_user_id)
+                .await?,
+            notify_on_interactions: user.notify_on_interactions,
+            interactions,
+        },
+    )?))
+}
+
+async fn post_manually_approves_followers(
+    extract::Tx(mut tx): extract::Tx,
+    auth_user: AuthUser,
+    federation_data: federation::Data,
+    Form(input): Form<EditManuallyApprovesFollowers>,
+) -> ResponseResult<Redirect> {
+    let ap_user = db::ap_users::set_manually_approves_followers(
+        &mut tx,
+        auth_user.ap_user_id,
+        input.manually_approves_followers,
+    )
+    .await?;
+
+    // Nobody is left waiting once the account accepts all followers
+    let mut accepted = Vec::new();
+    if !input.manually_approves_followers {
+        for follower in db::ap_users::list_pending_followers(&mut tx, ap_user.id).await? {
+            accepted.push(accept_follow_request(&mut tx, &auth_user, follower.id).await?);
+        }
+    }
+
+    tx.commit().await?;
+
+    for (follow, user) in accepted {
+        federation::Accept::send(&user, follow, &federation_data).await?;
+    }
+    // Let other instances know whether this account is locked now
+    federation::UpdatePerson::send_to_followers(&ap_user, &federation_data).await?;
+
+    Ok(Redirect::to("/notifications"))
+}
+
+async fn post_notify_on_interactions(
+    extract::Tx(mut tx): extract::Tx,
+    auth_user: AuthUser,
+    Form(input): Form<EditNotifyOnInteractions>,
+) -> ResponseResult<Redirect> {
+    db::users::set_notify_on_interactions(&mut tx, auth_user.user_id, input.notify_on_interactions)
+        .await?;
+
+    tx.commit().await?;
+
+    Ok(Redirect::to("/notifications"))
+}
+
+async fn post_accept_follow_request(
+    extract::Tx(mut tx): extract::Tx,
+    auth_user: AuthUser,
+    federation_data: federation::Data,
+    Path(follower_id): Path<Uuid>,
+) -> ResponseResult<Redirect> {
+    let (follow, user) = accept_follow_request(&mut tx, &auth_user, follower_id).await?;
+
+    tx.commit().await?;
+
+    federation::Accept::send(&user, follow, &federation_data).await?;
+
+    Ok(Redirect::to("/notifications"))
+}
+
+async fn post_reject_follow_request(
+    extract::Tx(mut tx): extract::Tx,
+    auth_user: AuthUser,
+    federation_data: federation::Data,
+    Path(follower_id): Path<Uuid>,
+) -> ResponseResult<Redirect> {
+    let (follow, user) = take_follow_request(&mut tx, &auth_user, follower_id).await?;
+
+    tx.commit().await?;
+
+    federation::Reject::send(&user, follow, &federation_data).await?;
+
+    Ok(Redirect::to("/notifications"))
+}
+
+/// Turn a pending follow request into a follow. Send the returned `Follow` back
+/// in an `Accept` after committing.
+async fn accept_follow_request(
+    tx: &mut AppTx,
+    auth_user: &AuthUser,
+    follower_id: Uuid,
+) -> ResponseResult<(federation::Follow, db::ApUser)> {
+    let (follow, user) = take_follow_request(tx, auth_user, follower_id).await?;
+
+    db::follows::upsert(
+        tx,
+        db::follows::Insert {
+            follower_id,
+            following_id: user.id,
+        },
+    )
+    .await?;
+
+    Ok((follow, user))
+}
+
+/// Remove a pending follow request and rebuild the original `Follow` activity,
+/// so we can answer it.
+async fn take_follow_request(
+    tx: &mut AppTx,
+    auth_user: &AuthUser,
+    follower_id: Uuid,
+) -> ResponseResult<(federation::Follow, db::ApUser)> {
+    let request = db::follow_requests::read(tx, follower_id, auth_user.ap_user_id).await?;
+    let follower = db::ap_users::read_by_id(tx, follower_id).await?;
+    let user = db::ap_users::read_by_id(tx, auth_user.ap_user_id).await?;
+
+    db::follow_requests::remove(tx, follower_id, user.id).await?;
+
+    let follow = federation::Follow {
+        actor: follower.ap_id,
+        object: user.ap_id.clone(),
+        kind: FollowType::Follow,
+        id: request.ap_id,
+    };
+
+    Ok((follow, user))
+}
@@ -0,0 +1,176 @@
+use axum::{
+    Form, Router,
+    extract::{Path, State},
+    response::{IntoResponse, Redirect, Response},
+    routing::get,
+};
+use garde::Validate;
+use uuid::Uuid;
+
+use crate::{
+    authentication::AuthUser,
+    db::{self, AppTx},
+    extract, federation,
+    form_errors::FormErrors,
+    forms::reports::CreateReport,
+    htmf_response::HtmfResponse,
+    response_error::{ResponseError, ResponseResult},
+    server::AppState,
+    views::{self, layout},
+};
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/user/{username}/report",
+            get(get_report_user).post(post_report_user),
+        )
+        .route(
+            "/bookmarks/{id}/report",
+            get(get_report_bookmark).post(post_report_bookmark),
+        )
+}
+
+async fn get_report_user(
+    extract::Tx(mut tx): extract::Tx,
+    auth_user: AuthUser,
+    Path(handle): Path<String>,
+    State(state): State<AppState>,
+) -> ResponseResult<HtmfResponse> {
+    read_reported_user(&mut tx, &auth_user, &handle, &state).await?;
+
+    Ok(HtmfResponse(views::create_report::view(
+        &views::create_report::Data {
+            layout: layout::Template::from_db(&mut tx, Some(&auth_user)).await?,
+            subject: format!("@{handle}"),
+            form_action: format!("/user/{handle}/report"),
+            input: CreateReport::default(),
+            errors: FormErrors::default(),
+        },
+    )))
+}
+
+async fn post_report_user(
+    extract::Tx(mut tx): extract::Tx,
+    auth_user: AuthUser,
+    federation_data: federation::Data,
+    Path(handle): Path<String>,
+    State(state): State<AppState>,
+    Form(input): Form<CreateReport>,
+) -> ResponseResult<Response> {
+    let reported = read_reported_user(&mut tx, &auth_user, &handle, &state).await?;
+
+    if let Err(errors) = input.validate() {
+        return Ok(HtmfResponse(views::create_report::view(
+            &views::create_report::Data {
+                layout: layout::Template::from_db(&mut tx, Some(&auth_user)).await?,
+                subject: format!("@{handle}"),
+                form_action: format!("/user/{handle}/report"),
+                input,
+                errors: errors.into(),
+            },
+        ))
+        .into_response());
+    }
+
+    let reporter = db::ap_users::read_by_id(&mut tx, auth_user.ap_user_id).await?;
+    drop(tx);
+    federation::Flag::send(&reporter, &reported, None, input.comment, &federation_data).await?;
+
+    Ok(Redirect::to(&format!("/user/{handle}")).into_response())
+}
+
+async fn get_report_bookmark(
+    extract::Tx(mut tx): extract::Tx,
+    auth_user: AuthUser,
+    Path(id): Path<Uuid>,
+) -> ResponseResult<HtmfResponse> {
+    let bookmark = read_reported_bookmark(&mut tx, &auth_user, id).await?;
+
+    Ok(HtmfResponse(views::create_report::view(
+        &views::create_report::Data {
+            layout: layout::Template::from_db(&mut tx, Some(&auth_user)).await?,
+            subject: format!("\"{}\"", bookmark.title),
+            form_action: format!("{}/report", bookmark.path()),
+            input: CreateReport::default(),
+            errors: FormErrors::default(),
+        },
+    )))
+}
+
+async fn post_report_bookmark(
+    extract::Tx(mut tx): extract::Tx,
+    auth_user: AuthUser,
+    federation_data: federation::Data,
+    Path(id): Path<Uuid>,
+    Form(input): Form<CreateReport>,
+) -> ResponseResult<Response> {
+    let bookmark = read_reported_bookmark(&mut tx, &auth_user, id).await?;
+
+    if let Err(errors) = input.validate() {
+        return Ok(HtmfResponse(views::create_report::view(
+            &views::create_report::Data {
+                layout: layout::Template::from_db(&mut tx, Some(&auth_user)).await?,
+                subject: format!("\"{}\"", bookmark.title),
+                form_action: format!("{}/report", bookmark.path()),
+                input,
+                errors: errors.into(),
+            },
+        ))
+        .into_response());
+    }
+
+    let reporter = db::ap_users::read_by_id(&mut tx, auth_user.ap_user_id).await?;
+    let reported = db::ap_users::read_by_id(&mut tx, bookmark.ap_user_id).await?;
+    drop(tx);
+    federation::Flag::send(
+        &reporter,
+        &reported,
+        Some(&bookmark),
+        input.comment,
+        &federation_data,
+    )
+    .await?;
+
+    Ok(Redirect::to(&bookmark.path()).into_response())
+}
+
+/// Users can report anyone but themselves.
+async fn read_reported_user(
+    tx: &mut AppTx,
+    auth_user: &AuthUser,
+    handle: &str,
+    state: &AppState,
+) -> ResponseResult<db::ApUser> {
+    let ap_user = db::ap_users::read_by_username(
+        tx,
+        federation::webfinger::Resource::parse_handle(handle, &state.base_url)?,
+    )
+    .await?;
+
+    if ap_user.id == auth_user.ap_user_id {
+        return Err(ResponseError::NotFound);
+    }
+
+    Ok(ap_user)
+}
+
+/// Users can report bookmarks of others, as long as they can see them.
+async fn read_reported_bookmark(
+    tx: &mut AppTx,
+    auth_user: &AuthUser,
+    id: Uuid,
+) -> ResponseResult<db::Bookmark> {
+    let bookmark = db::bookmarks::by_id(tx, id).await?;
+
+    let is_local = db::users::by_ap_user_id(tx, bookmark.ap_user_id)
+        .await?
+        .is_some();
+    if bookmark.ap_user_id == auth_user.ap_user_id
+        || (is_local && !db::bookmarks::is_public(tx, bookmark.id).await?)
+    {
+        return Err(ResponseError::NotFound);
+    }
+
+    Ok(bookmark)
+}
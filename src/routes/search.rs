@@ -0,0 +1,45 @@
+use axum::{Router, extract::Query, routing::get};
+
+use crate::{
+    authentication::AuthUser,
+    db, extract,
+    forms::search::SearchQuery,
+    htmf_response::HtmfResponse,
+    response_error::ResponseResult,
+    server::AppState,
+    views::{self, layout},
+};
+
+const SEARCH_LIMIT: i64 = 100;
+
+pub fn router() -> Router<AppState> {
+    Router::new().route("/search", get(get_search))
+}
+
+async fn get_search(
+    extract::Tx(mut tx): extract::Tx,
+    auth_user: AuthUser,
+    Query(query): Query<SearchQuery>,
+) -> ResponseResult<HtmfResponse> {
+    let term = query.q.trim();
+    let results = if term.is_empty() {
+        None
+    } else {
+        Some(
+            db::bookmarks::search_trusted(
+                &mut tx,
+                term,
+                auth_user.ap_user_id,
+                query.include_trusted_of_trusted,
+                SEARCH_LIMIT,
+            )
+            .await?,
+        )
+    };
+
+    Ok(HtmfResponse(views::search::view(&views::search::Data {
+        layout: layout::Template::from_db(&mut tx, Some(&auth_user)).await?,
+        query,
+        results,
+    })))
+}
@@ -0,0 +1,746 @@
+use anyhow::Context;
+use axum::{
+    Router,
+    body::Bytes,
+    extract::{DefaultBodyLimit, Multipart, State},
+    response::{IntoResponse, Redirect, Response},
+    routing::{get, post},
+};
+use garde::{Report, Validate};
+use htmf::element::Element;
+use tower_sessions::Session;
+
+use crate::{
+    authentication::{self, AuthUser},
+    db::{
+        self, AppTx,
+        images::{ImageKind, MAX_IMAGE_SIZE},
+    },
+    extract::{self, qs_form::QsForm},
+    federation,
+    form_errors::FormErrors,
+    forms::{
+        ap_users::{ActorReference, MAX_ALIASES, UpdateApUser},
+        users::{ChangePassword, ChangeUsername, EditEmail, TotpCode},
+    },
+    htmf_response::HtmfResponse,
+    oidc,
+    response_error::{ResponseResult, into_option},
+    server::AppState,
+    totp,
+    views::{self, layout},
+};
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/settings/profile",
+            get(get_profile)
+                .post(post_profile)
+                // Leave some room for the text fields next to both images
+                .layer(DefaultBodyLimit::max(3 * MAX_IMAGE_SIZE)),
+        )
+        .route("/settings/migration", get(get_migration))
+        .route("/settings/migration/aliases", post(post_add_alias))
+        .route(
+            "/settings/migration/aliases/remove",
+            post(post_remove_alias),
+        )
+        .route("/settings/migration/move", post(post_move))
+        .route("/settings/account", get(get_account))
+        .route("/settings/account/password", post(post_password))
+        .route("/settings/account/email", post(post_email))
+        .route("/settings/account/username", post(post_username))
+        .route("/settings/account/oidc/link", get(get_link_oidc))
+        .route("/settings/account/oidc/unlink", post(post_unlink_oidc))
+        .route("/settings/account/totp", get(get_totp).post(post_totp))
+        .route("/settings/account/totp/disable", post(post_disable_totp))
+}
+
+async fn get_profile(
+    extract::Tx(mut tx): extract::Tx,
+    auth_user: AuthUser,
+) -> ResponseResult<HtmfResponse> {
+    let ap_user = db::ap_users::read_by_id(&mut tx, auth_user.ap_user_id).await?;
+
+    Ok(HtmfResponse(views::profile_settings::view(
+        &views::profile_settings::Data {
+            layout: layout::Template::from_db(&mut tx, Some(&auth_user)).await?,
+            input: UpdateApUser {
+                display_name: ap_user.display_name,
+                bio: ap_user.bio,
+            },
+            errors: FormErrors::default(),
+        },
+    )))
+}
+
+struct ImageUpload {
+    kind: ImageKind,
+    content_type: &'static str,
+    data: Bytes,
+}
+
+async fn post_profile(
+    extract::Tx(mut tx): extract::Tx,
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+    federation_data: federation::Data,
+    mut multipart: Multipart,
+) -> ResponseResult<Response> {
+    let mut input = UpdateApUser::default();
+    let mut uploads = Vec::new();
+    let mut errors = Report::new();
+
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .context("Failed to read form field")?
+    {
+        let name = field.name().unwrap_or_default().to_string();
+        let kind = match name.as_str() {
+            "display_name" => {
+                input.display_name = non_empty(field.text().await.context("Invalid text")?);
+                continue;
+            }
+            "bio" => {
+                input.bio = non_empty(field.text().await.context("Invalid text")?);
+                continue;
+            }
+            "avatar" => ImageKind::Avatar,
+            "header" => ImageKind::Header,
+            _ => continue,
+        };
+
+        let data = field.bytes().await.context("Failed to read image")?;
+        // Browsers send an empty file if no image was selected
+        if data.is_empty() {
+            continue;
+        }
+        if data.len() > MAX_IMAGE_SIZE {
+            errors.append(
+                garde::Path::new(kind.as_str()),
+                garde::Error::new("Image can be at most 2 MiB large"),
+            );
+            continue;
+        }
+        let Some(content_type) = db::images::detect_content_type(&data) else {
+            errors.append(
+                garde::Path::new(kind.as_str()),
+                garde::Error::new("Only PNG, JPEG, GIF and WebP images are supported"),
+            );
+            continue;
+        };
+        uploads.push(ImageUpload {
+            kind,
+            content_type,
+            data,
+        });
+    }
+
+    if let Err(report) = input.validate() {
+        for (path, error) in report.into_inner() {
+            errors.append(path, error);
+        }
+    }
+
+    if !errors.is_empty() {
+        return Ok(HtmfResponse(views::profile_settings::view(
+            &views::profile_settings::Data {
+                layout: layout::Template::from_db(&mut tx, Some(&auth_user)).await?,
+                input,
+                errors: errors.into(),
+            },
+        ))
+        .into_response());
+    }
+
+    let ap_user = db::ap_users::update(&mut tx, auth_user.ap_user_id, input).await?;
+    for upload in uploads {
+        let id = db::images::replace(
+            &mut tx,
+            ap_user.id,
+            upload.kind,
+            upload.content_type,
+            &upload.data,
+        )
+        .await?;
+        let url = db::images::url(&state.base_url, id)?;
+        match upload.kind {
+            ImageKind::Avatar => db::ap_users::set_avatar_url(&mut tx, ap_user.id, &url).await?,
+            ImageKind::Header => db::ap_users::set_header_url(&mut tx, ap_user.id, &url).await?,
+        }
+    }
+
+    tx.commit().await?;
+
+    federation::UpdatePerson::send_to_followers(&ap_user, &federation_data).await?;
+
+    Ok(Redirect::to(&format!("/user/{}", ap_user.username)).into_response())
+}
+
+fn non_empty(text: String) -> Option<String> {
+    let text = text.trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+async fn get_migration(
+    extract::Tx(mut tx): extract::Tx,
+    auth_user: AuthUser,
+) -> ResponseResult<HtmfResponse> {
+    Ok(HtmfResponse(
+        migration_view(
+            &mut tx,
+            &auth_user,
+            ActorReference::default(),
+            FormErrors::default(),
+            ActorReference::default(),
+            FormErrors::default(),
+        )
+        .await?,
+    ))
+}
+
+async fn migration_view(
+    tx: &mut AppTx,
+    auth_user: &AuthUser,
+    alias_input: ActorReference,
+    alias_errors: FormErrors,
+    move_input: ActorReference,
+    move_errors: FormErrors,
+) -> ResponseResult<Element> {
+    Ok(views::migration_settings::view(
+        &views::migration_settings::Data {
+            layout: layout::Template::from_db(tx, Some(auth_user)).await?,
+            ap_user: db::ap_users::read_by_id(tx, auth_user.ap_user_id).await?,
+            alias_input,
+            alias_errors,
+            move_input,
+            move_errors,
+        },
+    ))
+}
+
+/// Add another account as an alias, allowing it to move to this account.
+async fn post_add_alias(
+    extract::Tx(mut tx): extract::Tx,
+    auth_user: AuthUser,
+    federation_data: federation::Data,
+    QsForm(input): QsForm<ActorReference>,
+) -> ResponseResult<Response> {
+    let ap_user = db::ap_users::read_by_id(&mut tx, auth_user.ap_user_id).await?;
+
+    let mut errors = Report::new();
+    if let Err(report) = input.validate() {
+        errors = report;
+    } else if ap_user.also_known_as.len() >= MAX_ALIASES {
+        errors.append(
+            garde::Path::new("actor"),
+            garde::Error::new(format!("You can add at most {MAX_ALIASES} aliases")),
+        );
+    }
+
+    let alias = if errors.is_empty() {
+        resolve_actor(&input, &federation_data, &mut errors).await
+    } else {
+        None
+    };
+    let Some(alias) = alias else {
+        return Ok(HtmfResponse(
+            migration_view(
+                &mut tx,
+                &auth_user,
+                input,
+                errors.into(),
+                ActorReference::default(),
+                FormErrors::default(),
+            )
+            .await?,
+        )
+        .into_response());
+    };
+
+    let mut also_known_as = ap_user.also_known_as;
+    if !also_known_as.contains(alias.ap_id.inner()) {
+        also_known_as.push(alias.ap_id.into_inner());
+    }
+    let ap_user = db::ap_users::set_also_known_as(&mut tx, ap_user.id, &also_known_as).await?;
+
+    tx.commit().await?;
+
+    federation::UpdatePerson::send_to_followers(&ap_user, &federation_data).await?;
+
+    Ok(Redirect::to("/settings/migration").into_response())
+}
+
+async fn post_remove_alias(
+    extract::Tx(mut tx): extract::Tx,
+    auth_user: AuthUser,
+    federation_data: federation::Data,
+    QsForm(input): QsForm<ActorReference>,
+) -> ResponseResult<Redirect> {
+    let ap_user = db::ap_users::read_by_id(&mut tx, auth_user.ap_user_id).await?;
+
+    let also_known_as = ap_user
+        .also_known_as
+        .into_iter()
+        .filter(|alias| alias.as_str() != input.actor)
+        .collect::<Vec<_>>();
+    let ap_user = db::ap_users::set_also_known_as(&mut tx, ap_user.id, &also_known_as).await?;
+
+    tx.commit().await?;
+
+    federation::UpdatePerson::send_to_followers(&ap_user, &federation_data).await?;
+
+    Ok(Redirect::to("/settings/migration"))
+}
+
+/// Move to a different account, taking all followers along.
+async fn post_move(
+    extract::Tx(mut tx): extract::Tx,
+    auth_user: AuthUser,
+    federation_data: federation::Data,
+    QsForm(input): QsForm<ActorReference>,
+) -> ResponseResult<Response> {
+    let ap_user = db::ap_users::read_by_id(&mut tx, auth_user.ap_user_id).await?;
+
+    let mut errors = input.validate().err().unwrap_or_else(Report::new);
+    let target = if errors.is_empty() {
+        resolve_actor(&input, &federation_data, &mut errors).await
+    } else {
+        None
+    };
+
+    // Make sure we know about recently added aliases
+    let target = match target {
+        Some(target) if !target.ap_id.is_local(&federation_data) => {
+            Some(target.ap_id.dereference_forced(&federation_data).await?)
+        }
+        other => other,
+    };
+
+    if let Some(target) = &target {
+        if target.id == ap_user.id {
+            errors.append(
+                garde::Path::new("actor"),
+                garde::Error::new("You can't move to the same account"),
+            );
+        } else if !target.also_known_as.contains(ap_user.ap_id.inner()) {
+            errors.append(
+                garde::Path::new("actor"),
+                garde::Error::new(format!(
+                    "Add {} as an alias to the new account first",
+                    ap_user.ap_id.inner()
+                )),
+            );
+        }
+    }
+
+    let Some(target) = target.filter(|_| errors.is_empty()) else {
+        return Ok(HtmfResponse(
+            migration_view(
+                &mut tx,
+                &auth_user,
+                ActorReference::default(),
+                FormErrors::default(),
+                input,
+                errors.into(),
+            )
+            .await?,
+        )
+        .into_response());
+    };
+
+    let ap_user = db::ap_users::set_moved_to(&mut tx, ap_user.id, target.ap_id.inner()).await?;
+    tx.commit().await?;
+
+    // Local followers don't receive the `Move`, so follow the new account for them
+    federation::move_actor::move_local_followers(&ap_user, &target, &federation_data).await?;
+    federation::Move::send_to_followers(&ap_user, &target, &federation_data).await?;
+
+    Ok(Redirect::to("/settings/migration").into_response())
+}
+
+/// Look up the account referenced in a form, adding a form error if it can't
+/// be found.
+async fn resolve_actor(
+    input: &ActorReference,
+    federation_data: &federation::Data,
+    errors: &mut Report,
+) -> Option<db::ApUser> {
+    match federation::webfinger::resolve_actor(&input.actor, federation_data).await {
+        Ok(actor) => Some(actor),
+        Err(e) => {
+            tracing::debug!("{e:?}");
+            errors.append(
+                garde::Path::new("actor"),
+                garde::Error::new("Could not find this account"),
+            );
+            None
+        }
+    }
+}
+
+async fn get_account(
+    extract::Tx(mut tx): extract::Tx,
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+) -> ResponseResult<HtmfResponse> {
+    let data = account_data(&mut tx, &auth_user, &state).await?;
+
+    Ok(HtmfResponse(views::account_settings::view(&data)))
+}
+
+/// Everything the account settings page shows, with empty forms.
+async fn account_data(
+    tx: &mut AppTx,
+    auth_user: &AuthUser,
+    state: &AppState,
+) -> ResponseResult<views::account_settings::Data> {
+    let user = db::users::by_id(tx, auth_user.user_id).await?;
+
+    Ok(views::account_settings::Data {
+        layout: layout::Template::from_db(tx, Some(auth_user)).await?,
+        oidc_name: state
+            .oidc_state
+            .clone()
+            .get_config()
+            .map(|config| config.name),
+        password_errors: FormErrors::default(),
+        email_input: EditEmail {
+            email: user.email.clone().unwrap_or_default(),
+        },
+        email_errors: FormErrors::default(),
+        username_input: ChangeUsername {
+            username: user.username.clone(),
+        },
+        username_errors: FormErrors::default(),
+        oidc_errors: FormErrors::default(),
+        recovery_codes_left: db::recovery_codes::count(tx, user.id).await?,
+        totp_errors: FormErrors::default(),
+        user,
+    })
+}
+
+fn form_error(path: &str, message: &str) -> FormErrors {
+    let mut errors = Report::new();
+    errors.append(garde::Path::new(path), garde::Error::new(message));
+    errors.into()
+}
+
+/// Changing the password logs out all other sessions, and this one continues
+/// under a new session id.
+async fn post_password(
+    extract::Tx(mut tx): extract::Tx,
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+    session: Session,
+    QsForm(input): QsForm<ChangePassword>,
+) -> ResponseResult<Response> {
+    let user = db::users::by_id(&mut tx, auth_user.user_id).await?;
+
+    let mut errors = input.validate().err().unwrap_or_else(Report::new);
+    // Users that only logged in via OIDC so far can set a password directly
+    if user.password_hash.is_some() {
+        let current_password = input.current_password.as_deref().unwrap_or_default();
+        if authentication::verify_password(&user, current_password).is_err() {
+            errors.append(
+                garde::Path::new("current_password"),
+                garde::Error::new("Password not correct"),
+            );
+        }
+    }
+
+    if !errors.is_empty() {
+        let mut data = account_data(&mut tx, &auth_user, &state).await?;
+        data.password_errors = errors.into();
+        return Ok(HtmfResponse(views::account_settings::view(&data)).into_response());
+    }
+
+    let user = db::users::set_password(&mut tx, user.id, &input.new_password).await?;
+    tx.commit().await?;
+
+    session
+        .cycle_id()
+        .await
+        .context("Failed to cycle session id")?;
+    AuthUser::save_in_session(&session, &user).await?;
+
+    Ok(Redirect::to("/settings/account").into_response())
+}
+
+async fn post_email(
+    extract::Tx(mut tx): extract::Tx,
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+    QsForm(input): QsForm<EditEmail>,
+) -> ResponseResult<Response> {
+    let input = EditEmail {
+        email: input.email.trim().to_string(),
+    };
+    if let Err(errors) = input.validate() {
+        let mut data = account_data(&mut tx, &auth_user, &state).await?;
+        data.email_input = input;
+        data.email_errors = errors.into();
+        return Ok(HtmfResponse(views::account_settings::view(&data)).into_response());
+    }
+
+    let email = (!input.email.is_empty()).then_some(input.email.as_str());
+    db::users::set_email(&mut tx, auth_user.user_id, email).await?;
+    tx.commit().await?;
+
+    Ok(Redirect::to("/settings/account").into_response())
+}
+
+/// Change the username of both the local user and its actor. The actor keeps
+/// its `ap_id`, so remote followers stay, and they learn about the new handle
+/// through an `Update`.
+async fn post_username(
+    extract::Tx(mut tx): extract::Tx,
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+    federation_data: federation::Data,
+    QsForm(input): QsForm<ChangeUsername>,
+) -> ResponseResult<Response> {
+    let user = db::users::by_id(&mut tx, auth_user.user_id).await?;
+
+    let mut errors = input.validate().err().unwrap_or_else(Report::new);
+    if errors.is_empty() {
+        let settings = db::registrations::read_settings(&mut tx).await?;
+        // Allow changing only the case of the current username, or going back
+        // to a previous one
+        let is_own_username = input.username.to_lowercase() == user.username.to_lowercase()
+            || db::users::had_username(&mut tx, user.id, &input.username).await?;
+        if let Some(error) = settings.username_error(&input.username) {
+            errors.append(garde::Path::new("username"), garde::Error::new(error));
+        } else if !is_own_username
+            && db::registrations::is_username_taken(&mut tx, &input.username).await?
+        {
+            errors.append(
+                garde::Path::new("username"),
+                garde::Error::new("This username is taken"),
+            );
+        }
+    }
+
+    if !errors.is_empty() {
+        let mut data = account_data(&mut tx, &auth_user, &state).await?;
+        data.username_input = input;
+        data.username_errors = errors.into();
+        return Ok(HtmfResponse(views::account_settings::view(&data)).into_response());
+    }
+
+    db::users::set_username(&mut tx, user.id, &input.username).await?;
+    let ap_user =
+        db::ap_users::set_username(&mut tx, auth_user.ap_user_id, &input.username).await?;
+    tx.commit().await?;
+
+    federation::UpdatePerson::send_to_followers(&ap_user, &federation_data).await?;
+
+    Ok(Redirect::to("/settings/account").into_response())
+}
+
+async fn get_link_oidc(
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+    session: Session,
+) -> ResponseResult<Redirect> {
+    let oidc_config = state
+        .oidc_state
+        .get_config()
+        .context("OIDC client not configured")?;
+    let attempt = oidc::LoginAttempt::for_linking(&oidc_config.client, auth_user.user_id);
+    let authorize_url = attempt.authorize_url.clone();
+    attempt.save_in_session(&session).await?;
+
+    Ok(Redirect::to(authorize_url.as_str()))
+}
+
+/// Finish linking an OIDC identity after the provider redirected back to us.
+pub async fn link_oidc(
+    mut tx: AppTx,
+    auth_user: &AuthUser,
+    state: &AppState,
+    oidc_info: oidc::AuthenticatedOidcUserInfo,
+) -> ResponseResult<Response> {
+    let linked_user = into_option(db::users::by_oidc_id(&mut tx, &oidc_info.oidc_id).await)?;
+    if linked_user.is_some_and(|linked_user| linked_user.id != auth_user.user_id) {
+        let mut data = account_data(&mut tx, auth_user, state).await?;
+        data.oidc_errors = form_error("oidc", "This login is already linked to another account");
+        return Ok(HtmfResponse(views::account_settings::view(&data)).into_response());
+    }
+
+    let user = db::users::set_oidc_id(&mut tx, auth_user.user_id, Some(&oidc_info.oidc_id)).await?;
+    if user.email.is_none() {
+        db::users::set_email(&mut tx, user.id, Some(&oidc_info.email)).await?;
+    }
+    tx.commit().await?;
+
+    Ok(Redirect::to("/settings/account").into_response())
+}
+
+async fn post_unlink_oidc(
+    extract::Tx(mut tx): extract::Tx,
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+) -> ResponseResult<Response> {
+    let user = db::users::by_id(&mut tx, auth_user.user_id).await?;
+    if user.password_hash.is_none() {
+        let mut data = account_data(&mut tx, &auth_user, &state).await?;
+        data.oidc_errors = form_error(
+            "oidc",
+            "Set a password first, otherwise you can't log in anymore",
+        );
+        return Ok(HtmfResponse(views::account_settings::view(&data)).into_response());
+    }
+
+    db::users::set_oidc_id(&mut tx, user.id, None).await?;
+    tx.commit().await?;
+
+    Ok(Redirect::to("/settings/account").into_response())
+}
+
+async fn get_totp(
+    extract::Tx(mut tx): extract::Tx,
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+    session: Session,
+) -> ResponseResult<Response> {
+    let user = db::users::by_id(&mut tx, auth_user.user_id).await?;
+    if user.totp_secret.is_some() {
+        return Ok(Redirect::to("/settings/account").into_response());
+    }
+
+    // Keep the secret when reloading the page, so an already scanned QR code
+    // stays valid
+    let secret = match totp::Enrollment::from_session(&session).await? {
+        Some(enrollment) => enrollment.secret,
+        None => totp::generate_secret(),
+    };
+    totp::Enrollment {
+        secret: secret.clone(),
+    }
+    .save_in_session(&session)
+    .await?;
+
+    Ok(HtmfResponse(
+        enroll_view(
+            &mut tx,
+            &auth_user,
+            &state,
+            &user,
+            secret,
+            FormErrors::default(),
+        )
+        .await?,
+    )
+    .into_response())
+}
+
+async fn enroll_view(
+    tx: &mut AppTx,
+    auth_user: &AuthUser,
+    state: &AppState,
+    user: &db::User,
+    secret: String,
+    errors: FormErrors,
+) -> ResponseResult<Element> {
+    Ok(views::totp_settings::view(&views::totp_settings::Data {
+        layout: layout::Template::from_db(tx, Some(auth_user)).await?,
+        step: views::totp_settings::Step::Enroll {
+            qr_code: totp::qr_code_data_url(&secret, &user.username, &state.base_url)?,
+            secret,
+            errors,
+        },
+    }))
+}
+
+/// Enable two-factor authentication once the user entered a valid code for
+/// the secret shown to them.
+async fn post_totp(
+    extract::Tx(mut tx): extract::Tx,
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+    session: Session,
+    QsForm(input): QsForm<TotpCode>,
+) -> ResponseResult<Response> {
+    let user = db::users::by_id(&mut tx, auth_user.user_id).await?;
+    let Some(enrollment) = totp::Enrollment::from_session(&session)
+        .await?
+        .filter(|_| user.totp_secret.is_none())
+    else {
+        return Ok(Redirect::to("/settings/account/totp").into_response());
+    };
+
+    let mut errors = input.validate().err().unwrap_or_else(Report::new);
+    if errors.is_empty()
+        && totp::check_code(
+            &enrollment.secret,
+            &user.username,
+            &state.base_url,
+            &input.code,
+            None,
+        )?
+        .is_none()
+    {
+        errors.append(
+            garde::Path::new("code"),
+            garde::Error::new("Code not correct"),
+        );
+    }
+    if !errors.is_empty() {
+        return Ok(HtmfResponse(
+            enroll_view(
+                &mut tx,
+                &auth_user,
+                &state,
+                &user,
+                enrollment.secret,
+                errors.into(),
+            )
+            .await?,
+        )
+        .into_response());
+    }
+
+    db::users::set_totp_secret(&mut tx, user.id, Some(&enrollment.secret)).await?;
+    let recovery_codes = totp::generate_recovery_codes();
+    db::recovery_codes::replace(&mut tx, user.id, &recovery_codes).await?;
+    let layout = layout::Template::from_db(&mut tx, Some(&auth_user)).await?;
+    tx.commit().await?;
+
+    totp::Enrollment::remove_from_session(&session).await?;
+
+    Ok(
+        HtmfResponse(views::totp_settings::view(&views::totp_settings::Data {
+            layout,
+            step: views::totp_settings::Step::RecoveryCodes(recovery_codes),
+        }))
+        .into_response(),
+    )
+}
+
+async fn post_disable_totp(
+    extract::Tx(mut tx): extract::Tx,
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+    QsForm(input): QsForm<TotpCode>,
+) -> ResponseResult<Response> {
+    let user = db::users::by_id(&mut tx, auth_user.user_id).await?;
+
+    let verified = input.validate().is_ok()
+        && authentication::verify_second_factor(&mut tx, &user, &state.base_url, &input.code)
+            .await
+            .is_ok();
+    if !verified {
+        let mut data = account_data(&mut tx, &auth_user, &state).await?;
+        data.totp_errors = form_error("code", "Code not correct");
+        return Ok(HtmfResponse(views::account_settings::view(&data)).into_response());
+    }
+
+    db::users::set_totp_secret(&mut tx, user.id, None).await?;
+    db::recovery_codes::delete_all(&mut tx, user.id).await?;
+    tx.commit().await?;
+
+    Ok(Redirect::to("/settings/account").into_response())
+}
@@ -0,0 +1,34 @@
+use axum::{Router, extract::Path, routing::get};
+
+use crate::{
+    authentication::AuthUser,
+    db, extract,
+    forms::bookmarks::parse_tag,
+    htmf_response::HtmfResponse,
+    response_error::{ResponseError, ResponseResult},
+    server::AppState,
+    views::{self, layout},
+};
+
+const TAG_LIMIT: i64 = 100;
+
+pub fn router() -> Router<AppState> {
+    Router::new().route("/tags/{tag}", get(get_tag))
+}
+
+/// Public bookmarks with a hashtag, also linked from the hashtags we send to
+/// other instances.
+async fn get_tag(
+    extract::Tx(mut tx): extract::Tx,
+    auth_user: Option<AuthUser>,
+    Path(tag): Path<String>,
+) -> ResponseResult<HtmfResponse> {
+    let tag = parse_tag(&tag).ok_or(ResponseError::NotFound)?;
+    let bookmarks = db::bookmarks::list_public_by_tag(&mut tx, &tag, TAG_LIMIT).await?;
+
+    Ok(HtmfResponse(views::tag::view(&views::tag::Data {
+        layout: layout::Template::from_db(&mut tx, auth_user.as_ref()).await?,
+        tag,
+        bookmarks,
+    })))
+}
@@ -10,27 +10,44 @@ use serde::Deserialize;
 use tower_sessions::Session;
 
 use crate::{
-    authentication::{self, AuthUser},
-    db,
-    extract::{self, qs_form::QsForm},
-    forms::users::{CreateOidcUser, Login, OidcLoginQuery, OidcSelectUsername},
+    authentication::{self, AuthUser, LoginStep},
+    db::{self, AppTx, registrations::RegistrationMode},
+    extract::{
+        self,
+        accept::{Accept, negotiated},
+        qs_form::QsForm,
+        signed_fetch::SignedFetch,
+    },
+    federation,
+    form_errors::FormErrors,
+    forms::users::{
+        CreateOidcUser, CreateUser, Login, OidcLoginQuery, OidcSelectUsername, SignUp, TotpLogin,
+    },
     htmf_response::HtmfResponse,
     oidc::{self},
     response_error::{ResponseError, ResponseResult},
+    routes,
     server::AppState,
+    totp,
     views::{self, layout, login, oidc_select_username, users::ProfileTemplate},
 };
 
 pub fn router() -> Router<AppState> {
     Router::new()
         .route("/login", get(get_login).post(post_login))
+        .route("/login/totp", post(post_login_totp))
         .route("/login_oidc_redirect", get(get_login_oidc_redirect))
         .route("/login_oidc_redirect", post(post_login_oidc_redirect))
         .route("/login_oidc", get(get_login_oidc))
         .route("/login_demo", post(post_login_demo))
+        .route("/signup", get(get_signup).post(post_signup))
         .route("/logout", post(logout))
         .route("/start", get(get_start_page))
         .route("/user/{username}", get(get_profile))
+        .route("/user/{username}/block", post(post_block))
+        .route("/user/{username}/unblock", post(post_unblock))
+        .route("/user/{username}/trust", post(post_trust))
+        .route("/user/{username}/untrust", post(post_untrust))
 }
 
 async fn post_login(
@@ -43,12 +60,25 @@ async fn post_login(
         return Ok(HtmfResponse(login::login(&login::Template::new(
             errors,
             input,
-            state.oidc_state,
+            state.oidc_state.clone(),
+            registration_mode(&mut tx, &state).await? != RegistrationMode::Closed,
         )))
         .into_response());
     }
 
     let logged_in = authentication::login(&mut tx, session, &input.credentials).await;
+    if let Ok(LoginStep::SecondFactorRequired) = logged_in {
+        return Ok(
+            HtmfResponse(views::login_totp::view(&views::login_totp::Data {
+                input: TotpLogin {
+                    previous_uri: input.previous_uri,
+                    code: String::new(),
+                },
+                errors: FormErrors::default(),
+            }))
+            .into_response(),
+        );
+    }
     if let Err(e) = logged_in {
         tracing::debug!("{e:?}");
         let mut errors = Report::new();
@@ -59,7 +89,8 @@ async fn post_login(
         return Ok(HtmfResponse(login::login(&login::Template::new(
             errors,
             input,
-            state.oidc_state,
+            state.oidc_state.clone(),
+            registration_mode(&mut tx, &state).await? != RegistrationMode::Closed,
         )))
         .into_response());
     }
@@ -69,6 +100,52 @@ async fn post_login(
     Ok(Redirect::to(redirect_to.as_str()).into_response())
 }
 
+async fn post_login_totp(
+    extract::Tx(mut tx): extract::Tx,
+    session: Session,
+    State(state): State<AppState>,
+    QsForm(input): QsForm<TotpLogin>,
+) -> ResponseResult<Response> {
+    // The password step was skipped or the session expired
+    if totp::PendingLogin::from_session(&session).await?.is_none() {
+        return Ok(Redirect::to("/login").into_response());
+    }
+
+    let mut errors = input.validate().err().unwrap_or_else(Report::new);
+    if errors.is_empty() {
+        let logged_in =
+            authentication::login_second_factor(&mut tx, &session, &state.base_url, &input.code)
+                .await;
+        if let Err(e) = logged_in {
+            tracing::debug!("{e:?}");
+            errors.append(
+                garde::Path::new("code"),
+                garde::Error::new("Code not correct"),
+            );
+        }
+    }
+
+    if !errors.is_empty() {
+        return Ok(
+            HtmfResponse(views::login_totp::view(&views::login_totp::Data {
+                input: TotpLogin {
+                    code: String::new(),
+                    ..input
+                },
+                errors: errors.into(),
+            }))
+            .into_response(),
+        );
+    }
+
+    // Persist used up recovery codes
+    tx.commit().await?;
+
+    let redirect_to = input.previous_uri.unwrap_or(state.base_url);
+
+    Ok(Redirect::to(redirect_to.as_str()).into_response())
+}
+
 async fn get_login_oidc(
     State(state): State<AppState>,
     session: Session,
@@ -90,6 +167,7 @@ async fn get_login_oidc_redirect(
     session: Session,
     Query(query): Query<OidcLoginQuery>,
     state: State<AppState>,
+    auth_user: Option<AuthUser>,
     extract::Tx(mut tx): extract::Tx,
 ) -> ResponseResult<Response> {
     let oidc_config = state
@@ -99,6 +177,7 @@ async fn get_login_oidc_redirect(
         .context("OIDC not configured")?;
 
     let oidc_session: oidc::LoginAttempt = oidc::LoginAttempt::from_session(&session).await?;
+    let link_to_user = oidc_session.link_to_user;
     let authed_oidc_info = oidc_session
         .login(
             &oidc_config.client,
@@ -108,6 +187,13 @@ async fn get_login_oidc_redirect(
         )
         .await?;
 
+    if let Some(user_id) = link_to_user {
+        let auth_user = auth_user
+            .filter(|auth_user| auth_user.user_id == user_id)
+            .ok_or(ResponseError::NotAuthenticated)?;
+        return routes::settings::link_oidc(tx, &auth_user, &state, authed_oidc_info).await;
+    }
+
     let existing_user = db::users::by_oidc_id(&mut tx, &authed_oidc_info.oidc_id).await;
     match existing_user {
         // Authenticate existing users in session
@@ -187,6 +273,7 @@ struct LoginQuery {
 // TODO: redirect to homepage if already logged in
 // https://github.com/raffomania/linkblocks/issues/177
 async fn get_login(
+    extract::Tx(mut tx): extract::Tx,
     Query(query): Query<LoginQuery>,
     State(state): State<AppState>,
 ) -> ResponseResult<Response> {
@@ -204,12 +291,135 @@ async fn get_login(
                 previous_uri,
                 ..Default::default()
             },
-            state.oidc_state,
+            state.oidc_state.clone(),
+            registration_mode(&mut tx, &state).await? != RegistrationMode::Closed,
         )))
         .into_response())
     }
 }
 
+#[derive(Deserialize)]
+struct SignUpQuery {
+    /// Prefill the form when following a link from an invite
+    invite_code: Option<String>,
+}
+
+async fn get_signup(
+    extract::Tx(mut tx): extract::Tx,
+    Query(query): Query<SignUpQuery>,
+    State(state): State<AppState>,
+) -> ResponseResult<HtmfResponse> {
+    Ok(HtmfResponse(views::signup::view(&views::signup::Data {
+        registration_mode: registration_mode(&mut tx, &state).await?,
+        input: SignUp {
+            invite_code: query.invite_code,
+            ..Default::default()
+        },
+        errors: FormErrors::default(),
+    })))
+}
+
+async fn post_signup(
+    extract::Tx(mut tx): extract::Tx,
+    session: Session,
+    State(state): State<AppState>,
+    QsForm(input): QsForm<SignUp>,
+) -> ResponseResult<Response> {
+    let registration_mode = registration_mode(&mut tx, &state).await?;
+    if registration_mode == RegistrationMode::Closed {
+        return Err(ResponseError::NotFound);
+    }
+
+    let settings = db::registrations::read_settings(&mut tx).await?;
+    let mut errors = input.validate().err().unwrap_or_else(Report::new);
+    if let Some(error) = settings.username_error(&input.user.username) {
+        errors.append(garde::Path::new("user.username"), garde::Error::new(error));
+    }
+    if db::registrations::is_username_taken(&mut tx, &input.user.username).await? {
+        errors.append(
+            garde::Path::new("user.username"),
+            garde::Error::new("This username is taken"),
+        );
+    }
+    let invite_code = input.invite_code.clone().unwrap_or_default();
+    if registration_mode == RegistrationMode::Invite
+        && !db::registrations::is_invite_unused(&mut tx, &invite_code).await?
+    {
+        errors.append(
+            garde::Path::new("invite_code"),
+            garde::Error::new("This invite code is not valid"),
+        );
+    }
+
+    if !errors.is_empty() {
+        return Ok(signup_with_errors(registration_mode, input, errors));
+    }
+
+    if registration_mode == RegistrationMode::Approval {
+        db::registrations::insert_request(
+            &mut tx,
+            db::registrations::InsertRequest {
+                password_hash: authentication::hash_password(&input.user.password)?,
+                username: input.user.username,
+                reason: input.reason.unwrap_or_default(),
+            },
+        )
+        .await?;
+        tx.commit().await?;
+
+        return Ok(HtmfResponse(views::signup::view_pending()).into_response());
+    }
+
+    let user = db::users::insert(&mut tx, input.user.clone(), &state.base_url).await?;
+    // Someone else might have used the invite since we checked it. Dropping
+    // the transaction undoes creating the user.
+    if registration_mode == RegistrationMode::Invite
+        && !db::registrations::redeem_invite(&mut tx, &invite_code, user.id).await?
+    {
+        let mut errors = Report::new();
+        errors.append(
+            garde::Path::new("invite_code"),
+            garde::Error::new("This invite code is not valid"),
+        );
+        return Ok(signup_with_errors(registration_mode, input, errors));
+    }
+    AuthUser::save_in_session(&session, &user).await?;
+    tx.commit().await?;
+
+    Ok(Redirect::to("/").into_response())
+}
+
+fn signup_with_errors(
+    registration_mode: RegistrationMode,
+    input: SignUp,
+    errors: Report,
+) -> Response {
+    HtmfResponse(views::signup::view(&views::signup::Data {
+        registration_mode,
+        input: SignUp {
+            user: CreateUser {
+                username: input.user.username,
+                // Never render the password we got from the user
+                password: String::new(),
+            },
+            ..input
+        },
+        errors: errors.into(),
+    }))
+    .into_response()
+}
+
+/// Registrations are always closed in demo mode, which has its own way of
+/// creating users.
+async fn registration_mode(tx: &mut AppTx, state: &AppState) -> ResponseResult<RegistrationMode> {
+    if state.demo_mode {
+        return Ok(RegistrationMode::Closed);
+    }
+    Ok(db::registrations::read_settings(tx)
+        .await?
+        .registration_mode)
+}
+
 async fn get_start_page(
     extract::Tx(mut tx): extract::Tx,
     auth_user: AuthUser,
@@ -223,39 +433,238 @@ async fn get_start_page(
     })))
 }
 
-// TODO: set this route as @url in activitypub person objects
-// https://www.w3.org/TR/activitystreams-vocabulary/#dfn-url
-// https://github.com/raffomania/linkblocks/issues/150
+/// How many bookmarks to show on the profile of a remote user
+const REMOTE_BOOKMARKS_LIMIT: i64 = 50;
+
+/// Show a user's profile to browsers, or serve local users as JSON to other
+/// instances.
 async fn get_profile(
     extract::Tx(mut tx): extract::Tx,
+    auth_user: Option<AuthUser>,
+    accept: Accept,
+    signed_fetch: SignedFetch,
+    federation_data: federation::Data,
     Path(handle): Path<String>,
     State(state): State<AppState>,
-) -> ResponseResult<HtmfResponse> {
-    let layout = layout::Template::from_db(&mut tx, None).await?;
-
+) -> ResponseResult<Response> {
     let ap_user = db::ap_users::read_by_username(
         &mut tx,
         crate::federation::webfinger::Resource::parse_handle(&handle, &state.base_url)?,
     )
     .await?;
     let maybe_user = db::users::by_ap_user_id(&mut tx, ap_user.id).await?;
-    let public_lists = if let Some(user) = maybe_user {
+
+    if accept == Accept::ActivityJson {
+        if maybe_user.is_none() {
+            return Err(ResponseError::NotFound);
+        }
+        signed_fetch.verify(&federation_data).await?;
+        return routes::federation::person_response(ap_user, accept, &federation_data).await;
+    }
+
+    let layout = layout::Template::from_db(&mut tx, auth_user.as_ref()).await?;
+    let public_lists = if let Some(user) = &maybe_user {
         db::lists::list_public_by_user(&mut tx, user.ap_user_id).await?
     } else {
         Vec::new()
     };
+    // Show what remote users share on their own instance
+    let remote_content = if ap_user.ap_id.is_local(&federation_data) {
+        None
+    } else {
+        federation::collections::refresh_remote_profile(&ap_user, &federation_data).await?;
+        Some(views::profile::RemoteContent {
+            lists: db::remote_lists::list_by_ap_user(&mut tx, ap_user.id).await?,
+            bookmarks: db::bookmarks::list_public_by_ap_user(
+                &mut tx,
+                ap_user.id,
+                REMOTE_BOOKMARKS_LIMIT,
+            )
+            .await?,
+        })
+    };
+
+    // Only remote users can be blocked
+    let blocked = match (&auth_user, &maybe_user) {
+        (Some(auth_user), None) => {
+            Some(db::actor_blocks::exists(&mut tx, auth_user.ap_user_id, ap_user.id).await?)
+        }
+        _ => None,
+    };
+    // Users can trust anyone but themselves
+    let trusted = match &auth_user {
+        Some(auth_user) if auth_user.ap_user_id != ap_user.id => {
+            Some(db::trusts::exists(&mut tx, auth_user.ap_user_id, ap_user.id).await?)
+        }
+        _ => None,
+    };
+    let editable = auth_user.is_some_and(|auth_user| auth_user.ap_user_id == ap_user.id);
 
     let elem = views::profile::view(
         tx,
         &views::profile::Data {
             layout,
+            handle,
             ap_user,
             public_lists,
+            remote_content,
+            blocked,
+            trusted,
+            editable,
+        },
+    )
+    .await?;
+
+    Ok(negotiated(HtmfResponse(elem)))
+}
+
+/// Block a remote user, removing all follows between them and the current
+/// user and dropping any further activities they send to the current user.
+async fn post_block(
+    extract::Tx(mut tx): extract::Tx,
+    auth_user: AuthUser,
+    Path(handle): Path<String>,
+    State(state): State<AppState>,
+) -> ResponseResult<Redirect> {
+    let ap_user = read_remote_user(&mut tx, &handle, &state).await?;
+
+    db::actor_blocks::insert(
+        &mut tx,
+        db::actor_blocks::Insert {
+            blocker_id: auth_user.ap_user_id,
+            blocked_id: ap_user.id,
         },
     )
     .await?;
+    db::follows::remove(
+        &mut tx,
+        db::follows::Insert {
+            follower_id: ap_user.id,
+            following_id: auth_user.ap_user_id,
+        },
+    )
+    .await?;
+    db::follows::remove(
+        &mut tx,
+        db::follows::Insert {
+            follower_id: auth_user.ap_user_id,
+            following_id: ap_user.id,
+        },
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(Redirect::to(&format!("/user/{handle}")))
+}
+
+async fn post_unblock(
+    extract::Tx(mut tx): extract::Tx,
+    auth_user: AuthUser,
+    Path(handle): Path<String>,
+    State(state): State<AppState>,
+) -> ResponseResult<Redirect> {
+    let ap_user = read_remote_user(&mut tx, &handle, &state).await?;
+
+    db::actor_blocks::remove(
+        &mut tx,
+        db::actor_blocks::Insert {
+            blocker_id: auth_user.ap_user_id,
+            blocked_id: ap_user.id,
+        },
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(Redirect::to(&format!("/user/{handle}")))
+}
+
+/// Trust a local or remote user, including their public bookmarks in the
+/// current user's searches.
+async fn post_trust(
+    extract::Tx(mut tx): extract::Tx,
+    auth_user: AuthUser,
+    federation_data: federation::Data,
+    Path(handle): Path<String>,
+    State(state): State<AppState>,
+) -> ResponseResult<Redirect> {
+    let ap_user = read_other_user(&mut tx, &auth_user, &handle, &state).await?;
+
+    db::trusts::insert(
+        &mut tx,
+        db::trusts::Insert {
+            truster_id: auth_user.ap_user_id,
+            trusted_id: ap_user.id,
+        },
+    )
+    .await?;
+    tx.commit().await?;
+
+    federation::trust::follow_trusted(&ap_user, &federation_data).await?;
+
+    Ok(Redirect::to(&format!("/user/{handle}")))
+}
+
+async fn post_untrust(
+    extract::Tx(mut tx): extract::Tx,
+    auth_user: AuthUser,
+    federation_data: federation::Data,
+    Path(handle): Path<String>,
+    State(state): State<AppState>,
+) -> ResponseResult<Redirect> {
+    let ap_user = read_other_user(&mut tx, &auth_user, &handle, &state).await?;
+
+    db::trusts::remove(
+        &mut tx,
+        db::trusts::Insert {
+            truster_id: auth_user.ap_user_id,
+            trusted_id: ap_user.id,
+        },
+    )
+    .await?;
+    tx.commit().await?;
+
+    federation::trust::unfollow_untrusted(&ap_user, &federation_data).await?;
+
+    Ok(Redirect::to(&format!("/user/{handle}")))
+}
+
+async fn read_other_user(
+    tx: &mut AppTx,
+    auth_user: &AuthUser,
+    handle: &str,
+    state: &AppState,
+) -> ResponseResult<db::ApUser> {
+    let ap_user = db::ap_users::read_by_username(
+        tx,
+        crate::federation::webfinger::Resource::parse_handle(handle, &state.base_url)?,
+    )
+    .await?;
+
+    if ap_user.id == auth_user.ap_user_id {
+        return Err(ResponseError::NotFound);
+    }
+
+    Ok(ap_user)
+}
+
+async fn read_remote_user(
+    tx: &mut AppTx,
+    handle: &str,
+    state: &AppState,
+) -> ResponseResult<db::ApUser> {
+    let ap_user = db::ap_users::read_by_username(
+        tx,
+        crate::federation::webfinger::Resource::parse_handle(handle, &state.base_url)?,
+    )
+    .await?;
+
+    if db::users::by_ap_user_id(tx, ap_user.id).await?.is_some() {
+        return Err(ResponseError::NotFound);
+    }
 
-    Ok(HtmfResponse(elem))
+    Ok(ap_user)
 }
 
 async fn logout(auth_user: AuthUser) -> ResponseResult<Redirect> {
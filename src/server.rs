@@ -23,6 +23,8 @@ pub struct AppState {
     pub base_url: Url,
     pub demo_mode: bool,
     pub oidc_state: oidc::State,
+    /// How long to keep entries in the federation debug log
+    pub activity_log_retention: time::Duration,
     pub federation_config: FederationConfig<federation::Context>,
 }
 
@@ -35,6 +37,17 @@ pub async fn app(state: AppState) -> anyhow::Result<Router> {
             .continuously_delete_expired(tokio::time::Duration::from_secs(6 * 60 * 60)),
     );
 
+    tokio::task::spawn(federation::delivery::periodically_deliver(
+        state.federation_config.clone(),
+    ));
+    tokio::task::spawn(federation::activity_log::periodically_prune(
+        state.pool.clone(),
+        state.activity_log_retention,
+    ));
+    tokio::task::spawn(federation::refresh::periodically_refresh_remote_actors(
+        state.federation_config.clone(),
+    ));
+
     if state.demo_mode {
         tokio::task::spawn(periodically_wipe_all_data(state.pool.clone()));
     }
@@ -54,14 +67,20 @@ pub async fn app(state: AppState) -> anyhow::Result<Router> {
 
     Ok(Router::new()
         .merge(routes::users::router())
+        .merge(routes::admin::router())
+        .merge(routes::notifications::router())
+        .merge(routes::reports::router())
+        .merge(routes::search::router())
+        .merge(routes::settings::router())
+        .merge(routes::tags::router())
+        .merge(routes::discover::router())
+        .merge(routes::images::router())
         .merge(routes::index::router())
         .merge(routes::lists::router())
         .merge(routes::bookmarks::router())
         .merge(routes::links::router())
         .merge(routes::federation::router())
         .merge(routes::assets::router().with_state(()))
-        // TODO add layer to use the same URL for AP and HTML
-        // this should simplify things and be more error tolerant for other services
         .layer(
             ServiceBuilder::new()
                 .layer(TraceLayer::new_for_http())
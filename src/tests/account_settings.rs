@@ -0,0 +1,184 @@
+use std::collections::HashMap;
+
+use axum::http::StatusCode;
+
+use crate::{
+    db::{self, ap_users},
+    forms::users::CreateUser,
+    tests::util::test_app::TestApp,
+};
+
+#[test_log::test(tokio::test)]
+async fn can_change_password() -> anyhow::Result<()> {
+    let mut app = TestApp::new().await;
+    app.create_user("alice", "alicepassword").await;
+    app.login_user("alice", "alicepassword").await;
+    let other_session = app.logged_in_cookie.take();
+    app.login_user("alice", "alicepassword").await;
+
+    let settings = app.req().get("/settings/account").await.test_page().await;
+    assert_eq!(
+        settings.dom.find("input[name='current_password']").length(),
+        1
+    );
+
+    let wrong_password = app
+        .req()
+        .post(
+            "/settings/account/password",
+            &HashMap::from([
+                ("current_password", "wrongpassword"),
+                ("new_password", "newpassword"),
+            ]),
+        )
+        .await
+        .test_page()
+        .await;
+    assert!(wrong_password.dom.text().contains("Password not correct"));
+
+    let response = app
+        .req()
+        .expect_status(StatusCode::SEE_OTHER)
+        .post(
+            "/settings/account/password",
+            &HashMap::from([
+                ("current_password", "alicepassword"),
+                ("new_password", "newpassword"),
+            ]),
+        )
+        .await;
+
+    // This session continues under a new id, all others are logged out
+    let cookie = response.headers().get("Set-Cookie").unwrap();
+    let cookie = cookie.to_str().unwrap().split_once(';').unwrap().0;
+    app.logged_in_cookie = Some(cookie.to_string());
+    app.req().get("/settings/account").await.test_page().await;
+    app.logged_in_cookie = other_session;
+    app.req()
+        .expect_status(StatusCode::SEE_OTHER)
+        .get("/settings/account")
+        .await;
+
+    app.logged_in_cookie = None;
+    app.login_user("alice", "newpassword").await;
+
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn can_set_email() -> anyhow::Result<()> {
+    let mut app = TestApp::new().await;
+    let user = app.create_test_user().await;
+    app.login_test_user().await;
+
+    let invalid = app
+        .req()
+        .post(
+            "/settings/account/email",
+            &HashMap::from([("email", "not an email")]),
+        )
+        .await
+        .test_page()
+        .await;
+    assert_eq!(
+        invalid
+            .dom
+            .find("input[name='email']")
+            .attr("value")
+            .map(|value| value.to_string()),
+        Some("not an email".to_string())
+    );
+
+    app.req()
+        .expect_status(StatusCode::SEE_OTHER)
+        .post(
+            "/settings/account/email",
+            &HashMap::from([("email", " test@example.com ")]),
+        )
+        .await;
+    let mut tx = app.tx().await;
+    let email = db::users::by_id(&mut tx, user.id).await?.email;
+    drop(tx);
+    assert_eq!(email.as_deref(), Some("test@example.com"));
+
+    app.req()
+        .expect_status(StatusCode::SEE_OTHER)
+        .post("/settings/account/email", &HashMap::from([("email", "")]))
+        .await;
+    let mut tx = app.tx().await;
+    assert_eq!(db::users::by_id(&mut tx, user.id).await?.email, None);
+
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn can_change_username() -> anyhow::Result<()> {
+    let mut app = TestApp::new().await;
+    let user = app.create_user("alice", "alicepassword").await;
+    let mut tx = app.tx().await;
+    db::users::insert(
+        &mut tx,
+        CreateUser {
+            username: "bob".to_string(),
+            password: "bobpassword".to_string(),
+        },
+        &app.base_url,
+    )
+    .await?;
+    tx.commit().await?;
+    app.login_user("alice", "alicepassword").await;
+
+    let taken = app
+        .req()
+        .post(
+            "/settings/account/username",
+            &HashMap::from([("username", "Bob")]),
+        )
+        .await
+        .test_page()
+        .await;
+    assert!(taken.dom.text().contains("This username is taken"));
+
+    // Changing only the case of the own username is fine
+    app.req()
+        .expect_status(StatusCode::SEE_OTHER)
+        .post(
+            "/settings/account/username",
+            &HashMap::from([("username", "Alice")]),
+        )
+        .await;
+    app.req()
+        .expect_status(StatusCode::SEE_OTHER)
+        .post(
+            "/settings/account/username",
+            &HashMap::from([("username", "carol")]),
+        )
+        .await;
+
+    let mut tx = app.tx().await;
+    let user = db::users::by_id(&mut tx, user.id).await?;
+    let ap_user = ap_users::read_by_id(&mut tx, user.ap_user_id).await?;
+    drop(tx);
+    assert_eq!(user.username, "carol");
+    assert_eq!(ap_user.username, "carol");
+
+    // Nobody else can take the previous username
+    let mut tx = app.tx().await;
+    assert!(db::registrations::is_username_taken(&mut tx, "alice").await?);
+    drop(tx);
+
+    app.req().get("/user/carol").await;
+    app.logged_in_cookie = None;
+    app.login_user("carol", "alicepassword").await;
+
+    // But the user can go back to it
+    app.req()
+        .expect_status(StatusCode::SEE_OTHER)
+        .post(
+            "/settings/account/username",
+            &HashMap::from([("username", "alice")]),
+        )
+        .await;
+
+    Ok(())
+}
@@ -0,0 +1,223 @@
+use std::collections::HashMap;
+
+use axum::http::StatusCode;
+
+use crate::{
+    db::{self, bookmarks::InsertBookmark, domain_blocks::Severity, users::Role},
+    forms::{domain_blocks::CreateDomainBlock, users::CreateUser},
+    tests::util::test_app::TestApp,
+};
+
+#[test_log::test(tokio::test)]
+async fn can_block_and_unblock_domain() -> anyhow::Result<()> {
+    let mut app = TestApp::new().await;
+    app.create_test_user().await;
+    app.login_test_user().await;
+
+    app.req()
+        .get("/admin/domain_blocks")
+        .await
+        .test_page()
+        .await;
+
+    let input = CreateDomainBlock {
+        domain: " Example.com ".to_string(),
+        severity: Severity::Silence,
+        reason: Some("Spam".to_string()),
+    };
+    app.req()
+        .expect_status(StatusCode::SEE_OTHER)
+        .post("/admin/domain_blocks", &input)
+        .await;
+
+    let mut tx = app.tx().await;
+    let blocks = db::domain_blocks::list(&mut tx).await?;
+    drop(tx);
+    assert_eq!(blocks.len(), 1);
+    let block = &blocks[0];
+    assert_eq!(block.domain, "example.com");
+    assert_eq!(block.severity, Severity::Silence);
+
+    let subdomain_url = "https://social.example.com/users/test".parse()?;
+    assert!(block.matches(&subdomain_url));
+
+    app.req()
+        .expect_status(StatusCode::SEE_OTHER)
+        .post(
+            &format!("/admin/domain_blocks/{}/delete", block.id),
+            &HashMap::<String, String>::new(),
+        )
+        .await;
+
+    let mut tx = app.tx().await;
+    assert!(db::domain_blocks::list(&mut tx).await?.is_empty());
+
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn admin_pages_require_admin_role() -> anyhow::Result<()> {
+    let mut app = TestApp::new().await;
+    let user = app.create_test_user().await;
+    let mut tx = app.tx().await;
+    db::users::set_role(&mut tx, user.id, db::users::Role::User).await?;
+    tx.commit().await?;
+    app.login_test_user().await;
+
+    app.req()
+        .expect_status(StatusCode::NOT_FOUND)
+        .get("/admin/domain_blocks")
+        .await;
+    app.req()
+        .expect_status(StatusCode::NOT_FOUND)
+        .get("/admin/users")
+        .await;
+
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn can_retry_failed_delivery() -> anyhow::Result<()> {
+    let mut app = TestApp::new().await;
+    let user = app.create_test_user().await;
+    app.login_test_user().await;
+
+    let mut tx = app.tx().await;
+    let delivery = db::deliveries::insert(
+        &mut tx,
+        db::deliveries::Insert {
+            actor_id: user.ap_user_id,
+            activity_ap_id: "https://example.com/activity".parse()?,
+            activity: serde_json::json!({}),
+            inbox_url: "https://example.com/inbox".parse()?,
+        },
+        time::OffsetDateTime::now_utc(),
+    )
+    .await?;
+    db::deliveries::mark_attempt_failed(&mut tx, &delivery, "Gone", None).await?;
+    tx.commit().await?;
+
+    let mut tx = app.tx().await;
+    assert_eq!(db::deliveries::list_failed(&mut tx, 10).await?.len(), 1);
+    drop(tx);
+
+    app.req().get("/admin/deliveries").await.test_page().await;
+
+    app.req()
+        .expect_status(StatusCode::SEE_OTHER)
+        .post(
+            &format!("/admin/deliveries/{}/retry", delivery.id),
+            &HashMap::<String, String>::new(),
+        )
+        .await;
+
+    let mut tx = app.tx().await;
+    assert!(db::deliveries::list_failed(&mut tx, 10).await?.is_empty());
+    let pending = db::deliveries::list_pending(&mut tx, 10).await?;
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].attempts, 0);
+
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn can_manage_users() -> anyhow::Result<()> {
+    let mut app = TestApp::new().await;
+    app.create_test_user().await;
+    app.login_test_user().await;
+
+    let mut tx = app.tx().await;
+    let user = db::users::insert(
+        &mut tx,
+        CreateUser {
+            username: "someone".to_string(),
+            password: "somepassword".to_string(),
+        },
+        &app.base_url,
+    )
+    .await?;
+    let bookmark = db::bookmarks::insert_local(
+        &mut tx,
+        user.ap_user_id,
+        InsertBookmark {
+            url: "https://example.com".to_string(),
+            title: "Example".to_string(),
+        },
+        &app.base_url,
+    )
+    .await?;
+    tx.commit().await?;
+
+    let overview = app.req().get("/admin").await.test_page().await;
+    assert!(overview.dom.text().contains("Users"));
+    app.req().get("/admin/instances").await.test_page().await;
+
+    let users = app.req().get("/admin/users").await.test_page().await;
+    assert!(users.dom.text().contains("1 bookmarks, 0 lists"));
+
+    let no_input = HashMap::<String, String>::new();
+    for action in ["suspend", "make_admin"] {
+        app.req()
+            .expect_status(StatusCode::SEE_OTHER)
+            .post(&format!("/admin/users/{}/{action}", user.id), &no_input)
+            .await;
+    }
+    let mut tx = app.tx().await;
+    assert!(db::reports::is_suspended(&mut tx, user.ap_user_id).await?);
+    assert_eq!(db::users::by_id(&mut tx, user.id).await?.role, Role::Admin);
+    drop(tx);
+
+    app.req()
+        .expect_status(StatusCode::SEE_OTHER)
+        .post(&format!("/admin/users/{}/unsuspend", user.id), &no_input)
+        .await;
+    let reset = app
+        .req()
+        .post(
+            &format!("/admin/users/{}/reset_password", user.id),
+            &no_input,
+        )
+        .await
+        .test_page()
+        .await;
+    let new_password = reset.dom.find(".font-mono").text();
+    assert_eq!(new_password.len(), 32);
+
+    let admin_cookie = app.logged_in_cookie.take();
+    app.login_user("someone", &new_password).await;
+    app.logged_in_cookie = admin_cookie;
+
+    app.req()
+        .expect_status(StatusCode::SEE_OTHER)
+        .post(&format!("/admin/users/{}/delete", user.id), &no_input)
+        .await;
+    let mut tx = app.tx().await;
+    assert!(db::users::by_id(&mut tx, user.id).await.is_err());
+    assert!(db::bookmarks::by_id(&mut tx, bookmark.id).await.is_err());
+
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn admins_cannot_lock_themselves_out() -> anyhow::Result<()> {
+    let mut app = TestApp::new().await;
+    let admin = app.create_test_user().await;
+    app.login_test_user().await;
+
+    for action in ["suspend", "remove_admin", "delete"] {
+        app.req()
+            .expect_status(StatusCode::UNAUTHORIZED)
+            .post(
+                &format!("/admin/users/{}/{action}", admin.id),
+                &HashMap::<String, String>::new(),
+            )
+            .await;
+    }
+
+    let mut tx = app.tx().await;
+    let admin = db::users::by_id(&mut tx, admin.id).await?;
+    assert_eq!(admin.role, Role::Admin);
+    assert!(!db::reports::is_suspended(&mut tx, admin.ap_user_id).await?);
+
+    Ok(())
+}
@@ -1,12 +1,31 @@
-use activitypub_federation::fetch::webfinger::webfinger_resolve_actor;
+use std::collections::HashMap;
+
+use activitypub_federation::{
+    fetch::webfinger::webfinger_resolve_actor,
+    http_signatures::generate_actor_keypair,
+    kinds::activity::{AnnounceType, LikeType, UndoType},
+};
 use anyhow::Result;
-use axum::http::StatusCode;
+use axum::http::{StatusCode, header};
+use url::Url;
 
 use crate::{
-    db::{self, bookmarks::InsertBookmark},
-    federation::{self, webfinger},
-    forms::users::{Credentials, Login},
-    tests::util::test_app::TestApp,
+    db::{
+        self, activity_log::Direction, bookmarks::InsertBookmark, domain_blocks::Severity,
+        links::LinkDestinationWithChildren,
+    },
+    federation::{self, person::ActorKind, webfinger},
+    forms::{
+        activity_log::ActivityLogQuery,
+        ap_users::UpdateApUser,
+        domain_blocks::CreateDomainBlock,
+        links::CreateLink,
+        lists::CreateList,
+        reports::CreateReport,
+        users::{Credentials, Login},
+    },
+    response_error::{ResponseResult, into_option},
+    tests::util::{test_app::TestApp, two_instances::TwoInstances},
 };
 
 #[test_log::test(tokio::test)]
@@ -80,18 +99,7 @@ async fn can_resolve_bookmark() -> Result<()> {
     let app_b = TestApp::new().await;
 
     let user = app_a.create_test_user().await;
-    let mut tx = app_a.pool.begin().await?;
-    let bookmark = db::bookmarks::insert_local(
-        &mut tx,
-        user.ap_user_id,
-        InsertBookmark {
-            url: "https://www.rafa.ee".to_string(),
-            title: "Test Bookmark".to_string(),
-        },
-        &app_a.base_url,
-    )
-    .await?;
-    tx.commit().await?;
+    let bookmark = app_a.insert_public_bookmark(&user, "Test Bookmark").await;
 
     app_a.serve().await;
     let ap_cx_b = app_b.state.federation_config.to_request_data();
@@ -170,3 +178,2088 @@ async fn can_follow_undo_follow() -> Result<()> {
 
     Ok(())
 }
+
+#[test_log::test(tokio::test)]
+async fn can_receive_bookmark_via_shared_inbox() -> Result<()> {
+    let app_a = TestApp::new().await;
+    let user_a = app_a.create_test_user().await;
+    let mut tx_a = app_a.tx().await;
+    let ap_user_a = db::ap_users::read_by_id(&mut tx_a, user_a.ap_user_id).await?;
+    drop(tx_a);
+
+    let app_b = TestApp::new().await;
+    let user_b = app_b.create_test_user().await;
+    let mut tx_b = app_b.tx().await;
+    let ap_user_b = db::ap_users::read_by_id(&mut tx_b, user_b.ap_user_id).await?;
+    drop(tx_b);
+
+    app_a.serve().await;
+    app_b.serve().await;
+    let ap_cx_a = app_a.state.federation_config.to_request_data();
+    let ap_cx_b = app_b.state.federation_config.to_request_data();
+
+    // Instance B should learn about the shared inbox of instance A
+    let remote_ap_user_a = ap_user_a.ap_id.dereference(&ap_cx_b).await?;
+    assert_eq!(
+        remote_ap_user_a.shared_inbox_url,
+        Some(app_a.base_url.join("/ap/inbox")?)
+    );
+
+    // Instance A records the follow once B accepts it
+    federation::Follow::new(&ap_user_a, &ap_user_b, &ap_cx_a)?
+        .send(&ap_user_a, &ap_user_b, &ap_cx_a)
+        .await?;
+    let mut tx_a = app_a.tx().await;
+    let local_ap_user_b = db::ap_users::read_by_ap_id(&mut tx_a, ap_user_b.ap_id.inner()).await?;
+    let local_followers = db::ap_users::list_local_followers(&mut tx_a, local_ap_user_b.id).await?;
+    drop(tx_a);
+    assert_eq!(local_followers.len(), 1);
+
+    let mut tx_b = app_b.tx().await;
+    let bookmark = db::bookmarks::insert_local(
+        &mut tx_b,
+        user_b.ap_user_id,
+        InsertBookmark {
+            url: "https://www.rafa.ee".to_string(),
+            title: "Test Bookmark".to_string(),
+        },
+        &app_b.base_url,
+    )
+    .await?;
+    tx_b.commit().await?;
+
+    federation::CreateBookmark::send_to_followers(&ap_user_b, bookmark.clone(), &ap_cx_b).await?;
+
+    let mut tx_a = app_a.tx().await;
+    let received_bookmark = db::bookmarks::by_ap_id(&mut tx_a, bookmark.ap_id).await?;
+    assert_eq!(received_bookmark.url, bookmark.url);
+    assert_eq!(received_bookmark.ap_user_id, local_ap_user_b.id);
+
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn serves_nodeinfo() -> Result<()> {
+    let mut app = TestApp::new().await;
+    let user = app.create_test_user().await;
+    let mut tx = app.tx().await;
+    db::bookmarks::insert_local(
+        &mut tx,
+        user.ap_user_id,
+        InsertBookmark {
+            url: "https://www.rafa.ee".to_string(),
+            title: "Test Bookmark".to_string(),
+        },
+        &app.base_url,
+    )
+    .await?;
+    tx.commit().await?;
+
+    let discovery = app.req().get("/.well-known/nodeinfo").await.json().await;
+    assert_eq!(
+        discovery["links"][0]["href"],
+        app.base_url.join("/nodeinfo/2.1")?.as_str()
+    );
+
+    let nodeinfo = app.req().get("/nodeinfo/2.1").await.json().await;
+    assert_eq!(nodeinfo["version"], "2.1");
+    assert_eq!(nodeinfo["software"]["name"], "linkblocks");
+    assert_eq!(nodeinfo["protocols"][0], "activitypub");
+    assert_eq!(nodeinfo["usage"]["users"]["total"], 1);
+    assert_eq!(nodeinfo["usage"]["localPosts"], 1);
+    assert_eq!(nodeinfo["openRegistrations"], false);
+
+    let nodeinfo = app.req().get("/nodeinfo/2.0").await.json().await;
+    assert_eq!(nodeinfo["version"], "2.0");
+    assert!(nodeinfo["software"].get("repository").is_none());
+
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn can_resolve_instance_actor() -> Result<()> {
+    let app_a = TestApp::new().await;
+    let mut tx_a = app_a.tx().await;
+    let instance_actor = db::ap_users::read_instance_actor(&mut tx_a, &app_a.base_url).await?;
+    drop(tx_a);
+    app_a.serve().await;
+
+    let app_b = TestApp::new().await;
+    let ap_cx_b = app_b.state.federation_config.to_request_data();
+
+    let remote_instance_actor = instance_actor.ap_id.dereference(&ap_cx_b).await?;
+
+    assert_eq!(remote_instance_actor.kind, ActorKind::Application);
+    assert_eq!(remote_instance_actor.username, instance_actor.username);
+
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn domain_block_prevents_delivery() -> Result<()> {
+    let app_a = TestApp::new().await;
+    let user_a = app_a.create_test_user().await;
+    let mut tx_a = app_a.tx().await;
+    let ap_user_a = db::ap_users::read_by_id(&mut tx_a, user_a.ap_user_id).await?;
+
+    let app_b = TestApp::new().await;
+    let user_b = app_b.create_test_user().await;
+    let mut tx_b = app_b.tx().await;
+    let ap_user_b = db::ap_users::read_by_id(&mut tx_b, user_b.ap_user_id).await?;
+    drop(tx_b);
+
+    db::domain_blocks::upsert(
+        &mut tx_a,
+        CreateDomainBlock {
+            domain: db::domain_blocks::url_domain(&app_b.base_url).unwrap(),
+            severity: Severity::Reject,
+            reason: None,
+        },
+    )
+    .await?;
+    tx_a.commit().await?;
+
+    app_a.serve().await;
+    app_b.serve().await;
+    let ap_cx_a = app_a.state.federation_config.to_request_data();
+
+    // Instance A refuses to fetch anything from instance B
+    assert!(ap_user_b.ap_id.dereference(&ap_cx_a).await.is_err());
+
+    // Instance A doesn't deliver activities to instance B
+    federation::Follow::new(&ap_user_a, &ap_user_b, &ap_cx_a)?
+        .send(&ap_user_a, &ap_user_b, &ap_cx_a)
+        .await?;
+
+    let mut tx_b = app_b.tx().await;
+    let followers = db::ap_users::list_followers(&mut tx_b, user_b.ap_user_id).await?;
+    assert!(followers.is_empty());
+
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn domain_block_applies_to_all_ports() -> Result<()> {
+    let app_a = TestApp::new().await;
+
+    let app_b = TestApp::new().await;
+    let user_b = app_b.create_test_user().await;
+    let mut tx_b = app_b.tx().await;
+    let ap_user_b = db::ap_users::read_by_id(&mut tx_b, user_b.ap_user_id).await?;
+    drop(tx_b);
+
+    let mut tx_a = app_a.tx().await;
+    db::domain_blocks::upsert(
+        &mut tx_a,
+        CreateDomainBlock {
+            domain: app_b.base_url.host_str().unwrap().to_string(),
+            severity: Severity::Reject,
+            reason: None,
+        },
+    )
+    .await?;
+    tx_a.commit().await?;
+
+    app_a.serve().await;
+    app_b.serve().await;
+    let ap_cx_a = app_a.state.federation_config.to_request_data();
+
+    assert!(ap_user_b.ap_id.dereference(&ap_cx_a).await.is_err());
+
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn blocked_actor_cannot_follow() -> Result<()> {
+    let app_a = TestApp::new().await;
+    let user_a = app_a.create_test_user().await;
+    let mut tx_a = app_a.tx().await;
+    let ap_user_a = db::ap_users::read_by_id(&mut tx_a, user_a.ap_user_id).await?;
+    drop(tx_a);
+
+    let app_b = TestApp::new().await;
+    let user_b = app_b.create_test_user().await;
+    let mut tx_b = app_b.tx().await;
+    let ap_user_b = db::ap_users::read_by_id(&mut tx_b, user_b.ap_user_id).await?;
+    drop(tx_b);
+
+    app_a.serve().await;
+    app_b.serve().await;
+    let ap_cx_a = app_a.state.federation_config.to_request_data();
+    let ap_cx_b = app_b.state.federation_config.to_request_data();
+
+    let remote_ap_user_a = ap_user_a.ap_id.dereference(&ap_cx_b).await?;
+    let mut tx_b = app_b.tx().await;
+    db::actor_blocks::insert(
+        &mut tx_b,
+        db::actor_blocks::Insert {
+            blocker_id: user_b.ap_user_id,
+            blocked_id: remote_ap_user_a.id,
+        },
+    )
+    .await?;
+    tx_b.commit().await?;
+
+    federation::Follow::new(&ap_user_a, &ap_user_b, &ap_cx_a)?
+        .send(&ap_user_a, &ap_user_b, &ap_cx_a)
+        .await?;
+
+    let mut tx_b = app_b.tx().await;
+    let followers = db::ap_users::list_followers(&mut tx_b, user_b.ap_user_id).await?;
+    assert!(followers.is_empty());
+
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn can_approve_follow_requests() -> Result<()> {
+    let app_a = TestApp::new().await;
+    let user_a = app_a.create_test_user().await;
+    let mut tx_a = app_a.tx().await;
+    let ap_user_a = db::ap_users::read_by_id(&mut tx_a, user_a.ap_user_id).await?;
+    drop(tx_a);
+
+    let mut app_b = TestApp::new().await;
+    let user_b = app_b.create_test_user().await;
+    let mut tx_b = app_b.tx().await;
+    let ap_user_b =
+        db::ap_users::set_manually_approves_followers(&mut tx_b, user_b.ap_user_id, true).await?;
+    tx_b.commit().await?;
+
+    app_a.serve().await;
+    app_b.serve().await;
+    let ap_cx_a = app_a.state.federation_config.to_request_data();
+
+    // Locked accounts are advertised as such
+    let remote_ap_user_b = ap_user_b.ap_id.dereference(&ap_cx_a).await?;
+    assert!(remote_ap_user_b.manually_approves_followers);
+
+    federation::Follow::new(&ap_user_a, &ap_user_b, &ap_cx_a)?
+        .send(&ap_user_a, &ap_user_b, &ap_cx_a)
+        .await?;
+
+    // The follow is pending until B approves it
+    let mut tx_b = app_b.tx().await;
+    assert!(
+        db::ap_users::list_followers(&mut tx_b, user_b.ap_user_id)
+            .await?
+            .is_empty()
+    );
+    let pending = db::ap_users::list_pending_followers(&mut tx_b, user_b.ap_user_id).await?;
+    drop(tx_b);
+    assert_eq!(pending.len(), 1);
+
+    app_b.login_test_user().await;
+    app_b
+        .req()
+        .expect_status(StatusCode::SEE_OTHER)
+        .post(
+            &format!("/notifications/follow_requests/{}/accept", pending[0].id),
+            &HashMap::<String, String>::new(),
+        )
+        .await;
+
+    let mut tx_b = app_b.tx().await;
+    let followers = db::ap_users::list_followers(&mut tx_b, user_b.ap_user_id).await?;
+    drop(tx_b);
+    assert_eq!(followers.len(), 1);
+
+    // Instance A learns about the accepted follow
+    let mut tx_a = app_a.tx().await;
+    let local_followers =
+        db::ap_users::list_local_followers(&mut tx_a, remote_ap_user_b.id).await?;
+    assert_eq!(local_followers.len(), 1);
+
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn unlocking_accounts_accepts_pending_follow_requests() -> Result<()> {
+    let TwoInstances {
+        app_a,
+        ap_user_a,
+        mut app_b,
+        user_b,
+        ap_user_b,
+        ..
+    } = TwoInstances::serve().await?;
+    app_b.login_test_user().await;
+    let ap_cx_a = app_a.state.federation_config.to_request_data();
+
+    let mut tx_b = app_b.tx().await;
+    db::ap_users::set_manually_approves_followers(&mut tx_b, user_b.ap_user_id, true).await?;
+    tx_b.commit().await?;
+
+    federation::Follow::new(&ap_user_a, &ap_user_b, &ap_cx_a)?
+        .send(&ap_user_a, &ap_user_b, &ap_cx_a)
+        .await?;
+
+    app_b
+        .req()
+        .expect_status(StatusCode::SEE_OTHER)
+        .post(
+            "/notifications/manually_approves_followers",
+            &HashMap::from([("manually_approves_followers", "false")]),
+        )
+        .await;
+
+    let mut tx_b = app_b.tx().await;
+    assert!(
+        db::ap_users::list_pending_followers(&mut tx_b, user_b.ap_user_id)
+            .await?
+            .is_empty()
+    );
+    let followers = db::ap_users::list_followers(&mut tx_b, user_b.ap_user_id).await?;
+    drop(tx_b);
+    assert_eq!(followers.len(), 1);
+
+    // Instance A learns about the accepted follow
+    let mut tx_a = app_a.tx().await;
+    let remote_ap_user_b = db::ap_users::read_by_ap_id(&mut tx_a, ap_user_b.ap_id.inner()).await?;
+    let local_followers =
+        db::ap_users::list_local_followers(&mut tx_a, remote_ap_user_b.id).await?;
+    assert_eq!(local_followers.len(), 1);
+
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn profile_updates_reach_followers() -> Result<()> {
+    let app_a = TestApp::new().await;
+    let user_a = app_a.create_test_user().await;
+    let mut tx_a = app_a.tx().await;
+    let ap_user_a = db::ap_users::read_by_id(&mut tx_a, user_a.ap_user_id).await?;
+    drop(tx_a);
+
+    let app_b = TestApp::new().await;
+    let user_b = app_b.create_test_user().await;
+    let mut tx_b = app_b.tx().await;
+    let ap_user_b = db::ap_users::read_by_id(&mut tx_b, user_b.ap_user_id).await?;
+    drop(tx_b);
+
+    app_a.serve().await;
+    app_b.serve().await;
+    let ap_cx_a = app_a.state.federation_config.to_request_data();
+    let ap_cx_b = app_b.state.federation_config.to_request_data();
+
+    federation::Follow::new(&ap_user_a, &ap_user_b, &ap_cx_a)?
+        .send(&ap_user_a, &ap_user_b, &ap_cx_a)
+        .await?;
+
+    let mut tx_b = app_b.tx().await;
+    let ap_user_b = db::ap_users::update(
+        &mut tx_b,
+        ap_user_b.id,
+        UpdateApUser {
+            display_name: Some("Test B".to_string()),
+            bio: Some("Updated bio".to_string()),
+        },
+    )
+    .await?;
+    tx_b.commit().await?;
+
+    federation::UpdatePerson::send_to_followers(&ap_user_b, &ap_cx_b).await?;
+
+    let mut tx_a = app_a.tx().await;
+    let remote_ap_user_b = db::ap_users::read_by_ap_id(&mut tx_a, ap_user_b.ap_id.inner()).await?;
+    assert_eq!(remote_ap_user_b.display_name.as_deref(), Some("Test B"));
+    assert_eq!(remote_ap_user_b.bio.as_deref(), Some("Updated bio"));
+
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn refetches_actor_after_key_rotation() -> Result<()> {
+    let app_a = TestApp::new().await;
+    let user_a = app_a.create_test_user().await;
+    let mut tx_a = app_a.tx().await;
+    let ap_user_a = db::ap_users::read_by_id(&mut tx_a, user_a.ap_user_id).await?;
+    drop(tx_a);
+
+    let app_b = TestApp::new().await;
+    let user_b = app_b.create_test_user().await;
+    let mut tx_b = app_b.tx().await;
+    let ap_user_b = db::ap_users::read_by_id(&mut tx_b, user_b.ap_user_id).await?;
+    drop(tx_b);
+
+    app_a.serve().await;
+    app_b.serve().await;
+    let ap_cx_a = app_a.state.federation_config.to_request_data();
+    let ap_cx_b = app_b.state.federation_config.to_request_data();
+
+    // Instance B remembers the old key of user A, fetched long enough ago to
+    // be fetched again
+    let remote_ap_user_a = ap_user_a.ap_id.dereference(&ap_cx_b).await?;
+    sqlx::query("update ap_users set last_refreshed_at = now() - interval '1 hour' where id = $1")
+        .bind(remote_ap_user_a.id)
+        .execute(&app_b.pool)
+        .await?;
+
+    let keypair = generate_actor_keypair()?;
+    sqlx::query("update ap_users set public_key = $1, private_key = $2 where id = $3")
+        .bind(&keypair.public_key)
+        .bind(&keypair.private_key)
+        .bind(ap_user_a.id)
+        .execute(&app_a.pool)
+        .await?;
+    let mut tx_a = app_a.tx().await;
+    let ap_user_a = db::ap_users::read_by_id(&mut tx_a, user_a.ap_user_id).await?;
+    drop(tx_a);
+
+    federation::Follow::new(&ap_user_a, &ap_user_b, &ap_cx_a)?
+        .send(&ap_user_a, &ap_user_b, &ap_cx_a)
+        .await?;
+
+    let mut tx_b = app_b.tx().await;
+    let followers = db::ap_users::list_followers(&mut tx_b, user_b.ap_user_id).await?;
+    assert_eq!(followers.len(), 1);
+    assert_eq!(followers[0].public_key, keypair.public_key);
+
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn refreshes_stale_remote_actors() -> Result<()> {
+    let app_a = TestApp::new().await;
+    let user_a = app_a.create_test_user().await;
+    let mut tx_a = app_a.tx().await;
+    let ap_user_a = db::ap_users::read_by_id(&mut tx_a, user_a.ap_user_id).await?;
+    drop(tx_a);
+
+    let app_b = TestApp::new().await;
+
+    app_a.serve().await;
+    app_b.serve().await;
+    let ap_cx_b = app_b.state.federation_config.to_request_data();
+
+    let remote_ap_user_a = ap_user_a.ap_id.dereference(&ap_cx_b).await?;
+
+    // Change the profile without telling instance B about it
+    let mut tx_a = app_a.tx().await;
+    db::ap_users::update(
+        &mut tx_a,
+        ap_user_a.id,
+        UpdateApUser {
+            display_name: Some("Refreshed".to_string()),
+            bio: None,
+        },
+    )
+    .await?;
+    tx_a.commit().await?;
+
+    sqlx::query("update ap_users set last_refreshed_at = now() - interval '2 days' where id = $1")
+        .bind(remote_ap_user_a.id)
+        .execute(&app_b.pool)
+        .await?;
+
+    federation::refresh::refresh_remote_actors(&ap_cx_b).await?;
+
+    let mut tx_b = app_b.tx().await;
+    let remote_ap_user_a = db::ap_users::read_by_id(&mut tx_b, remote_ap_user_a.id).await?;
+    assert_eq!(remote_ap_user_a.display_name.as_deref(), Some("Refreshed"));
+
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn prunes_remote_actors_that_are_gone() -> Result<()> {
+    let TwoInstances {
+        ap_user_a, app_b, ..
+    } = TwoInstances::serve().await?;
+    let ap_cx_b = app_b.state.federation_config.to_request_data();
+    let remote_ap_user_a = ap_user_a.ap_id.dereference(&ap_cx_b).await?;
+
+    // An instance that responds to everything with `410 Gone`, like Mastodon
+    // does for deleted accounts
+    let listener = tokio::net::TcpListener::bind("localhost:0").await?;
+    let gone_url = Url::parse(&format!(
+        "http://localhost:{}/users/gone",
+        listener.local_addr()?.port()
+    ))?;
+    tokio::spawn(async move {
+        axum::serve(
+            listener,
+            axum::Router::new().fallback(|| async { StatusCode::GONE }),
+        )
+        .await
+        .unwrap();
+    });
+
+    sqlx::query(
+        "update ap_users set ap_id = $1, last_refreshed_at = now() - interval '2 days' where id = $2",
+    )
+    .bind(gone_url.as_str())
+    .bind(remote_ap_user_a.id)
+    .execute(&app_b.pool)
+    .await?;
+
+    federation::refresh::refresh_remote_actors(&ap_cx_b).await?;
+
+    let mut tx_b = app_b.tx().await;
+    assert!(into_option(db::ap_users::read_by_id(&mut tx_b, remote_ap_user_a.id).await)?.is_none());
+
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn followers_move_along_with_account() -> Result<()> {
+    let app_a = TestApp::new().await;
+    let user_a = app_a.create_test_user().await;
+    let mut tx_a = app_a.tx().await;
+    let ap_user_a = db::ap_users::read_by_id(&mut tx_a, user_a.ap_user_id).await?;
+    drop(tx_a);
+
+    let app_b = TestApp::new().await;
+    let old_user = app_b.create_user("old", "testpassword").await;
+    let new_user = app_b.create_user("new", "testpassword").await;
+    let mut tx_b = app_b.tx().await;
+    let old_ap_user = db::ap_users::read_by_id(&mut tx_b, old_user.ap_user_id).await?;
+    let new_ap_user = db::ap_users::set_also_known_as(
+        &mut tx_b,
+        new_user.ap_user_id,
+        &[old_ap_user.ap_id.inner().clone()],
+    )
+    .await?;
+    tx_b.commit().await?;
+
+    app_a.serve().await;
+    app_b.serve().await;
+    let ap_cx_a = app_a.state.federation_config.to_request_data();
+    let ap_cx_b = app_b.state.federation_config.to_request_data();
+
+    federation::Follow::new(&ap_user_a, &old_ap_user, &ap_cx_a)?
+        .send(&ap_user_a, &old_ap_user, &ap_cx_a)
+        .await?;
+
+    federation::Move::send_to_followers(&old_ap_user, &new_ap_user, &ap_cx_b).await?;
+
+    // Instance A now follows the new account
+    let mut tx_a = app_a.tx().await;
+    let remote_old = db::ap_users::read_by_ap_id(&mut tx_a, old_ap_user.ap_id.inner()).await?;
+    let remote_new = db::ap_users::read_by_ap_id(&mut tx_a, new_ap_user.ap_id.inner()).await?;
+    assert!(
+        db::ap_users::list_local_followers(&mut tx_a, remote_old.id)
+            .await?
+            .is_empty()
+    );
+    assert_eq!(
+        db::ap_users::list_local_followers(&mut tx_a, remote_new.id)
+            .await?
+            .len(),
+        1
+    );
+    drop(tx_a);
+
+    // Instance B knows about the new follower
+    let mut tx_b = app_b.tx().await;
+    let followers = db::ap_users::list_followers(&mut tx_b, new_ap_user.id).await?;
+    assert_eq!(followers.len(), 1);
+
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn moved_followers_switch_once_the_new_account_accepts() -> Result<()> {
+    let app_a = TestApp::new().await;
+    let user_a = app_a.create_test_user().await;
+    let mut tx_a = app_a.tx().await;
+    let ap_user_a = db::ap_users::read_by_id(&mut tx_a, user_a.ap_user_id).await?;
+    drop(tx_a);
+
+    let mut app_b = TestApp::new().await;
+    let old_user = app_b.create_user("old", "testpassword").await;
+    let new_user = app_b.create_user("new", "testpassword").await;
+    let mut tx_b = app_b.tx().await;
+    let old_ap_user = db::ap_users::read_by_id(&mut tx_b, old_user.ap_user_id).await?;
+    db::ap_users::set_manually_approves_followers(&mut tx_b, new_user.ap_user_id, true).await?;
+    let new_ap_user = db::ap_users::set_also_known_as(
+        &mut tx_b,
+        new_user.ap_user_id,
+        &[old_ap_user.ap_id.inner().clone()],
+    )
+    .await?;
+    tx_b.commit().await?;
+
+    app_a.serve().await;
+    app_b.serve().await;
+    let ap_cx_a = app_a.state.federation_config.to_request_data();
+    let ap_cx_b = app_b.state.federation_config.to_request_data();
+
+    federation::Follow::new(&ap_user_a, &old_ap_user, &ap_cx_a)?
+        .send(&ap_user_a, &old_ap_user, &ap_cx_a)
+        .await?;
+
+    federation::Move::send_to_followers(&old_ap_user, &new_ap_user, &ap_cx_b).await?;
+
+    // Instance A keeps following the old account while the request is pending
+    let mut tx_a = app_a.tx().await;
+    let remote_old = db::ap_users::read_by_ap_id(&mut tx_a, old_ap_user.ap_id.inner()).await?;
+    assert_eq!(
+        db::ap_users::list_local_followers(&mut tx_a, remote_old.id)
+            .await?
+            .len(),
+        1
+    );
+    drop(tx_a);
+
+    let mut tx_b = app_b.tx().await;
+    let pending = db::ap_users::list_pending_followers(&mut tx_b, new_ap_user.id).await?;
+    drop(tx_b);
+    assert_eq!(pending.len(), 1);
+
+    app_b.login_user("new", "testpassword").await;
+    app_b
+        .req()
+        .expect_status(StatusCode::SEE_OTHER)
+        .post(
+            &format!("/notifications/follow_requests/{}/accept", pending[0].id),
+            &HashMap::<String, String>::new(),
+        )
+        .await;
+
+    // Once accepted, instance A follows only the new account
+    let mut tx_a = app_a.tx().await;
+    let remote_new = db::ap_users::read_by_ap_id(&mut tx_a, new_ap_user.ap_id.inner()).await?;
+    assert!(
+        db::ap_users::list_local_followers(&mut tx_a, remote_old.id)
+            .await?
+            .is_empty()
+    );
+    assert_eq!(
+        db::ap_users::list_local_followers(&mut tx_a, remote_new.id)
+            .await?
+            .len(),
+        1
+    );
+
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn serves_html_and_activity_json_on_same_urls() -> Result<()> {
+    let mut app = TestApp::new().await;
+    let user = app.create_test_user().await;
+    let mut tx = app.tx().await;
+    let ap_user = db::ap_users::read_by_id(&mut tx, user.ap_user_id).await?;
+    let list = db::lists::insert(
+        &mut tx,
+        user.ap_user_id,
+        CreateList {
+            title: "Public list".to_string(),
+            content: None,
+            private: false,
+        },
+    )
+    .await?;
+    let bookmark = db::bookmarks::insert_local(
+        &mut tx,
+        user.ap_user_id,
+        InsertBookmark {
+            url: "https://www.rafa.ee".to_string(),
+            title: "Test Bookmark".to_string(),
+        },
+        &app.base_url,
+    )
+    .await?;
+    db::links::insert(
+        &mut tx,
+        user.id,
+        CreateLink {
+            src: list.id,
+            dest: bookmark.id,
+        },
+    )
+    .await?;
+    tx.commit().await?;
+
+    let profile_url = app.base_url.join(&format!("/user/{}", user.username))?;
+    let person = app
+        .req()
+        .header(header::ACCEPT, "application/activity+json")
+        .get(profile_url.path())
+        .await
+        .json()
+        .await;
+    assert_eq!(person["id"], ap_user.ap_id.inner().as_str());
+    assert_eq!(person["url"], profile_url.as_str());
+
+    // Browsers visiting the ActivityPub id end up on the profile page
+    let redirect = app
+        .req()
+        .header(header::ACCEPT, "text/html,application/xhtml+xml,*/*;q=0.8")
+        .expect_status(StatusCode::SEE_OTHER)
+        .get(ap_user.ap_id.inner().path())
+        .await;
+    assert_eq!(redirect.headers()[header::LOCATION], profile_url.as_str());
+
+    let json_list = app
+        .req()
+        .header(header::ACCEPT, "application/activity+json")
+        .get(&list.path())
+        .await
+        .json()
+        .await;
+    assert_eq!(json_list["url"], app.base_url.join(&list.path())?.as_str());
+    assert_eq!(
+        json_list["orderedItems"],
+        serde_json::json!([bookmark.ap_id.inner().as_str()])
+    );
+
+    let json_bookmark = app
+        .req()
+        .header(header::ACCEPT, "application/activity+json")
+        .get(&bookmark.path())
+        .await
+        .json()
+        .await;
+    assert_eq!(json_bookmark["id"], bookmark.ap_id.inner().as_str());
+    let weighted = app
+        .req()
+        .header(header::ACCEPT, "text/html;q=0.1, application/activity+json")
+        .get(&bookmark.path())
+        .await
+        .json()
+        .await;
+    assert_eq!(weighted["id"], bookmark.ap_id.inner().as_str());
+    assert_eq!(
+        json_bookmark["url"],
+        app.base_url.join(&bookmark.path())?.as_str()
+    );
+
+    let redirect = app
+        .req()
+        .expect_status(StatusCode::SEE_OTHER)
+        .get(bookmark.ap_id.inner().path())
+        .await;
+    assert_eq!(redirect.headers()[header::LOCATION], bookmark.path());
+
+    let bookmark_page = app.req().get(&bookmark.path()).await.test_page().await;
+    assert!(
+        bookmark_page
+            .dom
+            .find("h1")
+            .text()
+            .contains("Test Bookmark")
+    );
+
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn failed_deliveries_are_retried() -> Result<()> {
+    let mut app_a = TestApp::new().await;
+    let user_a = app_a.create_test_user().await;
+    app_a.login_test_user().await;
+    let mut tx_a = app_a.tx().await;
+    let ap_user_a = db::ap_users::read_by_id(&mut tx_a, user_a.ap_user_id).await?;
+    drop(tx_a);
+
+    let app_b = TestApp::new().await;
+    let user_b = app_b.create_test_user().await;
+    let mut tx_b = app_b.tx().await;
+    let ap_user_b = db::ap_users::read_by_id(&mut tx_b, user_b.ap_user_id).await?;
+    drop(tx_b);
+
+    app_b.serve().await;
+    let ap_cx_a = app_a.state.federation_config.to_request_data();
+    let remote_ap_user_b = ap_user_b.ap_id.dereference(&ap_cx_a).await?;
+
+    // Instance A isn't reachable yet, so instance B can't verify the follow
+    federation::Follow::new(&ap_user_a, &remote_ap_user_b, &ap_cx_a)?
+        .send(&ap_user_a, &remote_ap_user_b, &ap_cx_a)
+        .await?;
+
+    let mut tx_a = app_a.tx().await;
+    let pending = db::deliveries::list_pending(&mut tx_a, 10).await?;
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].attempts, 1);
+    assert!(pending[0].next_attempt_at > time::OffsetDateTime::now_utc());
+    assert_eq!(
+        db::deliveries::list_inbox_failures(&mut tx_a).await?.len(),
+        1
+    );
+    drop(tx_a);
+
+    app_a.req().get("/admin/deliveries").await.test_page().await;
+
+    app_a.serve().await;
+    sqlx::query("update deliveries set next_attempt_at = now()")
+        .execute(&app_a.pool)
+        .await?;
+    federation::delivery::deliver_due(&ap_cx_a).await?;
+
+    let mut tx_a = app_a.tx().await;
+    assert!(
+        db::deliveries::list_pending(&mut tx_a, 10)
+            .await?
+            .is_empty()
+    );
+    assert!(
+        db::deliveries::list_inbox_failures(&mut tx_a)
+            .await?
+            .is_empty()
+    );
+    drop(tx_a);
+
+    let mut tx_b = app_b.tx().await;
+    let followers = db::ap_users::list_followers(&mut tx_b, user_b.ap_user_id).await?;
+    assert_eq!(followers.len(), 1);
+
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn logs_received_and_sent_activities() -> Result<()> {
+    let app_a = TestApp::new().await;
+    let user_a = app_a.create_test_user().await;
+    let mut tx_a = app_a.tx().await;
+    let ap_user_a = db::ap_users::read_by_id(&mut tx_a, user_a.ap_user_id).await?;
+    drop(tx_a);
+
+    let mut app_b = TestApp::new().await;
+    let user_b = app_b.create_test_user().await;
+    app_b.login_test_user().await;
+    let mut tx_b = app_b.tx().await;
+    let ap_user_b = db::ap_users::read_by_id(&mut tx_b, user_b.ap_user_id).await?;
+    drop(tx_b);
+
+    app_a.serve().await;
+    app_b.serve().await;
+    let ap_cx_a = app_a.state.federation_config.to_request_data();
+
+    federation::Follow::new(&ap_user_a, &ap_user_b, &ap_cx_a)?
+        .send(&ap_user_a, &ap_user_b, &ap_cx_a)
+        .await?;
+
+    let domain_a = db::domain_blocks::url_domain(&app_a.base_url);
+    let domain_b = db::domain_blocks::url_domain(&app_b.base_url);
+
+    let mut tx_a = app_a.tx().await;
+    let sent = db::activity_log::list(
+        &mut tx_a,
+        &ActivityLogQuery {
+            domain: domain_b.clone(),
+            activity_type: Some("Follow".to_string()),
+        },
+        10,
+    )
+    .await?;
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].direction, Direction::Outgoing);
+    assert_eq!(sent[0].http_status, Some(200));
+    assert_eq!(sent[0].error, None);
+    drop(tx_a);
+
+    let mut tx_b = app_b.tx().await;
+    let received = db::activity_log::list(
+        &mut tx_b,
+        &ActivityLogQuery {
+            domain: domain_a.clone(),
+            activity_type: None,
+        },
+        10,
+    )
+    .await?;
+    let follow = received
+        .iter()
+        .find(|entry| entry.activity_type.as_deref() == Some("Follow"))
+        .unwrap();
+    assert_eq!(follow.direction, Direction::Incoming);
+    assert_eq!(follow.signature_valid, Some(true));
+    assert_eq!(follow.http_status, Some(200));
+    assert_eq!(
+        follow.actor_ap_id.as_deref(),
+        Some(ap_user_a.ap_id.inner().as_str())
+    );
+    drop(tx_b);
+
+    app_b
+        .req()
+        .get(&format!(
+            "/admin/activity_log?domain={}&activity_type=Follow",
+            domain_a.unwrap_or_default()
+        ))
+        .await
+        .test_page()
+        .await;
+
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn logs_only_the_start_of_refused_activities() -> Result<()> {
+    let app = TestApp::new().await;
+    let body = serde_json::json!({
+        "id": "https://spam.example/activities/1",
+        "type": "Spam".repeat(100),
+        "actor": "https://spam.example/users/spammer",
+        "content": "Buy now! ".repeat(10_000),
+    })
+    .to_string();
+    let result: ResponseResult<()> = Err(anyhow::anyhow!("Refused").into());
+    federation::activity_log::record_incoming(&app.pool, body.as_bytes(), &result).await;
+
+    let mut tx = app.tx().await;
+    let entry = db::activity_log::list(&mut tx, &ActivityLogQuery::default(), 1)
+        .await?
+        .pop()
+        .unwrap();
+    assert_eq!(entry.activity_type.map(|kind| kind.len()), Some(255));
+    assert!(entry.body.len() < body.len());
+    assert!(entry.body.starts_with(&body[..100]));
+
+    Ok(())
+}
+
+#[test_log::test]
+fn reads_bookmarks_from_lemmy_pages() -> Result<()> {
+    let page: federation::BookmarkJson = serde_json::from_value(serde_json::json!({
+        "id": "https://lemmy.example/post/1",
+        "type": "Page",
+        "attributedTo": "https://lemmy.example/u/alice",
+        "to": ["https://lemmy.example/c/rust", "https://www.w3.org/ns/activitystreams#Public"],
+        "name": "A post about Rust",
+        "content": "<p>Found this &amp; liked it</p>",
+        "url": "https://blog.example/rust",
+    }))?;
+    let bookmark = InsertBookmark::try_from(page)?;
+    assert_eq!(bookmark.url, "https://blog.example/rust");
+    assert_eq!(bookmark.title, "A post about Rust");
+
+    // Newer Lemmy versions attach the link instead
+    let page: federation::BookmarkJson = serde_json::from_value(serde_json::json!({
+        "id": "https://lemmy.example/post/2",
+        "type": "Page",
+        "attributedTo": "https://lemmy.example/u/alice",
+        "to": "https://www.w3.org/ns/activitystreams#Public",
+        "name": "Another post",
+        "url": "https://lemmy.example/post/2",
+        "attachment": [{
+            "type": "Link",
+            "href": "https://blog.example/other",
+            "mediaType": "text/html; charset=utf-8",
+        }],
+    }))?;
+    let bookmark = InsertBookmark::try_from(page)?;
+    assert_eq!(bookmark.url, "https://blog.example/other");
+
+    Ok(())
+}
+
+#[test_log::test]
+fn reads_bookmarks_from_mastodon_statuses() -> Result<()> {
+    let status = |content: &str| {
+        serde_json::from_value::<federation::BookmarkJson>(serde_json::json!({
+            "id": "https://mastodon.example/users/bob/statuses/1",
+            "type": "Note",
+            "attributedTo": "https://mastodon.example/users/bob",
+            "to": ["https://www.w3.org/ns/activitystreams#Public"],
+            "content": content,
+            "url": "https://mastodon.example/@bob/1",
+            "attachment": [{
+                "type": "Document",
+                "mediaType": "image/png",
+                "url": "https://mastodon.example/media/1.png",
+            }],
+        }))
+    };
+
+    let bookmark = InsertBookmark::try_from(status(
+        r#"<p><span class="h-card"><a href="https://other.example/@carol" class="u-url mention">@<span>carol</span></a></span> this is great &amp; short</p><p><a href="https://blog.example/post?a=1&amp;b=2" rel="nofollow noopener" target="_blank"><span class="invisible">https://</span>blog.example/post</a> <a href="https://mastodon.example/tags/links" class="mention hashtag" rel="tag">#<span>links</span></a></p>"#,
+    )?)?;
+    assert_eq!(bookmark.url, "https://blog.example/post?a=1&b=2");
+    assert_eq!(bookmark.title, "this is great & short");
+
+    // Without a title, we can't tell which of several links is the bookmark
+    let ambiguous = InsertBookmark::try_from(status(
+        r#"<p><a href="https://a.example">a</a> or <a href="https://b.example">b</a>?</p>"#,
+    )?);
+    assert!(ambiguous.is_err());
+
+    let without_link = InsertBookmark::try_from(status("<p>Just chatting</p>")?);
+    assert!(without_link.is_err());
+
+    Ok(())
+}
+
+#[test_log::test]
+fn reads_bookmarks_from_betula_notes() -> Result<()> {
+    let note: federation::BookmarkJson = serde_json::from_value(serde_json::json!({
+        "id": "https://betula.example/7",
+        "type": "Note",
+        "attributedTo": "https://betula.example/@dave",
+        "to": ["https://www.w3.org/ns/activitystreams#Public"],
+        "name": "Betula",
+        "content": r#"<h3><a href="https://betula.mycorrhiza.wiki">Betula</a></h3><p>Federated bookmarking, see <a href="https://example.org/docs">the docs</a></p>"#,
+    }))?;
+    let bookmark = InsertBookmark::try_from(note)?;
+    assert_eq!(bookmark.url, "https://betula.mycorrhiza.wiki/");
+    assert_eq!(bookmark.title, "Betula");
+
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn can_save_remote_bookmark_with_attribution() -> Result<()> {
+    let app_a = TestApp::new().await;
+    let user_a = app_a.create_test_user().await;
+    let mut tx_a = app_a.tx().await;
+    let ap_user_a = db::ap_users::read_by_id(&mut tx_a, user_a.ap_user_id).await?;
+    drop(tx_a);
+    let original = app_a.insert_public_bookmark(&user_a, "Original").await;
+
+    let mut app_b = TestApp::new().await;
+    let user_b = app_b.create_test_user().await;
+    app_b.login_test_user().await;
+    let mut tx_b = app_b.tx().await;
+    let ap_user_b = db::ap_users::read_by_id(&mut tx_b, user_b.ap_user_id).await?;
+    let public_list = db::lists::insert(
+        &mut tx_b,
+        user_b.ap_user_id,
+        CreateList {
+            title: "Saved".to_string(),
+            content: None,
+            private: false,
+        },
+    )
+    .await?;
+    tx_b.commit().await?;
+
+    app_a.serve().await;
+    app_b.serve().await;
+    let ap_cx_a = app_a.state.federation_config.to_request_data();
+    let ap_cx_b = app_b.state.federation_config.to_request_data();
+
+    // A follows B to receive the saved copy, B follows A to see who saved what
+    federation::Follow::new(&ap_user_a, &ap_user_b, &ap_cx_a)?
+        .send(&ap_user_a, &ap_user_b, &ap_cx_a)
+        .await?;
+    federation::Follow::new(&ap_user_b, &ap_user_a, &ap_cx_b)?
+        .send(&ap_user_b, &ap_user_a, &ap_cx_b)
+        .await?;
+
+    let remote_original = original.ap_id.dereference(&ap_cx_b).await?;
+    let remote_page = app_b
+        .req()
+        .get(&remote_original.path())
+        .await
+        .test_page()
+        .await;
+    assert!(
+        remote_page
+            .dom
+            .find("header")
+            .text()
+            .contains("Saved by 1 person you follow")
+    );
+
+    let create_page = app_b
+        .req()
+        .get(&format!("/bookmarks/create?via={}", remote_original.id))
+        .await
+        .test_page()
+        .await;
+    assert_eq!(
+        create_page
+            .dom
+            .find("input[name='title']")
+            .attr("value")
+            .map(|value| value.to_string()),
+        Some("Original".to_string())
+    );
+
+    app_b
+        .req()
+        .expect_status(StatusCode::SEE_OTHER)
+        .post(
+            "/bookmarks/create",
+            &serde_json::json!({
+                "url": remote_original.url,
+                "title": remote_original.title,
+                "parents": [public_list.id],
+                "via": remote_original.id,
+                "submitted": true,
+            }),
+        )
+        .await;
+
+    let mut tx_b = app_b.tx().await;
+    let link = db::links::list_by_list(&mut tx_b, public_list.id, Some(user_b.ap_user_id))
+        .await?
+        .pop()
+        .unwrap();
+    let LinkDestinationWithChildren::Bookmark(copy) = link.dest else {
+        panic!("Expected the saved copy in the list");
+    };
+    assert_eq!(copy.via_ap_id, Some(original.ap_id.clone()));
+    assert_eq!(copy.via_ap_user_id, Some(remote_original.ap_user_id));
+    drop(tx_b);
+
+    let copy_page = app_b.req().get(&copy.path()).await.test_page().await;
+    assert!(copy_page.dom.find("header").text().contains("via"));
+
+    // The copy is federated with a reference to the original
+    let mut tx_a = app_a.tx().await;
+    let received_copy = db::bookmarks::by_ap_id(&mut tx_a, copy.ap_id.clone()).await?;
+    assert_eq!(received_copy.via_ap_id, Some(original.ap_id));
+    assert_eq!(received_copy.via_ap_user_id, Some(user_a.ap_user_id));
+
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn bookmark_content_is_escaped() -> Result<()> {
+    let mut app = TestApp::new().await;
+    let user = app.create_test_user().await;
+    let bookmark = app
+        .insert_public_bookmark(&user, "<script>alert(1)</script>")
+        .await;
+    sqlx::query("update bookmarks set url = $1 where id = $2")
+        .bind("https://example.com/?a=1&b=\"2\"")
+        .bind(bookmark.id)
+        .execute(&app.pool)
+        .await?;
+
+    let json_bookmark = app
+        .req()
+        .header(header::ACCEPT, "application/activity+json")
+        .get(&bookmark.path())
+        .await
+        .json()
+        .await;
+    let content = json_bookmark["content"].as_str().unwrap();
+    assert!(!content.contains("<script>"));
+    assert!(content.contains("&lt;script&gt;alert(1)&lt;/script&gt;"));
+    assert!(content.contains(r#"href="https://example.com/?a=1&amp;b=&quot;2&quot;""#));
+    assert_eq!(json_bookmark["name"], "<script>alert(1)</script>");
+    // Our own properties are declared in the JSON-LD context
+    assert_eq!(json_bookmark["@context"][2]["via"]["@type"], "@id");
+
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn receives_bookmarks_of_trusted_remote_users() -> Result<()> {
+    let mut app_a = TestApp::new().await;
+    let user_a = app_a.create_test_user().await;
+    app_a.login_test_user().await;
+    let mut tx_a = app_a.tx().await;
+    let ap_user_a = db::ap_users::read_by_id(&mut tx_a, user_a.ap_user_id).await?;
+    let public_list = db::lists::insert(
+        &mut tx_a,
+        user_a.ap_user_id,
+        CreateList {
+            title: "Rust".to_string(),
+            content: None,
+            private: false,
+        },
+    )
+    .await?;
+    tx_a.commit().await?;
+
+    let mut app_b = TestApp::new().await;
+    let user_b = app_b.create_test_user().await;
+    app_b.login_test_user().await;
+
+    app_a.serve().await;
+    app_b.serve().await;
+    let ap_cx_b = app_b.state.federation_config.to_request_data();
+    let remote_a = ap_user_a.ap_id.dereference(&ap_cx_b).await?;
+    let handle_a = format!(
+        "{}@{}",
+        remote_a.username,
+        db::domain_blocks::url_domain(&app_a.base_url).unwrap()
+    );
+
+    app_b
+        .req()
+        .expect_status(StatusCode::SEE_OTHER)
+        .post(
+            &format!("/user/{handle_a}/trust"),
+            &HashMap::<String, String>::new(),
+        )
+        .await;
+
+    // B's instance actor follows A, so B doesn't show up as a follower of A
+    let mut tx_a = app_a.tx().await;
+    let followers = db::ap_users::list_followers(&mut tx_a, user_a.ap_user_id).await?;
+    assert_eq!(followers.len(), 1);
+    assert_eq!(followers[0].kind, ActorKind::Application);
+    drop(tx_a);
+
+    app_a
+        .req()
+        .expect_status(StatusCode::SEE_OTHER)
+        .post(
+            "/bookmarks/create",
+            &serde_json::json!({
+                "url": "https://www.rust-lang.org",
+                "title": "Rust",
+                "parents": [public_list.id],
+                "submitted": true,
+            }),
+        )
+        .await;
+
+    let mut tx_b = app_b.tx().await;
+    let results =
+        db::bookmarks::search_trusted(&mut tx_b, "rust", user_b.ap_user_id, false, 10).await?;
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].url, "https://www.rust-lang.org");
+    drop(tx_b);
+
+    app_b
+        .req()
+        .expect_status(StatusCode::SEE_OTHER)
+        .post(
+            &format!("/user/{handle_a}/untrust"),
+            &HashMap::<String, String>::new(),
+        )
+        .await;
+
+    let mut tx_a = app_a.tx().await;
+    let followers = db::ap_users::list_followers(&mut tx_a, user_a.ap_user_id).await?;
+    assert!(followers.is_empty());
+
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn receives_and_sends_replies() -> Result<()> {
+    let mut app_a = TestApp::new().await;
+    let user_a = app_a.create_test_user().await;
+    app_a.create_user("otheruser", "testpassword").await;
+    app_a.login_test_user().await;
+    let mut tx_a = app_a.tx().await;
+    let list = db::lists::insert(
+        &mut tx_a,
+        user_a.ap_user_id,
+        CreateList {
+            title: "Public".to_string(),
+            content: None,
+            private: false,
+        },
+    )
+    .await?;
+    let bookmark = db::bookmarks::insert_local(
+        &mut tx_a,
+        user_a.ap_user_id,
+        InsertBookmark {
+            url: "https://www.rafa.ee".to_string(),
+            title: "Discuss me".to_string(),
+        },
+        &app_a.base_url,
+    )
+    .await?;
+    db::links::insert(
+        &mut tx_a,
+        user_a.id,
+        CreateLink {
+            src: list.id,
+            dest: bookmark.id,
+        },
+    )
+    .await?;
+    tx_a.commit().await?;
+
+    let mut app_b = TestApp::new().await;
+    app_b.create_test_user().await;
+    app_b.login_test_user().await;
+
+    app_a.serve().await;
+    app_b.serve().await;
+    let ap_cx_b = app_b.state.federation_config.to_request_data();
+    let remote_bookmark = bookmark.ap_id.dereference(&ap_cx_b).await?;
+
+    // B replies to the bookmark of A
+    app_b
+        .req()
+        .expect_status(StatusCode::SEE_OTHER)
+        .post(
+            &format!("{}/replies", remote_bookmark.path()),
+            &serde_json::json!({ "content": "Great find!" }),
+        )
+        .await;
+
+    let mut tx_a = app_a.tx().await;
+    let received = db::replies::list_by_bookmark(&mut tx_a, bookmark.id, true)
+        .await?
+        .pop()
+        .unwrap();
+    assert_eq!(received.content, "Great find!");
+    assert_eq!(received.parent_id, None);
+    drop(tx_a);
+
+    // A answers the reply of B
+    app_a
+        .req()
+        .expect_status(StatusCode::SEE_OTHER)
+        .post(
+            &format!("{}/replies", bookmark.path()),
+            &serde_json::json!({ "content": "Thanks!", "parent_id": received.id }),
+        )
+        .await;
+
+    let page_b = app_b
+        .req()
+        .get(&remote_bookmark.path())
+        .await
+        .test_page()
+        .await;
+    let replies_b = page_b.dom.find("section").last().text();
+    assert!(replies_b.contains("Great find!"));
+    assert!(replies_b.contains("Thanks!"));
+
+    // A hides the reply of B from everyone else on instance A
+    app_a
+        .req()
+        .expect_status(StatusCode::SEE_OTHER)
+        .post(
+            &format!("{}/replies/{}/hide", bookmark.path(), received.id),
+            &HashMap::<String, String>::new(),
+        )
+        .await;
+    let page_a = app_a.req().get(&bookmark.path()).await.test_page().await;
+    assert!(page_a.dom.find("section").last().text().contains("Hidden"));
+
+    app_a.login_user("otheruser", "testpassword").await;
+    let page_other = app_a.req().get(&bookmark.path()).await.test_page().await;
+    assert!(
+        !page_other
+            .dom
+            .find("section")
+            .last()
+            .text()
+            .contains("Great find!")
+    );
+
+    // Hiding can be undone, so it isn't federated
+    let mut tx_b = app_b.tx().await;
+    let reply_b = db::replies::by_ap_id(&mut tx_b, received.ap_id.inner()).await?;
+    drop(tx_b);
+    assert!(!reply_b.hidden);
+
+    // B deletes the reply, which also removes the answer of A
+    app_b
+        .req()
+        .expect_status(StatusCode::SEE_OTHER)
+        .post(
+            &format!("{}/replies/{}/delete", remote_bookmark.path(), reply_b.id),
+            &HashMap::<String, String>::new(),
+        )
+        .await;
+    let mut tx_a = app_a.tx().await;
+    assert!(
+        db::replies::list_by_bookmark(&mut tx_a, bookmark.id, true)
+            .await?
+            .is_empty()
+    );
+
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn blocked_actor_cannot_reply() -> Result<()> {
+    let mut app_a = TestApp::new().await;
+    let user_a = app_a.create_test_user().await;
+    let mut tx_a = app_a.tx().await;
+    let list = db::lists::insert(
+        &mut tx_a,
+        user_a.ap_user_id,
+        CreateList {
+            title: "Public".to_string(),
+            content: None,
+            private: false,
+        },
+    )
+    .await?;
+    let bookmark = db::bookmarks::insert_local(
+        &mut tx_a,
+        user_a.ap_user_id,
+        InsertBookmark {
+            url: "https://www.rafa.ee".to_string(),
+            title: "Discuss me".to_string(),
+        },
+        &app_a.base_url,
+    )
+    .await?;
+    db::links::insert(
+        &mut tx_a,
+        user_a.id,
+        CreateLink {
+            src: list.id,
+            dest: bookmark.id,
+        },
+    )
+    .await?;
+    tx_a.commit().await?;
+
+    let mut app_b = TestApp::new().await;
+    let user_b = app_b.create_test_user().await;
+    app_b.login_test_user().await;
+    let mut tx_b = app_b.tx().await;
+    let ap_user_b = db::ap_users::read_by_id(&mut tx_b, user_b.ap_user_id).await?;
+    drop(tx_b);
+
+    app_a.serve().await;
+    app_b.serve().await;
+    let ap_cx_a = app_a.state.federation_config.to_request_data();
+    let ap_cx_b = app_b.state.federation_config.to_request_data();
+
+    let remote_ap_user_b = ap_user_b.ap_id.dereference(&ap_cx_a).await?;
+    let mut tx_a = app_a.tx().await;
+    db::actor_blocks::insert(
+        &mut tx_a,
+        db::actor_blocks::Insert {
+            blocker_id: user_a.ap_user_id,
+            blocked_id: remote_ap_user_b.id,
+        },
+    )
+    .await?;
+    tx_a.commit().await?;
+
+    let remote_bookmark = bookmark.ap_id.dereference(&ap_cx_b).await?;
+    app_b
+        .req()
+        .expect_status(StatusCode::SEE_OTHER)
+        .post(
+            &format!("{}/replies", remote_bookmark.path()),
+            &serde_json::json!({ "content": "Hello?" }),
+        )
+        .await;
+
+    let mut tx_a = app_a.tx().await;
+    assert!(
+        db::replies::list_by_bookmark(&mut tx_a, bookmark.id, true)
+            .await?
+            .is_empty()
+    );
+
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn receives_likes_and_boosts() -> Result<()> {
+    let mut app_a = TestApp::new().await;
+    let user_a = app_a.create_test_user().await;
+    app_a.login_test_user().await;
+    let mut tx_a = app_a.tx().await;
+    let ap_user_a = db::ap_users::read_by_id(&mut tx_a, user_a.ap_user_id).await?;
+    let list = db::lists::insert(
+        &mut tx_a,
+        user_a.ap_user_id,
+        CreateList {
+            title: "Public".to_string(),
+            content: None,
+            private: false,
+        },
+    )
+    .await?;
+    let bookmark = db::bookmarks::insert_local(
+        &mut tx_a,
+        user_a.ap_user_id,
+        InsertBookmark {
+            url: "https://www.rafa.ee".to_string(),
+            title: "Like me".to_string(),
+        },
+        &app_a.base_url,
+    )
+    .await?;
+    db::links::insert(
+        &mut tx_a,
+        user_a.id,
+        CreateLink {
+            src: list.id,
+            dest: bookmark.id,
+        },
+    )
+    .await?;
+    let private_bookmark = db::bookmarks::insert_local(
+        &mut tx_a,
+        user_a.ap_user_id,
+        InsertBookmark {
+            url: "https://www.rafa.ee/private".to_string(),
+            title: "Secret".to_string(),
+        },
+        &app_a.base_url,
+    )
+    .await?;
+    tx_a.commit().await?;
+
+    let app_b = TestApp::new().await;
+    let user_b = app_b.create_test_user().await;
+    let mut tx_b = app_b.tx().await;
+    let ap_user_b = db::ap_users::read_by_id(&mut tx_b, user_b.ap_user_id).await?;
+    drop(tx_b);
+
+    app_a.serve().await;
+    app_b.serve().await;
+    let ap_cx_b = app_b.state.federation_config.to_request_data();
+
+    // B likes and boosts the bookmark of A, like a Mastodon user would
+    let like = federation::Like {
+        actor: ap_user_b.ap_id.clone(),
+        object: bookmark.ap_id.inner().clone(),
+        kind: LikeType::Like,
+        id: federation::activity::generate_id(&ap_cx_b)?,
+    };
+    federation::activity::send(&ap_user_b, like.clone(), &[&ap_user_a], &ap_cx_b).await?;
+    let announce = federation::Announce {
+        actor: ap_user_b.ap_id.clone(),
+        object: bookmark.ap_id.inner().clone(),
+        kind: AnnounceType::Announce,
+        id: federation::activity::generate_id(&ap_cx_b)?,
+    };
+    federation::activity::send(&ap_user_b, announce, &[&ap_user_a], &ap_cx_b).await?;
+
+    let page = app_a.req().get(&bookmark.path()).await.test_page().await;
+    let text = page.dom.text();
+    assert!(text.contains("1 like · 1 boost"));
+    assert!(text.contains("Liked by"));
+    assert!(text.contains("Boosted by"));
+
+    let notifications = app_a.req().get("/notifications").await.test_page().await;
+    assert!(notifications.dom.text().contains("liked"));
+
+    // B takes back the like
+    let undo = federation::UndoInteraction {
+        actor: ap_user_b.ap_id.clone(),
+        object: federation::interaction::Interaction::Like(like),
+        kind: UndoType::Undo,
+        id: federation::activity::generate_id(&ap_cx_b)?,
+    };
+    federation::activity::send(&ap_user_b, undo, &[&ap_user_a], &ap_cx_b).await?;
+
+    let mut tx_a = app_a.tx().await;
+    let kinds = db::interactions::list_by_bookmark(&mut tx_a, bookmark.id)
+        .await?
+        .into_iter()
+        .map(|interaction| interaction.kind)
+        .collect::<Vec<_>>();
+    assert_eq!(kinds, vec![db::interactions::Kind::Boost]);
+    drop(tx_a);
+
+    // Private bookmarks can't be liked
+    let private_like = federation::Like {
+        actor: ap_user_b.ap_id.clone(),
+        object: private_bookmark.ap_id.inner().clone(),
+        kind: LikeType::Like,
+        id: federation::activity::generate_id(&ap_cx_b)?,
+    };
+    federation::activity::send(&ap_user_b, private_like, &[&ap_user_a], &ap_cx_b).await?;
+    let mut tx_a = app_a.tx().await;
+    assert!(
+        db::interactions::list_by_bookmark(&mut tx_a, private_bookmark.id)
+            .await?
+            .is_empty()
+    );
+
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn hashtags_federate() -> Result<()> {
+    let mut app_a = TestApp::new().await;
+    let user_a = app_a.create_test_user().await;
+    app_a.login_test_user().await;
+    let mut tx_a = app_a.tx().await;
+    let public_list = db::lists::insert(
+        &mut tx_a,
+        user_a.ap_user_id,
+        CreateList {
+            title: "Public".to_string(),
+            content: None,
+            private: false,
+        },
+    )
+    .await?;
+    tx_a.commit().await?;
+
+    app_a
+        .req()
+        .expect_status(StatusCode::SEE_OTHER)
+        .post(
+            "/bookmarks/create",
+            &serde_json::json!({
+                "url": "https://www.rafa.ee",
+                "title": "Tagged",
+                "tags": "#Rust, web #rust",
+                "parents": [public_list.id],
+                "submitted": true,
+            }),
+        )
+        .await;
+
+    let mut tx_a = app_a.tx().await;
+    let link = db::links::list_by_list(&mut tx_a, public_list.id, Some(user_a.ap_user_id))
+        .await?
+        .pop()
+        .unwrap();
+    let LinkDestinationWithChildren::Bookmark(bookmark) = link.dest else {
+        panic!("Expected the bookmark in the list");
+    };
+    assert_eq!(bookmark.tags, vec!["rust".to_string(), "web".to_string()]);
+    drop(tx_a);
+
+    let tag_page = app_a.req().get("/tags/rust").await.test_page().await;
+    assert!(tag_page.dom.find("main").text().contains("Tagged"));
+    let other_tag_page = app_a.req().get("/tags/go").await.test_page().await;
+    assert!(!other_tag_page.dom.find("main").text().contains("Tagged"));
+
+    let app_b = TestApp::new().await;
+    app_a.serve().await;
+    let ap_cx_b = app_b.state.federation_config.to_request_data();
+    let remote_bookmark = bookmark.ap_id.dereference(&ap_cx_b).await?;
+    assert_eq!(remote_bookmark.tags, bookmark.tags);
+
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn authorized_fetch_requires_signatures() -> Result<()> {
+    let mut app_a = TestApp::with_authorized_fetch().await;
+    let user_a = app_a.create_test_user().await;
+    let mut tx_a = app_a.tx().await;
+    let ap_user_a = db::ap_users::read_by_id(&mut tx_a, user_a.ap_user_id).await?;
+    drop(tx_a);
+    let bookmark = app_a.insert_public_bookmark(&user_a, "Test Bookmark").await;
+
+    // Unsigned requests for ActivityPub objects are refused
+    for path in [
+        ap_user_a.ap_id.inner().path().to_string(),
+        bookmark.ap_id.inner().path().to_string(),
+        bookmark.path(),
+        format!("/user/{}", user_a.username),
+    ] {
+        app_a
+            .req()
+            .header(header::ACCEPT, "application/activity+json")
+            .expect_status(StatusCode::UNAUTHORIZED)
+            .get(&path)
+            .await;
+    }
+
+    // The instance actor and HTML pages are still available
+    app_a
+        .req()
+        .header(header::ACCEPT, "application/activity+json")
+        .get("/ap/actor")
+        .await
+        .json()
+        .await;
+    app_a
+        .req()
+        .get(&format!("/user/{}", user_a.username))
+        .await
+        .test_page()
+        .await;
+
+    // Other instances sign their fetches with their instance actor
+    let app_b = TestApp::new().await;
+    app_a.serve().await;
+    app_b.serve().await;
+    let ap_cx_b = app_b.state.federation_config.to_request_data();
+    let remote_bookmark = bookmark.ap_id.dereference(&ap_cx_b).await?;
+    assert_eq!(remote_bookmark.title, bookmark.title);
+
+    // Blocked instances can't read anything, even with a signature
+    let mut tx_a = app_a.tx().await;
+    db::domain_blocks::upsert(
+        &mut tx_a,
+        CreateDomainBlock {
+            domain: db::domain_blocks::url_domain(&app_b.base_url).unwrap(),
+            severity: Severity::Reject,
+            reason: None,
+        },
+    )
+    .await?;
+    tx_a.commit().await?;
+    assert!(bookmark.ap_id.dereference_forced(&ap_cx_b).await.is_err());
+
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn relays_pass_on_link_posts() -> Result<()> {
+    let mut app_a = TestApp::new().await;
+    let user_a = app_a.create_test_user().await;
+    app_a.login_test_user().await;
+    let mut tx_a = app_a.tx().await;
+    let public_list = db::lists::insert(
+        &mut tx_a,
+        user_a.ap_user_id,
+        CreateList {
+            title: "Public".to_string(),
+            content: None,
+            private: false,
+        },
+    )
+    .await?;
+    tx_a.commit().await?;
+
+    // A user on instance B stands in for a LitePub relay
+    let app_b = TestApp::new().await;
+    let relay_user = app_b.create_user("relay", "testpassword").await;
+    let mut tx_b = app_b.tx().await;
+    let relay_actor = db::ap_users::read_by_id(&mut tx_b, relay_user.ap_user_id).await?;
+    drop(tx_b);
+    let relayed = app_b
+        .insert_public_bookmark(&relay_user, "Relayed bookmark")
+        .await;
+
+    app_a.serve().await;
+    app_b.serve().await;
+    let ap_cx_b = app_b.state.federation_config.to_request_data();
+
+    app_a.req().get("/admin/relays").await.test_page().await;
+    app_a
+        .req()
+        .expect_status(StatusCode::SEE_OTHER)
+        .post(
+            "/admin/relays",
+            &serde_json::json!({
+                "url": relay_actor.ap_id.inner(),
+                "kind": "litepub",
+            }),
+        )
+        .await;
+
+    let mut tx_a = app_a.tx().await;
+    let relays = db::relays::list(&mut tx_a).await?;
+    assert_eq!(relays.len(), 1);
+    assert_eq!(relays[0].state, db::relays::State::Accepted);
+    drop(tx_a);
+
+    // The relay announces a link post to the instance actor of A
+    let mut tx_b = app_b.tx().await;
+    let instance_actor_a = db::ap_users::read_instance_actor(&mut tx_b, &app_a.base_url).await?;
+    drop(tx_b);
+    let announce = federation::Announce {
+        actor: relay_actor.ap_id.clone(),
+        object: relayed.ap_id.inner().clone(),
+        kind: AnnounceType::Announce,
+        id: federation::activity::generate_id(&ap_cx_b)?,
+    };
+    federation::activity::send(&relay_actor, announce, &[&instance_actor_a], &ap_cx_b).await?;
+
+    let discover = app_a.req().get("/discover").await.test_page().await;
+    assert!(
+        discover
+            .dom
+            .find("main")
+            .text()
+            .contains("Relayed bookmark")
+    );
+
+    // Public bookmarks of A are sent to the relay
+    app_a
+        .req()
+        .expect_status(StatusCode::SEE_OTHER)
+        .post(
+            "/bookmarks/create",
+            &serde_json::json!({
+                "url": "https://www.rafa.ee",
+                "title": "Public bookmark",
+                "parents": [public_list.id],
+                "submitted": true,
+            }),
+        )
+        .await;
+
+    let mut tx_b = app_b.tx().await;
+    let creates = db::activity_log::list(
+        &mut tx_b,
+        &ActivityLogQuery {
+            domain: None,
+            activity_type: Some("Create".to_string()),
+        },
+        10,
+    )
+    .await?;
+    assert_eq!(creates.len(), 1);
+    drop(tx_b);
+
+    // Unsubscribing removes the relay and its discover feed
+    app_a
+        .req()
+        .expect_status(StatusCode::SEE_OTHER)
+        .post(
+            &format!("/admin/relays/{}/delete", relays[0].id),
+            &HashMap::<String, String>::new(),
+        )
+        .await;
+    let discover = app_a.req().get("/discover").await.test_page().await;
+    assert!(
+        !discover
+            .dom
+            .find("main")
+            .text()
+            .contains("Relayed bookmark")
+    );
+
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn shows_public_lists_and_bookmarks_of_remote_users() -> Result<()> {
+    let mut app_a = TestApp::new().await;
+    app_a.create_test_user().await;
+    app_a.login_test_user().await;
+
+    let mut app_b = TestApp::new().await;
+    let user_b = app_b.create_test_user().await;
+    app_b.login_test_user().await;
+    let mut tx_b = app_b.tx().await;
+    let ap_user_b = db::ap_users::read_by_id(&mut tx_b, user_b.ap_user_id).await?;
+    let public_list = db::lists::insert(
+        &mut tx_b,
+        user_b.ap_user_id,
+        CreateList {
+            title: "Reading list".to_string(),
+            content: Some("Things to read".to_string()),
+            private: false,
+        },
+    )
+    .await?;
+    let private_list = db::lists::insert(
+        &mut tx_b,
+        user_b.ap_user_id,
+        CreateList {
+            title: "Secret list".to_string(),
+            content: None,
+            private: true,
+        },
+    )
+    .await?;
+    tx_b.commit().await?;
+    for (title, list) in [
+        ("Public bookmark", &public_list),
+        ("Private bookmark", &private_list),
+    ] {
+        app_b
+            .req()
+            .expect_status(StatusCode::SEE_OTHER)
+            .post(
+                "/bookmarks/create",
+                &serde_json::json!({
+                    "url": "https://www.rafa.ee",
+                    "title": title,
+                    "parents": [list.id],
+                    "submitted": true,
+                }),
+            )
+            .await;
+    }
+
+    app_a.serve().await;
+    app_b.serve().await;
+    let ap_cx_a = app_a.state.federation_config.to_request_data();
+    let remote_b = ap_user_b.ap_id.dereference(&ap_cx_a).await?;
+    let handle_b = format!(
+        "{}@{}",
+        remote_b.username,
+        db::domain_blocks::url_domain(&app_b.base_url).unwrap()
+    );
+
+    let profile = app_a
+        .req()
+        .get(&format!("/user/{handle_b}"))
+        .await
+        .test_page()
+        .await;
+    let text = profile.dom.find("main").text();
+    assert!(text.contains("1 public list"));
+    assert!(text.contains("Reading list"));
+    assert!(text.contains("Things to read"));
+    assert!(text.contains("Public bookmark"));
+    assert!(!text.contains("Secret list"));
+    assert!(!text.contains("Private bookmark"));
+
+    // The bookmarks are kept, so they can be saved and replied to
+    let mut tx_a = app_a.tx().await;
+    let bookmarks = db::bookmarks::list_public_by_ap_user(&mut tx_a, remote_b.id, 10).await?;
+    assert_eq!(bookmarks.len(), 1);
+
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn reports_reach_the_instance_of_reported_users() -> Result<()> {
+    let mut app_a = TestApp::new().await;
+    app_a.create_test_user().await;
+    app_a.login_test_user().await;
+
+    let mut app_b = TestApp::new().await;
+    app_b.create_test_user().await;
+    app_b.login_test_user().await;
+    let spammer = app_b.create_user("spammer", "testpassword").await;
+    let bookmark = app_b.insert_public_bookmark(&spammer, "Buy now").await;
+
+    app_a.serve().await;
+    app_b.serve().await;
+    let ap_cx_a = app_a.state.federation_config.to_request_data();
+    let remote_bookmark = bookmark.ap_id.dereference(&ap_cx_a).await?;
+
+    let report_path = format!("{}/report", remote_bookmark.path());
+    app_a.req().get(&report_path).await.test_page().await;
+    app_a
+        .req()
+        .expect_status(StatusCode::SEE_OTHER)
+        .post(
+            &report_path,
+            &CreateReport {
+                comment: "Spam".to_string(),
+            },
+        )
+        .await;
+
+    let mut tx_a = app_a.tx().await;
+    let reports_a = db::reports::list_open(&mut tx_a).await?;
+    assert_eq!(reports_a.len(), 1);
+    assert_eq!(reports_a[0].bookmark_id, Some(remote_bookmark.id));
+    drop(tx_a);
+
+    // The report arrived as a `Flag` from instance A
+    let mut tx_b = app_b.tx().await;
+    let reports_b = db::reports::list_open(&mut tx_b).await?;
+    assert_eq!(reports_b.len(), 1);
+    assert_eq!(reports_b[0].reported_id, spammer.ap_user_id);
+    assert_eq!(reports_b[0].bookmark_id, Some(bookmark.id));
+    assert_eq!(reports_b[0].comment, "Spam");
+    drop(tx_b);
+
+    let queue = app_b.req().get("/admin/reports").await.test_page().await;
+    let text = queue.dom.find("main").text();
+    assert!(text.contains("@spammer@"));
+    assert!(text.contains("Buy now"));
+    assert!(text.contains("Spam"));
+
+    app_b
+        .req()
+        .expect_status(StatusCode::SEE_OTHER)
+        .post(
+            &format!("/admin/reports/{}/suspend", reports_b[0].id),
+            &HashMap::<String, String>::new(),
+        )
+        .await;
+
+    let mut tx_b = app_b.tx().await;
+    assert!(db::reports::list_open(&mut tx_b).await?.is_empty());
+    assert!(db::reports::is_suspended(&mut tx_b, spammer.ap_user_id).await?);
+    drop(tx_b);
+
+    // Suspended users can't log in anymore
+    let login_page = app_b.req().get("/login").await.test_page().await;
+    let login_response = login_page
+        .fill_form(
+            "form",
+            &Login {
+                credentials: Credentials {
+                    username: "spammer".to_string(),
+                    password: "testpassword".to_string(),
+                },
+                previous_uri: None,
+            },
+        )
+        .await
+        .test_page()
+        .await;
+    assert!(
+        login_response
+            .dom
+            .find("form")
+            .text()
+            .contains("Username or password not correct")
+    );
+
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn deleting_reported_bookmarks_federates() -> Result<()> {
+    let TwoInstances {
+        mut app_a,
+        ap_user_a,
+        mut app_b,
+        ..
+    } = TwoInstances::serve().await?;
+    app_a.login_test_user().await;
+    app_b.login_test_user().await;
+    let ap_cx_a = app_a.state.federation_config.to_request_data();
+
+    let spammer = app_b.create_user("spammer", "testpassword").await;
+    let mut tx_b = app_b.tx().await;
+    let spammer_ap_user = db::ap_users::read_by_id(&mut tx_b, spammer.ap_user_id).await?;
+    drop(tx_b);
+    federation::Follow::new(&ap_user_a, &spammer_ap_user, &ap_cx_a)?
+        .send(&ap_user_a, &spammer_ap_user, &ap_cx_a)
+        .await?;
+
+    let bookmark = app_b.insert_public_bookmark(&spammer, "Buy now").await;
+    let remote_bookmark = bookmark.ap_id.dereference(&ap_cx_a).await?;
+    app_a
+        .req()
+        .expect_status(StatusCode::SEE_OTHER)
+        .post(
+            &format!("{}/report", remote_bookmark.path()),
+            &CreateReport {
+                comment: "Spam".to_string(),
+            },
+        )
+        .await;
+
+    let mut tx_b = app_b.tx().await;
+    let report = db::reports::list_open(&mut tx_b).await?.pop().unwrap();
+    drop(tx_b);
+    app_b
+        .req()
+        .expect_status(StatusCode::SEE_OTHER)
+        .post(
+            &format!("/admin/reports/{}/delete_content", report.id),
+            &HashMap::<String, String>::new(),
+        )
+        .await;
+
+    // The followers of the spammer don't keep a copy of the bookmark
+    let mut tx_a = app_a.tx().await;
+    assert!(into_option(db::bookmarks::by_ap_id(&mut tx_a, bookmark.ap_id).await)?.is_none());
+
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn deleted_accounts_are_removed_remotely() -> Result<()> {
+    let TwoInstances {
+        app_a,
+        ap_user_a,
+        mut app_b,
+        ..
+    } = TwoInstances::serve().await?;
+    app_b.login_test_user().await;
+    let ap_cx_a = app_a.state.federation_config.to_request_data();
+
+    let leaver = app_b.create_user("leaver", "testpassword").await;
+    let mut tx_b = app_b.tx().await;
+    let leaver_ap_user = db::ap_users::read_by_id(&mut tx_b, leaver.ap_user_id).await?;
+    drop(tx_b);
+    federation::Follow::new(&ap_user_a, &leaver_ap_user, &ap_cx_a)?
+        .send(&ap_user_a, &leaver_ap_user, &ap_cx_a)
+        .await?;
+
+    app_b
+        .req()
+        .expect_status(StatusCode::SEE_OTHER)
+        .post(
+            &format!("/admin/users/{}/delete", leaver.id),
+            &HashMap::<String, String>::new(),
+        )
+        .await;
+
+    let mut tx_a = app_a.tx().await;
+    assert!(
+        into_option(db::ap_users::read_by_ap_id(&mut tx_a, leaver_ap_user.ap_id.inner()).await)?
+            .is_none()
+    );
+
+    Ok(())
+}
@@ -0,0 +1,68 @@
+use crate::{
+    cli::UserCommand,
+    db::{self, users::Role},
+    manage_users,
+    tests::util::test_app::TestApp,
+};
+
+#[test_log::test(tokio::test)]
+async fn can_manage_users_on_the_command_line() -> anyhow::Result<()> {
+    let mut app = TestApp::new().await;
+
+    manage_users::run(
+        &app.pool,
+        &app.base_url,
+        UserCommand::Create {
+            username: "operator".to_string(),
+            password: Some(redact::Secret::new("operatorpassword".to_string())),
+            admin: false,
+        },
+    )
+    .await?;
+    manage_users::run(
+        &app.pool,
+        &app.base_url,
+        UserCommand::SetRole {
+            username: "operator".to_string(),
+            role: Role::Admin,
+        },
+    )
+    .await?;
+    for command in [
+        UserCommand::List,
+        UserCommand::Show {
+            handle: "@operator".to_string(),
+        },
+    ] {
+        manage_users::run(&app.pool, &app.base_url, command).await?;
+    }
+
+    app.login_user("operator", "operatorpassword").await;
+    app.req().get("/admin").await.test_page().await;
+
+    // Usernames can't be taken twice
+    let duplicate = manage_users::run(
+        &app.pool,
+        &app.base_url,
+        UserCommand::Create {
+            username: "Operator".to_string(),
+            password: None,
+            admin: false,
+        },
+    )
+    .await;
+    assert!(duplicate.is_err());
+
+    manage_users::run(
+        &app.pool,
+        &app.base_url,
+        UserCommand::Delete {
+            username: "operator".to_string(),
+        },
+    )
+    .await?;
+    let mut tx = app.tx().await;
+    assert!(db::users::by_username(&mut tx, "operator").await.is_err());
+
+    Ok(())
+}
@@ -2,11 +2,18 @@
 //! for information on why our tests are inside the `src` folder.
 #![expect(clippy::unwrap_used)]
 #![expect(clippy::expect_used)]
+mod account_settings;
+mod admin;
 mod bookmarks;
 mod federation;
 mod index;
 mod lists;
+mod manage_users;
 mod migrations;
+mod privacy;
+mod registrations;
 mod response_error;
+mod search;
+mod two_factor;
 mod users;
 mod util;
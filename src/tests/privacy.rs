@@ -0,0 +1,196 @@
+use std::collections::HashMap;
+
+use axum::http::{StatusCode, header};
+
+use crate::{
+    db::{self, bookmarks::InsertBookmark},
+    forms::{links::CreateLink, lists::CreateList, users::CreateUser},
+    tests::util::test_app::TestApp,
+};
+
+struct PrivateData {
+    list: db::List,
+    bookmark: db::Bookmark,
+    link: db::links::Link,
+}
+
+/// Create two regular users. The first one has a bookmark in a private list.
+async fn create_users(app: &TestApp) -> anyhow::Result<PrivateData> {
+    let mut tx = app.tx().await;
+    let owner = db::users::insert(
+        &mut tx,
+        CreateUser {
+            username: "owner".to_string(),
+            password: "ownerpassword".to_string(),
+        },
+        &app.base_url,
+    )
+    .await?;
+    db::users::insert(
+        &mut tx,
+        CreateUser {
+            username: "other".to_string(),
+            password: "otherpassword".to_string(),
+        },
+        &app.base_url,
+    )
+    .await?;
+
+    let list = db::lists::insert(
+        &mut tx,
+        owner.ap_user_id,
+        CreateList {
+            title: "Secret list".to_string(),
+            content: None,
+            private: true,
+        },
+    )
+    .await?;
+    let bookmark = db::bookmarks::insert_local(
+        &mut tx,
+        owner.ap_user_id,
+        InsertBookmark {
+            url: "https://example.com".to_string(),
+            title: "Secret bookmark".to_string(),
+        },
+        &app.base_url,
+    )
+    .await?;
+    let link = db::links::insert(
+        &mut tx,
+        owner.id,
+        CreateLink {
+            src: list.id,
+            dest: bookmark.id,
+        },
+    )
+    .await?;
+    tx.commit().await?;
+
+    Ok(PrivateData {
+        list,
+        bookmark,
+        link,
+    })
+}
+
+#[test_log::test(tokio::test)]
+async fn users_cannot_see_private_data_of_others() -> anyhow::Result<()> {
+    let mut app = TestApp::new().await;
+    let PrivateData { list, bookmark, .. } = create_users(&app).await?;
+
+    let mut tx = app.tx().await;
+    let unsorted = db::bookmarks::insert_local(
+        &mut tx,
+        list.ap_user_id,
+        InsertBookmark {
+            url: "https://example.org".to_string(),
+            title: "Unsorted bookmark".to_string(),
+        },
+        &app.base_url,
+    )
+    .await?;
+    tx.commit().await?;
+
+    app.login_user("other", "otherpassword").await;
+
+    for path in [list.path(), bookmark.path(), unsorted.path()] {
+        app.req()
+            .expect_status(StatusCode::NOT_FOUND)
+            .get(&path)
+            .await;
+        app.req()
+            .header(header::ACCEPT, "application/activity+json")
+            .expect_status(StatusCode::NOT_FOUND)
+            .get(&path)
+            .await;
+    }
+
+    let own_unsorted = app.req().get("/bookmarks/unsorted").await.test_page().await;
+    assert!(!own_unsorted.dom.text().contains("Unsorted bookmark"));
+
+    app.req()
+        .expect_status(StatusCode::NOT_FOUND)
+        .get(&format!("/links/create?dest_id={}", list.id))
+        .await;
+    let create_bookmark = app
+        .req()
+        .get(&format!("/bookmarks/create?parent_id={}", list.id))
+        .await
+        .test_page()
+        .await;
+    assert!(!create_bookmark.dom.text().contains("Secret list"));
+
+    // The owner can still see everything
+    app.logged_in_cookie = None;
+    app.login_user("owner", "ownerpassword").await;
+    let show_list = app.req().get(&list.path()).await.test_page().await;
+    assert!(show_list.dom.text().contains("Secret bookmark"));
+    app.req().get(&unsorted.path()).await;
+
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn users_cannot_change_data_of_others() -> anyhow::Result<()> {
+    let mut app = TestApp::new().await;
+    let PrivateData {
+        list,
+        bookmark,
+        link,
+    } = create_users(&app).await?;
+    app.login_user("other", "otherpassword").await;
+
+    app.req()
+        .expect_status(StatusCode::NOT_FOUND)
+        .post(
+            &format!("/lists/{}/edit_title", list.id),
+            &HashMap::from([("title", "Hijacked")]),
+        )
+        .await;
+
+    app.req()
+        .expect_status(StatusCode::NOT_FOUND)
+        .post(
+            "/bookmarks/create",
+            &serde_json::json!({
+                "url": "https://example.net",
+                "title": "Intruder",
+                "parents": [list.id],
+                "submitted": true,
+            }),
+        )
+        .await;
+
+    app.req()
+        .expect_status(StatusCode::NOT_FOUND)
+        .post(
+            "/links/create",
+            &serde_json::json!({
+                "src": list.id,
+                "dest": bookmark.id,
+                "submitted": true,
+            }),
+        )
+        .await;
+
+    app.req()
+        .expect_status(StatusCode::NOT_FOUND)
+        .delete(&format!("/links/{}", link.id))
+        .await;
+    app.req()
+        .expect_status(StatusCode::NOT_FOUND)
+        .delete(&bookmark.path())
+        .await;
+
+    let mut tx = app.tx().await;
+    assert_eq!(
+        db::lists::by_id(&mut tx, list.id).await?.title,
+        "Secret list"
+    );
+    assert!(db::bookmarks::by_id(&mut tx, bookmark.id).await.is_ok());
+    let links = db::links::list_by_list(&mut tx, list.id, Some(list.ap_user_id)).await?;
+    assert_eq!(links.len(), 1);
+
+    Ok(())
+}
@@ -0,0 +1,272 @@
+use std::collections::HashMap;
+
+use axum::http::StatusCode;
+
+use crate::{
+    db::{self, registrations::RegistrationMode, users::Role},
+    forms::{
+        registrations::EditRegistrationSettings,
+        users::{CreateUser, SignUp},
+    },
+    tests::util::test_app::TestApp,
+};
+
+async fn set_registration_mode(app: &mut TestApp, registration_mode: RegistrationMode) {
+    let input = EditRegistrationSettings {
+        registration_mode,
+        reserved_usernames: "Admin, moderator".to_string(),
+        min_username_length: 5,
+    };
+    app.req()
+        .expect_status(StatusCode::SEE_OTHER)
+        .post("/admin/registrations", &input)
+        .await;
+}
+
+fn sign_up(username: &str, password: &str) -> SignUp {
+    SignUp {
+        user: CreateUser {
+            username: username.to_string(),
+            password: password.to_string(),
+        },
+        invite_code: None,
+        reason: None,
+    }
+}
+
+#[test_log::test(tokio::test)]
+async fn registrations_are_closed_by_default() -> anyhow::Result<()> {
+    let mut app = TestApp::new().await;
+
+    let signup = app.req().get("/signup").await.test_page().await;
+    assert!(
+        signup
+            .dom
+            .find("h1")
+            .text()
+            .contains("Registrations are closed")
+    );
+    assert_eq!(signup.dom.find("form[action='/signup']").length(), 0);
+
+    app.req()
+        .expect_status(StatusCode::NOT_FOUND)
+        .post("/signup", &sign_up("newuser", "newpassword"))
+        .await;
+
+    let mut tx = app.tx().await;
+    assert!(db::users::by_username(&mut tx, "newuser").await.is_err());
+
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn can_sign_up_while_registration_is_open() -> anyhow::Result<()> {
+    let mut app = TestApp::new().await;
+    app.create_test_user().await;
+    app.login_test_user().await;
+    set_registration_mode(&mut app, RegistrationMode::Open).await;
+    app.logged_in_cookie = None;
+
+    let login = app.req().get("/login").await.test_page().await;
+    assert_eq!(login.dom.find("a[href='/signup']").length(), 1);
+
+    for (username, error) in [
+        ("moderator", "This username is reserved"),
+        ("ADMIN", "This username is reserved"),
+        ("abcd", "Usernames need at least 5 characters"),
+        ("TestUser", "This username is taken"),
+    ] {
+        let signup = app
+            .req()
+            .get("/signup")
+            .await
+            .test_page()
+            .await
+            .fill_form("form", &sign_up(username, "newpassword"))
+            .await
+            .test_page()
+            .await;
+        assert!(signup.dom.find("form").text().contains(error), "{username}");
+    }
+
+    app.req()
+        .get("/signup")
+        .await
+        .test_page()
+        .await
+        .expect_status(StatusCode::SEE_OTHER)
+        .fill_form("form", &sign_up("newuser", "newpassword"))
+        .await;
+
+    let mut tx = app.tx().await;
+    let user = db::users::by_username(&mut tx, "newuser").await?;
+    drop(tx);
+    assert_eq!(user.role, Role::User);
+
+    app.login_user("newuser", "newpassword").await;
+
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn invites_can_only_be_used_once() -> anyhow::Result<()> {
+    let mut app = TestApp::new().await;
+    let admin = app.create_test_user().await;
+    app.login_test_user().await;
+    set_registration_mode(&mut app, RegistrationMode::Invite).await;
+    app.req()
+        .expect_status(StatusCode::SEE_OTHER)
+        .post("/admin/invites", &HashMap::<String, String>::new())
+        .await;
+    let admin_cookie = app.logged_in_cookie.take();
+
+    let mut tx = app.tx().await;
+    let invites = db::registrations::list_invites(&mut tx).await?;
+    drop(tx);
+    assert_eq!(invites.len(), 1);
+    let code = invites[0].code.clone();
+
+    let mut wrong_code = sign_up("newuser", "newpassword");
+    wrong_code.invite_code = Some("not-a-code".to_string());
+    let signup = app
+        .req()
+        .get("/signup")
+        .await
+        .test_page()
+        .await
+        .fill_form("form", &wrong_code)
+        .await
+        .test_page()
+        .await;
+    assert!(
+        signup
+            .dom
+            .find("form")
+            .text()
+            .contains("This invite code is not valid")
+    );
+
+    let signup_page = app
+        .req()
+        .get(&format!("/signup?invite_code={code}"))
+        .await
+        .test_page()
+        .await;
+    assert_eq!(
+        signup_page
+            .dom
+            .find("input[name='invite_code']")
+            .attr("value")
+            .map(|value| value.to_string()),
+        Some(code.clone())
+    );
+
+    let mut with_code = sign_up("newuser", "newpassword");
+    with_code.invite_code = Some(code.clone());
+    app.req()
+        .expect_status(StatusCode::SEE_OTHER)
+        .post("/signup", &with_code)
+        .await;
+
+    let mut reused_code = sign_up("otheruser", "otherpassword");
+    reused_code.invite_code = Some(code.clone());
+    let signup = app
+        .req()
+        .post("/signup", &reused_code)
+        .await
+        .test_page()
+        .await;
+    assert!(
+        signup
+            .dom
+            .find("form")
+            .text()
+            .contains("This invite code is not valid")
+    );
+
+    // Sign-ups racing for the same code both pass the check above, but only
+    // one of them can redeem it
+    let mut tx = app.tx().await;
+    assert!(!db::registrations::redeem_invite(&mut tx, &code, admin.id).await?);
+    drop(tx);
+
+    app.logged_in_cookie = admin_cookie;
+    let registrations = app
+        .req()
+        .get("/admin/registrations")
+        .await
+        .test_page()
+        .await;
+    assert!(registrations.dom.text().contains("used by newuser"));
+
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn sign_ups_wait_for_approval() -> anyhow::Result<()> {
+    let mut app = TestApp::new().await;
+    app.create_test_user().await;
+    app.login_test_user().await;
+    set_registration_mode(&mut app, RegistrationMode::Approval).await;
+    let admin_cookie = app.logged_in_cookie.take();
+
+    let mut input = sign_up("newuser", "newpassword");
+    input.reason = Some("I collect links".to_string());
+    let pending = app.req().post("/signup", &input).await.test_page().await;
+    assert!(
+        pending
+            .dom
+            .find("h1")
+            .text()
+            .contains("Thanks for signing up")
+    );
+
+    let mut tx = app.tx().await;
+    assert!(db::users::by_username(&mut tx, "newuser").await.is_err());
+    let requests = db::registrations::list_requests(&mut tx).await?;
+    drop(tx);
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].reason, "I collect links");
+
+    // The username stays taken while the request is pending
+    let signup = app
+        .req()
+        .post("/signup", &sign_up("NewUser", "otherpassword"))
+        .await
+        .test_page()
+        .await;
+    assert!(
+        signup
+            .dom
+            .find("form")
+            .text()
+            .contains("This username is taken")
+    );
+
+    app.logged_in_cookie = admin_cookie;
+    let registrations = app
+        .req()
+        .get("/admin/registrations")
+        .await
+        .test_page()
+        .await;
+    assert!(registrations.dom.text().contains("I collect links"));
+    app.req()
+        .expect_status(StatusCode::SEE_OTHER)
+        .post(
+            &format!("/admin/registration_requests/{}/approve", requests[0].id),
+            &HashMap::<String, String>::new(),
+        )
+        .await;
+
+    let mut tx = app.tx().await;
+    let user = db::users::by_username(&mut tx, "newuser").await?;
+    assert!(db::registrations::list_requests(&mut tx).await?.is_empty());
+    drop(tx);
+    assert_eq!(user.role, Role::User);
+
+    app.logged_in_cookie = None;
+    app.login_user("newuser", "newpassword").await;
+
+    Ok(())
+}
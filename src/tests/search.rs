@@ -0,0 +1,158 @@
+use std::collections::HashMap;
+
+use axum::http::StatusCode;
+
+use crate::{
+    db::{self, bookmarks::InsertBookmark},
+    forms::{links::CreateLink, lists::CreateList},
+    tests::util::test_app::TestApp,
+};
+
+async fn insert_bookmark(
+    app: &TestApp,
+    user: &db::User,
+    url: &str,
+    title: &str,
+    private: bool,
+) -> anyhow::Result<()> {
+    let mut tx = app.tx().await;
+    let bookmark = db::bookmarks::insert_local(
+        &mut tx,
+        user.ap_user_id,
+        InsertBookmark {
+            url: url.to_string(),
+            title: title.to_string(),
+        },
+        &app.base_url,
+    )
+    .await?;
+    let list = db::lists::insert(
+        &mut tx,
+        user.ap_user_id,
+        CreateList {
+            title: "Bookmarks".to_string(),
+            content: None,
+            private,
+        },
+    )
+    .await?;
+    db::links::insert(
+        &mut tx,
+        user.id,
+        CreateLink {
+            src: list.id,
+            dest: bookmark.id,
+        },
+    )
+    .await?;
+    tx.commit().await?;
+
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn searches_bookmarks_of_trusted_users() -> anyhow::Result<()> {
+    let mut app = TestApp::new().await;
+    let user = app.create_test_user().await;
+    let trusted = app.create_user("trusted", "testpassword").await;
+    let trusted_of_trusted = app.create_user("trusted_of_trusted", "testpassword").await;
+    let stranger = app.create_user("stranger", "testpassword").await;
+
+    insert_bookmark(&app, &trusted, "https://www.rust-lang.org", "Rust", false).await?;
+    insert_bookmark(
+        &app,
+        &trusted,
+        "https://private.example",
+        "Rust secrets",
+        true,
+    )
+    .await?;
+    insert_bookmark(
+        &app,
+        &trusted_of_trusted,
+        "https://www.rust-lang.org",
+        "Rust language",
+        false,
+    )
+    .await?;
+    insert_bookmark(
+        &app,
+        &trusted_of_trusted,
+        "https://docs.rs",
+        "Rust docs",
+        false,
+    )
+    .await?;
+    insert_bookmark(
+        &app,
+        &stranger,
+        "https://rust.example",
+        "Rust elsewhere",
+        false,
+    )
+    .await?;
+
+    app.login_test_user().await;
+    app.req()
+        .expect_status(StatusCode::SEE_OTHER)
+        .post("/user/trusted/trust", &HashMap::<String, String>::new())
+        .await;
+    let mut tx = app.tx().await;
+    db::trusts::insert(
+        &mut tx,
+        db::trusts::Insert {
+            truster_id: trusted.ap_user_id,
+            trusted_id: trusted_of_trusted.ap_user_id,
+        },
+    )
+    .await?;
+    tx.commit().await?;
+
+    let mut tx = app.tx().await;
+    let direct = db::bookmarks::search_trusted(&mut tx, "rust", user.ap_user_id, false, 10).await?;
+    assert_eq!(direct.len(), 1);
+    assert_eq!(direct[0].url, "https://www.rust-lang.org");
+    assert_eq!(direct[0].saved_by_count, 1);
+
+    let wide = db::bookmarks::search_trusted(&mut tx, "rust", user.ap_user_id, true, 10).await?;
+    let urls = wide
+        .iter()
+        .map(|result| result.url.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(urls, vec!["https://www.rust-lang.org", "https://docs.rs"]);
+    assert_eq!(wide[0].saved_by_count, 2);
+    // Wildcards in the search term are matched literally
+    assert!(
+        db::bookmarks::search_trusted(&mut tx, "%", user.ap_user_id, true, 10)
+            .await?
+            .is_empty()
+    );
+    drop(tx);
+
+    let search_page = app
+        .req()
+        .get("/search?q=rust&include_trusted_of_trusted=true")
+        .await
+        .test_page()
+        .await;
+    assert!(
+        search_page
+            .dom
+            .find("main")
+            .text()
+            .contains("Saved by 2 people you trust")
+    );
+
+    app.req()
+        .expect_status(StatusCode::SEE_OTHER)
+        .post("/user/trusted/untrust", &HashMap::<String, String>::new())
+        .await;
+    let mut tx = app.tx().await;
+    assert!(
+        db::bookmarks::search_trusted(&mut tx, "rust", user.ap_user_id, true, 10)
+            .await?
+            .is_empty()
+    );
+
+    Ok(())
+}
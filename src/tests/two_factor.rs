@@ -0,0 +1,259 @@
+use std::collections::HashMap;
+
+use axum::http::StatusCode;
+use totp_rs::{Algorithm, Secret, TOTP};
+
+use crate::{
+    cli::UserCommand,
+    db,
+    forms::users::{Credentials, Login},
+    manage_users,
+    tests::util::test_app::TestApp,
+    totp::RECOVERY_CODE_COUNT,
+};
+
+fn current_code(secret: &str) -> String {
+    let secret = Secret::Encoded(secret.to_string()).to_bytes().unwrap();
+    TOTP::new(Algorithm::SHA1, 6, 1, 30, secret, None, String::new())
+        .unwrap()
+        .generate_current()
+        .unwrap()
+}
+
+/// Enable two-factor authentication for the logged in user. Returns the
+/// secret and the first recovery code.
+async fn enable_totp(app: &mut TestApp) -> (String, String) {
+    let enroll = app
+        .req()
+        .get("/settings/account/totp")
+        .await
+        .test_page()
+        .await;
+    assert_eq!(
+        enroll.dom.find("img[src^='data:image/svg+xml']").length(),
+        1
+    );
+    let secret = enroll.dom.find("p.font-mono").text();
+
+    let wrong_code = app
+        .req()
+        .post(
+            "/settings/account/totp",
+            &HashMap::from([("code", "000000")]),
+        )
+        .await
+        .test_page()
+        .await;
+    assert!(wrong_code.dom.text().contains("Code not correct"));
+    // The secret stays the same until it's confirmed
+    assert_eq!(wrong_code.dom.find("p.font-mono").text(), secret);
+
+    let enabled = app
+        .req()
+        .post(
+            "/settings/account/totp",
+            &HashMap::from([("code", current_code(&secret))]),
+        )
+        .await
+        .test_page()
+        .await;
+    let recovery_codes = enabled.dom.find("ul.font-mono li");
+    assert_eq!(recovery_codes.length(), RECOVERY_CODE_COUNT);
+
+    (secret, recovery_codes.first().text())
+}
+
+/// Log out and enter the password, which should ask for a second factor.
+async fn enter_password(app: &mut TestApp, username: &str, password: &str) {
+    app.logged_in_cookie = None;
+    let input = Login {
+        credentials: Credentials {
+            username: username.to_string(),
+            password: password.to_string(),
+        },
+        previous_uri: None,
+    };
+    let response = app
+        .req()
+        .get("/login")
+        .await
+        .test_page()
+        .await
+        .fill_form("form", &input)
+        .await;
+
+    // Keep the session that remembers the correct password
+    let cookie = response.headers().get("Set-Cookie").unwrap();
+    let cookie = cookie.to_str().unwrap().split_once(';').unwrap().0;
+    app.logged_in_cookie = Some(cookie.to_string());
+
+    let code_page = response.test_page().await;
+    assert_eq!(code_page.dom.find("form[action='/login/totp']").length(), 1);
+}
+
+#[test_log::test(tokio::test)]
+async fn can_log_in_with_two_factor_authentication() -> anyhow::Result<()> {
+    let mut app = TestApp::new().await;
+    app.create_user("alice", "alicepassword").await;
+    app.login_user("alice", "alicepassword").await;
+    let (secret, recovery_code) = enable_totp(&mut app).await;
+
+    let settings = app.req().get("/settings/account").await.test_page().await;
+    assert!(
+        settings
+            .dom
+            .text()
+            .contains(&format!("{RECOVERY_CODE_COUNT} unused recovery codes"))
+    );
+
+    // The password alone isn't enough
+    enter_password(&mut app, "alice", "alicepassword").await;
+    app.req()
+        .expect_status(StatusCode::SEE_OTHER)
+        .get("/settings/account")
+        .await;
+    let wrong_code = app
+        .req()
+        .post("/login/totp", &HashMap::from([("code", "000000")]))
+        .await
+        .test_page()
+        .await;
+    assert!(wrong_code.dom.text().contains("Code not correct"));
+
+    app.req()
+        .expect_status(StatusCode::SEE_OTHER)
+        .post(
+            "/login/totp",
+            &HashMap::from([("code", current_code(&secret))]),
+        )
+        .await;
+    app.req().get("/settings/account").await;
+
+    // Recovery codes work exactly once
+    enter_password(&mut app, "alice", "alicepassword").await;
+    app.req()
+        .expect_status(StatusCode::SEE_OTHER)
+        .post("/login/totp", &HashMap::from([("code", &recovery_code)]))
+        .await;
+    let settings = app.req().get("/settings/account").await.test_page().await;
+    assert!(settings.dom.text().contains(&format!(
+        "{} unused recovery codes",
+        RECOVERY_CODE_COUNT - 1
+    )));
+
+    enter_password(&mut app, "alice", "alicepassword").await;
+    let used_code = app
+        .req()
+        .post("/login/totp", &HashMap::from([("code", &recovery_code)]))
+        .await
+        .test_page()
+        .await;
+    assert!(used_code.dom.text().contains("Code not correct"));
+
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn one_time_passwords_work_once() -> anyhow::Result<()> {
+    let mut app = TestApp::new().await;
+    app.create_user("alice", "alicepassword").await;
+    app.login_user("alice", "alicepassword").await;
+    let (secret, _recovery_code) = enable_totp(&mut app).await;
+    let code = current_code(&secret);
+
+    enter_password(&mut app, "alice", "alicepassword").await;
+    app.req()
+        .expect_status(StatusCode::SEE_OTHER)
+        .post("/login/totp", &HashMap::from([("code", &code)]))
+        .await;
+
+    // Someone who saw the code can't use it while it's still valid
+    enter_password(&mut app, "alice", "alicepassword").await;
+    let replayed = app
+        .req()
+        .post("/login/totp", &HashMap::from([("code", &code)]))
+        .await
+        .test_page()
+        .await;
+    assert!(replayed.dom.text().contains("Code not correct"));
+
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn wrong_codes_require_the_password_again() -> anyhow::Result<()> {
+    let mut app = TestApp::new().await;
+    app.create_user("alice", "alicepassword").await;
+    app.login_user("alice", "alicepassword").await;
+    let (secret, _recovery_code) = enable_totp(&mut app).await;
+
+    enter_password(&mut app, "alice", "alicepassword").await;
+    for _ in 0..crate::totp::MAX_FAILED_ATTEMPTS {
+        app.req()
+            .post("/login/totp", &HashMap::from([("code", "000000")]))
+            .await;
+    }
+
+    let response = app
+        .req()
+        .expect_status(StatusCode::SEE_OTHER)
+        .post(
+            "/login/totp",
+            &HashMap::from([("code", current_code(&secret))]),
+        )
+        .await;
+    assert_eq!(response.headers().get("Location").unwrap(), "/login");
+
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn can_disable_two_factor_authentication() -> anyhow::Result<()> {
+    let mut app = TestApp::new().await;
+    let user = app.create_user("alice", "alicepassword").await;
+    app.login_user("alice", "alicepassword").await;
+    let (secret, _recovery_code) = enable_totp(&mut app).await;
+
+    let wrong_code = app
+        .req()
+        .post(
+            "/settings/account/totp/disable",
+            &HashMap::from([("code", "000000")]),
+        )
+        .await
+        .test_page()
+        .await;
+    assert!(wrong_code.dom.text().contains("Code not correct"));
+
+    app.req()
+        .expect_status(StatusCode::SEE_OTHER)
+        .post(
+            "/settings/account/totp/disable",
+            &HashMap::from([("code", current_code(&secret))]),
+        )
+        .await;
+    let mut tx = app.tx().await;
+    assert!(
+        db::users::by_id(&mut tx, user.id)
+            .await?
+            .totp_secret
+            .is_none()
+    );
+    assert_eq!(db::recovery_codes::count(&mut tx, user.id).await?, 0);
+    drop(tx);
+
+    // Admins can disable it on the command line for locked-out users
+    enable_totp(&mut app).await;
+    manage_users::run(
+        &app.pool,
+        &app.base_url,
+        UserCommand::DisableTotp {
+            username: "alice".to_string(),
+        },
+    )
+    .await?;
+    app.logged_in_cookie = None;
+    app.login_user("alice", "alicepassword").await;
+
+    Ok(())
+}
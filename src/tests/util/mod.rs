@@ -2,3 +2,4 @@ pub mod db;
 pub mod dom;
 pub mod request_builder;
 pub mod test_app;
+pub mod two_instances;
@@ -96,6 +96,30 @@ impl RequestBuilder {
         }
     }
 
+    pub async fn delete(mut self, url: &str) -> TestResponse {
+        let request = self
+            .request
+            .method(http::Method::DELETE)
+            .uri(url)
+            .body(Body::empty())
+            .unwrap();
+
+        let response = ServiceExt::<Request<Body>>::ready(&mut self.router)
+            .await
+            .unwrap()
+            .call(request)
+            .await
+            .unwrap();
+
+        Self::assert_expected_status(self.expected_status, &response, "DELETE", url);
+
+        TestResponse {
+            response,
+            router: self.router,
+            original_url: url.to_string(),
+        }
+    }
+
     fn assert_expected_status(
         expected_status: StatusCode,
         response: &Response<Body>,
@@ -130,6 +154,17 @@ impl TestResponse {
         Vis::load(String::from_utf8(body).unwrap()).unwrap()
     }
 
+    pub async fn json(self) -> serde_json::Value {
+        let body = self
+            .response
+            .into_body()
+            .collect()
+            .await
+            .unwrap()
+            .to_bytes();
+        serde_json::from_slice(&body).unwrap()
+    }
+
     pub fn headers(&self) -> &HeaderMap {
         self.response.headers()
     }
@@ -7,9 +7,9 @@ use url::Url;
 
 use super::request_builder::RequestBuilder;
 use crate::{
-    db::{self, AppTx},
+    db::{self, AppTx, bookmarks::InsertBookmark},
     federation,
-    forms::users::CreateUser,
+    forms::{links::CreateLink, lists::CreateList, users::CreateUser},
     server::{AppState, app},
 };
 
@@ -29,6 +29,15 @@ pub struct TestApp {
 
 impl TestApp {
     pub async fn new() -> Self {
+        Self::build(false).await
+    }
+
+    /// An instance that only serves ActivityPub objects to signed requests.
+    pub async fn with_authorized_fetch() -> Self {
+        Self::build(true).await
+    }
+
+    async fn build(authorized_fetch: bool) -> Self {
         let port = NEXT_TEST_APP_PORT.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
         let base_url = Url::parse(&format!("http://localhost:{port}",))
             .expect("Failed to parse URL for test instance");
@@ -43,9 +52,14 @@ impl TestApp {
             base_url: base_url.clone(),
             demo_mode: false,
             oidc_state: crate::oidc::State::NotConfigured,
-            federation_config: federation::config::new_config(pool.clone(), base_url.clone())
-                .await
-                .unwrap(),
+            activity_log_retention: time::Duration::days(7),
+            federation_config: federation::config::new_config(
+                pool.clone(),
+                base_url.clone(),
+                authorized_fetch,
+            )
+            .await
+            .unwrap(),
         };
 
         TestApp {
@@ -129,6 +143,47 @@ impl TestApp {
         self.logged_in_cookie = Some(cookie.to_string());
     }
 
+    /// Insert a bookmark of the given user into a new public list, so that it
+    /// federates.
+    pub async fn insert_public_bookmark(&self, user: &db::User, title: &str) -> db::Bookmark {
+        let mut tx = self.tx().await;
+        let list = db::lists::insert(
+            &mut tx,
+            user.ap_user_id,
+            CreateList {
+                title: "Public".to_string(),
+                content: None,
+                private: false,
+            },
+        )
+        .await
+        .expect("Failed to create list");
+        let bookmark = db::bookmarks::insert_local(
+            &mut tx,
+            user.ap_user_id,
+            InsertBookmark {
+                url: "https://www.rafa.ee".to_string(),
+                title: title.to_string(),
+            },
+            &self.base_url,
+        )
+        .await
+        .expect("Failed to create bookmark");
+        db::links::insert(
+            &mut tx,
+            user.id,
+            CreateLink {
+                src: list.id,
+                dest: bookmark.id,
+            },
+        )
+        .await
+        .expect("Failed to create link");
+        tx.commit().await.expect("Failed to commit transaction");
+
+        bookmark
+    }
+
     pub async fn serve(&self) {
         let listener = TcpListener::bind(format!("localhost:{}", self.port))
             .await
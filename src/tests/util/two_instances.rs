@@ -0,0 +1,43 @@
+use anyhow::Result;
+
+use super::test_app::TestApp;
+use crate::db;
+
+/// Two instances with a test user each, serving requests so that they can
+/// federate with each other.
+pub struct TwoInstances {
+    pub app_a: TestApp,
+    pub user_a: db::User,
+    pub ap_user_a: db::ApUser,
+    pub app_b: TestApp,
+    pub user_b: db::User,
+    pub ap_user_b: db::ApUser,
+}
+
+impl TwoInstances {
+    pub async fn serve() -> Result<Self> {
+        let app_a = TestApp::new().await;
+        let user_a = app_a.create_test_user().await;
+        let mut tx_a = app_a.tx().await;
+        let ap_user_a = db::ap_users::read_by_id(&mut tx_a, user_a.ap_user_id).await?;
+        drop(tx_a);
+
+        let app_b = TestApp::new().await;
+        let user_b = app_b.create_test_user().await;
+        let mut tx_b = app_b.tx().await;
+        let ap_user_b = db::ap_users::read_by_id(&mut tx_b, user_b.ap_user_id).await?;
+        drop(tx_b);
+
+        app_a.serve().await;
+        app_b.serve().await;
+
+        Ok(TwoInstances {
+            app_a,
+            user_a,
+            ap_user_a,
+            app_b,
+            user_b,
+            ap_user_b,
+        })
+    }
+}
@@ -0,0 +1,178 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, anyhow};
+use percent_encoding::{NON_ALPHANUMERIC, utf8_percent_encode};
+use serde::{Deserialize, Serialize};
+use totp_rs::{Algorithm, Secret, TOTP};
+use tower_sessions::Session;
+use url::Url;
+use uuid::Uuid;
+
+use crate::response_error::ResponseResult;
+
+/// How many recovery codes users get when enabling two-factor authentication
+pub const RECOVERY_CODE_COUNT: usize = 10;
+
+/// How long each one-time password is valid
+const STEP_SECONDS: u64 = 30;
+
+/// After this many wrong codes, users have to enter their password again
+pub const MAX_FAILED_ATTEMPTS: u8 = 5;
+
+/// A new random secret, encoded in base32 as expected by authenticator apps.
+pub fn generate_secret() -> String {
+    Secret::generate_secret().to_encoded().to_string()
+}
+
+/// Random codes that can be used once instead of a one-time password.
+pub fn generate_recovery_codes() -> Vec<String> {
+    (0..RECOVERY_CODE_COUNT)
+        .map(|_| {
+            let random = Uuid::new_v4().simple().to_string();
+            format!("{}-{}-{}", &random[0..4], &random[4..8], &random[8..12])
+        })
+        .collect()
+}
+
+/// Check whether the input looks like one of our recovery codes, so that we
+/// only hash inputs that could possibly match.
+pub fn is_recovery_code(code: &str) -> bool {
+    let groups: Vec<&str> = code.trim().split('-').collect();
+    groups.len() == 3
+        && groups
+            .iter()
+            .all(|group| group.len() == 4 && group.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+fn totp(secret: &str, username: &str, base_url: &Url) -> ResponseResult<TOTP> {
+    let secret = Secret::Encoded(secret.to_string())
+        .to_bytes()
+        .map_err(|e| anyhow!("Invalid TOTP secret: {e}"))?;
+    // Authenticator apps show the issuer next to the username. It can't
+    // contain colons, so leave out the port.
+    let issuer = base_url.host_str().map(ToString::to_string);
+
+    // We check neighboring steps ourselves in `check_code`, so no skew here
+    Ok(TOTP::new(
+        Algorithm::SHA1,
+        6,
+        0,
+        STEP_SECONDS,
+        secret,
+        issuer,
+        username.to_string(),
+    )
+    .context("Failed to set up TOTP")?)
+}
+
+/// Check a one-time password, allowing for one step of clock drift. Returns
+/// the time step of the code if it's correct and later than `last_step`, so
+/// that each code can only be used once.
+pub fn check_code(
+    secret: &str,
+    username: &str,
+    base_url: &Url,
+    code: &str,
+    last_step: Option<i64>,
+) -> ResponseResult<Option<i64>> {
+    let code = code.trim().replace(' ', "");
+    let totp = totp(secret, username, base_url)?;
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .context("System time is before the unix epoch")?;
+    let current_step = i64::try_from(now.as_secs() / STEP_SECONDS).context("Invalid time")?;
+
+    Ok((current_step - 1..=current_step + 1)
+        .filter(|step| last_step.is_none_or(|last_step| *step > last_step))
+        .find(|step| u64::try_from(*step).is_ok_and(|step| totp.check(&code, step * STEP_SECONDS))))
+}
+
+/// A QR code with the `otpauth://` URL for authenticator apps, as an SVG data
+/// URL that can be used as the `src` of an image.
+pub fn qr_code_data_url(secret: &str, username: &str, base_url: &Url) -> ResponseResult<String> {
+    let otpauth_url = totp(secret, username, base_url)?.get_url();
+    let svg = qrcode::QrCode::new(otpauth_url.as_bytes())
+        .map_err(|e| anyhow!("Failed to create QR code: {e}"))?
+        .render::<qrcode::render::svg::Color>()
+        .min_dimensions(200, 200)
+        .build();
+
+    Ok(format!(
+        "data:image/svg+xml,{}",
+        utf8_percent_encode(&svg, NON_ALPHANUMERIC)
+    ))
+}
+
+/// A secret that was shown to the user, but not confirmed with a valid code
+/// yet.
+#[derive(Serialize, Deserialize)]
+pub struct Enrollment {
+    pub secret: String,
+}
+
+impl Enrollment {
+    const SESSION_KEY: &'static str = "totp_enrollment";
+
+    pub async fn save_in_session(self, session: &Session) -> ResponseResult<()> {
+        session
+            .insert(Self::SESSION_KEY, self)
+            .await
+            .context("Failed to insert TOTP enrollment into session")?;
+
+        Ok(())
+    }
+
+    pub async fn from_session(session: &Session) -> ResponseResult<Option<Self>> {
+        Ok(session
+            .get(Self::SESSION_KEY)
+            .await
+            .context("Failed to load TOTP enrollment from session")?)
+    }
+
+    pub async fn remove_from_session(session: &Session) -> ResponseResult<()> {
+        session
+            .remove::<Self>(Self::SESSION_KEY)
+            .await
+            .context("Failed to remove TOTP enrollment from session")?;
+
+        Ok(())
+    }
+}
+
+/// A user that entered the correct password, but still needs to enter a
+/// one-time password.
+#[derive(Serialize, Deserialize)]
+pub struct PendingLogin {
+    pub user_id: Uuid,
+    /// Wrong codes entered so far
+    pub failed_attempts: u8,
+}
+
+impl PendingLogin {
+    const SESSION_KEY: &'static str = "totp_pending_login";
+
+    pub async fn save_in_session(self, session: &Session) -> ResponseResult<()> {
+        session
+            .insert(Self::SESSION_KEY, self)
+            .await
+            .context("Failed to insert pending login into session")?;
+
+        Ok(())
+    }
+
+    pub async fn from_session(session: &Session) -> ResponseResult<Option<Self>> {
+        Ok(session
+            .get(Self::SESSION_KEY)
+            .await
+            .context("Failed to load pending login from session")?)
+    }
+
+    pub async fn remove_from_session(session: &Session) -> ResponseResult<()> {
+        session
+            .remove::<Self>(Self::SESSION_KEY)
+            .await
+            .context("Failed to remove pending login from session")?;
+
+        Ok(())
+    }
+}
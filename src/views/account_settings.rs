@@ -0,0 +1,206 @@
+use htmf::prelude::*;
+
+use super::layout;
+use crate::{
+    db,
+    form_errors::FormErrors,
+    forms::users::{ChangeUsername, EditEmail},
+};
+
+pub struct Data {
+    pub layout: layout::Template,
+    pub user: db::User,
+    /// The name of the configured OIDC provider, if any
+    pub oidc_name: Option<String>,
+    pub password_errors: FormErrors,
+    pub email_input: EditEmail,
+    pub email_errors: FormErrors,
+    pub username_input: ChangeUsername,
+    pub username_errors: FormErrors,
+    pub oidc_errors: FormErrors,
+    pub recovery_codes_left: i64,
+    pub totp_errors: FormErrors,
+}
+
+pub fn view(data: &Data) -> Element {
+    let Data {
+        layout,
+        user,
+        oidc_name,
+        password_errors,
+        email_input,
+        email_errors,
+        username_input,
+        username_errors,
+        oidc_errors,
+        recovery_codes_left,
+        totp_errors,
+    } = data;
+
+    layout::layout(
+        div(class("flex flex-col max-w-xl mx-4 mb-4 grow")).with([
+            header(class("mt-3 mb-4"))
+                .with([h1(class("text-xl font-bold")).with("Account settings")]),
+            h2(class("mt-4 font-bold")).with("Password"),
+            password_form(user.password_hash.is_some(), password_errors),
+            h2(class("mt-8 font-bold")).with("Email"),
+            form([
+                action("/settings/account/email"),
+                method("POST"),
+                class("flex flex-col mt-2"),
+            ])
+            .with([
+                email_errors.view("email"),
+                input([
+                    name("email"),
+                    type_("email"),
+                    placeholder("you@example.com"),
+                    value(&email_input.email),
+                    class("rounded py-1.5 px-3 bg-neutral-900"),
+                ]),
+                submit_button("Save email"),
+            ]),
+            h2(class("mt-8 font-bold")).with("Username"),
+            p(class("mt-2 text-neutral-400")).with(
+                "Your followers will see the new username. Links to your old profile and your old \
+                 handle stop working.",
+            ),
+            form([
+                action("/settings/account/username"),
+                method("POST"),
+                class("flex flex-col mt-2"),
+            ])
+            .with([
+                username_errors.view("username"),
+                input([
+                    required(""),
+                    name("username"),
+                    type_("text"),
+                    value(&username_input.username),
+                    class("rounded py-1.5 px-3 bg-neutral-900"),
+                ]),
+                submit_button("Change username"),
+            ]),
+            h2(class("mt-8 font-bold")).with("Two-factor authentication"),
+            if user.totp_secret.is_some() {
+                disable_totp_form(*recovery_codes_left, totp_errors)
+            } else {
+                div(class("flex flex-col mt-2")).with([
+                    p(class("text-neutral-400")).with(
+                        "Require a code from an authenticator app when logging in with your \
+                         password.",
+                    ),
+                    a([
+                        href("/settings/account/totp"),
+                        class("bg-neutral-300 py-1.5 px-3 text-neutral-900 rounded mt-2 self-end"),
+                    ])
+                    .with("Set up"),
+                ])
+            },
+            oidc_name.as_ref().map_or(fragment(), |oidc_name| {
+                oidc_section(oidc_name, user.oidc_id.is_some(), oidc_errors)
+            }),
+        ]),
+        layout,
+    )
+}
+
+fn password_form(has_password: bool, errors: &FormErrors) -> Element {
+    form([
+        action("/settings/account/password"),
+        method("POST"),
+        class("flex flex-col mt-2"),
+    ])
+    .with([
+        if has_password {
+            fragment().with([
+                label([for_("current_password"), class("text-neutral-400")])
+                    .with("Current password"),
+                errors.view("current_password"),
+                input([
+                    required(""),
+                    name("current_password"),
+                    type_("password"),
+                    class("rounded py-1.5 px-3 mt-2 bg-neutral-900"),
+                ]),
+            ])
+        } else {
+            p(class("text-neutral-400")).with("Set a password to log in without single sign-on.")
+        },
+        label([for_("new_password"), class("mt-4 text-neutral-400")]).with("New password"),
+        errors.view("new_password"),
+        input([
+            required(""),
+            name("new_password"),
+            type_("password"),
+            class("rounded py-1.5 px-3 mt-2 bg-neutral-900"),
+        ]),
+        submit_button(if has_password {
+            "Change password"
+        } else {
+            "Set password"
+        }),
+    ])
+}
+
+fn disable_totp_form(recovery_codes_left: i64, errors: &FormErrors) -> Element {
+    form([
+        action("/settings/account/totp/disable"),
+        method("POST"),
+        class("flex flex-col mt-2"),
+    ])
+    .with([
+        p(class("text-neutral-400")).with(format!(
+            "Two-factor authentication is enabled. You have {recovery_codes_left} unused \
+             recovery codes left."
+        )),
+        label([for_("code"), class("mt-4 text-neutral-400")])
+            .with("Code from your authenticator app or a recovery code"),
+        errors.view("code"),
+        input([
+            required(""),
+            name("code"),
+            type_("text"),
+            attr("autocomplete", "one-time-code"),
+            class("rounded py-1.5 px-3 mt-2 bg-neutral-900"),
+        ]),
+        submit_button("Disable"),
+    ])
+}
+
+fn oidc_section(oidc_name: &str, is_linked: bool, errors: &FormErrors) -> Element {
+    fragment().with([
+        h2(class("mt-8 font-bold")).with(format!("Login with {oidc_name}")),
+        errors.view("oidc"),
+        if is_linked {
+            form([
+                action("/settings/account/oidc/unlink"),
+                method("POST"),
+                class("flex flex-col mt-2"),
+            ])
+            .with([
+                p(class("text-neutral-400"))
+                    .with(format!("Your account is linked to {oidc_name}.")),
+                submit_button("Unlink"),
+            ])
+        } else {
+            div(class("flex flex-col mt-2")).with([
+                p(class("text-neutral-400"))
+                    .with(format!("Link your account to log in with {oidc_name}.")),
+                a([
+                    href("/settings/account/oidc/link"),
+                    class("bg-neutral-300 py-1.5 px-3 text-neutral-900 rounded mt-2 self-end"),
+                ])
+                .with("Link"),
+            ])
+        },
+    ])
+}
+
+fn submit_button(label_text: &str) -> Element {
+    button([
+        type_("submit"),
+        class("bg-neutral-300 py-1.5 px-3 text-neutral-900 rounded mt-2 self-end"),
+    ])
+    .with(label_text)
+}
@@ -0,0 +1,123 @@
+use htmf::prelude::*;
+
+use super::{admin, layout};
+use crate::{
+    date_time::format_utc,
+    db::activity_log::{Direction, Entry},
+    forms::activity_log::ActivityLogQuery,
+};
+
+pub struct Data {
+    pub layout: layout::Template,
+    pub query: ActivityLogQuery,
+    pub entries: Vec<Entry>,
+}
+
+pub fn view(
+    Data {
+        layout,
+        query,
+        entries,
+    }: &Data,
+) -> Element {
+    layout::layout(
+        fragment().with([
+            header(class("px-4 pt-3 mb-4"))
+                .with([h1(class("text-xl font-bold")).with("Activity log")]),
+            admin::navigation(),
+            filter_form(query),
+            fragment().with(entries.iter().map(entry).collect::<Vec<_>>()),
+        ]),
+        layout,
+    )
+}
+
+fn filter_form(query: &ActivityLogQuery) -> Element {
+    form([
+        action("/admin/activity_log"),
+        method("GET"),
+        class("flex flex-wrap items-end gap-4 mx-4 mb-4"),
+    ])
+    .with([
+        filter_input(
+            "domain",
+            "Remote domain",
+            "mastodon.social",
+            query.domain.as_deref(),
+        ),
+        filter_input(
+            "activity_type",
+            "Activity type",
+            "Follow",
+            query.activity_type.as_deref(),
+        ),
+        button([
+            type_("submit"),
+            class("bg-neutral-300 py-1.5 px-3 text-neutral-900 rounded"),
+        ])
+        .with("Filter"),
+    ])
+}
+
+fn filter_input(
+    input_name: &str,
+    label_text: &str,
+    placeholder_text: &str,
+    current: Option<&str>,
+) -> Element {
+    div(class("flex flex-col")).with([
+        label(for_(input_name)).with(label_text),
+        input([
+            name(input_name),
+            id(input_name),
+            type_("text"),
+            placeholder(placeholder_text),
+            value(current.unwrap_or("")),
+            class("rounded py-1.5 px-3 mt-2 bg-neutral-900"),
+        ]),
+    ])
+}
+
+fn entry(entry: &Entry) -> Element {
+    let direction = match entry.direction {
+        Direction::Incoming => "Received",
+        Direction::Outgoing => "Sent",
+    };
+    let signature = match entry.signature_valid {
+        Some(true) => "valid signature",
+        Some(false) => "invalid signature",
+        None => "signature not checked",
+    };
+    let status = entry.http_status.map_or_else(
+        || "status unknown".to_string(),
+        |status| format!("status {status}"),
+    );
+
+    section(class("px-4 py-4 border-t border-neutral-700")).with([
+        p(class("font-semibold leading-8")).with(format!(
+            "{direction} {} {} {}",
+            entry.activity_type.as_deref().unwrap_or("unknown activity"),
+            match entry.direction {
+                Direction::Incoming => "from",
+                Direction::Outgoing => "to",
+            },
+            entry.remote_domain.as_deref().unwrap_or("unknown domain"),
+        )),
+        p(class("text-sm text-neutral-400")).with(format!(
+            "{} ∙ {status} ∙ {signature}",
+            format_utc(entry.created_at)
+        )),
+        p(class("text-sm text-neutral-400 break-all")).with(format!(
+            "{} by {}",
+            entry.activity_ap_id.as_deref().unwrap_or("no id"),
+            entry.actor_ap_id.as_deref().unwrap_or("unknown actor"),
+        )),
+        entry.error.as_ref().map_or(fragment(), |error| {
+            p(class("mt-2 text-sm break-all")).with(error)
+        }),
+        details(class("mt-2")).with([
+            summary(class("text-sm cursor-pointer text-neutral-400")).with("Activity"),
+            pre(class("mt-2 overflow-x-auto text-xs")).with(&entry.body),
+        ]),
+    ])
+}
@@ -0,0 +1,27 @@
+use htmf::prelude::*;
+
+/// Links between the admin pages.
+pub fn navigation() -> Element {
+    nav(class(
+        "flex flex-wrap px-4 mb-4 text-sm gap-x-4 text-neutral-400",
+    ))
+    .with([
+        a([href("/admin"), class("hover:text-neutral-200")]).with("Overview"),
+        a([href("/admin/users"), class("hover:text-neutral-200")]).with("Users"),
+        a([href("/admin/reports"), class("hover:text-neutral-200")]).with("Reports"),
+        a([
+            href("/admin/registrations"),
+            class("hover:text-neutral-200"),
+        ])
+        .with("Registrations"),
+        a([
+            href("/admin/domain_blocks"),
+            class("hover:text-neutral-200"),
+        ])
+        .with("Blocked domains"),
+        a([href("/admin/instances"), class("hover:text-neutral-200")]).with("Instances"),
+        a([href("/admin/relays"), class("hover:text-neutral-200")]).with("Relays"),
+        a([href("/admin/deliveries"), class("hover:text-neutral-200")]).with("Deliveries"),
+        a([href("/admin/activity_log"), class("hover:text-neutral-200")]).with("Activity log"),
+    ])
+}
@@ -0,0 +1,68 @@
+use htmf::prelude::*;
+
+use super::{admin, layout};
+use crate::db::instance::Stats;
+
+pub struct Data {
+    pub layout: layout::Template,
+    pub stats: Stats,
+    pub remote_instances: usize,
+    pub open_reports: usize,
+    pub pending_registrations: usize,
+    pub failing_inboxes: usize,
+}
+
+pub fn view(
+    Data {
+        layout,
+        stats,
+        remote_instances,
+        open_reports,
+        pending_registrations,
+        failing_inboxes,
+    }: &Data,
+) -> Element {
+    layout::layout(
+        fragment().with([
+            header(class("px-4 pt-3 mb-4")).with([h1(class("text-xl font-bold")).with("Admin")]),
+            admin::navigation(),
+            div(class("grid grid-cols-2 gap-4 px-4 sm:grid-cols-3")).with([
+                stat("Users", stats.users.to_string(), "/admin/users"),
+                stat(
+                    "Bookmarks",
+                    stats.local_bookmarks.to_string(),
+                    "/admin/users",
+                ),
+                stat("Lists", stats.local_lists.to_string(), "/admin/users"),
+                stat(
+                    "Known instances",
+                    remote_instances.to_string(),
+                    "/admin/instances",
+                ),
+                stat("Open reports", open_reports.to_string(), "/admin/reports"),
+                stat(
+                    "Pending registrations",
+                    pending_registrations.to_string(),
+                    "/admin/registrations",
+                ),
+                stat(
+                    "Failing inboxes",
+                    failing_inboxes.to_string(),
+                    "/admin/deliveries",
+                ),
+            ]),
+        ]),
+        layout,
+    )
+}
+
+fn stat(label: &str, count: String, link: &str) -> Element {
+    a([
+        href(link),
+        class("block px-4 py-3 border rounded border-neutral-700 hover:bg-neutral-800"),
+    ])
+    .with([
+        p(class("text-2xl font-bold")).with(count),
+        p(class("text-sm text-neutral-400")).with(label),
+    ])
+}
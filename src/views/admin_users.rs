@@ -0,0 +1,143 @@
+use htmf::prelude::*;
+use uuid::Uuid;
+
+use super::{admin, layout};
+use crate::{
+    db::users::{Role, UserWithStats},
+    forms::users::UserAction,
+};
+
+pub struct Data {
+    pub layout: layout::Template,
+    pub users: Vec<UserWithStats>,
+    /// Admins can't suspend, delete or demote themselves
+    pub current_user_id: Uuid,
+    /// Shown once after an admin reset a password
+    pub new_password: Option<NewPassword>,
+}
+
+pub struct NewPassword {
+    pub username: String,
+    pub password: String,
+}
+
+pub fn view(
+    Data {
+        layout,
+        users,
+        current_user_id,
+        new_password,
+    }: &Data,
+) -> Element {
+    layout::layout(
+        fragment().with([
+            header(class("px-4 pt-3 mb-4")).with([h1(class("text-xl font-bold")).with("Users")]),
+            admin::navigation(),
+            new_password
+                .as_ref()
+                .map_or(fragment(), new_password_notice),
+            fragment().with(
+                users
+                    .iter()
+                    .map(|user| user_entry(user, user.id == *current_user_id))
+                    .collect::<Vec<_>>(),
+            ),
+        ]),
+        layout,
+    )
+}
+
+fn new_password_notice(new_password: &NewPassword) -> Element {
+    section(class(
+        "px-4 py-4 mx-4 mb-4 border rounded border-neutral-700",
+    ))
+    .with([
+        p([]).with(format!(
+            "The new password of {} is shown only once:",
+            new_password.username
+        )),
+        p(class("mt-2 font-mono break-all")).with(&new_password.password),
+    ])
+}
+
+fn user_entry(user: &UserWithStats, is_current_user: bool) -> Element {
+    section(class(
+        "flex flex-wrap items-end justify-between gap-2 px-4 py-4 border-t border-neutral-700",
+    ))
+    .with([
+        div(class("overflow-hidden")).with([
+            p(class("font-semibold leading-8")).with([
+                a([
+                    href(format!("/user/{}", user.username)),
+                    class("hover:underline"),
+                ])
+                .with(&user.username),
+                if user.role == Role::Admin {
+                    span(class("ml-2 text-sm font-normal text-neutral-400")).with("admin")
+                } else {
+                    fragment()
+                },
+                if user.suspended {
+                    span(class("ml-2 text-sm font-normal text-red-400")).with("suspended")
+                } else {
+                    fragment()
+                },
+            ]),
+            p(class("text-sm text-neutral-400")).with(format!(
+                "{} bookmarks, {} lists, {}",
+                user.bookmark_count,
+                user.list_count,
+                format_bytes(user.storage_bytes)
+            )),
+        ]),
+        if is_current_user {
+            div(class("flex flex-wrap gap-2")).with(action_button(
+                user,
+                UserAction::ResetPassword,
+                "Reset password",
+            ))
+        } else {
+            div(class("flex flex-wrap gap-2")).with([
+                if user.suspended {
+                    action_button(user, UserAction::Unsuspend, "Unsuspend")
+                } else {
+                    action_button(user, UserAction::Suspend, "Suspend")
+                },
+                action_button(user, UserAction::ResetPassword, "Reset password"),
+                if user.role == Role::Admin {
+                    action_button(user, UserAction::RemoveAdmin, "Remove admin")
+                } else {
+                    action_button(user, UserAction::MakeAdmin, "Make admin")
+                },
+                action_button(user, UserAction::Delete, "Delete"),
+            ])
+        },
+    ])
+}
+
+fn action_button(user: &UserWithStats, user_action: UserAction, label: &str) -> Element {
+    form([
+        action(format!("/admin/users/{}/{}", user.id, user_action.as_str())),
+        method("POST"),
+    ])
+    .with(
+        button([
+            type_("submit"),
+            class("px-4 py-1 border rounded border-neutral-700 hover:bg-neutral-700"),
+        ])
+        .with(label),
+    )
+}
+
+/// Sizes in bytes, rounded down to one decimal place.
+fn format_bytes(bytes: i64) -> String {
+    const KIB: i64 = 1024;
+    const MIB: i64 = 1024 * KIB;
+    if bytes < KIB {
+        format!("{bytes} B")
+    } else if bytes < MIB {
+        format!("{}.{} KiB", bytes / KIB, bytes % KIB * 10 / KIB)
+    } else {
+        format!("{}.{} MiB", bytes / MIB, bytes % MIB * 10 / MIB)
+    }
+}
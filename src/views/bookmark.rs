@@ -0,0 +1,399 @@
+use htmf::prelude::*;
+use uuid::Uuid;
+
+use super::{content, layout};
+use crate::{date_time::format_utc, db, form_errors::FormErrors, forms::replies::CreateReply};
+
+pub struct Data {
+    pub layout: layout::Template,
+    pub bookmark: db::Bookmark,
+    pub author: db::ApUser,
+    pub author_handle: String,
+    /// The handle and account of the author this bookmark was saved from
+    pub via_author: Option<(String, db::ApUser)>,
+    /// How many people the current user follows saved the original bookmark
+    pub saved_by_followed_count: i64,
+    /// Handles and accounts of everyone who liked the bookmark
+    pub likes: Vec<(String, db::ApUser)>,
+    /// Handles and accounts of everyone who boosted the bookmark
+    pub boosts: Vec<(String, db::ApUser)>,
+    /// Lists linking to the bookmark that the current user is allowed to see
+    pub lists: Vec<db::List>,
+    /// Whether the current user can report the bookmark to its instance
+    pub reportable: bool,
+    /// Replies to the bookmark, oldest first
+    pub replies: Vec<ThreadReply>,
+    pub reply_input: CreateReply,
+    pub reply_errors: FormErrors,
+}
+
+pub struct ThreadReply {
+    pub reply: db::Reply,
+    pub author: db::ApUser,
+    pub author_handle: String,
+}
+
+pub fn view(
+    Data {
+        layout,
+        bookmark,
+        author,
+        author_handle,
+        via_author,
+        saved_by_followed_count,
+        likes,
+        boosts,
+        lists,
+        reportable,
+        replies,
+        reply_input,
+        reply_errors,
+    }: &Data,
+) -> Element {
+    let current_user_id = layout.authed_info.as_ref().map(|info| info.ap_user_id);
+    layout::layout(
+        fragment()
+            .with([header(class("px-4 pt-3 mb-4")).with([
+                h1(class("text-xl font-bold")).with(&bookmark.title),
+                a([
+                    href(&bookmark.url),
+                    class("block overflow-hidden text-orange-100 hover:text-orange-300"),
+                ])
+                .with(content::link_url(&bookmark.url)),
+                div(class("flex flex-wrap text-sm gap-x-1 text-neutral-400")).with([
+                    a([
+                        href(format!("/user/{author_handle}")),
+                        class("hover:text-neutral-200"),
+                    ])
+                    .with(format!(
+                        "by {}",
+                        author.display_name.as_deref().unwrap_or(author_handle)
+                    )),
+                    via_author
+                        .as_ref()
+                        .map_or(fragment(), |(via_handle, via_author)| {
+                            a([
+                                href(format!("/user/{via_handle}")),
+                                class("hover:text-neutral-200"),
+                            ])
+                            .with(format!(
+                                "via {}",
+                                via_author.display_name.as_deref().unwrap_or(via_handle)
+                            ))
+                        }),
+                ]),
+                tags(&bookmark.tags),
+                saved_by_followed(*saved_by_followed_count),
+            ])])
+            .with(layout.authed_info.as_ref().map(|authed_info| {
+                section(class("flex flex-wrap m-4 gap-x-4 gap-y-2")).with([
+                    a([
+                        class(
+                            "block px-4 py-1 border rounded hover:bg-neutral-700 \
+                             border-neutral-700 w-max",
+                        ),
+                        href(format!("/links/create?dest_id={}", bookmark.id)),
+                    ])
+                    .with("Add to list"),
+                    if authed_info.ap_user_id == bookmark.ap_user_id {
+                        fragment()
+                    } else {
+                        a([
+                            class(
+                                "block px-4 py-1 border rounded hover:bg-neutral-700 \
+                                 border-neutral-700 w-max",
+                            ),
+                            href(format!("/bookmarks/create?via={}", bookmark.id)),
+                        ])
+                        .with("Save a copy")
+                    },
+                    if *reportable {
+                        a([
+                            class(
+                                "block px-4 py-1 border rounded hover:bg-neutral-700 \
+                                 border-neutral-700 w-max",
+                            ),
+                            href(format!("{}/report", bookmark.path())),
+                        ])
+                        .with("Report")
+                    } else {
+                        fragment()
+                    },
+                ])
+            }))
+            .with(interactions(likes, boosts))
+            .with(
+                section(class("px-4 pt-4 border-t border-neutral-700")).with([
+                    h2(class("mb-2 font-bold")).with("In lists"),
+                    ul([]).with(
+                        lists
+                            .iter()
+                            .map(|list| {
+                                li([]).with(
+                                    a([
+                                        href(list.path()),
+                                        class("font-semibold leading-8 hover:text-fuchsia-300"),
+                                    ])
+                                    .with(&list.title),
+                                )
+                            })
+                            .collect::<Vec<_>>(),
+                    ),
+                ]),
+            )
+            .with(
+                section(class("px-4 pt-4 mt-4 border-t border-neutral-700")).with([
+                    h2(class("mb-2 font-bold")).with("Replies"),
+                    thread(
+                        &Thread {
+                            bookmark,
+                            replies,
+                            current_user_id,
+                        },
+                        None,
+                    ),
+                    current_user_id.map_or(fragment(), |_| {
+                        reply_form(bookmark, replies, reply_input, reply_errors)
+                    }),
+                ]),
+            ),
+        layout,
+    )
+}
+
+struct Thread<'a> {
+    bookmark: &'a db::Bookmark,
+    replies: &'a [ThreadReply],
+    current_user_id: Option<Uuid>,
+}
+
+/// The replies answering `parent_id`, each followed by its own answers.
+/// Replies whose parent is hidden don't show up for anyone but the owner.
+fn thread(thread_data: &Thread, parent_id: Option<Uuid>) -> Element {
+    let children = thread_data
+        .replies
+        .iter()
+        .filter(|thread_reply| thread_reply.reply.parent_id == parent_id)
+        .map(|thread_reply| {
+            li(class("mt-3")).with([
+                reply(thread_data, thread_reply),
+                thread(thread_data, Some(thread_reply.reply.id)),
+            ])
+        })
+        .collect::<Vec<_>>();
+    if children.is_empty() {
+        return fragment();
+    }
+
+    let indent = if parent_id.is_some() {
+        "pl-4 border-l border-neutral-700"
+    } else {
+        ""
+    };
+    ul(class(indent)).with(children)
+}
+
+fn reply(
+    Thread {
+        bookmark,
+        current_user_id,
+        ..
+    }: &Thread,
+    ThreadReply {
+        reply,
+        author,
+        author_handle,
+    }: &ThreadReply,
+) -> Element {
+    let is_bookmark_owner = *current_user_id == Some(bookmark.ap_user_id);
+    let is_reply_author = *current_user_id == Some(reply.ap_user_id);
+    let reply_path = format!("{}/replies/{}", bookmark.path(), reply.id);
+
+    div([]).with([
+        div(class("flex flex-wrap text-sm gap-x-2 text-neutral-400")).with([
+            a([
+                href(format!("/user/{author_handle}")),
+                class("font-semibold text-neutral-200 hover:text-fuchsia-300"),
+            ])
+            .with(author.display_name.as_deref().unwrap_or(author_handle)),
+            span([]).with(format_utc(reply.created_at)),
+            if reply.hidden {
+                span(class("text-orange-300")).with("Hidden")
+            } else {
+                fragment()
+            },
+        ]),
+        div([]).with(
+            reply
+                .content
+                .split("\n\n")
+                .map(|paragraph| p(class("mt-1 whitespace-pre-line")).with(paragraph))
+                .collect::<Vec<_>>(),
+        ),
+        div(class("flex flex-wrap text-sm gap-x-3 text-neutral-400")).with([
+            if current_user_id.is_some() {
+                a([
+                    href(format!("{}?reply_to={}#reply", bookmark.path(), reply.id)),
+                    class("hover:text-neutral-200"),
+                ])
+                .with("Reply")
+            } else {
+                fragment()
+            },
+            if is_bookmark_owner {
+                let (path, label) = if reply.hidden {
+                    ("unhide", "Unhide")
+                } else {
+                    ("hide", "Hide")
+                };
+                action_button(&format!("{reply_path}/{path}"), label)
+            } else {
+                fragment()
+            },
+            if is_bookmark_owner || is_reply_author {
+                action_button(&format!("{reply_path}/delete"), "Delete")
+            } else {
+                fragment()
+            },
+        ]),
+    ])
+}
+
+fn action_button(path: &str, label: &str) -> Element {
+    form([action(path), method("post")])
+        .with(button([type_("submit"), class("hover:text-neutral-200")]).with(label))
+}
+
+fn reply_form(
+    bookmark: &db::Bookmark,
+    replies: &[ThreadReply],
+    input_data: &CreateReply,
+    errors: &FormErrors,
+) -> Element {
+    let parent = input_data.parent_id.and_then(|parent_id| {
+        replies
+            .iter()
+            .find(|thread_reply| thread_reply.reply.id == parent_id)
+    });
+
+    form([
+        id("reply"),
+        action(format!("{}/replies", bookmark.path())),
+        method("post"),
+        class("flex flex-col max-w-xl mt-4 mb-4"),
+    ])
+    .with([
+        parent.map_or(fragment(), |parent| {
+            fragment().with([
+                input([
+                    type_("hidden"),
+                    name("parent_id"),
+                    value(parent.reply.id.to_string()),
+                ]),
+                p(class("text-sm text-neutral-400")).with([
+                    text(format!(
+                        "Replying to {} ",
+                        parent
+                            .author
+                            .display_name
+                            .as_deref()
+                            .unwrap_or(&parent.author_handle)
+                    )),
+                    a([
+                        href(format!("{}#reply", bookmark.path())),
+                        class("hover:text-neutral-200"),
+                    ])
+                    .with("(cancel)"),
+                ]),
+            ])
+        }),
+        errors.view("content"),
+        textarea([
+            required(""),
+            name("content"),
+            placeholder("Write a reply"),
+            class("rounded py-1.5 px-3 mt-2 bg-neutral-900 block w-full"),
+        ])
+        .with(input_data.content.as_str()),
+        errors.view("root"),
+        button([
+            type_("submit"),
+            class("bg-neutral-300 py-1.5 px-3 text-neutral-900 rounded mt-4 self-end"),
+        ])
+        .with("Reply"),
+    ])
+}
+
+/// Who liked and boosted the bookmark on other platforms.
+fn interactions(likes: &[(String, db::ApUser)], boosts: &[(String, db::ApUser)]) -> Element {
+    if likes.is_empty() && boosts.is_empty() {
+        return fragment();
+    }
+
+    section(class("px-4 pt-4 mb-4 border-t border-neutral-700")).with([
+        h2(class("mb-2 font-bold")).with(format!(
+            "{} · {}",
+            content::pluralize(
+                i64::try_from(likes.len()).unwrap_or(i64::MAX),
+                "like",
+                "likes"
+            ),
+            content::pluralize(
+                i64::try_from(boosts.len()).unwrap_or(i64::MAX),
+                "boost",
+                "boosts"
+            )
+        )),
+        interacted_users("Liked by", likes),
+        interacted_users("Boosted by", boosts),
+    ])
+}
+
+fn interacted_users(label: &str, ap_users: &[(String, db::ApUser)]) -> Element {
+    if ap_users.is_empty() {
+        return fragment();
+    }
+
+    p(class("flex flex-wrap text-sm gap-x-2 text-neutral-400"))
+        .with(span([]).with(label))
+        .with(
+            ap_users
+                .iter()
+                .map(|(handle, ap_user)| {
+                    a([
+                        href(format!("/user/{handle}")),
+                        class("text-neutral-200 hover:text-fuchsia-300"),
+                    ])
+                    .with(ap_user.display_name.as_deref().unwrap_or(handle))
+                })
+                .collect::<Vec<_>>(),
+        )
+}
+
+fn tags(tags: &[String]) -> Element {
+    if tags.is_empty() {
+        return fragment();
+    }
+
+    div(class("flex flex-wrap text-sm gap-x-2")).with(
+        tags.iter()
+            .map(|tag| {
+                a([
+                    href(format!("/tags/{tag}")),
+                    class("text-fuchsia-200 hover:text-fuchsia-300"),
+                ])
+                .with(format!("#{tag}"))
+            })
+            .collect::<Vec<_>>(),
+    )
+}
+
+fn saved_by_followed(count: i64) -> Element {
+    match count {
+        0 => fragment(),
+        1 => p(class("text-sm text-neutral-400")).with("Saved by 1 person you follow"),
+        count => {
+            p(class("text-sm text-neutral-400")).with(format!("Saved by {count} people you follow"))
+        }
+    }
+}
@@ -1,5 +1,7 @@
 use htmf::prelude::*;
 
+use crate::db;
+
 pub fn link_url(url: &str) -> Element {
     p(class(
         "w-full max-w-sm overflow-hidden text-sm text-neutral-400 whitespace-nowrap text-ellipsis",
@@ -7,6 +9,26 @@ pub fn link_url(url: &str) -> Element {
     .with(url)
 }
 
+/// A bookmark in a feed of bookmarks from several people.
+pub fn bookmark_entry(bookmark: &db::Bookmark) -> Element {
+    section(class("px-4 py-4 border-t border-neutral-700")).with([
+        a([
+            href(&bookmark.url),
+            class(
+                "block overflow-hidden leading-8 text-orange-100 hover:text-orange-300 \
+                 text-ellipsis whitespace-nowrap",
+            ),
+        ])
+        .with(&bookmark.title),
+        link_url(&bookmark.url),
+        a([
+            href(bookmark.path()),
+            class("text-sm text-neutral-400 hover:text-neutral-200"),
+        ])
+        .with("Details"),
+    ])
+}
+
 pub fn pluralize<'a>(
     count: i64,
     singular_description: &'a str,
@@ -54,6 +54,18 @@ pub fn view(
                 required(""),
                 type_("text"),
             ]),
+            label([class("mt-4"), for_("tags")]).with("Hashtags"),
+            errors.view("tags"),
+            input([
+                value(&input_data.tags),
+                class("rounded py-1.5 px-3 mt-2 bg-neutral-900"),
+                name("tags"),
+                placeholder("#rust #web"),
+                type_("text"),
+            ]),
+            input_data.via.map_or(fragment(), |via| {
+                input([name("via"), type_("hidden"), value(via)])
+            }),
             label([class("mt-4"), for_("list_search_term")]).with("Add to Lists"),
             div(id("selected_lists")).with([
                 errors.view("parents"),
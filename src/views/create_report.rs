@@ -0,0 +1,55 @@
+use htmf::prelude::*;
+
+use super::layout;
+use crate::{form_errors::FormErrors, forms::reports::CreateReport};
+
+pub struct Data {
+    pub layout: layout::Template,
+    /// What is being reported, e.g. the handle of a user
+    pub subject: String,
+    /// Where to send the form to
+    pub form_action: String,
+    pub input: CreateReport,
+    pub errors: FormErrors,
+}
+
+pub fn view(
+    Data {
+        layout,
+        subject,
+        form_action,
+        input: input_data,
+        errors,
+    }: &Data,
+) -> Element {
+    layout::layout(
+        fragment().with([form([
+            action(form_action),
+            method("POST"),
+            class("flex flex-col max-w-xl mx-4 mb-4 grow"),
+        ])
+        .with([
+            header(class("mt-3 mb-4")).with([
+                h1(class("text-xl font-bold")).with(format!("Report {subject}")),
+                p(class("text-sm text-neutral-400")).with(
+                    "The admins of this instance and of the reported user's instance will see \
+                     the report. Other instances won't learn who sent it.",
+                ),
+            ]),
+            label(for_("comment")).with("Why are you reporting this?"),
+            errors.view("comment"),
+            textarea([
+                name("comment"),
+                class("rounded py-1.5 px-3 mt-2 bg-neutral-900 block w-full"),
+            ])
+            .with(input_data.comment.as_str()),
+            errors.view("root"),
+            button([
+                type_("submit"),
+                class("bg-neutral-300 py-1.5 px-3 text-neutral-900 rounded mt-4 self-end"),
+            ])
+            .with("Send report"),
+        ])]),
+        layout,
+    )
+}
@@ -0,0 +1,111 @@
+use htmf::prelude::*;
+
+use super::{admin, layout};
+use crate::{
+    date_time::format_utc,
+    db::deliveries::{Delivery, InboxFailure},
+};
+
+pub struct Data {
+    pub layout: layout::Template,
+    pub pending: Vec<Delivery>,
+    pub failed: Vec<Delivery>,
+    pub inbox_failures: Vec<InboxFailure>,
+}
+
+pub fn view(
+    Data {
+        layout,
+        pending,
+        failed,
+        inbox_failures,
+    }: &Data,
+) -> Element {
+    layout::layout(
+        fragment().with([
+            header(class("px-4 pt-3 mb-4"))
+                .with([h1(class("text-xl font-bold")).with("Deliveries")]),
+            admin::navigation(),
+            section_header("Failing inboxes"),
+            fragment().with(inbox_failures.iter().map(inbox_failure).collect::<Vec<_>>()),
+            section_header("Failed deliveries"),
+            fragment().with(failed.iter().map(failed_delivery).collect::<Vec<_>>()),
+            section_header("Pending deliveries"),
+            fragment().with(pending.iter().map(pending_delivery).collect::<Vec<_>>()),
+        ]),
+        layout,
+    )
+}
+
+fn section_header(title: &str) -> Element {
+    h2(class(
+        "px-4 pt-4 pb-2 font-bold border-t border-neutral-700",
+    ))
+    .with(title)
+}
+
+fn inbox_failure(failure: &InboxFailure) -> Element {
+    section(class("px-4 py-4 border-t border-neutral-700")).with([
+        p(class("font-semibold leading-8 break-all")).with(&failure.inbox_url),
+        p(class("text-sm text-neutral-400")).with(format!(
+            "{} failed deliveries between {} and {}",
+            failure.failure_count,
+            format_utc(failure.first_failure_at),
+            format_utc(failure.last_failure_at)
+        )),
+        p(class("mt-2 text-sm break-all")).with(&failure.last_error),
+    ])
+}
+
+fn delivery_details(delivery: &Delivery) -> Element {
+    div(class("overflow-hidden")).with([
+        p(class("font-semibold leading-8 break-all")).with(delivery.activity_ap_id.as_str()),
+        p(class("text-sm text-neutral-400 break-all")).with(format!("to {}", delivery.inbox_url)),
+        p(class("text-sm text-neutral-400")).with(format!(
+            "queued {}, {} attempts",
+            format_utc(delivery.created_at),
+            delivery.attempts
+        )),
+        delivery.last_error.as_ref().map_or(fragment(), |error| {
+            p(class("mt-2 text-sm break-all")).with(error)
+        }),
+    ])
+}
+
+fn pending_delivery(delivery: &Delivery) -> Element {
+    section(class("px-4 py-4 border-t border-neutral-700")).with([
+        delivery_details(delivery),
+        p(class("text-sm text-neutral-400")).with(format!(
+            "next attempt {}",
+            format_utc(delivery.next_attempt_at)
+        )),
+    ])
+}
+
+fn failed_delivery(delivery: &Delivery) -> Element {
+    section(class(
+        "flex flex-wrap items-end justify-between gap-2 px-4 py-4 border-t border-neutral-700",
+    ))
+    .with([
+        div(class("overflow-hidden")).with([
+            delivery_details(delivery),
+            p(class("text-sm text-neutral-400")).with(
+                delivery
+                    .failed_at
+                    .map(|failed_at| format!("gave up {}", format_utc(failed_at)))
+                    .unwrap_or_default(),
+            ),
+        ]),
+        form([
+            action(format!("/admin/deliveries/{}/retry", delivery.id)),
+            method("POST"),
+        ])
+        .with(
+            button([
+                type_("submit"),
+                class("px-4 py-1 border rounded border-neutral-700 hover:bg-neutral-700"),
+            ])
+            .with("Retry"),
+        ),
+    ])
+}
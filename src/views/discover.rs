@@ -0,0 +1,33 @@
+use htmf::prelude::*;
+
+use super::{content, layout};
+use crate::db;
+
+pub struct Data {
+    pub layout: layout::Template,
+    /// Bookmarks received through relays, most recently relayed first
+    pub bookmarks: Vec<db::Bookmark>,
+}
+
+pub fn view(Data { layout, bookmarks }: &Data) -> Element {
+    layout::layout(
+        fragment().with([
+            header(class("px-4 pt-3 mb-4")).with([
+                h1(class("text-xl font-bold")).with("Discover"),
+                p(class("text-sm text-neutral-400"))
+                    .with("Link posts from across the fediverse, passed on by relays."),
+            ]),
+            if bookmarks.is_empty() {
+                p(class("px-4 text-neutral-400")).with("Nothing to discover yet.")
+            } else {
+                fragment().with(
+                    bookmarks
+                        .iter()
+                        .map(content::bookmark_entry)
+                        .collect::<Vec<_>>(),
+                )
+            },
+        ]),
+        layout,
+    )
+}
@@ -0,0 +1,121 @@
+use htmf::{into_attrs::IntoAttrs, prelude::*};
+
+use super::{admin, layout};
+use crate::{
+    db::domain_blocks::{DomainBlock, Severity},
+    form_errors::FormErrors,
+    forms::domain_blocks::CreateDomainBlock,
+};
+
+pub struct Data {
+    pub layout: layout::Template,
+    pub blocks: Vec<DomainBlock>,
+    pub input: CreateDomainBlock,
+    pub errors: FormErrors,
+}
+
+pub fn view(
+    Data {
+        layout,
+        blocks,
+        input: input_data,
+        errors,
+    }: &Data,
+) -> Element {
+    layout::layout(
+        fragment().with([
+            header(class("px-4 pt-3 mb-4"))
+                .with([h1(class("text-xl font-bold")).with("Blocked domains")]),
+            admin::navigation(),
+            create_form(input_data, errors),
+            fragment().with(blocks.iter().map(block_entry).collect::<Vec<_>>()),
+        ]),
+        layout,
+    )
+}
+
+fn create_form(input_data: &CreateDomainBlock, errors: &FormErrors) -> Element {
+    form([
+        action("/admin/domain_blocks"),
+        method("POST"),
+        class("flex flex-col max-w-xl mx-4 mb-4"),
+    ])
+    .with([
+        label(for_("domain")).with("Domain"),
+        errors.view("domain"),
+        input([
+            required(""),
+            name("domain"),
+            type_("text"),
+            placeholder("example.com"),
+            value(&input_data.domain),
+            class("rounded py-1.5 px-3 mt-2 bg-neutral-900"),
+        ]),
+        div(class("flex flex-col gap-1 mt-4")).with([
+            severity_option(Severity::Reject, input_data.severity),
+            severity_option(Severity::Silence, input_data.severity),
+        ]),
+        label([for_("reason"), class("mt-4")]).with("Reason"),
+        errors.view("reason"),
+        input([
+            name("reason"),
+            type_("text"),
+            value(input_data.reason.as_deref().unwrap_or("")),
+            class("rounded py-1.5 px-3 mt-2 bg-neutral-900"),
+        ]),
+        errors.view("root"),
+        button([
+            type_("submit"),
+            class("bg-neutral-300 py-1.5 px-3 text-neutral-900 rounded mt-4 self-end"),
+        ])
+        .with("Block domain"),
+    ])
+}
+
+fn severity_option(severity: Severity, selected: Severity) -> Element {
+    let description = match severity {
+        Severity::Reject => "Reject: refuse all communication with this domain",
+        Severity::Silence => "Silence: keep existing follows, but ignore new followers",
+    };
+    label(()).with([
+        input([
+            type_("radio"),
+            name("severity"),
+            value(severity.as_str()),
+            (severity == selected).then(checked).into_attrs(),
+        ]),
+        text(description),
+    ])
+}
+
+fn block_entry(block: &DomainBlock) -> Element {
+    let severity = match block.severity {
+        Severity::Reject => "Rejected",
+        Severity::Silence => "Silenced",
+    };
+
+    section(class(
+        "flex flex-wrap items-end justify-between gap-2 px-4 py-4 border-t border-neutral-700",
+    ))
+    .with([
+        div(class("overflow-hidden")).with([
+            p(class("font-semibold leading-8")).with(&block.domain),
+            p(class("text-sm text-neutral-400")).with(severity),
+            block
+                .reason
+                .as_ref()
+                .map_or(fragment(), |reason| p(class("mt-2")).with(reason)),
+        ]),
+        form([
+            action(format!("/admin/domain_blocks/{}/delete", block.id)),
+            method("POST"),
+        ])
+        .with(
+            button([
+                type_("submit"),
+                class("px-4 py-1 border rounded border-neutral-700 hover:bg-neutral-700"),
+            ])
+            .with("Unblock"),
+        ),
+    ])
+}
@@ -0,0 +1,60 @@
+use htmf::prelude::*;
+
+use super::{admin, layout};
+use crate::{
+    date_time::format_utc,
+    db::{domain_blocks::Severity, instance::RemoteInstance},
+};
+
+pub struct Data {
+    pub layout: layout::Template,
+    pub instances: Vec<RemoteInstance>,
+}
+
+pub fn view(Data { layout, instances }: &Data) -> Element {
+    layout::layout(
+        fragment().with([
+            header(class("px-4 pt-3 mb-4"))
+                .with([h1(class("text-xl font-bold")).with("Instances")]),
+            admin::navigation(),
+            if instances.is_empty() {
+                p(class("px-4 py-4 text-neutral-400")).with("No known instances yet.")
+            } else {
+                fragment().with(instances.iter().map(instance_entry).collect::<Vec<_>>())
+            },
+        ]),
+        layout,
+    )
+}
+
+fn instance_entry(instance: &RemoteInstance) -> Element {
+    section(class("px-4 py-4 border-t border-neutral-700")).with([
+        p(class("font-semibold leading-8 break-all")).with([
+            text(&instance.domain),
+            match instance.block {
+                Some(Severity::Reject) => {
+                    span(class("ml-2 text-sm font-normal text-red-400")).with("rejected")
+                }
+                Some(Severity::Silence) => {
+                    span(class("ml-2 text-sm font-normal text-neutral-400")).with("silenced")
+                }
+                None => fragment(),
+            },
+        ]),
+        p(class("text-sm text-neutral-400")).with(format!(
+            "{} users, {} bookmarks, last refreshed {}",
+            instance.user_count,
+            instance.bookmark_count,
+            format_utc(instance.last_refreshed_at)
+        )),
+        if instance.failing_inboxes > 0 {
+            p(class("mt-2 text-sm")).with([a([
+                href("/admin/deliveries"),
+                class("text-red-400 hover:underline"),
+            ])
+            .with(format!("{} failing inboxes", instance.failing_inboxes))])
+        } else {
+            fragment()
+        },
+    ])
+}
@@ -53,6 +53,34 @@ fn sidebar(authed_info: &AuthedInfo) -> Element {
                 class("px-2 font-bold rounded  hover:bg-neutral-800"),
             ])
             .with(&authed_info.username),
+            a([
+                href("/search"),
+                class("px-2 rounded text-neutral-400 hover:bg-neutral-800"),
+            ])
+            .with("Search"),
+            a([
+                href("/discover"),
+                class("px-2 rounded text-neutral-400 hover:bg-neutral-800"),
+            ])
+            .with("Discover"),
+            a([
+                href("/notifications"),
+                class("px-2 rounded text-neutral-400 hover:bg-neutral-800"),
+            ])
+            .with(if authed_info.pending_follow_requests > 0 {
+                format!("Notifications ({})", authed_info.pending_follow_requests)
+            } else {
+                "Notifications".to_string()
+            }),
+            if authed_info.is_admin {
+                a([
+                    href("/admin"),
+                    class("px-2 rounded text-neutral-400 hover:bg-neutral-800"),
+                ])
+                .with("Admin")
+            } else {
+                fragment()
+            },
             form([action("/logout"), method("post")]).with(
                 button(class("rounded px-3  text-neutral-400 hover:bg-neutral-800")).with("Logout"),
             ),
@@ -32,10 +32,17 @@ pub struct Template {
     errors: FormErrors,
     input: Login,
     oidc_info: OidcInfo,
+    /// Link to the sign-up page unless registrations are closed
+    signup_enabled: bool,
 }
 
 impl Template {
-    pub fn new(errors: Report, input: Login, oidc_state: oidc::State) -> Self {
+    pub fn new(
+        errors: Report,
+        input: Login,
+        oidc_state: oidc::State,
+        signup_enabled: bool,
+    ) -> Self {
         Self {
             errors: errors.into(),
             input: Login {
@@ -47,6 +54,7 @@ impl Template {
                 ..input
             },
             oidc_info: oidc_state.into(),
+            signup_enabled,
         }
     }
 }
@@ -56,7 +64,11 @@ pub fn login(template: &Template) -> Element {
         div(class(
             "flex flex-col justify-center max-w-md min-h-full px-4 mx-auto",
         ))
-        .with([login_form(template), oidc_button(&template.oidc_info)]),
+        .with([
+            login_form(template),
+            oidc_button(&template.oidc_info),
+            signup_link(template.signup_enabled),
+        ]),
     )
 }
 
@@ -135,6 +147,14 @@ fn submit_button() -> Element {
     ])
 }
 
+fn signup_link(signup_enabled: bool) -> Element {
+    if signup_enabled {
+        a([class("mt-5 text-center underline"), href("/signup")]).with("No account yet? Sign up")
+    } else {
+        nothing()
+    }
+}
+
 fn oidc_button(oidc_info: &OidcInfo) -> Element {
     if let OidcInfo::Configured { name } = oidc_info {
         fragment().with([
@@ -0,0 +1,64 @@
+#[allow(clippy::wildcard_imports)]
+use htmf::prelude::*;
+
+use super::base_document::base_document;
+use crate::{form_errors::FormErrors, forms::users::TotpLogin};
+
+pub struct Data {
+    pub input: TotpLogin,
+    pub errors: FormErrors,
+}
+
+pub fn view(
+    Data {
+        input: input_data,
+        errors,
+    }: &Data,
+) -> Element {
+    base_document(
+        div(class(
+            "flex flex-col justify-center max-w-md min-h-full px-4 mx-auto",
+        ))
+        .with(
+            form([
+                action("/login/totp"),
+                method("post"),
+                attr("hx-boost", "true"),
+                attr("hx-disabled-elt", "button"),
+                class("flex flex-col w-full"),
+            ])
+            .with([
+                h1(class("text-2xl font-bold tracking-tight text-center"))
+                    .with("Two-factor authentication"),
+                p(class("mt-4 text-center text-neutral-400")).with(
+                    "Enter the code from your authenticator app, or one of your recovery codes.",
+                ),
+                label([class("mt-10 text-neutral-400"), for_("code")]).with("Code"),
+                errors.view("code"),
+                input([
+                    type_("text"),
+                    name("code"),
+                    attr("autocomplete", "one-time-code"),
+                    attr("autofocus", "true"),
+                    class("rounded py-1.5 px-3 mt-2 bg-neutral-900"),
+                    required("true"),
+                ]),
+                input_data
+                    .previous_uri
+                    .as_ref()
+                    .map(|previous_uri| {
+                        input([type_("hidden"), name("previous_uri"), value(previous_uri)])
+                    })
+                    .into(),
+                button([
+                    type_("submit"),
+                    class(
+                        "leading-6 bg-neutral-300 mt-5 font-semibold rounded py-1.5 flex \
+                         items-center justify-center disabled:bg-neutral-500 text-neutral-900",
+                    ),
+                ])
+                .with("Sign in"),
+            ]),
+        ),
+    )
+}
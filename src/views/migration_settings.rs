@@ -0,0 +1,114 @@
+use htmf::prelude::*;
+
+use super::layout;
+use crate::{db, form_errors::FormErrors, forms::ap_users::ActorReference};
+
+pub struct Data {
+    pub layout: layout::Template,
+    pub ap_user: db::ApUser,
+    pub alias_input: ActorReference,
+    pub alias_errors: FormErrors,
+    pub move_input: ActorReference,
+    pub move_errors: FormErrors,
+}
+
+pub fn view(
+    Data {
+        layout,
+        ap_user,
+        alias_input,
+        alias_errors,
+        move_input,
+        move_errors,
+    }: &Data,
+) -> Element {
+    layout::layout(
+        div(class("flex flex-col max-w-xl mx-4 mb-4 grow")).with([
+            header(class("mt-3 mb-4"))
+                .with([h1(class("text-xl font-bold")).with("Account migration")]),
+            h2(class("mt-4 font-bold")).with("Move from another account"),
+            p(class("mt-2 text-neutral-400")).with(
+                "To move your followers from another account to this one, add the other account \
+                 as an alias first.",
+            ),
+            ul(class("mt-2")).with(
+                ap_user
+                    .also_known_as
+                    .iter()
+                    .map(alias_item)
+                    .collect::<Vec<_>>(),
+            ),
+            actor_form(
+                "/settings/migration/aliases",
+                alias_input,
+                alias_errors,
+                "Add alias",
+            ),
+            h2(class("mt-8 font-bold")).with("Move to another account"),
+            match &ap_user.moved_to {
+                Some(moved_to) => p(class("mt-2")).with([
+                    text("This account moved to "),
+                    a([href(moved_to.as_str()), class("underline")]).with(moved_to.as_str()),
+                    text("."),
+                ]),
+                None => fragment().with([
+                    p(class("mt-2 text-neutral-400")).with(format!(
+                        "Your followers will follow the new account instead. Add {} as an alias \
+                         to the new account first.",
+                        ap_user.ap_id.inner()
+                    )),
+                    actor_form(
+                        "/settings/migration/move",
+                        move_input,
+                        move_errors,
+                        "Move account",
+                    ),
+                ]),
+            },
+        ]),
+        layout,
+    )
+}
+
+fn alias_item(alias: &url::Url) -> Element {
+    li(class("flex items-center justify-between gap-2 py-1")).with([
+        a([href(alias.as_str()), class("overflow-hidden text-ellipsis")]).with(alias.as_str()),
+        form([action("/settings/migration/aliases/remove"), method("post")]).with([
+            input([name("actor"), type_("hidden"), value(alias.as_str())]),
+            button([
+                type_("submit"),
+                class("px-4 py-1 border rounded border-neutral-700 hover:bg-neutral-700"),
+            ])
+            .with("Remove"),
+        ]),
+    ])
+}
+
+fn actor_form(
+    form_action: &str,
+    input_data: &ActorReference,
+    errors: &FormErrors,
+    submit_label: &str,
+) -> Element {
+    form([
+        action(form_action),
+        method("POST"),
+        class("flex flex-col mt-2"),
+    ])
+    .with([
+        errors.view("actor"),
+        input([
+            required(""),
+            name("actor"),
+            type_("text"),
+            placeholder("user@example.com"),
+            value(&input_data.actor),
+            class("rounded py-1.5 px-3 bg-neutral-900"),
+        ]),
+        button([
+            type_("submit"),
+            class("bg-neutral-300 py-1.5 px-3 text-neutral-900 rounded mt-2 self-end"),
+        ])
+        .with(submit_label),
+    ])
+}
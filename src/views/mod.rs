@@ -1,19 +1,41 @@
 #![allow(clippy::wildcard_imports)]
 #![allow(clippy::too_many_lines)]
+pub mod account_settings;
+pub mod activity_log;
+pub mod admin;
+pub mod admin_overview;
+pub mod admin_users;
 pub mod base_document;
+pub mod bookmark;
 pub mod content;
 pub mod create_bookmark;
 pub mod create_link;
 pub mod create_list;
+pub mod create_report;
+pub mod deliveries;
+pub mod discover;
+pub mod domain_blocks;
 pub mod edit_list_title;
 pub mod form;
 pub mod index;
+pub mod instances;
 pub mod layout;
 pub mod list;
 pub mod list_unpinned_lists;
 pub mod login;
 pub mod login_demo;
+pub mod login_totp;
+pub mod migration_settings;
+pub mod notifications;
 pub mod oidc_select_username;
 pub mod profile;
+pub mod profile_settings;
+pub mod registrations;
+pub mod relays;
+pub mod reports;
+pub mod search;
+pub mod signup;
+pub mod tag;
+pub mod totp_settings;
 pub mod unsorted_bookmarks;
 pub mod users;
@@ -0,0 +1,198 @@
+use htmf::prelude::*;
+
+use super::layout;
+use crate::{
+    date_time::format_utc,
+    db::{self, interactions::Kind},
+    federation::webfinger,
+    response_error::ResponseResult,
+};
+
+pub struct Data {
+    pub layout: layout::Template,
+    pub ap_user: db::ApUser,
+    pub pending_followers: Vec<db::ApUser>,
+    pub notify_on_interactions: bool,
+    /// Recent likes and boosts of the user's bookmarks, newest first
+    pub interactions: Vec<ReceivedInteraction>,
+}
+
+pub struct ReceivedInteraction {
+    pub interaction: db::interactions::Interaction,
+    /// The user that liked or boosted the bookmark
+    pub ap_user: db::ApUser,
+    pub bookmark: db::Bookmark,
+}
+
+pub fn view(
+    Data {
+        layout,
+        ap_user,
+        pending_followers,
+        notify_on_interactions,
+        interactions,
+    }: &Data,
+) -> ResponseResult<Element> {
+    let follow_requests = pending_followers
+        .iter()
+        .map(follow_request)
+        .collect::<ResponseResult<Vec<_>>>()?;
+    let interactions = interactions
+        .iter()
+        .map(received_interaction)
+        .collect::<ResponseResult<Vec<_>>>()?;
+
+    Ok(layout::layout(
+        fragment().with([
+            header(class(
+                "flex flex-wrap items-center justify-between gap-2 px-4 pt-3 mb-4",
+            ))
+            .with([
+                h1(class("text-xl font-bold")).with("Notifications"),
+                manually_approves_followers_form(ap_user.manually_approves_followers),
+            ]),
+            h2(class("px-4 mb-2 font-bold text-neutral-400")).with("Follow requests"),
+            if follow_requests.is_empty() {
+                p(class("px-4 text-neutral-400")).with("No pending follow requests.")
+            } else {
+                fragment().with(follow_requests)
+            },
+            header(class(
+                "flex flex-wrap items-center justify-between gap-2 px-4 mt-6 mb-2",
+            ))
+            .with([
+                h2(class("font-bold text-neutral-400")).with("Likes and boosts"),
+                notify_on_interactions_form(*notify_on_interactions),
+            ]),
+            if !notify_on_interactions {
+                p(class("px-4 text-neutral-400"))
+                    .with("Likes and boosts of your bookmarks are not shown.")
+            } else if interactions.is_empty() {
+                p(class("px-4 text-neutral-400"))
+                    .with("Nobody liked or boosted your bookmarks yet.")
+            } else {
+                fragment().with(interactions)
+            },
+        ]),
+        layout,
+    ))
+}
+
+fn manually_approves_followers_form(manually_approves_followers: bool) -> Element {
+    form([
+        action("/notifications/manually_approves_followers"),
+        method("post"),
+    ])
+    .with(
+        button([
+            class("block px-4 py-1 border rounded hover:bg-neutral-700 border-neutral-700 w-max"),
+            name("manually_approves_followers"),
+            type_("submit"),
+            value(if manually_approves_followers {
+                "false"
+            } else {
+                "true"
+            }),
+        ])
+        .with(if manually_approves_followers {
+            "Accept new followers automatically"
+        } else {
+            "Approve new followers manually"
+        }),
+    )
+}
+
+fn notify_on_interactions_form(notify_on_interactions: bool) -> Element {
+    form([
+        action("/notifications/notify_on_interactions"),
+        method("post"),
+    ])
+    .with(
+        button([
+            class("block px-4 py-1 border rounded hover:bg-neutral-700 border-neutral-700 w-max"),
+            name("notify_on_interactions"),
+            type_("submit"),
+            value(if notify_on_interactions {
+                "false"
+            } else {
+                "true"
+            }),
+        ])
+        .with(if notify_on_interactions {
+            "Hide likes and boosts"
+        } else {
+            "Show likes and boosts"
+        }),
+    )
+}
+
+fn received_interaction(
+    ReceivedInteraction {
+        interaction,
+        ap_user,
+        bookmark,
+    }: &ReceivedInteraction,
+) -> ResponseResult<Element> {
+    let resource =
+        webfinger::Resource::from_name_and_url(ap_user.username.clone(), ap_user.ap_id.inner())?;
+    let handle = format!("{}@{}", resource.name, resource.domain);
+    let verb = match interaction.kind {
+        Kind::Like => " liked ",
+        Kind::Boost => " boosted ",
+    };
+
+    Ok(section(class(
+        "flex flex-wrap items-baseline justify-between gap-2 px-4 py-2 border-t \
+         border-neutral-700",
+    ))
+    .with([
+        p([]).with([
+            a([
+                href(format!("/user/{handle}")),
+                class("font-semibold hover:text-fuchsia-300"),
+            ])
+            .with(ap_user.display_name.as_deref().unwrap_or(&handle)),
+            text(verb),
+            a([href(bookmark.path()), class("hover:text-fuchsia-300")]).with(&bookmark.title),
+        ]),
+        span(class("text-sm text-neutral-400")).with(format_utc(interaction.created_at)),
+    ]))
+}
+
+fn follow_request(follower: &db::ApUser) -> ResponseResult<Element> {
+    let resource =
+        webfinger::Resource::from_name_and_url(follower.username.clone(), follower.ap_id.inner())?;
+    let handle = format!("{}@{}", resource.name, resource.domain);
+
+    Ok(section(class(
+        "flex flex-wrap items-center justify-between gap-2 px-4 py-4 border-t border-neutral-700",
+    ))
+    .with([
+        a([
+            href(format!("/user/{handle}")),
+            class("font-semibold leading-8 hover:text-fuchsia-300"),
+        ])
+        .with(follower.display_name.as_deref().unwrap_or(&handle)),
+        div(class("flex gap-2")).with([
+            answer_button(follower, "accept", "Accept"),
+            answer_button(follower, "reject", "Reject"),
+        ]),
+    ]))
+}
+
+fn answer_button(follower: &db::ApUser, path: &str, label: &str) -> Element {
+    form([
+        action(format!(
+            "/notifications/follow_requests/{}/{path}",
+            follower.id
+        )),
+        method("post"),
+    ])
+    .with(
+        button([
+            type_("submit"),
+            class("px-4 py-1 border rounded border-neutral-700 hover:bg-neutral-700"),
+        ])
+        .with(label),
+    )
+}
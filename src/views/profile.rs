@@ -4,7 +4,10 @@ use uuid::Uuid;
 use crate::{
     db::{self, AppTx},
     response_error::ResponseResult,
-    views::{content::pluralize, layout},
+    views::{
+        content::{bookmark_entry, pluralize},
+        layout,
+    },
 };
 
 struct Metadata {
@@ -13,8 +16,26 @@ struct Metadata {
 
 pub struct Data {
     pub layout: layout::Template,
+    /// The handle used to look up this user, e.g. `username@example.com`
+    pub handle: String,
     pub ap_user: db::ApUser,
     pub public_lists: Vec<db::ListWithMetadata>,
+    /// What a remote user shares on their own instance. `None` for local
+    /// users.
+    pub remote_content: Option<RemoteContent>,
+    /// Whether the logged in user blocked this user. `None` if the user
+    /// can't be blocked, e.g. because they're a local user.
+    pub blocked: Option<bool>,
+    /// Whether the logged in user trusts this user. `None` if the user can't
+    /// be trusted, e.g. because it's the logged in user themselves.
+    pub trusted: Option<bool>,
+    /// Whether the logged in user is looking at their own profile
+    pub editable: bool,
+}
+
+pub struct RemoteContent {
+    pub lists: Vec<db::remote_lists::RemoteList>,
+    pub bookmarks: Vec<db::Bookmark>,
 }
 
 async fn get_metadata(tx: &mut AppTx, ap_user_id: Uuid) -> ResponseResult<Metadata> {
@@ -40,8 +61,13 @@ pub async fn view(
     mut tx: AppTx,
     Data {
         layout,
+        handle,
         ap_user,
         public_lists: lists,
+        remote_content,
+        blocked,
+        trusted,
+        editable,
     }: &Data,
 ) -> ResponseResult<Element> {
     let metadata = get_metadata(&mut tx, ap_user.id).await?;
@@ -51,19 +77,133 @@ pub async fn view(
         header(
             [class("pt-3 mb-8")],
             [
-                h1([class("text-xl font-bold px-4")], [&ap_user.username]),
+                ap_user.header_url.as_ref().map_or(nothing(), |url| {
+                    img([
+                        src(url.as_str()),
+                        alt(""),
+                        class("w-full h-40 object-cover mb-4"),
+                    ])
+                }),
+                div(
+                    class("flex items-center justify-between gap-4 px-4"),
+                    [
+                        div(
+                            class("flex items-center gap-4"),
+                            [
+                                ap_user.avatar_url.as_ref().map_or(nothing(), |url| {
+                                    img([
+                                        src(url.as_str()),
+                                        alt(""),
+                                        class("w-16 h-16 rounded object-cover"),
+                                    ])
+                                }),
+                                div(
+                                    (),
+                                    [
+                                        h1(
+                                            [class("text-xl font-bold")],
+                                            [ap_user
+                                                .display_name
+                                                .as_deref()
+                                                .unwrap_or(&ap_user.username)],
+                                        ),
+                                        p(class("text-neutral-400"), format!("@{handle}")),
+                                    ],
+                                ),
+                            ],
+                        ),
+                        if *editable {
+                            a(
+                                [
+                                    href("/settings/profile"),
+                                    class(
+                                        "px-4 py-1 border rounded border-neutral-700 \
+                                         hover:bg-neutral-700",
+                                    ),
+                                ],
+                                "Edit profile",
+                            )
+                        } else {
+                            div(
+                                class("flex gap-2"),
+                                [
+                                    trusted
+                                        .map_or(nothing(), |trusted| trust_button(handle, trusted)),
+                                    blocked
+                                        .map_or(nothing(), |blocked| block_button(handle, blocked)),
+                                    // Only remote users can be blocked and reported
+                                    blocked.map_or(nothing(), |_| report_link(handle)),
+                                ],
+                            )
+                        },
+                    ],
+                ),
+                ap_user.moved_to.as_ref().map_or(nothing(), |moved_to| {
+                    p(
+                        class("m-4 text-neutral-400"),
+                        [
+                            text("This account has moved to "),
+                            a(
+                                [href(moved_to.as_str()), class("underline")],
+                                moved_to.as_str(),
+                            ),
+                        ],
+                    )
+                }),
                 ap_user
                     .bio
                     .as_ref()
                     .map_or(nothing(), |bio| p(class("m-4"), bio)),
             ],
         ),
-        view_lists(lists, &metadata),
+        remote_content
+            .as_ref()
+            .map_or_else(|| view_lists(lists, &metadata), view_remote_content),
     ]);
 
     Ok(layout::layout(children, layout))
 }
 
+fn trust_button(handle: &str, trusted: bool) -> Element {
+    let (path, label) = if trusted {
+        ("untrust", "Untrust")
+    } else {
+        ("trust", "Trust")
+    };
+    form(
+        [action(format!("/user/{handle}/{path}")), method("post")],
+        [button(
+            class("px-4 py-1 border rounded border-neutral-700 hover:bg-neutral-700"),
+            label,
+        )],
+    )
+}
+
+fn report_link(handle: &str) -> Element {
+    a(
+        [
+            href(format!("/user/{handle}/report")),
+            class("px-4 py-1 border rounded border-neutral-700 hover:bg-neutral-700"),
+        ],
+        "Report",
+    )
+}
+
+fn block_button(handle: &str, blocked: bool) -> Element {
+    let (path, label) = if blocked {
+        ("unblock", "Unblock")
+    } else {
+        ("block", "Block")
+    };
+    form(
+        [action(format!("/user/{handle}/{path}")), method("post")],
+        [button(
+            class("px-4 py-1 border rounded border-neutral-700 hover:bg-neutral-700"),
+            label,
+        )],
+    )
+}
+
 fn view_lists(lists: &[db::ListWithMetadata], metadata: &Metadata) -> Element {
     section(
         [],
@@ -120,3 +260,65 @@ fn list_item(list: &db::ListWithMetadata) -> Element {
         ],
     )
 }
+
+fn view_remote_content(content: &RemoteContent) -> Element {
+    fragment([
+        section(
+            [],
+            [p(
+                class("px-4 text-neutral-400 pb-1 font-bold tracking-tight"),
+                pluralize(
+                    i64::try_from(content.lists.len()).unwrap_or(i64::MAX),
+                    "public list",
+                    "public lists",
+                ),
+            )],
+        )
+        .with(
+            content
+                .lists
+                .iter()
+                .map(remote_list_item)
+                .collect::<Vec<_>>(),
+        ),
+        section(
+            class("mt-8"),
+            [p(
+                class("px-4 text-neutral-400 pb-1 font-bold tracking-tight"),
+                "Recent bookmarks",
+            )],
+        )
+        .with(
+            content
+                .bookmarks
+                .iter()
+                .map(bookmark_entry)
+                .collect::<Vec<_>>(),
+        ),
+    ])
+}
+
+fn remote_list_item(list: &db::remote_lists::RemoteList) -> Element {
+    section(
+        class("px-4 pt-4 pb-4 border-t border-neutral-700"),
+        [
+            a(
+                [
+                    class(
+                        "block overflow-hidden font-semibold leading-8 hover:text-fuchsia-300 \
+                         text-ellipsis whitespace-nowrap",
+                    ),
+                    href(list.url.as_str()),
+                ],
+                &list.title,
+            ),
+            list.content
+                .as_ref()
+                .map_or(nothing(), |content| p(class("text-neutral-300"), content)),
+            p(
+                class("text-sm text-neutral-400"),
+                pluralize(list.item_count, "item", "items"),
+            ),
+        ],
+    )
+}
@@ -0,0 +1,84 @@
+use htmf::prelude::*;
+
+use super::layout;
+use crate::{
+    form_errors::FormErrors,
+    forms::ap_users::{MAX_BIO_LENGTH, UpdateApUser},
+};
+
+pub struct Data {
+    pub layout: layout::Template,
+    pub input: UpdateApUser,
+    pub errors: FormErrors,
+}
+
+pub fn view(
+    Data {
+        layout,
+        input: input_data,
+        errors,
+    }: &Data,
+) -> Element {
+    layout::layout(
+        fragment().with([form([
+            action("/settings/profile"),
+            method("POST"),
+            attr("enctype", "multipart/form-data"),
+            class("flex flex-col max-w-xl mx-4 mb-4 grow"),
+        ])
+        .with([
+            header(class("mt-3 mb-4")).with([h1(class("text-xl font-bold")).with("Edit profile")]),
+            label(for_("display_name")).with("Display name"),
+            errors.view("display_name"),
+            input([
+                name("display_name"),
+                type_("text"),
+                value(input_data.display_name.as_deref().unwrap_or("")),
+                class("rounded py-1.5 px-3 mt-2 bg-neutral-900"),
+            ]),
+            label(class("mt-4")).with([
+                text("Bio"),
+                errors.view("bio"),
+                textarea([
+                    name("bio"),
+                    attr("maxlength", MAX_BIO_LENGTH.to_string()),
+                    class("rounded py-1.5 px-3 mt-2 bg-neutral-900 block w-full"),
+                ])
+                .with(input_data.bio.as_deref().unwrap_or("")),
+            ]),
+            image_input("avatar", "Avatar"),
+            errors.view("avatar"),
+            image_input("header", "Header image"),
+            errors.view("header"),
+            errors.view("root"),
+            button([
+                type_("submit"),
+                class("bg-neutral-300 py-1.5 px-3 text-neutral-900 rounded mt-4 self-end"),
+            ])
+            .with("Save profile"),
+            a([
+                href("/settings/migration"),
+                class("mt-8 text-neutral-400 hover:text-neutral-300"),
+            ])
+            .with("Move to or from another account"),
+            a([
+                href("/settings/account"),
+                class("mt-2 text-neutral-400 hover:text-neutral-300"),
+            ])
+            .with("Password, email and username"),
+        ])]),
+        layout,
+    )
+}
+
+fn image_input(field_name: &str, label_text: &str) -> Element {
+    label(class("mt-4")).with([
+        text(label_text),
+        input([
+            name(field_name),
+            type_("file"),
+            attr("accept", "image/png,image/jpeg,image/gif,image/webp"),
+            class("block mt-2"),
+        ]),
+    ])
+}
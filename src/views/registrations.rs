@@ -0,0 +1,189 @@
+use htmf::{into_attrs::IntoAttrs, prelude::*};
+
+use super::{admin, layout};
+use crate::{
+    date_time::format_utc,
+    db::registrations::{Invite, RegistrationMode, Request},
+    form_errors::FormErrors,
+    forms::registrations::EditRegistrationSettings,
+};
+
+pub struct Data {
+    pub layout: layout::Template,
+    pub input: EditRegistrationSettings,
+    pub errors: FormErrors,
+    pub requests: Vec<Request>,
+    pub invites: Vec<Invite>,
+}
+
+pub fn view(
+    Data {
+        layout,
+        input: input_data,
+        errors,
+        requests,
+        invites,
+    }: &Data,
+) -> Element {
+    layout::layout(
+        fragment().with([
+            header(class("px-4 pt-3 mb-4"))
+                .with([h1(class("text-xl font-bold")).with("Registrations")]),
+            admin::navigation(),
+            settings_form(input_data, errors),
+            section_header("Pending registrations"),
+            if requests.is_empty() {
+                p(class("px-4 pb-4 text-sm text-neutral-400")).with("No pending registrations.")
+            } else {
+                fragment().with(requests.iter().map(request_entry).collect::<Vec<_>>())
+            },
+            section_header("Invites"),
+            form([action("/admin/invites"), method("POST"), class("px-4 pb-4")]).with(
+                button([
+                    type_("submit"),
+                    class("px-4 py-1 border rounded border-neutral-700 hover:bg-neutral-700"),
+                ])
+                .with("Create invite"),
+            ),
+            fragment().with(invites.iter().map(invite_entry).collect::<Vec<_>>()),
+        ]),
+        layout,
+    )
+}
+
+fn settings_form(input_data: &EditRegistrationSettings, errors: &FormErrors) -> Element {
+    form([
+        action("/admin/registrations"),
+        method("POST"),
+        class("flex flex-col max-w-xl mx-4 mb-4"),
+    ])
+    .with([
+        div(class("flex flex-col gap-1")).with([
+            mode_option(RegistrationMode::Closed, input_data.registration_mode),
+            mode_option(RegistrationMode::Open, input_data.registration_mode),
+            mode_option(RegistrationMode::Invite, input_data.registration_mode),
+            mode_option(RegistrationMode::Approval, input_data.registration_mode),
+        ]),
+        label([for_("min_username_length"), class("mt-4")]).with("Minimum username length"),
+        errors.view("min_username_length"),
+        input([
+            required(""),
+            name("min_username_length"),
+            type_("number"),
+            attr("min", "3"),
+            attr("max", "50"),
+            value(input_data.min_username_length.to_string()),
+            class("rounded py-1.5 px-3 mt-2 bg-neutral-900"),
+        ]),
+        label([for_("reserved_usernames"), class("mt-4")])
+            .with("Reserved usernames, separated by commas"),
+        errors.view("reserved_usernames"),
+        textarea([
+            name("reserved_usernames"),
+            class("rounded py-1.5 px-3 mt-2 bg-neutral-900"),
+        ])
+        .with(input_data.reserved_usernames.as_str()),
+        errors.view("root"),
+        button([
+            type_("submit"),
+            class("bg-neutral-300 py-1.5 px-3 text-neutral-900 rounded mt-4 self-end"),
+        ])
+        .with("Save"),
+    ])
+}
+
+fn mode_option(mode: RegistrationMode, selected: RegistrationMode) -> Element {
+    let description = match mode {
+        RegistrationMode::Closed => "Closed: only admins can create users",
+        RegistrationMode::Open => "Open: anyone can sign up",
+        RegistrationMode::Invite => "Invite only: people need an invite code to sign up",
+        RegistrationMode::Approval => "Approval required: admins review new sign-ups",
+    };
+    label(()).with([
+        input([
+            type_("radio"),
+            name("registration_mode"),
+            value(mode.as_str()),
+            (mode == selected).then(checked).into_attrs(),
+        ]),
+        text(description),
+    ])
+}
+
+fn section_header(title: &str) -> Element {
+    h2(class(
+        "px-4 pt-4 pb-2 font-bold border-t border-neutral-700",
+    ))
+    .with(title)
+}
+
+fn request_entry(request: &Request) -> Element {
+    section(class(
+        "flex flex-wrap items-end justify-between gap-2 px-4 py-4 border-t border-neutral-700",
+    ))
+    .with([
+        div(class("overflow-hidden")).with([
+            p(class("font-semibold leading-8")).with(&request.username),
+            p(class("text-sm text-neutral-400")).with(format_utc(request.created_at)),
+            if request.reason.is_empty() {
+                fragment()
+            } else {
+                p(class("mt-2 whitespace-pre-wrap")).with(&request.reason)
+            },
+        ]),
+        div(class("flex gap-2")).with([
+            request_button(request, "approve", "Approve"),
+            request_button(request, "reject", "Reject"),
+        ]),
+    ])
+}
+
+fn request_button(request: &Request, path: &str, label: &str) -> Element {
+    form([
+        action(format!(
+            "/admin/registration_requests/{}/{path}",
+            request.id
+        )),
+        method("POST"),
+    ])
+    .with(
+        button([
+            type_("submit"),
+            class("px-4 py-1 border rounded border-neutral-700 hover:bg-neutral-700"),
+        ])
+        .with(label),
+    )
+}
+
+fn invite_entry(invite: &Invite) -> Element {
+    section(class(
+        "flex flex-wrap items-end justify-between gap-2 px-4 py-4 border-t border-neutral-700",
+    ))
+    .with([
+        div(class("overflow-hidden")).with([
+            p(class("font-mono leading-8 break-all")).with(&invite.code),
+            p(class("text-sm text-neutral-400")).with(
+                match (invite.used_at, &invite.used_by_username) {
+                    (Some(_), Some(username)) => format!("used by {username}"),
+                    (Some(used_at), None) => format!("used {}", format_utc(used_at)),
+                    (None, _) => format!("created {}", format_utc(invite.created_at)),
+                },
+            ),
+        ]),
+        if invite.used_at.is_some() {
+            fragment()
+        } else {
+            form([
+                action(format!("/admin/invites/{}/delete", invite.id)),
+                method("POST"),
+            ])
+            .with(
+                button([
+                    type_("submit"),
+                    class("px-4 py-1 border rounded border-neutral-700 hover:bg-neutral-700"),
+                ])
+                .with("Delete"),
+            )
+        },
+    ])
+}
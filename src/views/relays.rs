@@ -0,0 +1,123 @@
+use htmf::{into_attrs::IntoAttrs, prelude::*};
+
+use super::{admin, layout};
+use crate::{
+    date_time::format_utc,
+    db::relays::{Kind, Relay, State},
+    form_errors::FormErrors,
+    forms::relays::CreateRelay,
+};
+
+pub struct Data {
+    pub layout: layout::Template,
+    pub relays: Vec<Relay>,
+    pub input: CreateRelay,
+    pub errors: FormErrors,
+}
+
+pub fn view(
+    Data {
+        layout,
+        relays,
+        input: input_data,
+        errors,
+    }: &Data,
+) -> Element {
+    layout::layout(
+        fragment().with([
+            header(class("px-4 pt-3 mb-4")).with([
+                h1(class("text-xl font-bold")).with("Relays"),
+                p(class("text-sm text-neutral-400")).with(
+                    "Public bookmarks are sent to all relays, and link posts received from them \
+                     show up in the discover feed.",
+                ),
+            ]),
+            admin::navigation(),
+            create_form(input_data, errors),
+            fragment().with(relays.iter().map(relay_entry).collect::<Vec<_>>()),
+        ]),
+        layout,
+    )
+}
+
+fn create_form(input_data: &CreateRelay, errors: &FormErrors) -> Element {
+    form([
+        action("/admin/relays"),
+        method("POST"),
+        class("flex flex-col max-w-xl mx-4 mb-4"),
+    ])
+    .with([
+        label(for_("url")).with("Relay URL"),
+        errors.view("url"),
+        input([
+            required(""),
+            name("url"),
+            type_("url"),
+            placeholder("https://relay.example/inbox"),
+            value(&input_data.url),
+            class("rounded py-1.5 px-3 mt-2 bg-neutral-900"),
+        ]),
+        div(class("flex flex-col gap-1 mt-4")).with([
+            kind_option(Kind::Mastodon, input_data.kind),
+            kind_option(Kind::Litepub, input_data.kind),
+        ]),
+        errors.view("root"),
+        button([
+            type_("submit"),
+            class("bg-neutral-300 py-1.5 px-3 text-neutral-900 rounded mt-4 self-end"),
+        ])
+        .with("Subscribe"),
+    ])
+}
+
+fn kind_option(kind: Kind, selected: Kind) -> Element {
+    let description = match kind {
+        Kind::Mastodon => "Mastodon-style: enter the inbox of the relay",
+        Kind::Litepub => "LitePub: enter the actor of the relay",
+    };
+    label(()).with([
+        input([
+            type_("radio"),
+            name("kind"),
+            value(kind.as_str()),
+            (kind == selected).then(checked).into_attrs(),
+        ]),
+        text(description),
+    ])
+}
+
+fn relay_entry(relay: &Relay) -> Element {
+    let state = match relay.state {
+        State::Pending => "Waiting for the relay to accept",
+        State::Accepted => "Subscribed",
+        State::Rejected => "Rejected by the relay",
+    };
+    let kind = match relay.kind {
+        Kind::Mastodon => "Mastodon-style",
+        Kind::Litepub => "LitePub",
+    };
+
+    section(class(
+        "flex flex-wrap items-end justify-between gap-2 px-4 py-4 border-t border-neutral-700",
+    ))
+    .with([
+        div(class("overflow-hidden")).with([
+            p(class("font-semibold leading-8")).with(relay.url.as_str()),
+            p(class("text-sm text-neutral-400")).with(format!(
+                "{kind} · {state} · added {}",
+                format_utc(relay.created_at)
+            )),
+        ]),
+        form([
+            action(format!("/admin/relays/{}/delete", relay.id)),
+            method("POST"),
+        ])
+        .with(
+            button([
+                type_("submit"),
+                class("px-4 py-1 border rounded border-neutral-700 hover:bg-neutral-700"),
+            ])
+            .with("Unsubscribe"),
+        ),
+    ])
+}
@@ -0,0 +1,96 @@
+use htmf::prelude::*;
+
+use super::{admin, layout};
+use crate::{
+    date_time::format_utc,
+    db::{self, reports::Report},
+    forms::reports::ModerationAction,
+};
+
+pub struct Data {
+    pub layout: layout::Template,
+    pub entries: Vec<Entry>,
+}
+
+pub struct Entry {
+    pub report: Report,
+    pub reporter_handle: String,
+    pub reported_handle: String,
+    /// Local users can't be blocked by domain
+    pub reported_is_local: bool,
+    /// The reported bookmark, unless the report is about the whole account or
+    /// the bookmark was deleted since
+    pub bookmark: Option<db::Bookmark>,
+}
+
+pub fn view(Data { layout, entries }: &Data) -> Element {
+    layout::layout(
+        fragment().with([
+            header(class("px-4 pt-3 mb-4")).with([h1(class("text-xl font-bold")).with("Reports")]),
+            admin::navigation(),
+            if entries.is_empty() {
+                p(class("px-4 py-4 text-neutral-400")).with("No open reports.")
+            } else {
+                fragment().with(entries.iter().map(report_entry).collect::<Vec<_>>())
+            },
+        ]),
+        layout,
+    )
+}
+
+fn report_entry(entry: &Entry) -> Element {
+    let report = &entry.report;
+    section(class("px-4 py-4 border-t border-neutral-700")).with([
+        p(class("font-semibold leading-8 break-all")).with([a([
+            href(format!("/user/{}", entry.reported_handle)),
+            class("hover:underline"),
+        ])
+        .with(format!("@{}", entry.reported_handle))]),
+        p(class("text-sm text-neutral-400 break-all")).with(format!(
+            "reported by @{} {}",
+            entry.reporter_handle,
+            format_utc(report.created_at)
+        )),
+        entry.bookmark.as_ref().map_or(fragment(), |bookmark| {
+            p(class("mt-2 break-all"))
+                .with([a([href(bookmark.path()), class("hover:underline")]).with(&bookmark.title)])
+        }),
+        if report.comment.is_empty() {
+            fragment()
+        } else {
+            p(class("mt-2 whitespace-pre-wrap")).with(&report.comment)
+        },
+        div(class("flex flex-wrap gap-2 mt-4")).with([
+            action_button(report, ModerationAction::Dismiss, "Dismiss"),
+            if entry.bookmark.is_some() {
+                action_button(report, ModerationAction::DeleteContent, "Delete bookmark")
+            } else {
+                fragment()
+            },
+            action_button(report, ModerationAction::Suspend, "Suspend account"),
+            if entry.reported_is_local {
+                fragment()
+            } else {
+                action_button(report, ModerationAction::BlockDomain, "Block domain")
+            },
+        ]),
+    ])
+}
+
+fn action_button(report: &Report, moderation_action: ModerationAction, label: &str) -> Element {
+    form([
+        action(format!(
+            "/admin/reports/{}/{}",
+            report.id,
+            moderation_action.as_str()
+        )),
+        method("POST"),
+    ])
+    .with(
+        button([
+            type_("submit"),
+            class("px-4 py-1 border rounded border-neutral-700 hover:bg-neutral-700"),
+        ])
+        .with(label),
+    )
+}
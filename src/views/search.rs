@@ -0,0 +1,93 @@
+use htmf::prelude::*;
+
+use super::{content, layout};
+use crate::{db::bookmarks::TrustedSearchResult, forms::search::SearchQuery};
+
+pub struct Data {
+    pub layout: layout::Template,
+    pub query: SearchQuery,
+    /// `None` if nothing was searched yet
+    pub results: Option<Vec<TrustedSearchResult>>,
+}
+
+pub fn view(
+    Data {
+        layout,
+        query,
+        results,
+    }: &Data,
+) -> Element {
+    layout::layout(
+        fragment().with([
+            header(class("px-4 pt-3 mb-4")).with([
+                h1(class("text-xl font-bold")).with("Search"),
+                p(class("text-sm text-neutral-400"))
+                    .with("Find bookmarks saved by people you trust."),
+            ]),
+            search_form(query),
+            results.as_ref().map_or(fragment(), |results| {
+                if results.is_empty() {
+                    p(class("px-4 text-neutral-400")).with("No bookmarks found.")
+                } else {
+                    fragment().with(results.iter().map(result).collect::<Vec<_>>())
+                }
+            }),
+        ]),
+        layout,
+    )
+}
+
+fn search_form(query: &SearchQuery) -> Element {
+    form([
+        action("/search"),
+        method("GET"),
+        class("flex flex-col max-w-xl mx-4 mb-4"),
+    ])
+    .with([
+        input([
+            name("q"),
+            type_("search"),
+            placeholder("Search trusted bookmarks"),
+            value(&query.q),
+            required(""),
+            class("rounded py-1.5 px-3 bg-neutral-900"),
+        ]),
+        label(class("mt-2")).with([
+            input([
+                type_("checkbox"),
+                name("include_trusted_of_trusted"),
+                value("true"),
+                class("mr-2"),
+                query.include_trusted_of_trusted.then(checked).into_attrs(),
+            ]),
+            text("Include people trusted by people you trust"),
+        ]),
+        button([
+            type_("submit"),
+            class("bg-neutral-300 py-1.5 px-3 text-neutral-900 rounded mt-4 self-end"),
+        ])
+        .with("Search"),
+    ])
+}
+
+fn result(result: &TrustedSearchResult) -> Element {
+    section(class("px-4 py-4 border-t border-neutral-700")).with([
+        a([
+            href(&result.url),
+            class(
+                "block overflow-hidden leading-8 text-orange-100 hover:text-orange-300 \
+                 text-ellipsis whitespace-nowrap",
+            ),
+        ])
+        .with(&result.title),
+        content::link_url(&result.url),
+        a([
+            href(format!("/bookmarks/{}", result.bookmark_id)),
+            class("text-sm text-neutral-400 hover:text-neutral-200"),
+        ])
+        .with(format!(
+            "Saved by {} you trust",
+            content::pluralize(result.saved_by_count, "person", "people")
+        )),
+    ])
+}
@@ -0,0 +1,133 @@
+#[allow(clippy::wildcard_imports)]
+use htmf::prelude::*;
+
+use super::base_document::base_document;
+use crate::{db::registrations::RegistrationMode, form_errors::FormErrors, forms::users::SignUp};
+
+pub struct Data {
+    pub registration_mode: RegistrationMode,
+    pub input: SignUp,
+    pub errors: FormErrors,
+}
+
+pub fn view(data: &Data) -> Element {
+    let content = if data.registration_mode == RegistrationMode::Closed {
+        message(
+            "Registrations are closed",
+            "This instance doesn't accept new users right now.",
+        )
+    } else {
+        signup_form(data)
+    };
+
+    base_document(
+        div(class(
+            "flex flex-col justify-center max-w-md min-h-full px-4 mx-auto",
+        ))
+        .with(content),
+    )
+}
+
+/// Shown after signing up while registration requires approval.
+pub fn view_pending() -> Element {
+    base_document(
+        div(class(
+            "flex flex-col justify-center max-w-md min-h-full px-4 mx-auto",
+        ))
+        .with(message(
+            "Thanks for signing up",
+            "An admin will review your registration. You can log in once it's approved.",
+        )),
+    )
+}
+
+fn message(title: &str, description: &str) -> Element {
+    fragment().with([
+        h1(class("text-2xl font-bold tracking-tight text-center")).with(title),
+        p(class("mt-4 text-center text-neutral-400")).with(description),
+        a([class("mt-4 text-center underline"), href("/login")]).with("Back to the login"),
+    ])
+}
+
+fn signup_form(
+    Data {
+        registration_mode,
+        input: input_data,
+        errors,
+    }: &Data,
+) -> Element {
+    form([
+        action("/signup"),
+        method("post"),
+        attr("hx-boost", "true"),
+        attr("hx-disabled-elt", "button"),
+        class("flex flex-col w-full"),
+    ])
+    .with([
+        h1(class("text-2xl font-bold tracking-tight text-center")).with("Create an account"),
+        label([class("mt-10 text-neutral-400"), for_("user[username]")]).with("Username"),
+        errors.view("user.username"),
+        input([
+            type_("text"),
+            name("user[username]"),
+            class("rounded py-1.5 px-3 mt-2 bg-neutral-900"),
+            value(&input_data.user.username),
+            required("true"),
+        ]),
+        label([class("mt-4 text-neutral-400"), for_("user[password]")]).with("Password"),
+        errors.view("user.password"),
+        input([
+            type_("password"),
+            name("user[password]"),
+            class("rounded py-1.5 px-3 mt-2 bg-neutral-900"),
+            required("true"),
+        ]),
+        match registration_mode {
+            RegistrationMode::Invite => {
+                invite_code_field(errors, input_data.invite_code.as_deref().unwrap_or(""))
+            }
+            RegistrationMode::Approval => {
+                reason_field(errors, input_data.reason.as_deref().unwrap_or(""))
+            }
+            RegistrationMode::Closed | RegistrationMode::Open => nothing(),
+        },
+        button([
+            type_("submit"),
+            class(
+                "leading-6 bg-neutral-300 mt-5 font-semibold rounded py-1.5 flex items-center \
+                 justify-center disabled:bg-neutral-500 text-neutral-900",
+            ),
+        ])
+        .with("Sign up"),
+        errors.view("root"),
+        a([class("mt-4 text-center underline"), href("/login")])
+            .with("Already have an account? Sign in"),
+    ])
+}
+
+fn invite_code_field(errors: &FormErrors, val: &str) -> Element {
+    fragment().with([
+        label([class("mt-4 text-neutral-400"), for_("invite_code")]).with("Invite code"),
+        errors.view("invite_code"),
+        input([
+            type_("text"),
+            name("invite_code"),
+            class("rounded py-1.5 px-3 mt-2 bg-neutral-900"),
+            value(val),
+            required("true"),
+        ]),
+    ])
+}
+
+fn reason_field(errors: &FormErrors, val: &str) -> Element {
+    fragment().with([
+        label([class("mt-4 text-neutral-400"), for_("reason")]).with("Why do you want to join?"),
+        errors.view("reason"),
+        textarea([
+            name("reason"),
+            attr("rows", "4"),
+            class("rounded py-1.5 px-3 mt-2 bg-neutral-900"),
+        ])
+        .with(val),
+    ])
+}
@@ -0,0 +1,41 @@
+use htmf::prelude::*;
+
+use super::{content, layout};
+use crate::db;
+
+pub struct Data {
+    pub layout: layout::Template,
+    pub tag: String,
+    /// Public bookmarks with the hashtag, newest first
+    pub bookmarks: Vec<db::Bookmark>,
+}
+
+pub fn view(
+    Data {
+        layout,
+        tag,
+        bookmarks,
+    }: &Data,
+) -> Element {
+    layout::layout(
+        fragment().with([
+            header(class("px-4 pt-3 mb-4")).with([
+                h1(class("text-xl font-bold")).with(format!("#{tag}")),
+                p(class("text-sm text-neutral-400")).with(format!(
+                    "Public bookmarks tagged with #{tag} on this instance."
+                )),
+            ]),
+            if bookmarks.is_empty() {
+                p(class("px-4 text-neutral-400")).with("No bookmarks found.")
+            } else {
+                fragment().with(
+                    bookmarks
+                        .iter()
+                        .map(content::bookmark_entry)
+                        .collect::<Vec<_>>(),
+                )
+            },
+        ]),
+        layout,
+    )
+}
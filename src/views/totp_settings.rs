@@ -0,0 +1,97 @@
+use htmf::prelude::*;
+
+use super::layout;
+use crate::form_errors::FormErrors;
+
+pub struct Data {
+    pub layout: layout::Template,
+    pub step: Step,
+}
+
+pub enum Step {
+    /// Scan the QR code and confirm it worked by entering a code
+    Enroll {
+        secret: String,
+        /// The QR code as an image URL
+        qr_code: String,
+        errors: FormErrors,
+    },
+    /// Two-factor authentication was just enabled
+    RecoveryCodes(Vec<String>),
+}
+
+pub fn view(Data { layout, step }: &Data) -> Element {
+    layout::layout(
+        div(class("flex flex-col max-w-xl mx-4 mb-4 grow")).with([
+            header(class("mt-3 mb-4"))
+                .with([h1(class("text-xl font-bold")).with("Two-factor authentication")]),
+            match step {
+                Step::Enroll {
+                    secret,
+                    qr_code,
+                    errors,
+                } => enroll(secret, qr_code, errors),
+                Step::RecoveryCodes(codes) => recovery_codes(codes),
+            },
+        ]),
+        layout,
+    )
+}
+
+fn enroll(secret: &str, qr_code: &str, errors: &FormErrors) -> Element {
+    fragment().with([
+        p(class("text-neutral-400")).with(
+            "Scan this QR code with your authenticator app, or enter the key below manually.",
+        ),
+        img([
+            src(qr_code),
+            alt("QR code for your authenticator app"),
+            class("mt-4 bg-white rounded w-52 h-52"),
+        ]),
+        p(class("mt-4 font-mono break-all")).with(secret),
+        form([
+            action("/settings/account/totp"),
+            method("POST"),
+            class("flex flex-col mt-4"),
+        ])
+        .with([
+            label([for_("code"), class("text-neutral-400")])
+                .with("Code from your authenticator app"),
+            errors.view("code"),
+            input([
+                required(""),
+                name("code"),
+                type_("text"),
+                attr("inputmode", "numeric"),
+                attr("autocomplete", "one-time-code"),
+                class("rounded py-1.5 px-3 mt-2 bg-neutral-900"),
+            ]),
+            button([
+                type_("submit"),
+                class("bg-neutral-300 py-1.5 px-3 text-neutral-900 rounded mt-2 self-end"),
+            ])
+            .with("Enable"),
+        ]),
+    ])
+}
+
+fn recovery_codes(codes: &[String]) -> Element {
+    fragment().with([
+        p([]).with(
+            "Two-factor authentication is enabled. Store these recovery codes in a safe place. \
+             Each of them lets you log in once if you lose access to your authenticator app. \
+             They are shown only once.",
+        ),
+        ul(class("mt-4 font-mono")).with(
+            codes
+                .iter()
+                .map(|code| li([]).with(code))
+                .collect::<Vec<_>>(),
+        ),
+        a([
+            href("/settings/account"),
+            class("mt-8 text-neutral-400 hover:text-neutral-300"),
+        ])
+        .with("Back to the account settings"),
+    ])
+}